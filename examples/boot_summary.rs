@@ -0,0 +1,205 @@
+//! # Boot summary
+//! Getting started on bare metal: the `firmware` module is written as it would be in a `#![no_std]`
+//! kernel, using only `core` to render the early-boot summary (model, memory, CPUs and console)
+//! of a DTB into a fixed 4 KiB buffer. The host shim in `main()` builds a DTB with `DtbBuilder`
+//! in place of the blob a bootloader would pass and prints the result.
+//!
+//! Run with `cargo run --example boot_summary`, `cargo test --example boot_summary` checks the output.
+
+mod firmware {
+    use core::fmt::{self, Write};
+
+    use static_dt_rs::{props, DeviceTree};
+
+    /// # FixedBuf
+    /// A `fmt::Write` into a fixed buffer, writes which do not fit fail with `fmt::Error`
+    pub struct FixedBuf<const N: usize> {
+        buf: [u8; N],
+        len: usize,
+    }
+
+    impl<const N: usize> FixedBuf<N> {
+        pub const fn new() -> Self {
+            FixedBuf { buf: [0; N], len: 0 }
+        }
+
+        /// Returns the text written so far
+        pub fn as_str(&self) -> &str {
+            /* Only whole &str are copied in */
+            core::str::from_utf8(&self.buf[..self.len]).unwrap_or_default()
+        }
+    }
+
+    impl<const N: usize> Write for FixedBuf<N> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let dst = self.buf.get_mut(self.len..self.len + s.len()).ok_or(fmt::Error)?;
+            dst.copy_from_slice(s.as_bytes());
+            self.len += s.len();
+            Ok(())
+        }
+    }
+
+    /// Write a name or string from the tree, bytes outside of printable ASCII as '?'
+    fn write_bytes(out: &mut dyn Write, s: &[u8]) -> fmt::Result {
+        s.iter().try_for_each(|c| out.write_char(if (0x20..0x7f).contains(c) { *c as char } else { '?' }))
+    }
+
+    /// Write the CPUs as "cpu@0 (reg 0x0, okay)"
+    fn write_cpus(out: &mut dyn Write, dt: &DeviceTree) -> fmt::Result {
+        for cpu in dt.cpus() {
+            out.write_str("cpu:      ")?;
+            write_bytes(out, cpu.name())?;
+            let status = if cpu.is_enabled() { "okay" } else { "disabled" };
+            match cpu.get_prop(props::REG).and_then(|reg| reg.prop_u32(0)) {
+                Some(hartid) => writeln!(out, " (reg {:#x}, {})", hartid, status)?,
+                None => writeln!(out, " ({})", status)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Render the early-boot summary of `dtb` into `out`
+    pub fn render(dtb: &[u8], out: &mut dyn Write) -> fmt::Result {
+        let dt = match DeviceTree::parse(dtb) {
+            Ok(dt) => dt,
+            Err(e) => return writeln!(out, "invalid devicetree: {:?}", e),
+        };
+
+        out.write_str("model:    ")?;
+        match dt.root().get_prop(props::MODEL).and_then(|prop| prop.prop_str()) {
+            Some(model) => write_bytes(out, model)?,
+            None => out.write_str("unknown")?,
+        }
+        out.write_char('\n')?;
+
+        /* Memory usable by this kernel, a crash kernel only gets its window */
+        let mut total = 0;
+        for region in dt.memory_regions_clamped() {
+            writeln!(out, "memory:   {:#x}..{:#x}", region.address, region.address + region.size)?;
+            total += region.size;
+        }
+        writeln!(out, "total:    {} MiB", total >> 20)?;
+
+        write_cpus(out, &dt)?;
+
+        match dt.earlycon() {
+            Some(console) => {
+                out.write_str("console:  ")?;
+                write_bytes(out, console.node.name())?;
+                if let Some(compatible) = console.compatible {
+                    out.write_str(" ")?;
+                    write_bytes(out, compatible)?;
+                }
+                if let Some(address) = console.address {
+                    write!(out, " at {:#x}", address)?;
+                }
+                if let Some(options) = console.options {
+                    write!(out, " {} baud", options.baud)?;
+                }
+                out.write_char('\n')?;
+            },
+            None => out.write_str("console:  none\n")?,
+        }
+
+        if let Some(bootargs) = dt.chosen().and_then(|chosen| chosen.get_prop(props::BOOTARGS)).and_then(|prop| prop.prop_str()) {
+            out.write_str("bootargs: ")?;
+            write_bytes(out, bootargs)?;
+            out.write_char('\n')?;
+        }
+        Ok(())
+    }
+}
+
+use static_dt_rs::DtbBuilder;
+
+/// Host shim: a small RISC-V board as a bootloader would pass it
+fn board_dtb(buf: &mut [u8]) -> &[u8] {
+    let mut builder = DtbBuilder::new(buf);
+    builder.begin_node(b"").unwrap();
+    builder.prop_u32(b"#address-cells", 2).unwrap();
+    builder.prop_u32(b"#size-cells", 2).unwrap();
+    builder.prop_str(b"model", b"Example RISC-V board").unwrap();
+    builder.begin_node(b"chosen").unwrap();
+    builder.prop_str(b"stdout-path", b"serial0:115200n8").unwrap();
+    builder.prop_str(b"bootargs", b"console=ttyS0 quiet").unwrap();
+    builder.end_node().unwrap();
+    builder.begin_node(b"aliases").unwrap();
+    builder.prop_str(b"serial0", b"/soc/serial@10000000").unwrap();
+    builder.end_node().unwrap();
+    builder.begin_node(b"cpus").unwrap();
+    builder.prop_u32(b"#address-cells", 1).unwrap();
+    builder.prop_u32(b"#size-cells", 0).unwrap();
+    for hartid in 0..2u32 {
+        builder.begin_node(if hartid == 0 { b"cpu@0" } else { b"cpu@1" }).unwrap();
+        builder.prop_str(b"device_type", b"cpu").unwrap();
+        builder.prop_u32(b"reg", hartid).unwrap();
+        builder.prop_str(b"status", if hartid == 0 { b"okay" } else { b"disabled" }).unwrap();
+        builder.end_node().unwrap();
+    }
+    builder.end_node().unwrap();
+    builder.begin_node(b"memory@80000000").unwrap();
+    builder.prop_str(b"device_type", b"memory").unwrap();
+    builder.prop_cells(b"reg", &[0, 0x80000000, 0, 0x10000000]).unwrap();
+    builder.end_node().unwrap();
+    builder.begin_node(b"soc").unwrap();
+    builder.prop_u32(b"#address-cells", 2).unwrap();
+    builder.prop_u32(b"#size-cells", 2).unwrap();
+    builder.property(b"compatible", b"simple-bus\0").unwrap();
+    builder.prop_empty(b"ranges").unwrap();
+    builder.begin_node(b"serial@10000000").unwrap();
+    builder.property(b"compatible", b"ns16550a\0").unwrap();
+    builder.prop_cells(b"reg", &[0, 0x10000000, 0, 0x100]).unwrap();
+    builder.end_node().unwrap();
+    builder.end_node().unwrap();
+    builder.end_node().unwrap();
+    builder.finish().unwrap()
+}
+
+fn main() {
+    let mut buf = [0u8; 2048];
+    let dtb = board_dtb(&mut buf);
+
+    let mut out = firmware::FixedBuf::<4096>::new();
+    firmware::render(dtb, &mut out).unwrap();
+    print!("{}", out.as_str());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{board_dtb, firmware};
+
+    #[test]
+    fn test_render() {
+        let mut buf = [0u8; 2048];
+        let dtb = board_dtb(&mut buf);
+        let mut out = firmware::FixedBuf::<4096>::new();
+        firmware::render(dtb, &mut out).unwrap();
+
+        let summary = out.as_str();
+        for expected in [
+            "model:    Example RISC-V board\n",
+            "memory:   0x80000000..0x90000000\n",
+            "total:    256 MiB\n",
+            "cpu:      cpu@0 (reg 0x0, okay)\n",
+            "cpu:      cpu@1 (reg 0x1, disabled)\n",
+            "console:  serial@10000000 ns16550a at 0x10000000 115200 baud\n",
+            "bootargs: console=ttyS0 quiet\n",
+        ] {
+            assert!(summary.contains(expected), "{:?} missing in:\n{}", expected, summary);
+        }
+    }
+
+    #[test]
+    fn test_render_errors() {
+        /* Output which does not fit is an error, not a panic */
+        let mut buf = [0u8; 2048];
+        let dtb = board_dtb(&mut buf);
+        let mut out = firmware::FixedBuf::<32>::new();
+        assert!(firmware::render(dtb, &mut out).is_err());
+        assert_eq!(out.as_str(), "model:    Example RISC-V board\n");
+
+        let mut out = firmware::FixedBuf::<4096>::new();
+        firmware::render(&[0; 64], &mut out).unwrap();
+        assert!(out.as_str().starts_with("invalid devicetree: "));
+    }
+}
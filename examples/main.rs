@@ -3,7 +3,7 @@ use static_dt_rs::{DeviceTree, Token};
 static FDT: &[u8] = include_bytes!("test.dtb");
 
 fn main() {
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(FDT).unwrap();
 
     let root = dt.root();
 
@@ -11,19 +11,19 @@ fn main() {
 
     let prop = node2.get_prop(b"a-cell-property").unwrap();
 
-    println!("/node2/a-cell-property.len = {}",  prop.len());
+    println!("/node2/a-cell-property.len = {}",  prop.prop_len().unwrap_or_default());
     for x in 0..5 {
         println!("/node2/a-cell-property.{} = {}", x, prop.prop_u32(x).unwrap_or_default());
     }
 
     if let Some(node_dont_exist) = root.get_node(b"node-i-dont-exist") {
-        println!("/node_dont_exist.len = {}",  node_dont_exist.len());
+        println!("/node_dont_exist.len = {}",  node_dont_exist.prop_count().unwrap_or_default());
     }else{
         println!("/node_dont_exist doesn't exist!");
     }
 
     if let Some(node1) = root.get_node(b"node1") {
-        println!("/node1.len = {}",  node1.len());
+        println!("/node1.children = {}",  node1.child_count().unwrap_or_default());
 
         for token in node1 {
             match token {
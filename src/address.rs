@@ -0,0 +1,441 @@
+//! # Addresses
+//! Decoding of `reg` and `ranges` using the `#address-cells` and `#size-cells` of the parent node.
+//!
+
+use crate::{props, Token};
+
+/// # CellSizes
+/// The `#address-cells` and `#size-cells` of a node, which applies to the `reg` of its children.
+/// Read once per bus and pass to `Token::reg_with()` to avoid looking up the parent of every child.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CellSizes {
+    pub address_cells: u32,
+    pub size_cells: u32
+}
+
+impl Default for CellSizes {
+    /// Default values used when a node has no `#address-cells` or `#size-cells`
+    fn default() -> Self {
+        CellSizes { address_cells: 2, size_cells: 1 }
+    }
+}
+
+impl CellSizes {
+    /// Read `#address-cells` and `#size-cells` of `node`, using the defaults for missing properties.
+    /// These are the sizes used by the `reg` properties of the children of `node`.
+    pub fn of_parent(node: &Token) -> CellSizes {
+        let default = CellSizes::default();
+        CellSizes {
+            address_cells: node.get_prop(props::ADDRESS_CELLS)
+                .and_then(|prop| prop.prop_u32(0))
+                .unwrap_or(default.address_cells),
+            size_cells: node.get_prop(props::SIZE_CELLS)
+                .and_then(|prop| prop.prop_u32(0))
+                .unwrap_or(default.size_cells)
+        }
+    }
+}
+
+/// Read a number of `cells` (at most 2) at `n` cells into `val`
+pub(crate) fn read_cells(val: &[u8], n: usize, cells: u32) -> Option<u64> {
+    match cells {
+        0 => Some(0),
+        1 => crate::utils::try_read_fdt_u32(val, n*4).map(|x| x as u64),
+        2 => crate::utils::try_read_fdt_u64(val, n*4),
+        _ => None
+    }
+}
+
+/// Parse one hex field of a unit address
+fn parse_hex(digits: &[u8]) -> Option<u64> {
+    if digits.is_empty() || digits.len() > 16 {
+        return None
+    }
+    digits.iter().try_fold(0u64, |addr, c| Some(addr << 4 | (*c as char).to_digit(16)? as u64))
+}
+
+/// Parse one hex field of a unit address, allowing a `0x` prefix and any number of leading zeros
+fn parse_hex_lenient(digits: &[u8]) -> Option<u64> {
+    let digits = digits.strip_prefix(b"0x").or_else(|| digits.strip_prefix(b"0X")).unwrap_or(digits);
+    let first = digits.iter().position(|c| *c != b'0').unwrap_or(digits.len().saturating_sub(1));
+    parse_hex(&digits[first..])
+}
+
+/// Parse the unit address of a node name as a single hex number
+fn unit_address(name: &[u8]) -> Option<u64> {
+    let at = name.iter().position(|c| *c == b'@')?;
+    parse_hex(&name[at + 1..])
+}
+
+/// Split a node name into the name before '@' and the unit address after it
+fn split_unit_address(name: &[u8]) -> (&[u8], Option<&[u8]>) {
+    match name.iter().position(|c| *c == b'@') {
+        Some(at) => (&name[..at], Some(&name[at + 1..])),
+        None => (name, None)
+    }
+}
+
+/// # UnitAddrCompare
+/// How node names with unit addresses are compared by the `*_with()` variants of lookups and
+/// `tree_diff_with()`, e.g. for paths copied from tools which spell `uart@1000` as `uart@0x1000` or `uart@01000`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum UnitAddrCompare {
+    /// Names are compared byte by byte, as everywhere else in the crate
+    #[default]
+    Exact,
+
+    /// Names before '@' are compared byte by byte and every comma-separated field of the unit addresses
+    /// as a hex number, allowing a `0x` prefix and leading zeros. Composite unit addresses only match if
+    /// all their fields do. Unit addresses which do not parse are compared byte by byte
+    NumericHex,
+
+    /// Same as `NumericHex`, and a name without a unit address matches a node with any unit address,
+    /// like libfdt, e.g. `uart` matches `uart@1000`
+    Lenient,
+}
+
+impl UnitAddrCompare {
+    /// Returns true if the node name `name` matches `wanted`.
+    /// `Token::child_by_unit_address_with()` compares unit addresses with this as well.
+    pub fn matches(&self, wanted: &[u8], name: &[u8]) -> bool {
+        if wanted == name {
+            return true
+        }
+        if *self == UnitAddrCompare::Exact {
+            return false
+        }
+
+        let (wanted_base, wanted_addr) = split_unit_address(wanted);
+        let (base, addr) = split_unit_address(name);
+        if wanted_base != base {
+            return false
+        }
+        match (wanted_addr, addr) {
+            (Some(wanted_addr), Some(addr)) => unit_addresses_eq(wanted_addr, addr),
+            (None, Some(_)) => *self == UnitAddrCompare::Lenient,
+            _ => false
+        }
+    }
+}
+
+/// Compare unit addresses field by field as hex numbers, or byte by byte if a field does not parse
+fn unit_addresses_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut a_fields = a.split(|c| *c == b',');
+    let mut b_fields = b.split(|c| *c == b',');
+    loop {
+        match (a_fields.next(), b_fields.next()) {
+            (None, None) => return true,
+            (Some(a_field), Some(b_field)) => match (parse_hex_lenient(a_field), parse_hex_lenient(b_field)) {
+                (Some(a_value), Some(b_value)) if a_value == b_value => (),
+                (Some(_), Some(_)) => return false,
+                _ => return a == b
+            },
+            _ => return false
+        }
+    }
+}
+
+/// # UnitAddrFields
+/// The comma-separated hex fields of a unit address, e.g. (device, function) of `port@1,0`
+/// or (chip-select, offset) of `flash@0,400000`. See `Token::unit_address_fields()`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct UnitAddrFields {
+    fields: [u64; UnitAddrFields::MAX],
+    len: usize
+}
+
+impl UnitAddrFields {
+    /// Maximum number of fields, further fields are ignored
+    pub const MAX: usize = 4;
+
+    /// Parse the fields after '@' in a node name.
+    /// Parsing stops at the first field which is not a hex number,
+    /// so the fields before it are still returned.
+    pub fn parse(name: &[u8]) -> UnitAddrFields {
+        let mut result = UnitAddrFields::default();
+        let at = match name.iter().position(|c| *c == b'@') {
+            Some(at) => at,
+            None => return result
+        };
+
+        for field in name[at + 1..].split(|c| *c == b',').take(Self::MAX) {
+            match parse_hex(field) {
+                Some(value) => {
+                    result.fields[result.len] = value;
+                    result.len += 1;
+                },
+                None => break
+            }
+        }
+        result
+    }
+
+    /// Returns the parsed fields
+    pub fn as_slice(&self) -> &[u64] {
+        &self.fields[..self.len]
+    }
+
+    /// Returns field `n`
+    /// Returns None if there are not that many fields.
+    pub fn get(&self, n: usize) -> Option<u64> {
+        self.as_slice().get(n).copied()
+    }
+
+    /// Returns the number of parsed fields
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no field could be parsed
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// # RegEntry
+/// One (address, size) pair of a `reg` property
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RegEntry {
+    pub address: u64,
+    pub size: u64
+}
+
+/// # RegIterator
+/// Iterates over the entries of a `reg` property, see `Token::reg()`.
+/// An incomplete entry at the end is ignored.
+pub struct RegIterator<'a> {
+    val: &'a [u8],
+    sizes: CellSizes,
+    n: usize
+}
+
+impl<'a> RegIterator<'a> {
+    /// Iterate over the (address, size) pairs of `val`
+    /// Returns None if the cell sizes are larger than 2.
+    pub(crate) fn new(val: &'a [u8], sizes: &CellSizes) -> Option<RegIterator<'a>> {
+        if sizes.address_cells > 2 || sizes.size_cells > 2 {
+            return None
+        }
+        Some(RegIterator { val, sizes: *sizes, n: 0 })
+    }
+}
+
+impl<'a> Iterator for RegIterator<'a> {
+    type Item = RegEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        /* Entries of zero cells would never advance */
+        if self.sizes.address_cells + self.sizes.size_cells == 0 || self.n * 4 >= self.val.len() {
+            return None
+        }
+        let address = read_cells(self.val, self.n, self.sizes.address_cells)?;
+        let size = read_cells(self.val, self.n + self.sizes.address_cells as usize, self.sizes.size_cells)?;
+        self.n += (self.sizes.address_cells + self.sizes.size_cells) as usize;
+        Some(RegEntry { address, size })
+    }
+}
+
+/// # RangeEntry
+/// One entry of a `ranges` property, mapping `size` bytes at `child_address`
+/// in the bus address space to `parent_address` in the parent address space.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RangeEntry {
+    pub child_address: u64,
+    pub parent_address: u64,
+    pub size: u64
+}
+
+impl RangeEntry {
+    /// Translate `address` in the bus address space to the parent address space
+    /// Returns None if `address` is outside of this range
+    pub fn translate(&self, address: u64) -> Option<u64> {
+        let offset = address.checked_sub(self.child_address)?;
+        if offset >= self.size {
+            return None
+        }
+        self.parent_address.checked_add(offset)
+    }
+}
+
+/// # RangesIterator
+/// Iterates over the entries of a `ranges` property, see `Token::ranges()`.
+/// Empty for an empty `ranges` property, meaning an identity mapping.
+pub struct RangesIterator<'a> {
+    val: &'a [u8],
+    child: CellSizes,
+    parent_address_cells: u32,
+    n: usize
+}
+
+impl<'a> Iterator for RangesIterator<'a> {
+    type Item = RangeEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        /* Entries of zero cells would never advance */
+        let cells = self.child.address_cells + self.parent_address_cells + self.child.size_cells;
+        if cells == 0 || self.n * 4 >= self.val.len() {
+            return None
+        }
+        let mut n = self.n;
+        let child_address = read_cells(self.val, n, self.child.address_cells)?;
+        n += self.child.address_cells as usize;
+        let parent_address = read_cells(self.val, n, self.parent_address_cells)?;
+        n += self.parent_address_cells as usize;
+        let size = read_cells(self.val, n, self.child.size_cells)?;
+        self.n = n + self.child.size_cells as usize;
+        Some(RangeEntry { child_address, parent_address, size })
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the entries of the `reg` property of this node,
+    /// using the cell sizes of its parent.
+    /// Returns None if there is no `reg` property, the node has no parent
+    /// or the cell sizes are larger than 2.
+    ///
+    pub fn reg(&self) -> Option<RegIterator<'a>> {
+        self.reg_with(&CellSizes::of_parent(&self.parent()?))
+    }
+
+    /// Same as `reg()` but with cell sizes given by the caller,
+    /// e.g. `CellSizes::of_parent()` computed once for all children of a bus.
+    ///
+    pub fn reg_with(&self, sizes: &CellSizes) -> Option<RegIterator<'a>> {
+        match self.get_prop(props::REG)? {
+            Token::Property(_, _, val) => RegIterator::new(val, sizes),
+            _ => None
+        }
+    }
+
+    /// Returns a iterator over the entries of the `ranges` property of this node.
+    /// The iterator is empty if `ranges` is empty, meaning a identity mapping.
+    /// Returns None if there is no `ranges` property, the node has no parent
+    /// or the cell sizes are larger than 2.
+    ///
+    pub fn ranges(&self) -> Option<RangesIterator<'a>> {
+        self.ranges_with(&CellSizes::of_parent(self), &CellSizes::of_parent(&self.parent()?))
+    }
+
+    /// Same as `ranges()` but with the cell sizes of this node (`child`)
+    /// and of its parent (`parent`) given by the caller.
+    ///
+    pub fn ranges_with(&self, child: &CellSizes, parent: &CellSizes) -> Option<RangesIterator<'a>> {
+        if child.address_cells > 2 || child.size_cells > 2 || parent.address_cells > 2 {
+            return None
+        }
+        match self.get_prop(props::RANGES)? {
+            Token::Property(_, _, val) => Some(RangesIterator {
+                val,
+                child: *child,
+                parent_address_cells: parent.address_cells,
+                n: 0
+            }),
+            _ => None
+        }
+    }
+
+    /// Returns the direct child whose unit address, the hex number after '@' in its name, is `addr`.
+    /// Children without a unit address or with one that is not a single hex number
+    /// (e.g. "device@1,1000") are skipped.
+    ///
+    pub fn child_by_unit_address(&self, addr: u64) -> Option<Token<'a>> {
+        self.into_iter().find(|child| match child {
+            Token::BeginNode(_, _, name) => unit_address(name) == Some(addr),
+            _ => false
+        })
+    }
+
+    /// Same as `child_by_unit_address()` with unit addresses compared by `UnitAddrCompare::matches()`,
+    /// as if looking for `@` followed by `addr` in lowercase hex without leading zeros. `Exact` only
+    /// finds unit addresses spelled that way, `NumericHex` and `Lenient` also ones with a `0x` prefix or
+    /// leading zeros. Composite unit addresses never match, see `child_by_unit_address_first()`.
+    ///
+    pub fn child_by_unit_address_with(&self, addr: u64, compare: UnitAddrCompare) -> Option<Token<'a>> {
+        /* Format "@<addr>" at the end of the buffer */
+        let mut buf = [0u8; 17];
+        let mut start = buf.len();
+        let mut value = addr;
+        loop {
+            start -= 1;
+            buf[start] = b"0123456789abcdef"[(value & 0xf) as usize];
+            value >>= 4;
+            if value == 0 {
+                break
+            }
+        }
+        start -= 1;
+        buf[start] = b'@';
+        let wanted = &buf[start..];
+
+        self.into_iter().find(|child| match child {
+            Token::BeginNode(_, _, name) => match name.iter().position(|c| *c == b'@') {
+                Some(at) => compare.matches(wanted, &name[at..]),
+                None => false
+            },
+            _ => false
+        })
+    }
+
+    /// Same as `get_node()` with names compared as given by `compare`.
+    /// A child named exactly `name` is preferred over an earlier one which only matches with `compare`.
+    ///
+    pub fn get_node_with(&self, name: &[u8], compare: UnitAddrCompare) -> Option<Token<'a>> {
+        self.get_node(name).or_else(|| self.children().find(|child| compare.matches(name, child.name())))
+    }
+
+    /// Same as `child_by_unit_address()` but only matches the first field of composite unit addresses,
+    /// e.g. the chip-select of "flash@1,400000" or the device of "port@1,0".
+    ///
+    pub fn child_by_unit_address_first(&self, addr: u64) -> Option<Token<'a>> {
+        self.into_iter().find(|child| child.unit_address_fields().get(0) == Some(addr))
+    }
+
+    /// Returns the comma-separated hex fields of the unit address of this node, e.g. [1, 0] for "port@1,0"
+    /// Returns no fields if not a node, the name has no unit address or its first field is not hex.
+    ///
+    pub fn unit_address_fields(&self) -> UnitAddrFields {
+        match self {
+            Token::BeginNode(_, _, name) => UnitAddrFields::parse(name),
+            _ => UnitAddrFields::default()
+        }
+    }
+
+    /// Returns the direct child whose first `reg` address is `addr`,
+    /// which may differ from the unit address in its name.
+    /// Children without a `reg` property are skipped.
+    ///
+    pub fn child_by_reg(&self, addr: u64) -> Option<Token<'a>> {
+        let sizes = CellSizes::of_parent(self);
+        self.into_iter().find(|child| match child {
+            Token::BeginNode(..) => child.reg_with(&sizes).and_then(|mut reg| reg.next()).map(|reg| reg.address) == Some(addr),
+            _ => false
+        })
+    }
+
+    /// Translate `address` in the address space of this node's `reg` to a CPU address
+    /// by applying the `ranges` of each ancestor bus.
+    /// Returns None if a bus has no `ranges` property (not mapped), `address` is outside
+    /// of its ranges, or the cell sizes are larger than 2.
+    ///
+    pub fn translate_address(&self, address: u64) -> Option<u64> {
+        translate_on_bus(self.parent()?, address)
+    }
+}
+
+/// Translate `address` on `bus` to a CPU address by applying the `ranges` of `bus` and its ancestors,
+/// see `Token::translate_address()`
+pub(crate) fn translate_on_bus(bus: Token, address: u64) -> Option<u64> {
+    let mut address = address;
+    let mut bus = bus;
+
+    /* The root node is the CPU address space */
+    while let Some(parent) = bus.parent() {
+        let mut ranges = bus.ranges_with(&CellSizes::of_parent(&bus), &CellSizes::of_parent(&parent))?.peekable();
+        if ranges.peek().is_some() {
+            address = ranges.find_map(|range| range.translate(address))?;
+        }
+        bus = parent;
+    }
+    Some(address)
+}
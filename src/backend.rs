@@ -0,0 +1,366 @@
+//! # Storage backends
+//! Parsing of a devicetree which is not resident in memory, e.g. stored in external flash.
+//! Small pieces (header, token headers, names and values) are copied into buffers on demand
+//! instead of being borrowed, see `DeviceTreeOn`. `DeviceTree` stays the zero-copy fast path.
+//!
+
+use crate::raw::{self, Header};
+use crate::{check_header_fields, Error};
+
+/// # FdtRead
+/// A source of devicetree bytes
+pub trait FdtRead {
+    /// Error returned by the storage
+    type Error;
+
+    /// Read up to `buf.len()` bytes at `offset` into `buf`, returns the number of bytes read.
+    /// Reading less than requested means the end of the storage was reached.
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Size of the storage in bytes
+    fn len(&self) -> usize;
+
+    /// Returns true if the storage is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl FdtRead for &[u8] {
+    type Error = core::convert::Infallible;
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let src = self.get(offset..).unwrap_or(&[]);
+        let n = src.len().min(buf.len());
+        buf[..n].copy_from_slice(&src[..n]);
+        Ok(n)
+    }
+
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+}
+
+/// # BackendError
+/// Errors returned by `DeviceTreeOn`
+#[derive(Debug, PartialEq, Eq)]
+pub enum BackendError<E> {
+    /// The storage returned an error
+    Read(E),
+
+    /// The devicetree is malformed, reads outside of a block return `Error::BlockOutOfBounds`
+    Format(Error),
+
+    /// A buffer given by the caller is too small, contains the minimum required size
+    BufferTooSmall(usize),
+}
+
+/// # RawToken
+/// A token read by `DeviceTreeOn::token_at()`.
+/// Offsets are relative to the structure block, names and values are read separately.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RawToken {
+    /// Unknown token id
+    Invalid(u32),
+
+    /// Begin of a node, `offset` is the offset of the FDT_BEGIN_NODE token (see `Token::node_offset()`)
+    BeginNode { offset: usize, name_len: usize },
+
+    EndNode,
+
+    /// A property with `len` bytes of value at `value_offset`, named by `nameoff` in the strings block
+    Property { nameoff: usize, value_offset: usize, len: usize },
+
+    NoOperation,
+
+    End
+}
+
+/// Size of the chunks read when searching for or comparing strings
+const CHUNK: usize = 16;
+
+/// # DeviceTreeOn
+/// A devicetree read through a `FdtRead` backend
+pub struct DeviceTreeOn<B: FdtRead> {
+    backend: B,
+    header: Header,
+    root_offset: usize,
+}
+
+impl<B: FdtRead> DeviceTreeOn<B> {
+
+    /// Read and check the header of the devicetree in `backend`
+    pub fn parse(backend: B) -> Result<DeviceTreeOn<B>, BackendError<B::Error>> {
+        let mut buf = [0u8; raw::HEADER_SIZE];
+        let n = backend.read(0, &mut buf).map_err(BackendError::Read)?;
+        let header = match Header::parse(&buf[..n]) {
+            Some(header) => header,
+            None => return Err(BackendError::Format(Error::Truncated))
+        };
+        check_header_fields(&header, backend.len()).map_err(BackendError::Format)?;
+
+        let mut dt = DeviceTreeOn { backend, header, root_offset: 0 };
+
+        /* Skip NOPs before the root node */
+        let mut offs = 0;
+        loop {
+            match dt.token_at(offs) {
+                Ok((RawToken::NoOperation, next)) => offs = next,
+                Ok((RawToken::BeginNode { .. }, _)) => break,
+                Err(BackendError::Read(e)) => return Err(BackendError::Read(e)),
+                _ => return Err(BackendError::Format(Error::NoRootNode))
+            }
+        }
+        dt.root_offset = offs;
+        Ok(dt)
+    }
+
+    /// Returns the backend
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Returns the header of the devicetree
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the offset of the root node in the structure block
+    pub fn root_offset(&self) -> usize {
+        self.root_offset
+    }
+
+    /// Read exactly `buf.len()` bytes at `offset` in a block of `size` bytes at `base`
+    fn read_block(&self, base: u32, size: u32, offset: usize, buf: &mut [u8]) -> Result<(), BackendError<B::Error>> {
+        match offset.checked_add(buf.len()) {
+            Some(end) if end <= size as usize => (),
+            _ => return Err(BackendError::Format(Error::BlockOutOfBounds))
+        }
+
+        let n = self.backend.read(base as usize + offset, buf).map_err(BackendError::Read)?;
+        if n < buf.len() {
+            return Err(BackendError::Format(Error::Truncated))
+        }
+        Ok(())
+    }
+
+    /// Read from the structure block
+    fn read_struct(&self, offset: usize, buf: &mut [u8]) -> Result<(), BackendError<B::Error>> {
+        self.read_block(self.header.off_dt_struct, self.header.struct_size(), offset, buf)
+    }
+
+    /// Read one cell from the structure block
+    fn read_struct_u32(&self, offset: usize) -> Result<u32, BackendError<B::Error>> {
+        let mut buf = [0u8; 4];
+        self.read_struct(offset, &mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    /// Returns the token at `offset` in the structure block and the offset of the next token
+    pub fn token_at(&self, offset: usize) -> Result<(RawToken, usize), BackendError<B::Error>> {
+        let token_id = self.read_struct_u32(offset)?;
+        let offs = offset + raw::FDT_TAGSIZE;
+
+        match token_id {
+            raw::FDT_BEGIN_NODE => {
+                /* Search for the end of the name */
+                let mut name_len = 0;
+                loop {
+                    let mut chunk = [0u8; CHUNK];
+                    let n = CHUNK.min((self.header.struct_size() as usize).saturating_sub(offs + name_len));
+                    self.read_struct(offs + name_len, &mut chunk[..n])?;
+                    match chunk[..n].iter().position(|c| *c == 0) {
+                        Some(end) => { name_len += end; break },
+                        None if n == 0 => return Err(BackendError::Format(Error::BlockOutOfBounds)),
+                        None => name_len += n
+                    }
+                }
+                Ok((RawToken::BeginNode { offset, name_len }, offs + (name_len/4 + 1)*4))
+            },
+            raw::FDT_END_NODE => Ok((RawToken::EndNode, offs)),
+            raw::FDT_PROP => {
+                let len = self.read_struct_u32(offs)? as usize;
+                let nameoff = self.read_struct_u32(offs + 4)? as usize;
+                let value_offset = offs + 8;
+                Ok((RawToken::Property { nameoff, value_offset, len }, value_offset + len.div_ceil(4)*4))
+            },
+            raw::FDT_NOP => Ok((RawToken::NoOperation, offs)),
+            raw::FDT_END => Ok((RawToken::End, offs)),
+            x => Ok((RawToken::Invalid(x), offs))
+        }
+    }
+
+    /// Returns a iterator over all tokens from the root node
+    pub fn tokens(&self) -> RawTokenIterator<'_, B> {
+        RawTokenIterator { dt: self, offs: Some(0) }
+    }
+
+    /// Copy the name of a node or property into `buf`, returns the name
+    pub fn name<'b>(&self, token: &RawToken, buf: &'b mut [u8]) -> Result<&'b [u8], BackendError<B::Error>> {
+        match *token {
+            RawToken::BeginNode { offset, name_len } => {
+                let buf = buf.get_mut(..name_len).ok_or(BackendError::BufferTooSmall(name_len))?;
+                self.read_struct(offset + raw::FDT_TAGSIZE, buf)?;
+                Ok(buf)
+            },
+            RawToken::Property { nameoff, .. } => {
+                let mut len = 0;
+                loop {
+                    let mut chunk = [0u8; CHUNK];
+                    let n = CHUNK.min((self.header.size_dt_strings as usize).saturating_sub(nameoff + len));
+                    self.read_block(self.header.off_dt_strings, self.header.size_dt_strings, nameoff + len, &mut chunk[..n])?;
+
+                    let end = chunk[..n].iter().position(|c| *c == 0);
+                    let copy = end.unwrap_or(n);
+                    buf.get_mut(len..len + copy)
+                        .ok_or(BackendError::BufferTooSmall(len + copy))?
+                        .copy_from_slice(&chunk[..copy]);
+                    len += copy;
+
+                    match end {
+                        Some(_) => {
+                            let buf: &'b [u8] = buf;
+                            return Ok(&buf[..len])
+                        },
+                        None if n == 0 => return Err(BackendError::Format(Error::BlockOutOfBounds)),
+                        None => ()
+                    }
+                }
+            },
+            _ => Ok(&[])
+        }
+    }
+
+    /// Returns true if the name of a node or property equals `name`, without copying it
+    pub fn name_eq(&self, token: &RawToken, name: &[u8]) -> Result<bool, BackendError<B::Error>> {
+        let (base, size, offset) = match *token {
+            RawToken::BeginNode { offset, name_len } => {
+                if name_len != name.len() { return Ok(false) }
+                (self.header.off_dt_struct, self.header.struct_size(), offset + raw::FDT_TAGSIZE)
+            },
+            RawToken::Property { nameoff, .. } => (self.header.off_dt_strings, self.header.size_dt_strings, nameoff),
+            _ => return Ok(false)
+        };
+
+        /* Compare the name and its NUL terminator chunk by chunk */
+        let mut pos = 0;
+        while pos <= name.len() {
+            let n = CHUNK.min(name.len() + 1 - pos);
+            let mut chunk = [0u8; CHUNK];
+            match self.read_block(base, size, offset + pos, &mut chunk[..n]) {
+                Ok(()) => (),
+                Err(BackendError::Format(Error::BlockOutOfBounds)) => return Ok(false),
+                Err(e) => return Err(e)
+            }
+            let expected = name.iter().chain(Some(&0u8)).skip(pos).take(n);
+            if !chunk[..n].iter().eq(expected) {
+                return Ok(false)
+            }
+            pos += n;
+        }
+        Ok(true)
+    }
+
+    /// Copy the value of a property into `buf`, returns the value
+    pub fn value<'b>(&self, token: &RawToken, buf: &'b mut [u8]) -> Result<&'b [u8], BackendError<B::Error>> {
+        match *token {
+            RawToken::Property { value_offset, len, .. } => {
+                let buf = buf.get_mut(..len).ok_or(BackendError::BufferTooSmall(len))?;
+                self.read_struct(value_offset, buf)?;
+                Ok(buf)
+            },
+            _ => Ok(&[])
+        }
+    }
+
+    /// Returns the first token inside the node at `node_offset`
+    fn node_contents(&self, node_offset: usize) -> Result<usize, BackendError<B::Error>> {
+        match self.token_at(node_offset)? {
+            (RawToken::BeginNode { .. }, next) => Ok(next),
+            _ => Err(BackendError::Format(Error::BlockOutOfBounds))
+        }
+    }
+
+    /// Find a sub-node with `name` in the node at `node_offset` (not recursive)
+    /// Returns the offset of the sub-node or None if there is no matching node.
+    pub fn get_node(&self, node_offset: usize, name: &[u8]) -> Result<Option<usize>, BackendError<B::Error>> {
+        let mut offs = self.node_contents(node_offset)?;
+        let mut depth = 0;
+        loop {
+            let (tok, next) = self.token_at(offs)?;
+            match tok {
+                RawToken::BeginNode { offset, .. } => {
+                    if depth == 0 && self.name_eq(&tok, name)? {
+                        return Ok(Some(offset))
+                    }
+                    depth += 1;
+                },
+                RawToken::EndNode => {
+                    if depth == 0 { return Ok(None) }
+                    depth -= 1;
+                },
+                RawToken::End | RawToken::Invalid(_) => return Ok(None),
+                _ => ()
+            }
+            offs = next;
+        }
+    }
+
+    /// Find a node by its full path, e.g. "/soc/serial@10000000"
+    /// Returns the offset of the node or None if it does not exist.
+    pub fn get_node_by_path(&self, path: &[u8]) -> Result<Option<usize>, BackendError<B::Error>> {
+        if path.first() != Some(&b'/') {
+            return Ok(None)
+        }
+
+        let mut node = self.root_offset;
+        for component in path.split(|c| *c == b'/').filter(|component| !component.is_empty()) {
+            node = match self.get_node(node, component)? {
+                Some(node) => node,
+                None => return Ok(None)
+            };
+        }
+        Ok(Some(node))
+    }
+
+    /// Find a property with `name` in the node at `node_offset` (not recursive)
+    /// Only properties preceding the first sub-node are searched, see `Token::get_prop()`.
+    /// Returns None if there is no matching property.
+    pub fn get_prop(&self, node_offset: usize, name: &[u8]) -> Result<Option<RawToken>, BackendError<B::Error>> {
+        let mut offs = self.node_contents(node_offset)?;
+        loop {
+            let (tok, next) = self.token_at(offs)?;
+            match tok {
+                RawToken::Property { .. } => {
+                    if self.name_eq(&tok, name)? {
+                        return Ok(Some(tok))
+                    }
+                },
+                RawToken::NoOperation => (),
+                _ => return Ok(None)
+            }
+            offs = next;
+        }
+    }
+}
+
+/// # RawTokenIterator
+/// Iterates over the tokens of a `DeviceTreeOn`, see `DeviceTreeOn::tokens()`.
+/// Stops after End, Invalid or the first error.
+pub struct RawTokenIterator<'a, B: FdtRead> {
+    dt: &'a DeviceTreeOn<B>,
+    offs: Option<usize>
+}
+
+impl<'a, B: FdtRead> Iterator for RawTokenIterator<'a, B> {
+    type Item = Result<RawToken, BackendError<B::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.dt.token_at(self.offs?);
+        self.offs = match result {
+            Ok((RawToken::End, _)) | Ok((RawToken::Invalid(_), _)) | Err(_) => None,
+            Ok((_, next)) => Some(next)
+        };
+        Some(result.map(|(tok, _)| tok))
+    }
+}
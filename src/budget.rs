@@ -0,0 +1,259 @@
+//! # Budgeted operations
+//! Variants of operations which scan the whole tree that stop after a number of steps, e.g. to pet
+//! a watchdog between installments. Every token read and every memory reservation entry checked is a step.
+//! The position is kept in a `Resume` owned by the caller. When the `Budget` runs out the operation returns
+//! `BudgetError::Exhausted`, repeating the same call with the same `Resume` and a new budget continues where it stopped.
+//!
+//! Splitting an operation into installments gives the same result as running it at once.
+//!
+
+use core::convert::Infallible;
+
+use crate::validate::StructureWalk;
+use crate::{prop_has_string, props, AttributedStep, AttributedTokens, DeviceTree, IndexError, PhandleEntry, PhandleIndex,
+            Token, TokenIterator, ValidationError, MAX_DEPTH};
+
+/// # Budget
+/// Number of steps an operation may take, decremented as it goes
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Budget {
+    pub steps_remaining: usize
+}
+
+impl Budget {
+    /// A budget of `steps` steps
+    pub fn new(steps: usize) -> Budget {
+        Budget { steps_remaining: steps }
+    }
+
+    /// Returns true if no steps are left
+    pub fn is_exhausted(&self) -> bool {
+        self.steps_remaining == 0
+    }
+
+    /// Take one step
+    /// Returns false if no steps are left.
+    fn take(&mut self) -> bool {
+        match self.steps_remaining.checked_sub(1) {
+            Some(remaining) => {
+                self.steps_remaining = remaining;
+                true
+            },
+            None => false
+        }
+    }
+}
+
+/// Operation a `Resume` belongs to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Op {
+    Validate,
+    FindCompatible,
+    GetPhandle,
+    PhandleIndex,
+}
+
+/// # Resume
+/// Where a budgeted operation stopped, see the module documentation.
+///
+/// It must be passed to the same operation with the same arguments (and for `build_phandle_index_budgeted()`
+/// the same storage). One last used for another tree or operation is reset and the operation starts from the beginning,
+/// as it is once the operation has finished.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Resume {
+    tree: usize,
+    op: Option<Op>,
+    /* Offset of the next token in the structure block, or of the next reservation entry */
+    offset: usize,
+    reservations_done: bool,
+    /* The structure walk of validate saw the end of the root node, or an attributed walk stopped */
+    root_done: bool,
+    /* Offsets of the FDT_BEGIN_NODE tokens of all open nodes */
+    stack: [u32; MAX_DEPTH],
+    depth: usize,
+    /* Phandles found so far */
+    count: usize,
+}
+
+impl Resume {
+    /// Offset of the next token to read in the structure block. While `DeviceTree::validate_budgeted()`
+    /// checks the memory reservation block it is the offset of the next entry in that block instead.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// A position at the start of any operation
+    pub const fn new() -> Resume {
+        Resume {
+            tree: 0,
+            op: None,
+            offset: 0,
+            reservations_done: false,
+            root_done: false,
+            stack: [0; MAX_DEPTH],
+            depth: 0,
+            count: 0
+        }
+    }
+
+    /// Start `op` on `dt` at `offset` unless this is where it stopped before
+    fn bind(&mut self, dt: &DeviceTree, op: Op, offset: usize) {
+        let tree = dt.fdt.as_ptr() as usize;
+        if self.op != Some(op) || self.tree != tree {
+            *self = Resume { tree, op: Some(op), offset, ..Resume::new() };
+        }
+    }
+
+    /// Reset once the operation has finished, passing `result` on
+    fn finish<T>(&mut self, result: T) -> T {
+        *self = Resume::new();
+        result
+    }
+
+    /// Stop for now
+    fn exhausted<E>(&self) -> BudgetError<E> {
+        BudgetError::Exhausted { offset: self.offset }
+    }
+
+    /// Attributed tokens continuing from this position
+    fn tokens<'a>(&self, dt: &'a DeviceTree<'a>) -> AttributedTokens<'a> {
+        AttributedTokens { tokeniter: TokenIterator::new_offs(dt, self.offset), stack: self.stack, depth: self.depth }
+    }
+
+    /// Save the position of `tokens`
+    fn save(&mut self, tokens: &AttributedTokens) {
+        /* A stopped iterator has no position */
+        self.root_done = tokens.tokeniter.dt.is_none();
+        self.offset = tokens.tokeniter.offs;
+        self.stack = tokens.stack;
+        self.depth = tokens.depth;
+    }
+}
+
+/// # BudgetError
+/// Errors returned by budgeted operations
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BudgetError<E> {
+    /// The budget ran out at `offset`, see `Resume::offset()`. Repeat the call to continue
+    Exhausted { offset: usize },
+
+    /// The operation failed
+    Failed(E),
+}
+
+impl Default for Resume {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Same as `validate()` but stops when `budget` runs out, see the module documentation.
+    /// Returns `BudgetError::Failed` with the first error found.
+    pub fn validate_budgeted(&self, budget: &mut Budget, resume: &mut Resume) -> Result<(), BudgetError<ValidationError>> {
+        resume.bind(self, Op::Validate, 0);
+
+        while !resume.reservations_done {
+            if !budget.take() {
+                return Err(resume.exhausted())
+            }
+            match self.validate_reservation(resume.offset) {
+                Some(Ok(())) => {
+                    resume.reservations_done = true;
+                    resume.offset = 0;
+                },
+                Some(Err(e)) => return resume.finish(Err(BudgetError::Failed(e))),
+                None => resume.offset += 16
+            }
+        }
+
+        let mut walk = StructureWalk { offs: resume.offset, depth: resume.depth, root_done: resume.root_done };
+        loop {
+            if !budget.take() {
+                resume.offset = walk.offs;
+                resume.depth = walk.depth;
+                resume.root_done = walk.root_done;
+                return Err(resume.exhausted())
+            }
+            if let Some(result) = self.validate_token(&mut walk) {
+                return resume.finish(result.map_err(BudgetError::Failed))
+            }
+        }
+    }
+
+    /// Run `visit` on every token of an attributed walk from `resume` until it returns Some or `budget` runs out.
+    /// Returns Ok(None) if the walk ended.
+    fn walk_budgeted<'s, T, E>(&'s self, budget: &mut Budget, resume: &mut Resume,
+                               mut visit: impl FnMut(&mut Resume, Token<'s>, usize) -> Option<T>) -> Result<Option<T>, BudgetError<E>> {
+        if resume.root_done {
+            return Ok(None)
+        }
+        let mut tokens = resume.tokens(self);
+        loop {
+            if !budget.take() {
+                resume.save(&tokens);
+                return Err(resume.exhausted())
+            }
+            match tokens.step() {
+                AttributedStep::Item(item) => {
+                    if let Some(found) = visit(resume, item.token, item.node_offset) {
+                        return Ok(Some(found))
+                    }
+                },
+                AttributedStep::Skipped => (),
+                AttributedStep::Stopped => return Ok(None)
+            }
+        }
+    }
+
+    /// Same as `find_compatible()` but stops when `budget` runs out, see the module documentation
+    pub fn find_compatible_budgeted(&self, compat: &[u8], budget: &mut Budget, resume: &mut Resume)
+        -> Result<Option<Token<'_>>, BudgetError<Infallible>> {
+        resume.bind(self, Op::FindCompatible, self.root_offset);
+        let found = self.walk_budgeted(budget, resume, |_, token, node| {
+            let matched = token.name() == props::COMPATIBLE && matches!(token, Token::Property(_, _, _)) && prop_has_string(&token, compat);
+            if matched { Some(node) } else { None }
+        })?;
+        resume.finish(Ok(found.and_then(|node| self.node_at_offset(node))))
+    }
+
+    /// Same as `get_phandle()` but stops when `budget` runs out, see the module documentation
+    pub fn get_phandle_budgeted(&self, phandle: u32, budget: &mut Budget, resume: &mut Resume)
+        -> Result<Option<Token<'_>>, BudgetError<Infallible>> {
+        /* zero is not a valid phandle */
+        if phandle == 0 { return resume.finish(Ok(None)) }
+
+        resume.bind(self, Op::GetPhandle, self.root_offset);
+        let found = self.walk_budgeted(budget, resume, |_, token, node| {
+            let matched = matches!(token.name(), props::PHANDLE | props::LINUX_PHANDLE)
+                && matches!(token, Token::Property(_, _, _))
+                && token.prop_u32(0) == Some(phandle);
+            if matched { Some(node) } else { None }
+        })?;
+        resume.finish(Ok(found.and_then(|node| self.node_at_offset(node))))
+    }
+
+    /// Same as `build_phandle_index()` but stops when `budget` runs out, see the module documentation.
+    /// Entries found before running out are kept in `storage`, which must be passed again to continue.
+    pub fn build_phandle_index_budgeted<'b>(&self, storage: &'b mut [PhandleEntry], budget: &mut Budget, resume: &mut Resume)
+        -> Result<PhandleIndex<'b>, BudgetError<IndexError>> {
+        resume.bind(self, Op::PhandleIndex, self.root_offset);
+        self.walk_budgeted(budget, resume, |resume, token, node| {
+            if !matches!(token.name(), props::PHANDLE | props::LINUX_PHANDLE) || !matches!(token, Token::Property(_, _, _)) {
+                return None
+            }
+            if let Some(phandle) = token.prop_u32(0) {
+                /* Keep counting to report the required size */
+                if let Some(entry) = storage.get_mut(resume.count) {
+                    *entry = PhandleEntry::new(phandle, node);
+                }
+                resume.count += 1;
+            }
+            None::<()>
+        })?;
+
+        let count = resume.count;
+        resume.finish(PhandleIndex::from_entries(self, storage, count).map_err(BudgetError::Failed))
+    }
+}
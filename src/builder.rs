@@ -0,0 +1,422 @@
+//! # Builder
+//! Sequential construction of a devicetree blob in a caller-provided buffer, without alloc.
+//!
+//! ```
+//! use static_dt_rs::{DeviceTree, DtbBuilder};
+//!
+//! let mut buf = [0u8; 256];
+//! let mut builder = DtbBuilder::new(&mut buf);
+//! builder.begin_node(b"").unwrap();
+//! builder.prop_str(b"model", b"Test board").unwrap();
+//! builder.begin_node(b"cpus").unwrap();
+//! builder.prop_u32(b"#address-cells", 1).unwrap();
+//! builder.end_node().unwrap();
+//! builder.end_node().unwrap();
+//! let fdt = builder.finish().unwrap();
+//!
+//! let dt = DeviceTree::parse(fdt).unwrap();
+//! assert!(dt.get_node_by_path(b"/cpus").is_some());
+//! ```
+//!
+
+use core::convert::TryFrom;
+
+use crate::raw;
+use crate::utils::dt_hash;
+
+/// Number of property names `DtbBuilder` indexes by default
+pub const STRING_INDEX_CAPACITY: usize = 32;
+
+/// # BuildError
+/// Errors returned by `DtbBuilder`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// The buffer is too small
+    NoSpace,
+
+    /// The call is not allowed in the current state, e.g. a property outside of a node,
+    /// a reservation after the first node or finishing with open nodes
+    InvalidState,
+
+    /// A name contains a NUL
+    InvalidName,
+}
+
+/// # DtbBuilder
+/// Writes a devicetree blob token by token into a buffer.
+/// The structure block grows from the start of the buffer and the strings block from the end,
+/// `finish()` moves the strings block after the structure block.
+/// Property names are stored once in the strings block. The first `N` names are kept in a sorted
+/// index of hashes so finding a name again is a binary search, names added after the index is full
+/// are found by scanning the strings added since. `BuildReport` tells if `N` was too small.
+pub struct DtbBuilder<'a, const N: usize = STRING_INDEX_CAPACITY> {
+    buf: &'a mut [u8],
+
+    /* End of the reservation block, or of the structure block once a node was started */
+    end: usize,
+    struct_start: usize,
+    /* Start of the strings block at the end of buf */
+    strings_start: usize,
+
+    /* (hash, offset in the final strings block) of the first N names, sorted */
+    index: [(u32, u32); N],
+    indexed: usize,
+    /* Start of the last indexed name, names below are not in the index */
+    indexed_floor: usize,
+    strings: usize,
+    string_compares: usize,
+
+    depth: usize,
+    root_done: bool,
+    version: u32,
+    boot_cpuid_phys: u32,
+}
+
+/// # BuildReport
+/// Statistics of a finished `DtbBuilder`, returned by `finish_with_report()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BuildReport {
+    /// Size of the blob
+    pub totalsize: usize,
+
+    /// Number of names in the strings block
+    pub strings: usize,
+
+    /// Number of names which did not fit in the string index, raise `N` if non-zero
+    pub unindexed: usize,
+
+    /// Number of names compared while looking up property names
+    pub string_compares: usize,
+}
+
+impl BuildReport {
+    /// Returns true if the string index was too small and lookups fell back to scanning
+    pub fn index_overflowed(&self) -> bool {
+        self.unindexed != 0
+    }
+}
+
+impl<'a> DtbBuilder<'a> {
+
+    /// Create a builder writing into `buf`, version 17 by default
+    pub fn new(buf: &'a mut [u8]) -> DtbBuilder<'a> {
+        DtbBuilder::with_string_index(buf)
+    }
+}
+
+impl<'a, const N: usize> DtbBuilder<'a, N> {
+
+    /// Create a builder writing into `buf` which indexes up to `N` property names, version 17 by default
+    pub fn with_string_index(buf: &'a mut [u8]) -> DtbBuilder<'a, N> {
+        let len = buf.len();
+        DtbBuilder {
+            buf,
+            end: raw::HEADER_SIZE,
+            struct_start: 0,
+            strings_start: len,
+            index: [(0, 0); N],
+            indexed: 0,
+            indexed_floor: len,
+            strings: 0,
+            string_compares: 0,
+            depth: 0,
+            root_done: false,
+            version: 17,
+            boot_cpuid_phys: 0,
+        }
+    }
+
+    /// Set the header version, 16 or 17.
+    /// `size_dt_struct` is only written for version 17 or later.
+    pub fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    /// Set the `boot_cpuid_phys` header field
+    pub fn set_boot_cpuid_phys(&mut self, id: u32) {
+        self.boot_cpuid_phys = id;
+    }
+
+    /// Returns the number of bytes written so far, excluding the header
+    pub fn len(&self) -> usize {
+        self.end - raw::HEADER_SIZE + self.buf.len() - self.strings_start
+    }
+
+    /// Returns true if nothing has been written yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write `data` at the end of the reservation or structure block
+    fn push(&mut self, data: &[u8]) -> Result<(), BuildError> {
+        let end = self.end.checked_add(data.len()).ok_or(BuildError::NoSpace)?;
+        if end > self.strings_start {
+            return Err(BuildError::NoSpace)
+        }
+
+        self.buf[self.end..end].copy_from_slice(data);
+        self.end = end;
+        Ok(())
+    }
+
+    /// Pad the structure block with zeroes to the next token
+    fn align(&mut self) -> Result<(), BuildError> {
+        let pad = self.end.div_ceil(raw::FDT_STRUCT_ALIGN) * raw::FDT_STRUCT_ALIGN - self.end;
+        self.push(&[0; raw::FDT_STRUCT_ALIGN][..pad])
+    }
+
+    /// Returns true if the name ending at `top` (before its NUL) is `name`
+    fn string_is(&mut self, top: usize, name: &[u8]) -> bool {
+        self.string_compares += 1;
+        /* Strings are stored downwards, the one before in memory is terminated right before this one */
+        match (top - 1).checked_sub(name.len()) {
+            Some(start) if start >= self.strings_start => {
+                &self.buf[start..top - 1] == name && (start == self.strings_start || self.buf[start - 1] == 0)
+            },
+            _ => false
+        }
+    }
+
+    /// Returns the offset of `name` in the final strings block, adding it if missing
+    fn string(&mut self, name: &[u8]) -> Result<u32, BuildError> {
+        if name.contains(&0) {
+            return Err(BuildError::InvalidName)
+        }
+
+        let hash = dt_hash(name);
+        let first = self.index[..self.indexed].partition_point(|(h, _)| *h < hash);
+        for i in first..self.indexed {
+            let (h, offset) = self.index[i];
+            if h != hash {
+                break
+            }
+            if self.string_is(self.buf.len() - offset as usize, name) {
+                return Ok(offset)
+            }
+        }
+
+        /* Strings are stored downwards, the first one at the end of buf. Only the ones not indexed are scanned */
+        let mut top = self.indexed_floor;
+        while top > self.strings_start {
+            self.string_compares += 1;
+            let s = &self.buf[self.strings_start..top - 1];
+            let start = s.iter().rposition(|c| *c == 0).map_or(self.strings_start, |i| self.strings_start + i + 1);
+            if &self.buf[start..top - 1] == name {
+                return Ok((self.buf.len() - top) as u32)
+            }
+            top = start;
+        }
+
+        let start = self.strings_start.checked_sub(name.len() + 1).ok_or(BuildError::NoSpace)?;
+        if start < self.end {
+            return Err(BuildError::NoSpace)
+        }
+        let offset = (self.buf.len() - self.strings_start) as u32;
+        self.buf[start..start + name.len()].copy_from_slice(name);
+        self.buf[start + name.len()] = 0;
+        self.strings_start = start;
+        self.strings += 1;
+
+        if self.indexed < N {
+            self.index.copy_within(first..self.indexed, first + 1);
+            self.index[first] = (hash, offset);
+            self.indexed += 1;
+            self.indexed_floor = start;
+        }
+        Ok(offset)
+    }
+
+    /// Add a memory reservation, must be called before the root node is started
+    pub fn add_reservation(&mut self, address: u64, size: u64) -> Result<(), BuildError> {
+        if self.struct_start != 0 {
+            return Err(BuildError::InvalidState)
+        }
+        self.push(&address.to_be_bytes())?;
+        self.push(&size.to_be_bytes())
+    }
+
+    /// Start a node named `name`, the root node is named ""
+    pub fn begin_node(&mut self, name: &[u8]) -> Result<(), BuildError> {
+        if name.contains(&0) {
+            return Err(BuildError::InvalidName)
+        }
+        if self.root_done {
+            return Err(BuildError::InvalidState)
+        }
+
+        /* Terminate the reservation block */
+        if self.struct_start == 0 {
+            self.push(&[0; 16])?;
+            self.struct_start = self.end;
+        }
+
+        let start = self.end;
+        self.push(&raw::FDT_BEGIN_NODE.to_be_bytes())
+            .and_then(|_| self.push(name))
+            .and_then(|_| self.push(&[0]))
+            .and_then(|_| self.align())
+            .inspect_err(|_| self.end = start)?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// End the current node
+    pub fn end_node(&mut self) -> Result<(), BuildError> {
+        if self.depth == 0 {
+            return Err(BuildError::InvalidState)
+        }
+        self.push(&raw::FDT_END_NODE.to_be_bytes())?;
+        self.depth -= 1;
+        if self.depth == 0 {
+            self.root_done = true;
+        }
+        Ok(())
+    }
+
+    /// Add a NOP token
+    pub fn nop(&mut self) -> Result<(), BuildError> {
+        if self.struct_start == 0 {
+            return Err(BuildError::InvalidState)
+        }
+        self.push(&raw::FDT_NOP.to_be_bytes())
+    }
+
+    /// Add a property named `name` with `value` to the current node
+    pub fn property(&mut self, name: &[u8], value: &[u8]) -> Result<(), BuildError> {
+        if self.depth == 0 {
+            return Err(BuildError::InvalidState)
+        }
+        let len = u32::try_from(value.len()).map_err(|_| BuildError::NoSpace)?;
+        let nameoff = self.string(name)?;
+
+        let start = self.end;
+        let result = self.push(&raw::FDT_PROP.to_be_bytes())
+            .and_then(|_| self.push(&len.to_be_bytes()))
+            .and_then(|_| self.push(&nameoff.to_be_bytes()))
+            .and_then(|_| self.push(value))
+            .and_then(|_| self.align());
+        if result.is_err() {
+            self.end = start;
+        }
+        result
+    }
+
+    /// Add an empty property
+    pub fn prop_empty(&mut self, name: &[u8]) -> Result<(), BuildError> {
+        self.property(name, &[])
+    }
+
+    /// Add a property with one cell
+    pub fn prop_u32(&mut self, name: &[u8], value: u32) -> Result<(), BuildError> {
+        self.property(name, &value.to_be_bytes())
+    }
+
+    /// Add a property with two cells
+    pub fn prop_u64(&mut self, name: &[u8], value: u64) -> Result<(), BuildError> {
+        self.property(name, &value.to_be_bytes())
+    }
+
+    /// Add a property with a list of cells
+    pub fn prop_cells(&mut self, name: &[u8], cells: &[u32]) -> Result<(), BuildError> {
+        if self.depth == 0 {
+            return Err(BuildError::InvalidState)
+        }
+        let len = cells.len().checked_mul(4).ok_or(BuildError::NoSpace)?;
+        let nameoff = self.string(name)?;
+
+        let start = self.end;
+        let mut result = self.push(&raw::FDT_PROP.to_be_bytes())
+            .and_then(|_| self.push(&(len as u32).to_be_bytes()))
+            .and_then(|_| self.push(&nameoff.to_be_bytes()));
+        for cell in cells {
+            result = result.and_then(|_| self.push(&cell.to_be_bytes()));
+        }
+        if result.is_err() {
+            self.end = start;
+        }
+        result
+    }
+
+    /// Add a property with a NUL-terminated string
+    pub fn prop_str(&mut self, name: &[u8], value: &[u8]) -> Result<(), BuildError> {
+        if value.contains(&0) {
+            return Err(BuildError::InvalidName)
+        }
+        if self.depth == 0 {
+            return Err(BuildError::InvalidState)
+        }
+        let len = u32::try_from(value.len() + 1).map_err(|_| BuildError::NoSpace)?;
+        let nameoff = self.string(name)?;
+
+        let start = self.end;
+        let result = self.push(&raw::FDT_PROP.to_be_bytes())
+            .and_then(|_| self.push(&len.to_be_bytes()))
+            .and_then(|_| self.push(&nameoff.to_be_bytes()))
+            .and_then(|_| self.push(value))
+            .and_then(|_| self.push(&[0]))
+            .and_then(|_| self.align());
+        if result.is_err() {
+            self.end = start;
+        }
+        result
+    }
+
+    /// Write FDT_END, move the strings block after the structure block and write the header.
+    /// Returns the blob, `totalsize` bytes at the start of the buffer.
+    pub fn finish(self) -> Result<&'a [u8], BuildError> {
+        self.finish_with_report().map(|(fdt, _)| fdt)
+    }
+
+    /// Same as `finish()`, also returns statistics of the build
+    pub fn finish_with_report(mut self) -> Result<(&'a [u8], BuildReport), BuildError> {
+        if !self.root_done {
+            return Err(BuildError::InvalidState)
+        }
+        self.push(&raw::FDT_END.to_be_bytes())?;
+
+        /* Strings are stored downwards with the first at the end, reverse the block
+         * and then each string to get them in order */
+        let strings = &mut self.buf[self.strings_start..];
+        strings.reverse();
+        let mut start = 0;
+        while start < strings.len() {
+            /* Each string now begins with its NUL */
+            let len = strings[start + 1..].iter().position(|c| *c == 0).map_or(strings.len() - start, |i| i + 1);
+            strings[start..start + len].reverse();
+            start += len;
+        }
+
+        let strings_size = self.buf.len() - self.strings_start;
+        self.buf.copy_within(self.strings_start.., self.end);
+        let totalsize = self.end + strings_size;
+
+        let header = raw::Header {
+            magic: raw::FDT_MAGIC,
+            totalsize: totalsize as u32,
+            off_dt_struct: self.struct_start as u32,
+            off_dt_strings: self.end as u32,
+            off_mem_rsvmap: raw::HEADER_SIZE as u32,
+            version: self.version,
+            last_comp_version: 16,
+            boot_cpuid_phys: self.boot_cpuid_phys,
+            size_dt_strings: strings_size as u32,
+            size_dt_struct: if self.version >= 17 { (self.end - self.struct_start) as u32 } else { 0 },
+        };
+        let fields = [
+            header.magic, header.totalsize, header.off_dt_struct, header.off_dt_strings, header.off_mem_rsvmap,
+            header.version, header.last_comp_version, header.boot_cpuid_phys, header.size_dt_strings, header.size_dt_struct
+        ];
+        for (i, field) in fields.iter().enumerate() {
+            self.buf[i*4..i*4 + 4].copy_from_slice(&field.to_be_bytes());
+        }
+
+        let report = BuildReport {
+            totalsize,
+            strings: self.strings,
+            unindexed: self.strings - self.indexed,
+            string_compares: self.string_compares,
+        };
+        let buf: &'a [u8] = self.buf;
+        Ok((&buf[..totalsize], report))
+    }
+}
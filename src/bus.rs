@@ -0,0 +1,207 @@
+//! # Bus context
+//! What decoding the `reg` and `interrupts` of a child depends on besides the child itself:
+//! the cell sizes of the bus, the `ranges` of every ancestor and the interrupt parent.
+//! A `BusContext` resolves these once per bus, so probing many identical children
+//! does not walk up the tree for each of them.
+//!
+
+use crate::address::translate_on_bus;
+use crate::interrupts::interrupt_cells;
+use crate::{props, CellSizes, InterruptIterator, IrqSpec, RangeEntry, RegIterator, Token};
+
+/// Maximum number of flattened `ranges` windows kept by a `BusContext`
+pub const MAX_BUS_WINDOWS: usize = 8;
+
+/// How addresses on the bus map to CPU addresses
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mapping {
+    /// Every ancestor has an empty `ranges`
+    Identity,
+    /// Through the first `n` windows
+    Windows(usize),
+    /// More than `MAX_BUS_WINDOWS` windows, translate by walking the ancestors
+    Walk,
+    /// An ancestor has no `ranges`
+    Unmapped,
+}
+
+/// # BusContext
+/// The resolved cell sizes, address translation and default interrupt parent of a bus,
+/// see `Token::bus_context()` and the `_in` variants of the `Token` decoding methods.
+///
+/// The `ranges` of the bus and its ancestors are flattened into at most `MAX_BUS_WINDOWS` windows
+/// from bus addresses to CPU addresses. A bus needing more falls back to walking the ancestors,
+/// giving the same result as `Token::translate_address()` either way.
+#[derive(Debug, Copy, Clone)]
+pub struct BusContext<'a> {
+    bus: Token<'a>,
+    sizes: CellSizes,
+    mapping: Mapping,
+    windows: [RangeEntry; MAX_BUS_WINDOWS],
+    interrupt_parent: Option<Token<'a>>,
+    interrupt_cells: usize,
+}
+
+/// Compose `window`, mapping bus addresses to addresses of a parent bus, with `range` of that parent bus
+fn compose(window: &RangeEntry, range: &RangeEntry) -> Option<RangeEntry> {
+    let window_end = window.parent_address.saturating_add(window.size);
+    let range_end = range.child_address.saturating_add(range.size);
+    let start = window.parent_address.max(range.child_address);
+    let end = window_end.min(range_end);
+    if start >= end {
+        return None
+    }
+    Some(RangeEntry {
+        child_address: window.child_address + (start - window.parent_address),
+        parent_address: range.parent_address.checked_add(start - range.child_address)?,
+        size: end - start
+    })
+}
+
+impl<'a> BusContext<'a> {
+
+    /// Resolve the context of the children of `bus`
+    /// Returns None if `bus` is not a node.
+    ///
+    pub fn new(bus: Token<'a>) -> Option<BusContext<'a>> {
+        if !matches!(bus, Token::BeginNode(_, _, _)) {
+            return None
+        }
+
+        let interrupt_parent = bus.interrupt_parent();
+        let mut ctx = BusContext {
+            bus,
+            sizes: CellSizes::of_parent(&bus),
+            mapping: Mapping::Identity,
+            windows: [RangeEntry { child_address: 0, parent_address: 0, size: 0 }; MAX_BUS_WINDOWS],
+            interrupt_parent,
+            interrupt_cells: interrupt_cells(interrupt_parent),
+        };
+        ctx.mapping = ctx.flatten_ranges();
+        Some(ctx)
+    }
+
+    /// Flatten the `ranges` from the bus up to the root into `windows`, see `Token::translate_address()`
+    fn flatten_ranges(&mut self) -> Mapping {
+        let mut mapping = Mapping::Identity;
+        let mut node = self.bus;
+
+        /* The root node is the CPU address space */
+        while let Some(parent) = node.parent() {
+            let (child, parent_sizes) = (CellSizes::of_parent(&node), CellSizes::of_parent(&parent));
+            let mut ranges = match node.ranges_with(&child, &parent_sizes) {
+                Some(ranges) => ranges.peekable(),
+                None => return Mapping::Unmapped
+            };
+
+            if ranges.peek().is_some() {
+                mapping = match mapping {
+                    Mapping::Identity => {
+                        let mut n = 0;
+                        for range in ranges {
+                            match self.windows.get_mut(n) {
+                                Some(window) => *window = range,
+                                None => return Mapping::Walk
+                            }
+                            n += 1;
+                        }
+                        Mapping::Windows(n)
+                    },
+                    Mapping::Windows(count) => {
+                        let mut windows = self.windows;
+                        let mut n = 0;
+                        for window in &self.windows[..count] {
+                            for range in node.ranges_with(&child, &parent_sizes).into_iter().flatten() {
+                                if let Some(composed) = compose(window, &range) {
+                                    match windows.get_mut(n) {
+                                        Some(slot) => *slot = composed,
+                                        None => return Mapping::Walk
+                                    }
+                                    n += 1;
+                                }
+                            }
+                        }
+                        self.windows = windows;
+                        Mapping::Windows(n)
+                    },
+                    other => other
+                };
+            }
+            node = parent;
+        }
+        mapping
+    }
+
+    /// Returns the bus node
+    pub fn bus(&self) -> Token<'a> {
+        self.bus
+    }
+
+    /// Returns the cell sizes of the `reg` of the children
+    pub fn sizes(&self) -> &CellSizes {
+        &self.sizes
+    }
+
+    /// Returns the flattened windows from bus addresses to CPU addresses
+    /// Returns None if the bus is identity mapped, not mapped or needs more than `MAX_BUS_WINDOWS` windows.
+    ///
+    pub fn windows(&self) -> Option<&[RangeEntry]> {
+        match self.mapping {
+            Mapping::Windows(n) => Some(&self.windows[..n]),
+            _ => None
+        }
+    }
+
+    /// Returns the interrupt parent of children without their own `interrupt-parent`
+    pub fn interrupt_parent(&self) -> Option<Token<'a>> {
+        self.interrupt_parent
+    }
+
+    /// Translate `address` on the bus to a CPU address, same as `Token::translate_address()` on a child
+    /// Returns None if the bus is not mapped or `address` is outside of its ranges.
+    ///
+    pub fn translate(&self, address: u64) -> Option<u64> {
+        match self.mapping {
+            Mapping::Identity => Some(address),
+            Mapping::Windows(n) => self.windows[..n].iter().find_map(|window| window.translate(address)),
+            Mapping::Walk => translate_on_bus(self.bus, address),
+            Mapping::Unmapped => None
+        }
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Same as `reg()` but with the cell sizes of `ctx`, for a child of its bus.
+    /// Use `BusContext::translate()` to map the addresses to CPU addresses.
+    ///
+    pub fn reg_in(&self, ctx: &BusContext) -> Option<RegIterator<'a>> {
+        self.reg_with(ctx.sizes())
+    }
+
+    /// Same as `interrupts()` but with the interrupt parent of `ctx` unless this node has its own
+    /// `interrupt-parent`, for a child of its bus
+    ///
+    pub fn interrupts_in(&self, ctx: &BusContext<'a>) -> InterruptIterator<'a> {
+        self.interrupts_from(|node| match node.get_prop(props::INTERRUPT_PARENT) {
+            Some(prop) => {
+                let parent = prop.prop_phandle();
+                (parent, interrupt_cells(parent))
+            },
+            None => (ctx.interrupt_parent, ctx.interrupt_cells)
+        })
+    }
+
+    /// Same as `irqs()` but with the interrupt parent of `ctx`, see `interrupts_in()`
+    ///
+    pub fn irqs_in(&self, ctx: &BusContext<'a>) -> impl Iterator<Item = IrqSpec<'a>> {
+        self.interrupts_in(ctx).map(IrqSpec::from)
+    }
+
+    /// Resolve the `BusContext` of the children of this node
+    /// Returns None if token is not a node.
+    ///
+    pub fn bus_context(&self) -> Option<BusContext<'a>> {
+        BusContext::new(*self)
+    }
+}
@@ -0,0 +1,119 @@
+//! # Cells
+//! Reading property values as arrays of cells. A value which is not a multiple of 4 bytes
+//! is usually an encoding bug: the strict accessors report it as `PropError::TrailingBytes`,
+//! while the lenient ones ignore the incomplete cell at the end.
+//!
+
+use crate::{props, utils, Token};
+
+/// # PropError
+/// Errors returned by the strict cell accessors
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PropError {
+    /// The token is not a property
+    NotAProperty,
+
+    /// The value at `offset` in the structure block is not a multiple of 4 bytes,
+    /// with `count` bytes after the last complete cell
+    TrailingBytes { offset: usize, count: usize },
+
+    /// The cell index is past the end of the value
+    OutOfRange,
+}
+
+/// Returns true if the property `name` always holds cells, e.g. `reg` or `#address-cells`.
+/// Used by `DeviceTree::warnings()` to report values which are not a multiple of 4 bytes,
+/// as other properties may legitimately hold strings or bytes.
+pub(crate) fn is_cell_property(name: &[u8]) -> bool {
+    name.first() == Some(&b'#') || matches!(name,
+        props::REG | props::RANGES | b"dma-ranges" | props::PHANDLE | props::LINUX_PHANDLE
+        | props::INTERRUPTS | props::INTERRUPTS_EXTENDED | props::INTERRUPT_PARENT | b"interrupt-map-mask"
+        | b"clocks" | b"clock-frequency" | b"virtual-reg")
+}
+
+/// # CellIterator
+/// Iterates over the cells of a property value, see `Token::prop_cells()`
+#[derive(Debug, Clone)]
+pub struct CellIterator<'a> {
+    val: &'a [u8],
+}
+
+impl<'a> Iterator for CellIterator<'a> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cell = utils::try_read_fdt_u32(self.val, 0)?;
+        self.val = &self.val[4..];
+        Some(cell)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.val.len() / 4, Some(self.val.len() / 4))
+    }
+}
+
+impl ExactSizeIterator for CellIterator<'_> {}
+
+impl<'a> Token<'a> {
+
+    /// Returns the number of cells of this property
+    /// Returns `PropError::TrailingBytes` if the value is not a multiple of 4 bytes.
+    ///
+    pub fn prop_cell_count(&self) -> Result<usize, PropError> {
+        match self {
+            Token::Property(_, _, val) if val.len() % 4 != 0 => Err(PropError::TrailingBytes {
+                offset: self.value_offset().unwrap_or(0),
+                count: val.len() % 4
+            }),
+            Token::Property(_, _, val) => Ok(val.len() / 4),
+            _ => Err(PropError::NotAProperty)
+        }
+    }
+
+    /// Returns a iterator over the cells of this property.
+    /// This is the lenient variant: trailing bytes after the last complete cell are ignored,
+    /// use `prop_cells_strict()` to detect them.
+    /// Returns a empty iterator if not a property.
+    ///
+    pub fn prop_cells(&self) -> CellIterator<'a> {
+        let val: &'a [u8] = match self {
+            Token::Property(_, _, val) => val,
+            _ => &[]
+        };
+        CellIterator { val: &val[..val.len() / 4 * 4] }
+    }
+
+    /// Same as `prop_cells()` but returns `PropError::TrailingBytes` if the value is not a multiple of 4 bytes
+    ///
+    pub fn prop_cells_strict(&self) -> Result<CellIterator<'a>, PropError> {
+        self.prop_cell_count()?;
+        Ok(self.prop_cells())
+    }
+
+    /// Same as `prop_u32()` but returns `PropError::TrailingBytes` if the value is not a multiple of 4 bytes,
+    /// even if cell `n` is complete.
+    ///
+    pub fn prop_u32_strict(&self, n: usize) -> Result<u32, PropError> {
+        if n >= self.prop_cell_count()? {
+            return Err(PropError::OutOfRange)
+        }
+        self.prop_u32(n).ok_or(PropError::OutOfRange)
+    }
+
+    /// Returns the value of this property as exactly `N` cells, e.g. a `reg` entry of 2 cells or a 3-cell PCI address,
+    /// decoded in one call without a `CellIterator`. See `value()` for the alignment this relies on.
+    /// Returns None if not a property, the value is not exactly `N` cells long or is misaligned (corrupt blob).
+    ///
+    pub fn cells_exact<const N: usize>(&self) -> Option<[u32; N]> {
+        let val = self.value()?;
+        if val.len() != N * 4 || self.value_offset()? % 4 != 0 {
+            return None
+        }
+
+        let mut cells = [0u32; N];
+        for (cell, bytes) in cells.iter_mut().zip(val.chunks_exact(4)) {
+            *cell = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        Some(cells)
+    }
+}
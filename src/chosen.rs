@@ -0,0 +1,191 @@
+//! # Chosen and aliases
+//! Lookup of the `/chosen`, `/aliases` and `/__symbols__` nodes and paths starting with an alias.
+//! `/aliases` and `/__symbols__` map names to paths, see `Token::as_string_map()`.
+//!
+//! The console is looked up the way Linux does, using the first of these which is present:
+//! 1. `stdout-path` in `/chosen`
+//! 2. `linux,stdout-path` in `/chosen`
+//! 3. `stdout` in `/aliases`
+//!
+//! A present but dangling source does not fall back to the next one.
+//!
+
+use crate::{props, DeviceTree, PathLookupError, Token};
+
+/// # AliasError
+/// Errors returned by `DeviceTree::resolve_alias()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AliasError {
+    /// There is no `/aliases` node
+    NoAliasesNode,
+
+    /// The `/aliases` node has no property with the name of the alias
+    NoAlias,
+
+    /// The alias is not a NUL-terminated string
+    NotAString,
+
+    /// The path of the alias does not lead to a node, indices of the error are into the stored path
+    Dangling(PathLookupError),
+}
+
+/// # StdoutSource
+/// Where the console of `DeviceTree::resolve_stdout()` was found
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StdoutSource {
+    /// `stdout-path` in `/chosen`
+    StdoutPath,
+
+    /// `linux,stdout-path` in `/chosen`
+    LinuxStdoutPath,
+
+    /// `stdout` in `/aliases`
+    Alias,
+}
+
+/// # Stdout
+/// The console node, see `DeviceTree::resolve_stdout()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Stdout<'a> {
+    /// The console node
+    pub node: Token<'a>,
+    /// Text after ':' in the path, e.g. "115200n8". Always None for a phandle.
+    pub options: Option<&'a [u8]>,
+    /// The property the console was found in
+    pub source: StdoutSource,
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the properties of this node as (name, first string of the value),
+    /// e.g. the entries of `/aliases`. Properties whose value does not end in NUL or starts with
+    /// an empty string are skipped, which excludes most cell values.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn as_string_map(&self) -> impl Iterator<Item = (&'a [u8], &'a [u8])> {
+        self.props().filter_map(|prop| match prop {
+            Token::Property(_, name, val) if val.last() == Some(&0) => {
+                Some((name, prop.prop_str().filter(|s| !s.is_empty())?))
+            },
+            _ => None
+        })
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns the `/chosen` node
+    /// Returns None if there is no such node.
+    ///
+    pub fn chosen(&self) -> Option<Token<'_>> {
+        self.root().get_node(b"chosen")
+    }
+
+    /// Returns the path of alias `name` from the `/aliases` node
+    /// Returns None if there is no such alias.
+    ///
+    #[inline]
+    pub fn alias<S: AsRef<[u8]>>(&self, name: S) -> Option<&[u8]> {
+        self.alias_bytes(name.as_ref())
+    }
+
+    /* Non-generic core of alias(), see "Names" in the crate documentation */
+    fn alias_bytes(&self, name: &[u8]) -> Option<&[u8]> {
+        self.aliases().find(|(alias, _)| *alias == name).map(|(_, path)| path)
+    }
+
+    /// Returns a iterator over the `/aliases` node as (alias, path)
+    /// Returns a empty iterator if there is no such node.
+    ///
+    pub fn aliases(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.root().get_node(b"aliases").into_iter().flat_map(|aliases| aliases.as_string_map())
+    }
+
+    /// Returns a iterator over the `/__symbols__` node as (label, path), present in trees compiled with `dtc -@`
+    /// Returns a empty iterator if there is no such node.
+    ///
+    pub fn symbols(&self) -> impl Iterator<Item = (&[u8], &[u8])> {
+        self.root().get_node(b"__symbols__").into_iter().flat_map(|symbols| symbols.as_string_map())
+    }
+
+    /// Returns the path of label `name` from the `/__symbols__` node
+    /// Returns None if there is no such label.
+    ///
+    #[inline]
+    pub fn symbol<S: AsRef<[u8]>>(&self, name: S) -> Option<&[u8]> {
+        self.symbol_bytes(name.as_ref())
+    }
+
+    /* Non-generic core of symbol(), see "Names" in the crate documentation */
+    fn symbol_bytes(&self, name: &[u8]) -> Option<&[u8]> {
+        self.symbols().find(|(label, _)| *label == name).map(|(_, path)| path)
+    }
+
+    /// Returns the node alias `name` points to, reporting which step failed
+    ///
+    pub fn resolve_alias(&self, name: &[u8]) -> Result<Token<'_>, AliasError> {
+        let aliases = self.root().get_node(b"aliases").ok_or(AliasError::NoAliasesNode)?;
+        let path = aliases.get_prop(name).ok_or(AliasError::NoAlias)?
+            .prop_str().ok_or(AliasError::NotAString)?;
+        self.get_node_by_path_verbose(path).map_err(AliasError::Dangling)
+    }
+
+    /// Find a node by a full path (e.g. "/soc/serial@10000000")
+    /// or a path beginning with an alias (e.g. "serial0" or "soc/serial@10000000").
+    /// Returns None if the alias or node does not exist.
+    ///
+    pub fn find_node(&self, path: &[u8]) -> Option<Token<'_>> {
+        if path.first() == Some(&b'/') {
+            return self.get_node_by_path(path)
+        }
+
+        let mut components = path.split(|c| *c == b'/');
+        let alias = self.alias(components.next()?)?;
+        components
+            .filter(|component| !component.is_empty())
+            .try_fold(self.get_node_by_path(alias)?, |node, component| node.get_node(component))
+    }
+
+    /// Returns the node and options (text after ':', e.g. "115200n8") of the console,
+    /// see `resolve_stdout()`.
+    /// Returns None if there is no console or its node does not exist.
+    ///
+    pub fn stdout(&self) -> Option<(Token<'_>, Option<&[u8]>)> {
+        self.resolve_stdout().map(|stdout| (stdout.node, stdout.options))
+    }
+
+    /// Returns the console from the first present of `stdout-path`, `linux,stdout-path` and `/aliases/stdout`.
+    /// The value is a path or alias with optional options after ':',
+    /// or a 4 byte phandle as used by some vendor trees.
+    /// Returns None if there is no source or the first present one does not lead to a node.
+    ///
+    pub fn resolve_stdout(&self) -> Option<Stdout<'_>> {
+        let chosen = self.chosen();
+        let (prop, source) = [
+            (chosen.and_then(|chosen| chosen.get_prop(props::STDOUT_PATH)), StdoutSource::StdoutPath),
+            (chosen.and_then(|chosen| chosen.get_prop(props::LINUX_STDOUT_PATH)), StdoutSource::LinuxStdoutPath),
+            (self.root().get_node(b"aliases").and_then(|aliases| aliases.get_prop(b"stdout")), StdoutSource::Alias),
+        ].iter().find_map(|(prop, source)| Some(((*prop)?, *source)))?;
+
+        let val = match prop {
+            Token::Property(_, _, val) => val,
+            _ => return None
+        };
+
+        /* A path is a non-empty string, anything else of 4 bytes is a phandle */
+        let stdout = match prop.prop_str() {
+            Some(s) if !s.is_empty() && s.len() + 1 == val.len() => s,
+            _ if val.len() == 4 => {
+                let node = self.get_phandle(prop.prop_u32(0)?)?;
+                return Some(Stdout { node, options: None, source })
+            },
+            _ => return None
+        };
+
+        let (path, options) = match stdout.iter().position(|c| *c == b':') {
+            Some(i) => (&stdout[..i], Some(&stdout[i + 1..])),
+            None => (stdout, None)
+        };
+        Some(Stdout { node: self.find_node(path)?, options, source })
+    }
+}
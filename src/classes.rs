@@ -0,0 +1,108 @@
+//! # Device classes
+//! Curated `compatible` lists for common classes of devices, available with the `classes` feature.
+//! The lists are opinionated and incomplete by nature, use `DeviceTree::probe_matches()`
+//! with a table of your own for anything more specific.
+//!
+//! ```
+//! use static_dt_rs::DeviceTree;
+//! use static_dt_rs::classes::DeviceClass;
+//!
+//! let dt = DeviceTree::parse(include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/console.dtb"))).unwrap();
+//! let uart = dt.serial_ports().next().unwrap();
+//! assert_eq!(uart.node.device_class(), Some(DeviceClass::Serial));
+//! ```
+//!
+
+use crate::{props, DeviceTree, DtMatch, PlatformDevice, Token};
+
+/// # DeviceClass
+/// A class of devices, see `device_class_table()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DeviceClass {
+    /// UARTs
+    Serial,
+
+    /// System timers, e.g. the ARM generic timer or the RISC-V CLINT
+    Timer,
+}
+
+static DEVICE_CLASSES: [DtMatch<DeviceClass>; 19] = [
+    DtMatch { compatible: b"ns16550a", data: DeviceClass::Serial },
+    DtMatch { compatible: b"ns16550", data: DeviceClass::Serial },
+    DtMatch { compatible: b"ns16750", data: DeviceClass::Serial },
+    DtMatch { compatible: b"ns8250", data: DeviceClass::Serial },
+    DtMatch { compatible: b"snps,dw-apb-uart", data: DeviceClass::Serial },
+    DtMatch { compatible: b"arm,pl011", data: DeviceClass::Serial },
+    DtMatch { compatible: b"arm,sbsa-uart", data: DeviceClass::Serial },
+    DtMatch { compatible: b"sifive,uart0", data: DeviceClass::Serial },
+    DtMatch { compatible: b"brcm,bcm2835-aux-uart", data: DeviceClass::Serial },
+    DtMatch { compatible: b"xlnx,xps-uartlite-1.00.a", data: DeviceClass::Serial },
+    DtMatch { compatible: b"arm,armv8-timer", data: DeviceClass::Timer },
+    DtMatch { compatible: b"arm,armv7-timer", data: DeviceClass::Timer },
+    DtMatch { compatible: b"arm,armv7-timer-mem", data: DeviceClass::Timer },
+    DtMatch { compatible: b"arm,sp804", data: DeviceClass::Timer },
+    DtMatch { compatible: b"riscv,clint0", data: DeviceClass::Timer },
+    DtMatch { compatible: b"sifive,clint0", data: DeviceClass::Timer },
+    DtMatch { compatible: b"riscv,aclint-mtimer", data: DeviceClass::Timer },
+    DtMatch { compatible: b"thead,c900-aclint-mtimer", data: DeviceClass::Timer },
+    DtMatch { compatible: b"starfive,jh7110-clint", data: DeviceClass::Timer },
+];
+
+/// Returns the table of `compatible` strings of every `DeviceClass`, usable with `Token::match_compatible()`
+pub fn device_class_table() -> &'static [DtMatch<DeviceClass>] {
+    &DEVICE_CLASSES
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns the class of this node by its most specific `compatible` string in `device_class_table()`
+    /// Returns None if not a node or no string is in the table.
+    ///
+    pub fn device_class(&self) -> Option<DeviceClass> {
+        self.match_compatible(&DEVICE_CLASSES).map(|entry| entry.data)
+    }
+
+    /// Guess the class of a node without a known `compatible` by its `device_type`
+    /// or the name before '@', e.g. "uart@1000" or "timer"
+    fn plausible_device_class(&self) -> Option<DeviceClass> {
+        let device_type = self.get_prop(props::DEVICE_TYPE).and_then(|prop| prop.prop_str());
+        let name = self.name().split(|c| *c == b'@').next().unwrap_or(&[]);
+        match (device_type, name) {
+            (Some(b"serial"), _) | (_, b"serial" | b"uart") => Some(DeviceClass::Serial),
+            (Some(b"timer"), _) | (_, b"timer") => Some(DeviceClass::Timer),
+            _ => None
+        }
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a iterator over the enabled nodes of `class` in tree order.
+    /// With `plausible` nodes whose `compatible` is not in `device_class_table()` are
+    /// also included if their `device_type` or name suggests the class, e.g. "uart@1000".
+    ///
+    pub fn devices_of_class(&self, class: DeviceClass, plausible: bool) -> impl Iterator<Item = PlatformDevice<'_>> {
+        self.nodes()
+            .filter(move |node| {
+                let found = match node.device_class() {
+                    None if plausible => node.plausible_device_class(),
+                    found => found
+                };
+                found == Some(class) && node.is_enabled()
+            })
+            .map(PlatformDevice::new)
+    }
+
+    /// Returns a iterator over the enabled UARTs with a known `compatible`, see `devices_of_class()`
+    ///
+    pub fn serial_ports(&self) -> impl Iterator<Item = PlatformDevice<'_>> {
+        self.devices_of_class(DeviceClass::Serial, false)
+    }
+
+    /// Returns a iterator over the enabled system timers with a known `compatible`, see `devices_of_class()`
+    ///
+    pub fn timers(&self) -> impl Iterator<Item = PlatformDevice<'_>> {
+        self.devices_of_class(DeviceClass::Timer, false)
+    }
+}
@@ -0,0 +1,63 @@
+//! # Clocks
+//! Provider side of the clock bindings: output names, `fixed-factor-clock` and
+//! resolving the rate of chains of fixed clocks.
+//!
+
+use crate::Token;
+
+/// Maximum number of providers followed by `Token::clock_rate_simple()`
+pub const MAX_CLOCK_CHAIN: usize = 8;
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the `clock-output-names` of this clock provider
+    /// Returns a empty iterator if there is no such property or token is not a node.
+    ///
+    pub fn clock_output_names(&self) -> impl Iterator<Item = &'a [u8]> {
+        self.get_prop(b"clock-output-names").into_iter().flat_map(|prop| prop.prop_strings())
+    }
+
+    /// Returns the (`clock-mult`, `clock-div`) of this `fixed-factor-clock` node
+    /// Returns None if any of them is missing.
+    ///
+    pub fn fixed_factor(&self) -> Option<(u32, u32)> {
+        let mult = self.get_prop(b"clock-mult")?.prop_u32(0)?;
+        let div = self.get_prop(b"clock-div")?.prop_u32(0)?;
+        Some((mult, div))
+    }
+
+    /// Returns the rate in Hz of this `fixed-clock` or `fixed-factor-clock` provider,
+    /// or of the first clock in `clocks` of a node which is not a clock provider.
+    /// Follows at most `MAX_CLOCK_CHAIN` providers, which must all be `fixed-clock` or `fixed-factor-clock`.
+    /// Returns None for chains with other providers (e.g. a programmable PLL),
+    /// missing properties, a zero divider or a rate not fitting in u64.
+    ///
+    pub fn clock_rate_simple(&self) -> Option<u64> {
+        /* Factors from this node towards the fixed clock, applied in reverse as each provider would */
+        let mut factors = [(1u32, 1u32); MAX_CLOCK_CHAIN];
+        let mut node = *self;
+
+        for depth in 0..=MAX_CLOCK_CHAIN {
+            if node.is_compatible(b"fixed-clock") {
+                let rate = node.get_prop(b"clock-frequency")?;
+                let rate = rate.prop_as::<u64>().or_else(|| rate.prop_as::<u32>().map(u64::from))?;
+                return factors[..depth].iter().rev().try_fold(rate, |rate, (mult, div)| {
+                    rate.checked_mul(*mult as u64)?.checked_div(*div as u64)
+                })
+            }
+
+            if depth == MAX_CLOCK_CHAIN {
+                break
+            }
+            if node.is_compatible(b"fixed-factor-clock") {
+                factors[depth] = node.fixed_factor()?;
+            } else if depth > 0 || node.get_prop(b"#clock-cells").is_some() {
+                /* A provider which is not fixed */
+                return None
+            }
+
+            node = node.get_prop(b"clocks")?.phandle_args(b"#clock-cells").next()?.provider;
+        }
+        None
+    }
+}
@@ -0,0 +1,110 @@
+//! # Compatibility shims
+//! Legacy spellings still found in real trees, in one place with their precedence.
+//! Each shim tries the modern spelling first, so modern trees never reach the fallback,
+//! and tells which spelling was used. `DeviceTree::warnings()` reports a `Warning::Deprecated`
+//! for every node where the legacy spelling is the one used.
+//!
+//! | Shim                  | Modern, tried first                                   | Legacy fallback                          |
+//! |-----------------------|-------------------------------------------------------|------------------------------------------|
+//! | `phandle()`           | `phandle`                                             | `linux,phandle`                          |
+//! | `gpio_cells()`        | `#gpio-cells` of the controller                       | `DEFAULT_GPIO_CELLS` with `gpio-controller` |
+//! | `interrupt_parent()`  | `interrupt-parent` of the node or a non-root ancestor | `interrupt-parent` of the root node      |
+//! | `cpu()`               | a child of `/cpus` named `cpu@...`                    | `device_type = "cpu"`                    |
+//!
+//! These shims are used by `DeviceTree::phandle_of()`, `Token::gpio()`, `Token::interrupt_parent()`
+//! and `DeviceTree::cpus()`.
+//!
+//! `holds_phandles()` tells which properties conventionally hold phandles, by name from `PHANDLE_PROPS`
+//! or by a suffix from `PHANDLE_PROP_SUFFIXES` (including the legacy `-gpio`) and `pinctrl-N`.
+//! It is used by `DeviceTree::phandle_references()`.
+//!
+
+use crate::{props, valid_phandle, Token};
+
+/// Number of GPIO cells assumed for a `gpio-controller` without `#gpio-cells`, pin and flags
+pub const DEFAULT_GPIO_CELLS: u32 = 2;
+
+/// Properties which conventionally hold phandles, see `holds_phandles()`
+pub const PHANDLE_PROPS: &[&[u8]] = &[
+    b"clocks", b"assigned-clocks", b"assigned-clock-parents", props::INTERRUPT_PARENT, props::INTERRUPTS_EXTENDED,
+    b"gpios", b"gpio", b"resets", b"power-domains", b"phys", b"dmas", b"iommus", b"mboxes", b"pwms",
+    b"nvmem-cells", b"memory-region", b"thermal-sensors", b"cooling-device", b"operating-points-v2",
+    b"cpu-idle-states", b"next-level-cache", b"msi-parent", b"remote-endpoint",
+];
+
+/// Name suffixes of property families which hold phandles, e.g. `vdd-supply` or `reset-gpios`
+pub const PHANDLE_PROP_SUFFIXES: &[&[u8]] = &[b"-supply", b"-gpios", b"-gpio"];
+
+/// Returns true if the property `name` conventionally holds phandles: it is in `PHANDLE_PROPS`,
+/// ends with one of `PHANDLE_PROP_SUFFIXES` or is a `pinctrl-N` pin configuration list.
+///
+pub fn holds_phandles(name: &[u8]) -> bool {
+    let pinctrl = name.strip_prefix(b"pinctrl-")
+        .is_some_and(|n| !n.is_empty() && n.iter().all(|c| c.is_ascii_digit()));
+    pinctrl || PHANDLE_PROPS.contains(&name) || PHANDLE_PROP_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// # Spelling
+/// Which spelling a shim found
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Spelling {
+    Modern,
+    Legacy,
+}
+
+/// Returns the phandle of `node` from `phandle`, or else from `linux,phandle`.
+/// Properties which are not exactly one cell and the reserved values 0 and 0xffffffff are ignored.
+/// Returns None if token is not a node or has no valid phandle.
+///
+pub fn phandle(node: &Token) -> Option<(u32, Spelling)> {
+    if !matches!(node, Token::BeginNode(_, _, _)) {
+        return None
+    }
+    match node.get_prop(props::PHANDLE).and_then(|prop| valid_phandle(&prop)) {
+        Some(phandle) => Some((phandle, Spelling::Modern)),
+        None => node.get_prop(props::LINUX_PHANDLE)
+            .and_then(|prop| valid_phandle(&prop))
+            .map(|phandle| (phandle, Spelling::Legacy))
+    }
+}
+
+/// Returns the number of cells of the GPIO specifiers of `controller` from `#gpio-cells`,
+/// or else `DEFAULT_GPIO_CELLS` if it has a `gpio-controller` property.
+/// Returns None if it has neither.
+///
+pub fn gpio_cells(controller: &Token) -> Option<(u32, Spelling)> {
+    match controller.get_prop(props::GPIO_CELLS) {
+        Some(prop) => prop.prop_u32(0).map(|cells| (cells, Spelling::Modern)),
+        None => controller.get_prop(props::GPIO_CONTROLLER).map(|_| (DEFAULT_GPIO_CELLS, Spelling::Legacy))
+    }
+}
+
+/// Returns the interrupt parent of `node` given by the `interrupt-parent` of `node` or the closest ancestor
+/// which has one. Taking it from the root node, as a global default for a node below it, is the legacy fallback.
+/// The search stops at the first `interrupt-parent`, even if its phandle does not exist.
+/// Returns None if token is not a node or no interrupt parent was found.
+///
+pub fn interrupt_parent<'a>(node: &Token<'a>) -> Option<(Token<'a>, Spelling)> {
+    let mut current = *node;
+    loop {
+        let parent = current.parent();
+        if let Some(prop) = current.get_prop(props::INTERRUPT_PARENT) {
+            let spelling = if parent.is_none() && current != *node { Spelling::Legacy } else { Spelling::Modern };
+            return prop.prop_phandle().map(|controller| (controller, spelling))
+        }
+        current = parent?;
+    }
+}
+
+/// Returns how `node`, a child of `/cpus`, is recognized as a CPU: by a name of `cpu` or `cpu@...`,
+/// or else by `device_type = "cpu"`.
+/// Returns None if it is not a CPU, e.g. `cpu-map`.
+///
+pub fn cpu(node: &Token) -> Option<Spelling> {
+    match node {
+        Token::BeginNode(_, _, name) if name.split(|c| *c == b'@').next() == Some(b"cpu") => Some(Spelling::Modern),
+        Token::BeginNode(_, _, _) if node.get_prop(props::DEVICE_TYPE).and_then(|prop| prop.prop_str()) == Some(b"cpu") =>
+            Some(Spelling::Legacy),
+        _ => None
+    }
+}
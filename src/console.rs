@@ -0,0 +1,118 @@
+//! # Console
+//! Early console discovery from `stdout-path` in `/chosen`.
+//!
+
+use crate::{props, DeviceTree, Token};
+
+/// # Parity
+/// Parity of a serial line
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Parity {
+    None,
+    Odd,
+    Even,
+}
+
+/// # UartOptions
+/// Serial line options in the form `<baud>{<parity>{<bits>{<flow>}}}` (e.g. "115200n8"),
+/// as given after ':' in `stdout-path`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UartOptions {
+    pub baud: u32,
+    pub parity: Parity,
+    pub bits: u8,
+    /// RTS/CTS flow control, 'r' suffix
+    pub flow_control: bool,
+}
+
+impl Default for UartOptions {
+    /// 115200 baud, no parity, 8 bits, no flow control
+    fn default() -> Self {
+        UartOptions { baud: 115200, parity: Parity::None, bits: 8, flow_control: false }
+    }
+}
+
+impl UartOptions {
+    /// Parse serial line options, missing parity, bits and flow control use the defaults.
+    /// Returns None if the baud rate is missing or the options are malformed.
+    pub fn parse(options: &[u8]) -> Option<UartOptions> {
+        let digits = options.iter().position(|c| !c.is_ascii_digit()).unwrap_or(options.len());
+        if digits == 0 {
+            return None
+        }
+
+        let baud = options[..digits].iter()
+            .try_fold(0u32, |baud, c| baud.checked_mul(10)?.checked_add((c - b'0') as u32))?;
+        let mut result = UartOptions { baud, ..UartOptions::default() };
+
+        let mut rest = options[digits..].iter();
+        if let Some(c) = rest.next() {
+            result.parity = match c {
+                b'n' => Parity::None,
+                b'o' => Parity::Odd,
+                b'e' => Parity::Even,
+                _ => return None
+            };
+        }
+        if let Some(c) = rest.next() {
+            result.bits = match c {
+                b'5'..=b'8' => c - b'0',
+                _ => return None
+            };
+        }
+        if let Some(c) = rest.next() {
+            if *c != b'r' {
+                return None
+            }
+            result.flow_control = true;
+        }
+        match rest.next() {
+            Some(_) => None,
+            None => Some(result)
+        }
+    }
+}
+
+/// # EarlyConsole
+/// The console node given by `stdout-path`, see `DeviceTree::earlycon()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EarlyConsole<'a> {
+    /// The console node
+    pub node: Token<'a>,
+    /// First compatible string of the node, to select a driver
+    pub compatible: Option<&'a [u8]>,
+    /// CPU address of the first `reg` entry
+    pub address: Option<u64>,
+    /// Options after ':' in `stdout-path`, None if missing or malformed
+    pub options: Option<UartOptions>,
+    /// Register stride as a power of 2, `reg-shift` property
+    pub reg_shift: Option<u32>,
+    /// Register access width in bytes, `reg-io-width` property
+    pub reg_io_width: Option<u32>,
+    /// Input clock in Hz, `clock-frequency` property
+    pub clock_frequency: Option<u32>,
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Find the console given by `stdout-path` (a path or alias) in `/chosen`,
+    /// or one of its fallbacks (see `resolve_stdout()`), and decode its address and serial options.
+    /// Returns None if there is no console or its node does not exist.
+    ///
+    pub fn earlycon(&self) -> Option<EarlyConsole<'_>> {
+        let (node, options) = self.stdout()?;
+
+        let u32_prop = |name: &[u8]| node.get_prop(name).and_then(|prop| prop.prop_u32(0));
+        Some(EarlyConsole {
+            node,
+            compatible: node.get_prop(props::COMPATIBLE).and_then(|prop| prop.prop_str()),
+            address: node.reg()
+                .and_then(|mut reg| reg.next())
+                .and_then(|reg| node.translate_address(reg.address)),
+            options: options.and_then(UartOptions::parse),
+            reg_shift: u32_prop(b"reg-shift"),
+            reg_io_width: u32_prop(b"reg-io-width"),
+            clock_frequency: u32_prop(b"clock-frequency"),
+        })
+    }
+}
@@ -0,0 +1,155 @@
+//! # Node contents
+//! Visiting the properties and then the children of a node in a single pass, see `Token::split_contents()`.
+//! Both iterators advance a shared `ContentCursor`, so each top-level token of the node is decoded once
+//! and sub-nodes are skipped over without decoding their contents.
+//!
+
+use core::cell::Cell;
+
+use crate::{raw, DeviceTree, Token, TokenIterator};
+
+/// # ContentCursor
+/// The position in the contents of a node shared by `PropsIter` and `ChildrenIter`.
+/// A cursor can be reused for another node, `Token::split_contents()` resets it.
+#[derive(Debug, Default)]
+pub struct ContentCursor<'a> {
+    dt: Cell<Option<&'a DeviceTree<'a>>>,
+    offs: Cell<usize>,
+    /* The token which ended the properties, read but not yet consumed */
+    pending: Cell<Option<Token<'a>>>,
+    props_done: Cell<bool>,
+}
+
+impl<'a> ContentCursor<'a> {
+
+    /// Create a cursor which is not in any node
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the offset in the structure block of the next token to decode
+    /// Returns None if the end of the node has been reached or the cursor is not in a node
+    pub fn offset(&self) -> Option<usize> {
+        self.dt.get()?;
+        match self.pending.get() {
+            Some(tok @ Token::BeginNode(_, _, _)) => tok.node_offset(),
+            Some(_) => Some(self.offs.get() - raw::FDT_TAGSIZE),
+            None => Some(self.offs.get())
+        }
+    }
+
+    fn reset(&self, dt: Option<&'a DeviceTree<'a>>, offs: usize) {
+        self.dt.set(dt);
+        self.offs.set(offs);
+        self.pending.set(None);
+        self.props_done.set(dt.is_none());
+    }
+
+    /// Decode the token at the cursor and advance past it
+    fn read(&self) -> Option<Token<'a>> {
+        let mut iter = TokenIterator::new_offs(self.dt.get()?, self.offs.get());
+        let tok = iter.next();
+        self.offs.set(iter.offs);
+        if tok.is_none() {
+            self.dt.set(None);
+        }
+        tok
+    }
+
+    fn next_prop(&self) -> Option<Token<'a>> {
+        while !self.props_done.get() {
+            match self.read() {
+                Some(tok @ Token::Property(_, _, _)) => return Some(tok),
+                Some(Token::NoOperation) => (),
+                tok => {
+                    self.props_done.set(true);
+                    self.pending.set(tok);
+                }
+            }
+        }
+        None
+    }
+
+    fn next_child(&self) -> Option<Token<'a>> {
+        /* Fast-forward over properties not consumed by PropsIter */
+        while self.next_prop().is_some() {}
+
+        loop {
+            match self.pending.take().or_else(|| self.read())? {
+                tok @ Token::BeginNode(dt, offs, _) => {
+                    let mut iter = TokenIterator::new_offs(dt, offs);
+                    if iter.skip_subtree() {
+                        self.offs.set(iter.offs);
+                    } else {
+                        self.dt.set(None);
+                    }
+                    return Some(tok)
+                },
+                Token::NoOperation => (),
+                _ => {
+                    self.dt.set(None);
+                    return None
+                }
+            }
+        }
+    }
+}
+
+/// # PropsIter
+/// Iterates over the properties of a node, see `Token::split_contents()`
+#[derive(Debug, Copy, Clone)]
+pub struct PropsIter<'a, 'c> {
+    cursor: &'c ContentCursor<'a>,
+}
+
+impl<'a, 'c> Iterator for PropsIter<'a, 'c> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.next_prop()
+    }
+}
+
+/// # ChildrenIter
+/// Iterates over the direct sub-nodes of a node, see `Token::split_contents()`
+#[derive(Debug, Copy, Clone)]
+pub struct ChildrenIter<'a, 'c> {
+    cursor: &'c ContentCursor<'a>,
+}
+
+impl<'a, 'c> Iterator for ChildrenIter<'a, 'c> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.next_child()
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Split the contents of this node into its properties and its direct sub-nodes, sharing `cursor`.
+    /// The children iterator continues where the properties iterator stopped, so the node is walked once in total.
+    /// If the children iterator is advanced first, it skips the remaining properties and the properties
+    /// iterator returns None from then on.
+    /// Both iterators are empty if not a node.
+    ///
+    /// ```
+    /// use static_dt_rs::{ContentCursor, DeviceTree};
+    ///
+    /// let fdt = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.dtb"));
+    /// let dt = DeviceTree::parse(fdt).unwrap();
+    /// let node = dt.get_node_by_path(b"/node1").unwrap();
+    ///
+    /// let cursor = ContentCursor::new();
+    /// let (props, children) = node.split_contents(&cursor);
+    /// assert_eq!(props.count(), 3);
+    /// assert_eq!(children.map(|child| child.name()).collect::<Vec<_>>(), [&b"child-node1"[..], b"child-node2"]);
+    /// ```
+    pub fn split_contents<'c>(&self, cursor: &'c ContentCursor<'a>) -> (PropsIter<'a, 'c>, ChildrenIter<'a, 'c>) {
+        match *self {
+            Token::BeginNode(dt, offs, _) => cursor.reset(Some(dt), offs),
+            _ => cursor.reset(None, 0)
+        }
+        (PropsIter { cursor }, ChildrenIter { cursor })
+    }
+}
@@ -0,0 +1,63 @@
+//! # CRC32 footer
+//! Integrity check for blobs stored with a CRC32 appended, a common convention of update tools
+//! which is not part of the devicetree specification.
+//!
+//! The footer is the big-endian CRC32 (IEEE 802.3, as computed by zlib) of the `totalsize` bytes of the blob,
+//! stored right after them. The CRC is computed bitwise, without a 1 KiB lookup table.
+//!
+
+use crate::{DeviceTree, Error};
+
+/// Reflected IEEE 802.3 polynomial
+const POLYNOMIAL: u32 = 0xedb88320;
+
+/// CRC32 of `data`
+const fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= data[i] as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            /* Subtract the polynomial if the low bit is set */
+            crc = (crc >> 1) ^ (POLYNOMIAL & (crc & 1).wrapping_neg());
+            bit += 1;
+        }
+        i += 1;
+    }
+    !crc
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Parse a devicetree followed by a CRC32 footer, see the module documentation.
+    /// Returns `Error::Truncated` if `fdt` has no room for the footer and
+    /// `Error::CrcMismatch` with the computed CRC if the footer does not match.
+    pub fn parse_with_footer(fdt: &'a [u8]) -> Result<DeviceTree<'a>, Error> {
+        let dt = DeviceTree::parse(fdt)?;
+        let footer = fdt.get(dt.totalsize()..dt.totalsize() + 4).ok_or(Error::Truncated)?;
+        let expected = u32::from_be_bytes([footer[0], footer[1], footer[2], footer[3]]);
+        let crc = dt.crc32();
+        if crc != expected {
+            return Err(Error::CrcMismatch(crc))
+        }
+        Ok(dt)
+    }
+
+    /// Name of `parse_with_footer()` in line with the old name of `parse()`.
+    ///
+    #[deprecated(since = "0.2.0", note = "use `DeviceTree::parse_with_footer()`")]
+    pub fn back_with_footer(fdt: &'a [u8]) -> Result<DeviceTree<'a>, Error> {
+        Self::parse_with_footer(fdt)
+    }
+
+    /// Returns the CRC32 of the `totalsize` bytes of the blob, see the module documentation
+    pub const fn crc32(&self) -> u32 {
+        crc32(self.fdt.split_at(self.totalsize).0)
+    }
+
+    /// Returns true if the CRC32 of the blob is `expected`
+    pub const fn verify_crc32(&self, expected: u32) -> bool {
+        self.crc32() == expected
+    }
+}
@@ -0,0 +1,88 @@
+//! # Lookup cursor
+//! A node position which can be kept across calls and layers that only share a `&DeviceTree`,
+//! re-bound against the tree passed to each call, see `DeviceTree::cursor_at()`.
+//!
+
+use crate::{DeviceTree, Token};
+
+/// # DtCursor
+/// The offset of a node together with the address of the blob it was found in.
+///
+/// Every method takes the tree to read from and returns None if it is not backed by the same blob
+/// as the tree the cursor was created from, instead of reading another tree at that offset.
+/// A different blob copied into the same buffer is not detected, but the offset must still
+/// be a node in it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DtCursor {
+    tree: usize,
+    offset: usize
+}
+
+impl DtCursor {
+    /// Offset of the FDT_BEGIN_NODE token of the node in the structure block, see `Token::node_offset()`
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns true if `dt` is backed by the blob this cursor was created from
+    pub fn is_of(&self, dt: &DeviceTree<'_>) -> bool {
+        self.tree == dt.fdt.as_ptr() as usize
+    }
+
+    /// Returns the node of this cursor in `dt`
+    /// Returns None if `dt` is another tree or there is no node at the offset.
+    ///
+    pub fn node<'t>(&self, dt: &'t DeviceTree<'t>) -> Option<Token<'t>> {
+        if !self.is_of(dt) {
+            return None
+        }
+        dt.node_at_offset(self.offset)
+    }
+
+    /// Returns the first cell of property `name` of the node, see `Token::prop_u32()`
+    /// Returns None if `dt` is another tree or there is no such property.
+    ///
+    pub fn prop_u32<S: AsRef<[u8]>>(&self, dt: &DeviceTree<'_>, name: S) -> Option<u32> {
+        self.node(dt)?.get_prop(name)?.prop_u32(0)
+    }
+
+    /// Returns property `name` of the node as a string, see `Token::prop_str()`
+    /// Returns None if `dt` is another tree or there is no such property.
+    ///
+    pub fn prop_str<'t, S: AsRef<[u8]>>(&self, dt: &'t DeviceTree<'t>, name: S) -> Option<&'t [u8]> {
+        self.node(dt)?.get_prop(name)?.prop_str()
+    }
+
+    /// Returns a cursor at child `name` of the node, see `Token::get_node()`
+    /// Returns None if `dt` is another tree or there is no such child.
+    ///
+    pub fn child<S: AsRef<[u8]>>(&self, dt: &DeviceTree<'_>, name: S) -> Option<DtCursor> {
+        self.node(dt)?.get_node(name)?.cursor()
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a cursor at this node
+    /// Returns None if token is not a node.
+    ///
+    pub fn cursor(&self) -> Option<DtCursor> {
+        match self {
+            Token::BeginNode(dt, _, _) => Some(DtCursor {
+                tree: dt.fdt.as_ptr() as usize,
+                offset: self.node_offset()?
+            }),
+            _ => None
+        }
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a cursor at the node at `path`, see `get_node_by_path()`
+    /// Returns None if there is no such node.
+    ///
+    pub fn cursor_at<S: AsRef<[u8]>>(&self, path: S) -> Option<DtCursor> {
+        self.get_node_by_path(path)?.cursor()
+    }
+}
@@ -0,0 +1,127 @@
+//! # Tree comparison
+//! Structural comparison of two trees where the order of siblings and the layout
+//! of the strings block do not matter, e.g. for blobs regenerated by firmware.
+//!
+
+use crate::{DeviceTree, Token, UnitAddrCompare, MAX_DEPTH};
+
+/// # DiffEntry
+/// The first difference found by `tree_diff()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DiffEntry<'a, 'b> {
+    /// A node or property of the first tree is missing in the second
+    OnlyInA(Token<'a>),
+
+    /// A node or property of the second tree is missing in the first
+    OnlyInB(Token<'b>),
+
+    /// A property has different values in the two trees
+    ValueDiffers(Token<'a>, Token<'b>),
+
+    /// A node of the first tree is nested deeper than `MAX_DEPTH`, it is not compared
+    TooDeep(Token<'a>),
+}
+
+/// Returns true if `a` and `b` are properties with the same name and value
+pub(crate) fn prop_eq(a: &Token, b: &Token) -> bool {
+    match (a, b) {
+        (Token::Property(_, a_name, a_val), Token::Property(_, b_name, b_val)) => a_name == b_name && a_val == b_val,
+        _ => false
+    }
+}
+
+/// Find the property or child node of `node` with the same kind and name as `tok`.
+/// A child with exactly the same name is preferred over one which only matches with `compare`.
+fn find_same<'b>(node: Token<'b>, tok: &Token, compare: UnitAddrCompare) -> Option<Token<'b>> {
+    let find = |compare: UnitAddrCompare| node.into_iter().find(|other| match (tok, other) {
+        (Token::Property(_, name, _), Token::Property(_, other, _)) => name == other,
+        (Token::BeginNode(_, _, name), Token::BeginNode(_, _, other)) => compare.matches(name, other) || compare.matches(other, name),
+        _ => false
+    });
+    find(UnitAddrCompare::Exact).or_else(|| if compare == UnitAddrCompare::Exact { None } else { find(compare) })
+}
+
+/// Returns the first property or child node of `b` without a counterpart in `a`
+fn find_added<'b>(a: Token, b: Token<'b>, compare: UnitAddrCompare) -> Option<Token<'b>> {
+    b.into_iter()
+        .filter(|tok| matches!(tok, Token::Property(..) | Token::BeginNode(..)))
+        .find(|tok| find_same(a, tok, compare).is_none())
+}
+
+/// Returns the first difference between `a` and `b`, comparing names, values and hierarchy.
+/// Children are matched by their full name (including the unit address) and properties by name,
+/// regardless of their order. Returns None if the trees are equal.
+///
+/// The trees are walked iteratively, keeping the open nodes of both in fixed arrays of `MAX_DEPTH` entries.
+///
+pub fn tree_diff<'a, 'b>(a: &'a DeviceTree<'a>, b: &'b DeviceTree<'b>) -> Option<DiffEntry<'a, 'b>> {
+    tree_diff_with(a, b, UnitAddrCompare::Exact)
+}
+
+/// Same as `tree_diff()` with the names of children matched as given by `compare`,
+/// e.g. `uart@1000` and `uart@0x1000` are the same node with `UnitAddrCompare::NumericHex`.
+///
+pub fn tree_diff_with<'a, 'b>(a: &'a DeviceTree<'a>, b: &'b DeviceTree<'b>, compare: UnitAddrCompare) -> Option<DiffEntry<'a, 'b>> {
+    if a.root().name() != b.root().name() {
+        return Some(DiffEntry::OnlyInA(a.root()))
+    }
+
+    /* Offsets of the open nodes of a and of their counterparts in b */
+    let mut a_nodes = [0u32; MAX_DEPTH];
+    let mut b_nodes = [0u32; MAX_DEPTH];
+    let mut depth = 0;
+
+    for tok in a.tokens() {
+        match tok {
+            Token::BeginNode(..) => {
+                let other = match depth {
+                    0 => b.root(),
+                    _ => match find_same(b.node_at_offset(b_nodes[depth - 1] as usize)?, &tok, compare) {
+                        Some(other) => other,
+                        None => return Some(DiffEntry::OnlyInA(tok))
+                    }
+                };
+                if depth >= MAX_DEPTH {
+                    return Some(DiffEntry::TooDeep(tok))
+                }
+                a_nodes[depth] = tok.node_offset()? as u32;
+                b_nodes[depth] = other.node_offset()? as u32;
+                depth += 1;
+            },
+            Token::Property(..) if depth > 0 => {
+                match find_same(b.node_at_offset(b_nodes[depth - 1] as usize)?, &tok, compare) {
+                    Some(other) if !prop_eq(&tok, &other) => return Some(DiffEntry::ValueDiffers(tok, other)),
+                    None => return Some(DiffEntry::OnlyInA(tok)),
+                    _ => ()
+                }
+            },
+            Token::EndNode if depth > 0 => {
+                /* Everything of the node is in b, look for additions in b */
+                depth -= 1;
+                let (a_node, b_node) = (a.node_at_offset(a_nodes[depth] as usize)?, b.node_at_offset(b_nodes[depth] as usize)?);
+                if let Some(added) = find_added(a_node, b_node, compare) {
+                    return Some(DiffEntry::OnlyInB(added))
+                }
+                if depth == 0 {
+                    break
+                }
+            },
+            Token::NoOperation => (),
+            _ => break
+        }
+    }
+    None
+}
+
+/// Returns true if `a` and `b` are equal except for the order of siblings and the strings block layout,
+/// see `tree_diff()`.
+///
+pub fn tree_eq(a: &DeviceTree, b: &DeviceTree) -> bool {
+    tree_diff(a, b).is_none()
+}
+
+/// Same as `tree_eq()` with the names of children matched as given by `compare`, see `tree_diff_with()`
+///
+pub fn tree_eq_with(a: &DeviceTree, b: &DeviceTree, compare: UnitAddrCompare) -> bool {
+    tree_diff_with(a, b, compare).is_none()
+}
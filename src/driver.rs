@@ -0,0 +1,43 @@
+//! # Driver matching
+//! Matching nodes against a table of `compatible` strings, the lookup at the heart of a driver model.
+//! The strings of `compatible` are ordered from most to least specific, so a node is matched to the
+//! entry for its earliest string which has one, regardless of the order of the table.
+//! See `dt_match_table!` for generating the table and probe loop.
+//!
+
+use crate::{props, DeviceTree, Token};
+
+/// # DtMatch
+/// A entry of a match table, `data` is usually a probe function
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DtMatch<T> {
+    pub compatible: &'static [u8],
+    pub data: T,
+}
+
+impl<'a> Token<'a> {
+
+    /// Find the entry of `table` matching the most specific `compatible` string of this node.
+    /// If several entries have the same string the first one is returned.
+    /// Returns None if not a node or no entry matches.
+    ///
+    pub fn match_compatible<'t, T>(&self, table: &'t [DtMatch<T>]) -> Option<&'t DtMatch<T>> {
+        self.get_prop(props::COMPATIBLE)?
+            .prop_strings()
+            .find_map(|compat| table.iter().find(|entry| entry.compatible == compat))
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a iterator over the enabled nodes matching an entry of `table` in tree order,
+    /// together with the entry, see `Token::match_compatible()`. Each node is yielded at most once.
+    ///
+    pub fn probe_matches<'s, 't, T>(&'s self, table: &'t [DtMatch<T>]) -> impl Iterator<Item = (Token<'s>, &'t DtMatch<T>)> + 's
+        where 't: 's
+    {
+        self.nodes()
+            .filter(|node| node.is_enabled())
+            .filter_map(move |node| Some((node, node.match_compatible(table)?)))
+    }
+}
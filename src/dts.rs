@@ -0,0 +1,370 @@
+//! # DTS
+//! Classification of property values and rendering of a tree as devicetree source
+//! or as a compact `Debug` listing, without alloc.
+//!
+//! ```
+//! use static_dt_rs::{DeviceTree, PropValue};
+//!
+//! let dt = DeviceTree::parse(include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.dtb"))).unwrap();
+//! let prop = dt.get_node_by_path(b"/node1").unwrap().get_prop(b"a-string-property").unwrap();
+//! assert_eq!(prop.prop_value(), Some(PropValue::String(b"A string")));
+//!
+//! let mut dts = String::new();
+//! dt.write_dts(&mut dts).unwrap();
+//! assert!(dts.contains("a-string-property = \"A string\";"));
+//! ```
+//!
+
+use core::fmt::{self, Write};
+
+use crate::{utils, DeviceTree, Token, TokenIterator};
+
+/// # PropValue
+/// Property value classified by its content, see `PropValue::classify()`.
+/// A devicetree blob does not record value types, so the classification is a guess.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum PropValue<'a> {
+    /// No value, e.g. a boolean property
+    Empty,
+
+    /// One NUL-terminated string, without the NUL
+    String(&'a [u8]),
+
+    /// Several NUL-terminated strings, the raw value
+    StringList(&'a [u8]),
+
+    /// A single cell
+    Cell(u32),
+
+    /// A multiple of 4 bytes, the raw value
+    Cells(&'a [u8]),
+
+    /// Anything else, the raw value
+    Bytes(&'a [u8]),
+}
+
+/// # ClassifyOptions
+/// Where `PropValue::classify_with()` draws the line between strings and binary values.
+/// Short binary values such as `61 62 63 64 00` look like strings, stricter options trade
+/// such false positives for real strings classified as binary.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ClassifyOptions {
+    /// Minimum length of every string, without its NUL
+    pub min_string_len: usize,
+    /// Only classify values with a single NUL, at the end, as strings, never as `StringList`
+    pub single_string: bool,
+    /// Allow bytes >= 0x80 in strings, e.g. UTF-8
+    pub allow_high_bytes: bool,
+}
+
+impl Default for ClassifyOptions {
+    /// The heuristic of `PropValue::classify()`: non-empty strings of printable ASCII, lists allowed
+    fn default() -> Self {
+        ClassifyOptions { min_string_len: 1, single_string: false, allow_high_bytes: false }
+    }
+}
+
+impl ClassifyOptions {
+    fn is_string(&self, s: &[u8]) -> bool {
+        s.len() >= self.min_string_len.max(1)
+            && s.iter().all(|c| (0x20..0x7f).contains(c) || (self.allow_high_bytes && *c >= 0x80))
+    }
+}
+
+impl<'a> PropValue<'a> {
+
+    /// Classify `val`:
+    /// 1. An empty value is `Empty`.
+    /// 2. A value ending in NUL where every string is non-empty printable ASCII is
+    ///    `String` if it contains one string, otherwise `StringList`.
+    /// 3. A value of 4 bytes is `Cell`, a non-empty multiple of 4 bytes is `Cells`.
+    /// 4. Anything else is `Bytes`.
+    pub fn classify(val: &'a [u8]) -> PropValue<'a> {
+        PropValue::classify_with(val, &ClassifyOptions::default())
+    }
+
+    /// Same as `classify()` but with strings accepted as given by `opts` in step 2.
+    /// Strings are never empty, even with a `min_string_len` of 0.
+    pub fn classify_with(val: &'a [u8], opts: &ClassifyOptions) -> PropValue<'a> {
+        if val.is_empty() {
+            return PropValue::Empty
+        }
+
+        if let Some(strings) = val.strip_suffix(&[0]) {
+            let printable = (!opts.single_string || !strings.contains(&0))
+                && strings.split(|c| *c == 0).all(|s| opts.is_string(s));
+            if printable {
+                return match strings.contains(&0) {
+                    false => PropValue::String(strings),
+                    true => PropValue::StringList(val),
+                }
+            }
+        }
+
+        match val.len() {
+            4 => PropValue::Cell(utils::read_fdt_u32(val, 0)),
+            len if len % 4 == 0 => PropValue::Cells(val),
+            _ => PropValue::Bytes(val)
+        }
+    }
+
+    /// Returns the strings of a `String` or `StringList`, an empty iterator otherwise
+    pub fn strings(&self) -> impl Iterator<Item = &'a [u8]> {
+        let list: Option<&'a [u8]> = match *self {
+            PropValue::String(s) => Some(s),
+            PropValue::StringList(val) => val.strip_suffix(&[0]),
+            _ => None
+        };
+        list.into_iter().flat_map(|list| list.split(|c| *c == 0))
+    }
+
+    /// Returns the cells of a `Cell` or `Cells`, an empty iterator otherwise
+    pub fn cells(&self) -> impl Iterator<Item = u32> + 'a {
+        let (first, val): (Option<u32>, &'a [u8]) = match *self {
+            PropValue::Cell(cell) => (Some(cell), &[]),
+            PropValue::Cells(val) => (None, val),
+            _ => (None, &[])
+        };
+        first.into_iter().chain(val.chunks_exact(4).map(|c| utils::read_fdt_u32(c, 0)))
+    }
+}
+
+/// Write `s` between quotes, escaping quotes, backslashes and non-printable bytes
+fn write_quoted(f: &mut dyn fmt::Write, s: &[u8]) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s {
+        match c {
+            b'"' | b'\\' => write!(f, "\\{}", *c as char)?,
+            0x20..=0x7e => f.write_char(*c as char)?,
+            _ => write!(f, "\\x{:02x}", c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Write a node or property name, non-printable bytes escaped
+pub(crate) fn write_name(f: &mut dyn fmt::Write, name: &[u8]) -> fmt::Result {
+    for c in name {
+        match c {
+            0x21..=0x7e => f.write_char(*c as char)?,
+            _ => write!(f, "\\x{:02x}", c)?,
+        }
+    }
+    Ok(())
+}
+
+/// DTS syntax of the value, e.g. `"a", "b"`, `<0x1 0x2>` or `[01 23]`.
+/// `Empty` writes nothing.
+impl fmt::Display for PropValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropValue::Empty => Ok(()),
+            PropValue::String(_) | PropValue::StringList(_) => {
+                for (i, s) in self.strings().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write_quoted(f, s)?;
+                }
+                Ok(())
+            },
+            PropValue::Cell(_) | PropValue::Cells(_) => {
+                f.write_char('<')?;
+                for (i, cell) in self.cells().enumerate() {
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write!(f, "{:#x}", cell)?;
+                }
+                f.write_char('>')
+            },
+            PropValue::Bytes(val) => {
+                f.write_char('[')?;
+                for (i, c) in val.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write!(f, "{:02x}", c)?;
+                }
+                f.write_char(']')
+            }
+        }
+    }
+}
+
+/// Same as `Display` wrapped in the variant name, e.g. `Cells(<0x1 0x2>)`
+impl fmt::Debug for PropValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            PropValue::Empty => return f.write_str("Empty"),
+            PropValue::String(_) => "String",
+            PropValue::StringList(_) => "StringList",
+            PropValue::Cell(_) => "Cell",
+            PropValue::Cells(_) => "Cells",
+            PropValue::Bytes(_) => "Bytes",
+        };
+        write!(f, "{}({})", name, self)
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns the classified value of a property, see `PropValue::classify()`
+    /// Returns None if not a property
+    pub fn prop_value(&self) -> Option<PropValue<'a>> {
+        self.classify_with(&ClassifyOptions::default())
+    }
+
+    /// Returns the value of a property classified with `opts`, see `PropValue::classify_with()`
+    /// Returns None if not a property
+    pub fn classify_with(&self, opts: &ClassifyOptions) -> Option<PropValue<'a>> {
+        match self {
+            Token::Property(_, _, val) => Some(PropValue::classify_with(val, opts)),
+            _ => None
+        }
+    }
+
+    /// Returns a formatter listing this node and its subtree with `{:?}`,
+    /// one line per property and node with `{:#?}`
+    pub fn debug_tree(&self) -> DebugTree<'a> {
+        self.debug_tree_with(&ClassifyOptions::default())
+    }
+
+    /// Same as `debug_tree()` but with values classified with `opts`
+    pub fn debug_tree_with(&self, opts: &ClassifyOptions) -> DebugTree<'a> {
+        DebugTree(*self, *opts)
+    }
+}
+
+/// # DebugTree
+/// `Debug` formatter of a subtree, see `Token::debug_tree()`.
+/// Nodes are maps of their property values followed by their sub-nodes.
+#[derive(Copy, Clone)]
+pub struct DebugTree<'a>(Token<'a>, ClassifyOptions);
+
+/// Start a entry of the innermost of `depth` open maps, `empty` if it has no entries yet
+fn debug_entry(f: &mut fmt::Formatter<'_>, depth: usize, empty: bool, name: &[u8]) -> fmt::Result {
+    if f.alternate() {
+        if empty {
+            f.write_char('\n')?;
+        }
+        (0..depth).try_for_each(|_| f.write_str("    "))?;
+    } else if !empty {
+        f.write_str(", ")?;
+    }
+    write_name(f, name)?;
+    f.write_str(": ")
+}
+
+/// End a entry of a map, `depth` is the number of maps still open
+fn debug_entry_end(f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+    match f.alternate() && depth > 0 {
+        true => f.write_str(",\n"),
+        false => Ok(())
+    }
+}
+
+/// Same layout as `Formatter::debug_map()`, written while streaming the tokens
+/// as nested `Debug` calls would recurse once per level.
+impl fmt::Debug for DebugTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (dt, offs) = match self.0 {
+            Token::BeginNode(dt, offs, _) => (dt, offs),
+            _ => return f.write_str("{}")
+        };
+
+        f.write_char('{')?;
+        /* Number of open maps and whether the innermost has no entries yet */
+        let mut depth = 1;
+        let mut empty = true;
+        for tok in TokenIterator::new_offs(dt, offs) {
+            match tok {
+                Token::Property(_, name, val) => {
+                    debug_entry(f, depth, empty, name)?;
+                    fmt::Debug::fmt(&PropValue::classify_with(val, &self.1), f)?;
+                    debug_entry_end(f, depth)?;
+                    empty = false;
+                },
+                Token::BeginNode(_, _, name) => {
+                    debug_entry(f, depth, empty, name)?;
+                    f.write_char('{')?;
+                    depth += 1;
+                    empty = true;
+                },
+                Token::EndNode => {
+                    depth -= 1;
+                    if f.alternate() && !empty {
+                        (0..depth).try_for_each(|_| f.write_str("    "))?;
+                    }
+                    f.write_char('}')?;
+                    debug_entry_end(f, depth)?;
+                    empty = false;
+                    if depth == 0 {
+                        return Ok(())
+                    }
+                },
+                Token::NoOperation => (),
+                Token::End | Token::Invalid(_) => break
+            }
+        }
+
+        /* Truncated tree, close the open maps */
+        (0..depth).try_for_each(|_| f.write_char('}'))
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Write the tree as devicetree source in the layout of `dtc -O dts`.
+    /// Values are written as classified by `PropValue::classify()`,
+    /// labels and phandle references are not reconstructed.
+    pub fn write_dts<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        self.write_dts_with(w, &ClassifyOptions::default())
+    }
+
+    /// Same as `write_dts()` but with values classified with `opts`
+    pub fn write_dts_with<W: fmt::Write>(&self, w: &mut W, opts: &ClassifyOptions) -> fmt::Result {
+        w.write_str("/dts-v1/;\n\n")?;
+
+        let mut depth = 0;
+        for tok in self.tokens() {
+            match tok {
+                Token::BeginNode(_, _, name) => {
+                    if depth > 0 {
+                        w.write_char('\n')?;
+                    }
+                    indent(w, depth)?;
+                    match depth {
+                        0 => w.write_char('/')?,
+                        _ => write_name(w, name)?,
+                    }
+                    w.write_str(" {\n")?;
+                    depth += 1;
+                },
+                Token::EndNode => {
+                    depth -= 1;
+                    indent(w, depth)?;
+                    w.write_str("};\n")?;
+                    if depth == 0 {
+                        break
+                    }
+                },
+                Token::Property(_, name, val) => {
+                    indent(w, depth)?;
+                    write_name(w, name)?;
+                    match PropValue::classify_with(val, opts) {
+                        PropValue::Empty => w.write_str(";\n")?,
+                        value => writeln!(w, " = {};", value)?,
+                    }
+                },
+                Token::NoOperation => (),
+                Token::End | Token::Invalid(_) => break,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn indent<W: fmt::Write>(w: &mut W, depth: usize) -> fmt::Result {
+    (0..depth).try_for_each(|_| w.write_char('\t'))
+}
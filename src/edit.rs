@@ -0,0 +1,586 @@
+//! # Editing
+//! In-place modification of a blob in a mutable buffer, which may extend past `totalsize`
+//! to leave room for growth. Nodes are addressed by path as offsets change with every edit.
+//!
+//! Deleted properties are overwritten with FDT_NOP tokens. Added properties reuse the NOP slack
+//! after the properties of a node (see `Token::nop_slack()`) before the rest of the blob is shifted.
+//!
+//! To grow a blob without shifting it on every edit, reserve free space after the structure and
+//! strings blocks once with `DeviceTreeMut::ensure_slack()`. Several edits which must all succeed
+//! or all be undone go in a `DeviceTreeMut::transaction()`.
+//!
+
+use crate::{compat, raw, DeviceTree, Error, Token, TokenIterator};
+use crate::utils::read_fdt_u32;
+
+/// # EditError
+/// Errors returned by `DeviceTreeMut`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// The buffer does not hold a valid devicetree
+    Parse(Error),
+
+    /// There is no node at the path
+    NodeNotFound,
+
+    /// The node has no property of that name
+    PropNotFound,
+
+    /// The node already has a property of that name
+    Exists,
+
+    /// The buffer is too small for the grown blob
+    NoSpace,
+
+    /// A name is empty or contains a NUL
+    InvalidName,
+
+    /// The cell index is past the end of the property value
+    OutOfRange,
+
+    /// The root node cannot be deleted
+    RootNode,
+
+    /// The node to delete or one of its sub-nodes is still referenced by phandle
+    Referenced,
+
+    /// A path does not fit in the fixed-size path buffer, see `MAX_OVERLAY_PATH`
+    PathTooLong,
+}
+
+impl From<Error> for EditError {
+    fn from(e: Error) -> Self {
+        EditError::Parse(e)
+    }
+}
+
+/// Maximum number of steps a `DeviceTreeMut::transaction()` can undo.
+/// Every overwritten 4-byte word of existing properties or nodes is a step, every shift of the blob
+/// and every use of NOP slack another.
+pub const MAX_TRANSACTION_STEPS: usize = 64;
+
+/// A change to the buffer made in a transaction, undone in reverse order.
+/// Offsets are into the buffer.
+#[derive(Debug, Copy, Clone)]
+enum Step {
+    /// The bytes `at..end` were moved `len` bytes up, leaving a gap at `at`
+    Shift { at: u32, len: u32, end: u32 },
+    /// NOP tokens at `at..end` were overwritten
+    Slack { at: u32, end: u32 },
+    /// The word at `at` held `old`
+    Word { at: u32, old: u32 },
+}
+
+/// Steps of the running transactions
+#[derive(Debug)]
+struct Journal {
+    active: bool,
+    len: usize,
+    steps: [Step; MAX_TRANSACTION_STEPS],
+}
+
+/// # DeviceTreeMut
+/// A devicetree in a mutable buffer, see the module documentation.
+/// The blob is checked once when created and every edit keeps it valid.
+#[derive(Debug)]
+pub struct DeviceTreeMut<'a> {
+    buf: &'a mut [u8],
+    journal: Journal
+}
+
+/// Size of a property token with a value of `len` bytes
+fn prop_size(len: usize) -> usize {
+    3*raw::FDT_TAGSIZE + len.div_ceil(raw::FDT_STRUCT_ALIGN)*raw::FDT_STRUCT_ALIGN
+}
+
+impl<'a> DeviceTreeMut<'a> {
+
+    /// Check that `buf` starts with a valid devicetree, see `DeviceTree::parse()`.
+    /// Bytes of `buf` after `totalsize` are free space for edits.
+    pub fn new(buf: &'a mut [u8]) -> Result<DeviceTreeMut<'a>, Error> {
+        DeviceTree::parse(buf)?;
+        Ok(DeviceTreeMut {
+            buf,
+            journal: Journal { active: false, len: 0, steps: [Step::Slack { at: 0, end: 0 }; MAX_TRANSACTION_STEPS] }
+        })
+    }
+
+    /// Borrow the devicetree
+    pub fn tree(&self) -> DeviceTree<'_> {
+        DeviceTree::parse(self.blob()).expect("blob is kept valid by edits")
+    }
+
+    /// Returns the blob, `totalsize` bytes
+    pub fn blob(&self) -> &[u8] {
+        &self.buf[..self.header(raw::OFF_TOTALSIZE)]
+    }
+
+    /// Returns the size of the buffer, the largest `totalsize` the blob can grow to
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn header(&self, field: usize) -> usize {
+        read_fdt_u32(self.buf, field) as usize
+    }
+
+    fn set_header(&mut self, field: usize, value: usize) {
+        self.write_u32(field, value as u32);
+    }
+
+    fn write_u32(&mut self, offs: usize, value: u32) {
+        self.buf[offs..offs + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    /// Returns `len` rounded up to the alignment of the blocks starting at or after `at`,
+    /// except the block at header field `grown`
+    fn gap_len(&self, at: usize, len: usize, grown: usize) -> usize {
+        let align = [(raw::OFF_DT_STRUCT, raw::FDT_STRUCT_ALIGN), (raw::OFF_DT_STRINGS, 1), (raw::OFF_MEM_RSVMAP, raw::FDT_RSVMAP_ALIGN)].iter()
+            .filter(|(field, _)| *field != grown && self.header(*field) >= at)
+            .fold(1, |align, (_, other)| align.max(*other));
+        len.div_ceil(align)*align
+    }
+
+    /// Move everything from `at` to the end of the blob `len` bytes up and update the header.
+    /// Blocks starting at or after `at` move, except the block at header field `grown`.
+    /// `len` is rounded up to keep the moved blocks aligned, see `gap_len()`.
+    /// The caller must have checked that the buffer is large enough.
+    fn insert_gap(&mut self, at: usize, len: usize, grown: usize) {
+        let len = self.gap_len(at, len, grown);
+        let totalsize = self.header(raw::OFF_TOTALSIZE);
+        self.buf.copy_within(at..totalsize, at + len);
+        self.buf[at..at + len].fill(0);
+        self.record(Step::Shift { at: at as u32, len: len as u32, end: totalsize as u32 });
+        for field in [raw::OFF_DT_STRUCT, raw::OFF_DT_STRINGS, raw::OFF_MEM_RSVMAP] {
+            let offs = self.header(field);
+            if field != grown && offs >= at {
+                self.set_header(field, offs + len);
+            }
+        }
+        self.set_header(raw::OFF_TOTALSIZE, totalsize + len);
+    }
+
+    /// Offset in the buffer of the end of the used part of the block at header field `field`
+    fn block_end(&self, field: usize) -> usize {
+        let start = self.header(field);
+        if field == raw::OFF_DT_STRINGS {
+            start + self.header(raw::OFF_SIZE_DT_STRINGS)
+        } else if self.header(raw::OFF_VERSION) >= 17 {
+            start + self.header(raw::OFF_SIZE_DT_STRUCT)
+        } else {
+            /* Before version 17 the structure block has no size, it is used up to FDT_END */
+            let tree = self.tree();
+            let mut iter = tree.tokens();
+            while !matches!(iter.next(), None | Some(Token::End)) {}
+            start + iter.offset()
+        }
+    }
+
+    /// Number of free bytes after the block at header field `field`, up to the next block or `totalsize`
+    fn block_room(&self, field: usize) -> usize {
+        let end = self.block_end(field);
+        [raw::OFF_DT_STRUCT, raw::OFF_DT_STRINGS, raw::OFF_MEM_RSVMAP].iter()
+            .filter(|other| **other != field)
+            .map(|other| self.header(*other))
+            .filter(|offs| *offs >= end)
+            .fold(self.header(raw::OFF_TOTALSIZE), usize::min) - end
+    }
+
+    /// Number of bytes the blob grows by if `grow_block()` grows the block at header field `field` by `len`
+    fn growth(&self, field: usize, len: usize) -> usize {
+        if len <= self.block_room(field) { 0 } else { self.gap_len(self.block_end(field), len, field) }
+    }
+
+    /// Make a gap of `len` zero bytes at `at` in the block at header field `field` and update its size.
+    /// The free space after the block is used if large enough, else the rest of the blob is shifted.
+    /// The caller must have checked that the buffer is large enough, see `growth()`.
+    fn grow_block(&mut self, at: usize, len: usize, field: usize) {
+        let end = self.block_end(field);
+        if self.block_room(field) >= len {
+            self.buf.copy_within(at..end, at + len);
+            self.buf[at..at + len].fill(0);
+            self.record(Step::Shift { at: at as u32, len: len as u32, end: end as u32 });
+        } else {
+            self.insert_gap(at, len, field);
+        }
+
+        if field == raw::OFF_DT_STRINGS {
+            let strings_size = self.header(raw::OFF_SIZE_DT_STRINGS);
+            self.set_header(raw::OFF_SIZE_DT_STRINGS, strings_size + len);
+        } else if self.header(raw::OFF_VERSION) >= 17 {
+            let struct_size = self.header(raw::OFF_SIZE_DT_STRUCT);
+            self.set_header(raw::OFF_SIZE_DT_STRUCT, struct_size + len);
+        }
+    }
+
+    /// Check that a running transaction can record `steps` more steps
+    fn reserve_steps(&self, steps: usize) -> Result<(), EditError> {
+        if self.journal.active && self.journal.len + steps > MAX_TRANSACTION_STEPS {
+            return Err(EditError::NoSpace)
+        }
+        Ok(())
+    }
+
+    /// Record a step if a transaction is running, the caller must have reserved it
+    fn record(&mut self, step: Step) {
+        if self.journal.active {
+            self.journal.steps[self.journal.len] = step;
+            self.journal.len += 1;
+        }
+    }
+
+    /// Record the words at `start..end` before they are overwritten
+    fn save_words(&mut self, start: usize, end: usize) {
+        for at in (start..end).step_by(4) {
+            let old = read_fdt_u32(self.buf, at);
+            self.record(Step::Word { at: at as u32, old });
+        }
+    }
+
+    /// Undo the last recorded step
+    fn undo(&mut self) {
+        self.journal.len -= 1;
+        match self.journal.steps[self.journal.len] {
+            Step::Shift { at, len, end } => {
+                let (at, len, end) = (at as usize, len as usize, end as usize);
+                self.buf.copy_within(at + len..end + len, at);
+                self.buf[end..end + len].fill(0);
+            },
+            Step::Slack { at, end } => {
+                for offs in (at as usize..end as usize).step_by(raw::FDT_TAGSIZE) {
+                    self.write_u32(offs, raw::FDT_NOP);
+                }
+            },
+            Step::Word { at, old } => self.write_u32(at as usize, old),
+        }
+    }
+
+    /// Make room for at least `struct_bytes` more bytes in the structure block and `strings_bytes`
+    /// in the strings block by shifting the blocks once, leaving free space after each of them.
+    /// Edits then fill this space instead of shifting the rest of the blob, so edits adding up to
+    /// at most the reserved bytes are done in place and do not fail with `EditError::NoSpace`.
+    ///
+    /// Free space already after a block counts towards the reserved bytes, the gaps are rounded
+    /// up to keep the blocks after them aligned. `totalsize` grows by the gaps, which must fit
+    /// in the buffer. On error the blob is left unchanged.
+    ///
+    pub fn ensure_slack(&mut self, struct_bytes: usize, strings_bytes: usize) -> Result<(), EditError> {
+        let align = |len: usize| len.div_ceil(raw::FDT_RSVMAP_ALIGN)*raw::FDT_RSVMAP_ALIGN;
+        let struct_gap = align(struct_bytes.saturating_sub(self.block_room(raw::OFF_DT_STRUCT)));
+        let strings_gap = align(strings_bytes.saturating_sub(self.block_room(raw::OFF_DT_STRINGS)));
+        if self.header(raw::OFF_TOTALSIZE) + struct_gap + strings_gap > self.buf.len() {
+            return Err(EditError::NoSpace)
+        }
+        self.reserve_steps(2)?;
+
+        for (field, gap) in [(raw::OFF_DT_STRUCT, struct_gap), (raw::OFF_DT_STRINGS, strings_gap)] {
+            if gap > 0 {
+                let end = self.block_end(field);
+                self.insert_gap(end, gap, field);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the edits of `f` as one: if it returns an error, every edit it made is undone before the
+    /// error is returned. Shifts are moved back, overwritten words restored, used NOP slack is filled
+    /// with NOPs again and the header is restored, leaving the blob as it was before `f`.
+    ///
+    /// Undoing needs no space in the buffer but at most `MAX_TRANSACTION_STEPS` steps are recorded,
+    /// an edit which would need more fails with `EditError::NoSpace`. Deleting a large node or
+    /// overwriting a large property in a transaction may not fit.
+    /// Transactions can be nested, an error in the inner one only undoes its own edits.
+    ///
+    pub fn transaction(&mut self, f: impl FnOnce(&mut Self) -> Result<(), EditError>) -> Result<(), EditError> {
+        let mut header = [0u8; raw::HEADER_SIZE];
+        header.copy_from_slice(&self.buf[..raw::HEADER_SIZE]);
+        let (mark, outer) = (self.journal.len, self.journal.active);
+
+        self.journal.active = true;
+        let result = f(self);
+        if result.is_err() {
+            while self.journal.len > mark {
+                self.undo();
+            }
+            self.buf[..raw::HEADER_SIZE].copy_from_slice(&header);
+        }
+
+        self.journal.active = outer;
+        if !outer {
+            self.journal.len = 0;
+        }
+        result
+    }
+
+    /// Offset of `name` in the strings block, if present
+    fn find_string(&self, name: &[u8]) -> Option<usize> {
+        let tree = self.tree();
+        tree.strings_block()
+            .windows(name.len() + 1)
+            .position(|s| s[..name.len()] == *name && s[name.len()] == 0)
+    }
+
+    /// Overwrite the property `name` of the node at `path` with NOP tokens.
+    /// The name is left in the strings block.
+    pub fn delete_prop(&mut self, path: &[u8], name: &[u8]) -> Result<(), EditError> {
+        let tree = self.tree();
+        let node = tree.get_node_by_path(path).ok_or(EditError::NodeNotFound)?;
+        let (start, len) = match node.get_prop_relaxed(name).ok_or(EditError::PropNotFound)? {
+            Token::Property(_, _, val) => {
+                let start = val.as_ptr() as usize - self.buf.as_ptr() as usize - 3*raw::FDT_TAGSIZE;
+                (start, prop_size(val.len()))
+            },
+            _ => return Err(EditError::PropNotFound)
+        };
+
+        self.reserve_steps(len/4)?;
+        self.save_words(start, start + len);
+        for offs in (start..start + len).step_by(raw::FDT_TAGSIZE) {
+            self.write_u32(offs, raw::FDT_NOP);
+        }
+        Ok(())
+    }
+
+    /// Overwrite the node at `path` and all its sub-nodes with NOP tokens.
+    /// Names in the strings block are left as is.
+    pub fn delete_node(&mut self, path: &[u8]) -> Result<(), EditError> {
+        self.delete_node_checked(path, false)
+    }
+
+    /// Same as `delete_node()`. If `check_dangling` is set, fails with `EditError::Referenced` while a phandle
+    /// of the node or one of its sub-nodes is referenced from outside of them, see `DeviceTree::phandle_references()`.
+    pub fn delete_node_checked(&mut self, path: &[u8], check_dangling: bool) -> Result<(), EditError> {
+        let tree = self.tree();
+        let node = tree.get_node_by_path(path).ok_or(EditError::NodeNotFound)?;
+        if node == tree.root() {
+            return Err(EditError::RootNode)
+        }
+        let (start, end) = match node {
+            Token::BeginNode(dt, offs, _) => {
+                let mut iter = TokenIterator::new_offs(dt, offs);
+                iter.skip_subtree();
+                (node.node_offset().ok_or(EditError::NodeNotFound)?, iter.offs)
+            },
+            _ => return Err(EditError::NodeNotFound)
+        };
+
+        if check_dangling {
+            let inside = |node: &Token| node.node_offset().is_some_and(|offs| (start..end).contains(&offs));
+            let referenced = tree.nodes()
+                .filter(|node| inside(node))
+                .filter_map(|node| compat::phandle(&node))
+                .any(|(phandle, _)| tree.phandle_references(phandle).any(|(referrer, _, _)| !inside(&referrer)));
+            if referenced {
+                return Err(EditError::Referenced)
+            }
+        }
+
+        let structs = self.header(raw::OFF_DT_STRUCT);
+        self.reserve_steps((end - start)/4)?;
+        self.save_words(structs + start, structs + end);
+        for offs in (structs + start..structs + end).step_by(raw::FDT_TAGSIZE) {
+            self.write_u32(offs, raw::FDT_NOP);
+        }
+        Ok(())
+    }
+
+    /// Overwrite cell `index` of the property `name` of the node at `path` with `value`
+    pub fn set_prop_u32(&mut self, path: &[u8], name: &[u8], index: usize, value: u32) -> Result<(), EditError> {
+        let tree = self.tree();
+        let node = tree.get_node_by_path(path).ok_or(EditError::NodeNotFound)?;
+        let at = match node.get_prop_relaxed(name).ok_or(EditError::PropNotFound)? {
+            Token::Property(_, _, val) if (index + 1)*4 <= val.len() => {
+                val.as_ptr() as usize - self.buf.as_ptr() as usize + index*4
+            },
+            _ => return Err(EditError::OutOfRange)
+        };
+
+        self.reserve_steps(1)?;
+        self.save_words(at, at + 4);
+        self.write_u32(at, value);
+        Ok(())
+    }
+
+    /// Set the value of the property `name` of the node at `path`, adding it if missing (see `add_prop()`).
+    /// An existing property is resized in place, shifting the rest of the blob if it grows.
+    /// On error the blob is left unchanged.
+    pub fn set_prop(&mut self, path: &[u8], name: &[u8], value: &[u8]) -> Result<(), EditError> {
+        self.write_prop(path, name, value, value.len())
+    }
+
+    /// Same as `set_prop()` but with a NUL-terminated string value, `value` must not include the NUL
+    pub fn set_prop_str(&mut self, path: &[u8], name: &[u8], value: &[u8]) -> Result<(), EditError> {
+        self.write_prop(path, name, value, value.len() + 1)
+    }
+
+    /// Set the property to `value` followed by zeros up to `len` bytes
+    fn write_prop(&mut self, path: &[u8], name: &[u8], value: &[u8], len: usize) -> Result<(), EditError> {
+        let tree = self.tree();
+        let node = tree.get_node_by_path(path).ok_or(EditError::NodeNotFound)?;
+        let (at, old_len) = match node.get_prop_relaxed(name) {
+            Some(Token::Property(_, _, val)) => {
+                (val.as_ptr() as usize - self.buf.as_ptr() as usize - 3*raw::FDT_TAGSIZE, val.len())
+            },
+            _ => return self.insert_prop(path, name, value, len)
+        };
+
+        let (old_size, size) = (prop_size(old_len), prop_size(len));
+        let gap = size.saturating_sub(old_size);
+        if self.header(raw::OFF_TOTALSIZE) + self.growth(raw::OFF_DT_STRUCT, gap) > self.buf.len() {
+            return Err(EditError::NoSpace)
+        }
+        self.reserve_steps(1 + old_size/4)?;
+
+        self.save_words(at, at + old_size);
+        if gap > 0 {
+            self.grow_block(at + old_size, gap, raw::OFF_DT_STRUCT);
+        }
+
+        self.write_u32(at + 4, len as u32);
+        self.buf[at + 12..at + 12 + value.len()].copy_from_slice(value);
+        self.buf[at + 12 + value.len()..at + size].fill(0);
+        for offs in (at + size..at + old_size).step_by(raw::FDT_TAGSIZE) {
+            self.write_u32(offs, raw::FDT_NOP);
+        }
+        Ok(())
+    }
+
+    /// Add the property `name` with `value` after the existing properties of the node at `path`.
+    /// The NOP slack there is used first, the rest of the blob is only shifted if it is too small.
+    /// On error the blob is left unchanged.
+    pub fn add_prop(&mut self, path: &[u8], name: &[u8], value: &[u8]) -> Result<(), EditError> {
+        self.insert_prop(path, name, value, value.len())
+    }
+
+    /// Add the property with `value` followed by zeros up to `len` bytes
+    fn insert_prop(&mut self, path: &[u8], name: &[u8], value: &[u8], len: usize) -> Result<(), EditError> {
+        if name.is_empty() || name.contains(&0) {
+            return Err(EditError::InvalidName)
+        }
+
+        let tree = self.tree();
+        let node = tree.get_node_by_path(path).ok_or(EditError::NodeNotFound)?;
+        if node.get_prop_relaxed(name).is_some() {
+            return Err(EditError::Exists)
+        }
+        let (slack_start, slack_end) = node.slack_span().ok_or(EditError::NodeNotFound)?;
+
+        /* Check all space needed up front so a failure leaves the blob untouched */
+        let size = prop_size(len);
+        let gap = size.saturating_sub(slack_end - slack_start);
+        let nameoff = self.find_string(name);
+        let string_len = if nameoff.is_some() { 0 } else { name.len() + 1 };
+        let growth = self.growth(raw::OFF_DT_STRUCT, gap) + self.growth(raw::OFF_DT_STRINGS, string_len);
+        if self.header(raw::OFF_TOTALSIZE) + growth > self.buf.len() {
+            return Err(EditError::NoSpace)
+        }
+        self.reserve_steps(3)?;
+
+        let nameoff = match nameoff {
+            Some(nameoff) => nameoff,
+            None => {
+                let strings = self.header(raw::OFF_DT_STRINGS);
+                let strings_size = self.header(raw::OFF_SIZE_DT_STRINGS);
+                self.grow_block(strings + strings_size, string_len, raw::OFF_DT_STRINGS);
+                self.buf[strings + strings_size..strings + strings_size + name.len()].copy_from_slice(name);
+                strings_size
+            }
+        };
+
+        /* The structure block may have moved if the strings block precedes it */
+        let structs = self.header(raw::OFF_DT_STRUCT);
+        if gap > 0 {
+            self.grow_block(structs + slack_end, gap, raw::OFF_DT_STRUCT);
+        }
+
+        let at = structs + slack_start;
+        if slack_end > slack_start {
+            self.record(Step::Slack { at: at as u32, end: (structs + slack_end) as u32 });
+        }
+        self.write_u32(at, raw::FDT_PROP);
+        self.write_u32(at + 4, len as u32);
+        self.write_u32(at + 8, nameoff as u32);
+        self.buf[at + 12..at + 12 + value.len()].copy_from_slice(value);
+        self.buf[at + 12 + value.len()..at + size].fill(0);
+        Ok(())
+    }
+
+    /// Add an empty node `name` after the existing sub-nodes of the node at `path`.
+    /// The rest of the blob is shifted unless there is free space after the structure block.
+    /// On error the blob is left unchanged.
+    pub fn add_node(&mut self, path: &[u8], name: &[u8]) -> Result<(), EditError> {
+        if name.is_empty() || name.contains(&0) || name.contains(&b'/') {
+            return Err(EditError::InvalidName)
+        }
+
+        let tree = self.tree();
+        let node = tree.get_node_by_path(path).ok_or(EditError::NodeNotFound)?;
+        if node.get_node(name).is_some() {
+            return Err(EditError::Exists)
+        }
+        /* The new node goes before the FDT_END_NODE of the parent */
+        let end = match node {
+            Token::BeginNode(dt, offs, _) => {
+                let mut iter = TokenIterator::new_offs(dt, offs);
+                iter.skip_subtree();
+                iter.offs - raw::FDT_TAGSIZE
+            },
+            _ => return Err(EditError::NodeNotFound)
+        };
+
+        let size = 2*raw::FDT_TAGSIZE + (name.len() + 1).div_ceil(raw::FDT_STRUCT_ALIGN)*raw::FDT_STRUCT_ALIGN;
+        if self.header(raw::OFF_TOTALSIZE) + self.growth(raw::OFF_DT_STRUCT, size) > self.buf.len() {
+            return Err(EditError::NoSpace)
+        }
+        self.reserve_steps(1)?;
+
+        let at = self.header(raw::OFF_DT_STRUCT) + end;
+        self.grow_block(at, size, raw::OFF_DT_STRUCT);
+        self.write_u32(at, raw::FDT_BEGIN_NODE);
+        self.buf[at + 4..at + 4 + name.len()].copy_from_slice(name);
+        self.write_u32(at + size - raw::FDT_TAGSIZE, raw::FDT_END_NODE);
+        Ok(())
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns the offsets in the structure block of the run of NOP tokens directly after the
+    /// properties of this node, before its first sub-node or its end. The run is empty if there are no NOPs.
+    /// Returns None if not a node.
+    pub(crate) fn slack_span(&self) -> Option<(usize, usize)> {
+        let (dt, offs) = match self {
+            Token::BeginNode(dt, offs, _) => (*dt, *offs),
+            _ => return None
+        };
+
+        let mut iter = TokenIterator::new_offs(dt, offs);
+        let mut start = offs;
+        loop {
+            let here = iter.offs;
+            match iter.next_checked()? {
+                Ok(Token::Property(_, _, _)) => start = iter.offs,
+                Ok(Token::NoOperation) => (),
+                /* A oversized property is no slack, nor can anything be added after it */
+                _ => return Some((start, here))
+            }
+        }
+    }
+
+    /// Returns the number of bytes of NOP tokens directly after the properties of this node,
+    /// which `DeviceTreeMut::add_prop()` can fill without shifting the rest of the blob.
+    /// Returns 0 if there are none or token is not a node.
+    ///
+    pub fn nop_slack(&self) -> usize {
+        self.slack_span().map_or(0, |(start, end)| end - start)
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns the number of bytes of NOP tokens in the structure block
+    pub fn total_nop_bytes(&self) -> usize {
+        /* Oversized properties are errors here, not NOPs */
+        self.try_tokens().filter(|tok| matches!(tok, Ok(Token::NoOperation))).count() * raw::FDT_TAGSIZE
+    }
+}
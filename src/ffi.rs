@@ -0,0 +1,193 @@
+//! # C FFI
+//! libfdt compatible functions for the read-only subset used by bootloaders, available with the `ffi` feature.
+//! Node offsets are offsets of FDT_BEGIN_NODE tokens in the structure block as in libfdt,
+//! see `Token::node_offset()`. Errors are returned as negative libfdt error codes.
+//!
+
+use core::ffi::{c_char, c_int, c_void};
+
+use crate::{DeviceTree, Error, Token, TokenIterator};
+
+/// The requested node or property does not exist
+pub const FDT_ERR_NOTFOUND: c_int = 1;
+/// Offset is not the offset of a node
+pub const FDT_ERR_BADOFFSET: c_int = 4;
+/// Path is malformed
+pub const FDT_ERR_BADPATH: c_int = 5;
+/// Blob is truncated
+pub const FDT_ERR_TRUNCATED: c_int = 8;
+/// Magic does not match
+pub const FDT_ERR_BADMAGIC: c_int = 9;
+/// Unsupported version
+pub const FDT_ERR_BADVERSION: c_int = 10;
+/// Malformed structure block
+pub const FDT_ERR_BADSTRUCTURE: c_int = 11;
+/// Blocks out of order or out of bounds
+pub const FDT_ERR_BADLAYOUT: c_int = 12;
+
+/// Largest blob accepted, offsets must fit in a c_int
+const MAX_SIZE: usize = c_int::MAX as usize;
+
+/// Translate a parse error to a negative libfdt error code
+fn error_code(e: Error) -> c_int {
+    -match e {
+        Error::InvalidMagic => FDT_ERR_BADMAGIC,
+        Error::UnsupportedVersion(_) => FDT_ERR_BADVERSION,
+        Error::Truncated | Error::TooLarge(_) | Error::BufferTooSmall(_) => FDT_ERR_TRUNCATED,
+        Error::BlockOutOfBounds => FDT_ERR_BADLAYOUT,
+        Error::NoRootNode | Error::Unaligned | Error::AlreadyInitialized | Error::CrcMismatch(_) => FDT_ERR_BADSTRUCTURE,
+    }
+}
+
+/// Parse the blob at `fdt`
+unsafe fn tree(fdt: *const c_void) -> Result<DeviceTree<'static>, c_int> {
+    DeviceTree::from_ptr(fdt as *const u8, MAX_SIZE).map_err(error_code)
+}
+
+/// Returns the node at `offset`
+fn node<'a>(dt: &'a DeviceTree<'a>, offset: c_int) -> Result<Token<'a>, c_int> {
+    if offset < 0 {
+        return Err(-FDT_ERR_BADOFFSET)
+    }
+    dt.node_at_offset(offset as usize).ok_or(-FDT_ERR_BADOFFSET)
+}
+
+/// Store `value` in `lenp` unless it's null
+unsafe fn set_len(lenp: *mut c_int, value: c_int) {
+    if !lenp.is_null() {
+        *lenp = value;
+    }
+}
+
+/// Check the header of the blob at `fdt`, returns 0 if valid.
+///
+/// # Safety
+/// `fdt` must point to a readable blob, see `DeviceTree::from_ptr()`.
+#[no_mangle]
+pub unsafe extern "C" fn fdt_check_header(fdt: *const c_void) -> c_int {
+    match tree(fdt) {
+        Ok(_) => 0,
+        Err(e) => e
+    }
+}
+
+/// Returns the offset of the node at `path`, e.g. "/soc/serial@10000000".
+///
+/// # Safety
+/// `fdt` must point to a readable blob, see `DeviceTree::from_ptr()`,
+/// and `path` to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn fdt_path_offset(fdt: *const c_void, path: *const c_char) -> c_int {
+    let dt = match tree(fdt) {
+        Ok(dt) => dt,
+        Err(e) => return e
+    };
+
+    let path = core::ffi::CStr::from_ptr(path).to_bytes();
+    if path.first() != Some(&b'/') {
+        return -FDT_ERR_BADPATH
+    }
+    match dt.get_node_by_path(path).and_then(|node| node.node_offset()) {
+        Some(offset) => offset as c_int,
+        None => -FDT_ERR_NOTFOUND
+    }
+}
+
+/// Returns the value of property `name` of the node at `nodeoffset` and stores its length in `lenp`.
+/// Returns null and stores a negative error code in `lenp` on failure.
+///
+/// # Safety
+/// `fdt` must point to a readable blob, see `DeviceTree::from_ptr()`, `name` to a NUL-terminated
+/// string and `lenp` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn fdt_getprop(fdt: *const c_void, nodeoffset: c_int, name: *const c_char, lenp: *mut c_int) -> *const c_void {
+    let dt = match tree(fdt) {
+        Ok(dt) => dt,
+        Err(e) => { set_len(lenp, e); return core::ptr::null() }
+    };
+    let node = match node(&dt, nodeoffset) {
+        Ok(node) => node,
+        Err(e) => { set_len(lenp, e); return core::ptr::null() }
+    };
+
+    match node.get_prop(core::ffi::CStr::from_ptr(name).to_bytes()) {
+        Some(Token::Property(_, _, val)) => {
+            set_len(lenp, val.len() as c_int);
+            val.as_ptr() as *const c_void
+        },
+        _ => {
+            set_len(lenp, -FDT_ERR_NOTFOUND);
+            core::ptr::null()
+        }
+    }
+}
+
+/// Returns the NUL-terminated name of the node at `nodeoffset` and stores its length in `lenp`.
+/// Returns null and stores a negative error code in `lenp` on failure.
+///
+/// # Safety
+/// `fdt` must point to a readable blob, see `DeviceTree::from_ptr()`,
+/// and `lenp` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn fdt_get_name(fdt: *const c_void, nodeoffset: c_int, lenp: *mut c_int) -> *const c_char {
+    let dt = match tree(fdt) {
+        Ok(dt) => dt,
+        Err(e) => { set_len(lenp, e); return core::ptr::null() }
+    };
+
+    match node(&dt, nodeoffset) {
+        Ok(node) => {
+            set_len(lenp, node.name().len() as c_int);
+            node.name().as_ptr() as *const c_char
+        },
+        Err(e) => {
+            set_len(lenp, e);
+            core::ptr::null()
+        }
+    }
+}
+
+/// Returns the offset of the node following the node at `offset` in depth-first order,
+/// or of the root node if `offset` is negative. If `depth` is not null it's increased
+/// when entering a node and decreased when leaving one, and the search stops when it becomes negative.
+/// Returns -FDT_ERR_NOTFOUND at the end of the tree.
+///
+/// # Safety
+/// `fdt` must point to a readable blob, see `DeviceTree::from_ptr()`,
+/// and `depth` must be null or valid for reads and writes.
+#[no_mangle]
+pub unsafe extern "C" fn fdt_next_node(fdt: *const c_void, offset: c_int, depth: *mut c_int) -> c_int {
+    let dt = match tree(fdt) {
+        Ok(dt) => dt,
+        Err(e) => return e
+    };
+
+    let mut iter = if offset >= 0 {
+        match node(&dt, offset) {
+            Ok(Token::BeginNode(_, contents, _)) => TokenIterator::new_offs(&dt, contents),
+            Ok(_) => return -FDT_ERR_BADOFFSET,
+            Err(e) => return e
+        }
+    } else {
+        TokenIterator::new(&dt)
+    };
+
+    loop {
+        let offs = iter.offs;
+        match iter.next() {
+            Some(Token::BeginNode(_, _, _)) => {
+                if !depth.is_null() { *depth += 1; }
+                return offs as c_int
+            },
+            Some(Token::EndNode) => {
+                if !depth.is_null() {
+                    *depth -= 1;
+                    if *depth < 0 { return iter.offs as c_int }
+                }
+            },
+            Some(Token::Property(_, _, _)) | Some(Token::NoOperation) => (),
+            Some(Token::End) => return -FDT_ERR_NOTFOUND,
+            Some(Token::Invalid(_)) | None => return -FDT_ERR_BADSTRUCTURE
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! # Framebuffer
+//! Decoding of `simple-framebuffer` nodes set up by the bootloader.
+//!
+
+use crate::{DeviceTree, Token};
+
+/// # PixelFormat
+/// Pixel format of a framebuffer, from the `format` property.
+/// Components are listed from the most significant bits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PixelFormat<'a> {
+    R5G6B5,
+    R5G5B5A1,
+    X1R5G5B5,
+    A1R5G5B5,
+    R8G8B8,
+    X8R8G8B8,
+    A8R8G8B8,
+    X8B8G8R8,
+    A8B8G8R8,
+    X2R10G10B10,
+    A2R10G10B10,
+    /// Format not known by this crate
+    Unknown(&'a [u8]),
+}
+
+impl<'a> PixelFormat<'a> {
+    /// Decode a `format` string
+    pub fn from_name(name: &'a [u8]) -> PixelFormat<'a> {
+        match name {
+            b"r5g6b5" => PixelFormat::R5G6B5,
+            b"r5g5b5a1" => PixelFormat::R5G5B5A1,
+            b"x1r5g5b5" => PixelFormat::X1R5G5B5,
+            b"a1r5g5b5" => PixelFormat::A1R5G5B5,
+            b"r8g8b8" => PixelFormat::R8G8B8,
+            b"x8r8g8b8" => PixelFormat::X8R8G8B8,
+            b"a8r8g8b8" => PixelFormat::A8R8G8B8,
+            b"x8b8g8r8" => PixelFormat::X8B8G8R8,
+            b"a8b8g8r8" => PixelFormat::A8B8G8R8,
+            b"x2r10g10b10" => PixelFormat::X2R10G10B10,
+            b"a2r10g10b10" => PixelFormat::A2R10G10B10,
+            _ => PixelFormat::Unknown(name)
+        }
+    }
+
+    /// Returns the size of a pixel in bytes
+    /// Returns None if the format is unknown
+    pub fn bytes_per_pixel(&self) -> Option<usize> {
+        match self {
+            PixelFormat::R5G6B5 | PixelFormat::R5G5B5A1 | PixelFormat::X1R5G5B5 | PixelFormat::A1R5G5B5 => Some(2),
+            PixelFormat::R8G8B8 => Some(3),
+            PixelFormat::Unknown(_) => None,
+            _ => Some(4)
+        }
+    }
+}
+
+/// # FramebufferInfo
+/// A `simple-framebuffer` node, see `DeviceTree::simple_framebuffer()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FramebufferInfo<'a> {
+    /// The framebuffer node
+    pub node: Token<'a>,
+    /// CPU address of the framebuffer, translated through the `ranges` of its ancestors
+    pub address: u64,
+    /// Size of the framebuffer in bytes
+    pub size: u64,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Length of a line in bytes
+    pub stride: u32,
+    pub format: PixelFormat<'a>,
+}
+
+impl<'a> FramebufferInfo<'a> {
+
+    /// Decode a framebuffer node.
+    /// Returns None if `node` is not compatible with "simple-framebuffer", is disabled,
+    /// or a `reg`, `width`, `height`, `stride` or `format` property is missing or invalid.
+    ///
+    pub fn from_node(node: &Token<'a>) -> Option<FramebufferInfo<'a>> {
+        if !node.is_compatible(b"simple-framebuffer") || !node.is_enabled() {
+            return None
+        }
+
+        let reg = node.reg()?.next()?;
+        let u32_prop = |name: &[u8]| node.get_prop(name).and_then(|prop| prop.prop_u32(0));
+        Some(FramebufferInfo {
+            node: *node,
+            address: node.translate_address(reg.address)?,
+            size: reg.size,
+            width: u32_prop(b"width")?,
+            height: u32_prop(b"height")?,
+            stride: u32_prop(b"stride")?,
+            format: PixelFormat::from_name(node.get_prop(b"format")?.prop_str()?),
+        })
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Find the first usable framebuffer node in /chosen, or at the root if there is none,
+    /// see `FramebufferInfo::from_node()`.
+    /// Returns None if there is no usable framebuffer.
+    ///
+    pub fn simple_framebuffer(&self) -> Option<FramebufferInfo<'_>> {
+        let root = self.root();
+        root.get_node(b"chosen")
+            .into_iter()
+            .chain(Some(root))
+            .flat_map(|parent| parent.into_iter())
+            .find_map(|node| FramebufferInfo::from_node(&node))
+    }
+}
@@ -0,0 +1,104 @@
+//! # GPIO
+//! Decoding of GPIO specifiers and the `gpio-keys` and `gpio-leds` bindings.
+//!
+
+use crate::{PhandleArgs, Token};
+
+/// # GpioSpec
+/// A GPIO specifier, e.g. `<&gpio0 5 1>`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GpioSpec<'a> {
+    /// The GPIO controller
+    pub controller: Token<'a>,
+    /// First argument, the pin number for most controllers
+    pub pin: u32,
+    /// Second argument, the flags (e.g. active low) for most controllers, 0 if missing
+    pub flags: u32,
+}
+
+impl<'a> GpioSpec<'a> {
+    /// Decode the arguments of a specifier
+    /// Returns None if there are no arguments
+    pub fn from_args(args: &PhandleArgs<'a>) -> Option<GpioSpec<'a>> {
+        Some(GpioSpec {
+            controller: args.provider,
+            pin: args.arg(0)?,
+            flags: args.arg(1).unwrap_or(0),
+        })
+    }
+}
+
+/// # GpioKey
+/// A child of a `gpio-keys` node, see `Token::gpio_keys()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GpioKey<'a> {
+    pub node: Token<'a>,
+    pub label: Option<&'a [u8]>,
+    /// Input event code, `linux,code` property
+    pub code: Option<u32>,
+    pub gpio: Option<GpioSpec<'a>>,
+}
+
+/// # LedDefaultState
+/// Initial state of a LED, from the `default-state` property
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LedDefaultState {
+    On,
+    Off,
+    /// Keep the state set by the bootloader
+    Keep,
+}
+
+/// # GpioLed
+/// A child of a `gpio-leds` node, see `Token::gpio_leds()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GpioLed<'a> {
+    pub node: Token<'a>,
+    pub label: Option<&'a [u8]>,
+    pub gpio: Option<GpioSpec<'a>>,
+    /// None if missing or unknown
+    pub default_state: Option<LedDefaultState>,
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns the first GPIO specifier of the property `name` (e.g. `gpios` or `reset-gpios`) of this node
+    /// Returns None if there is no such property or it could not be decoded.
+    ///
+    pub fn gpio(&self, name: &[u8]) -> Option<GpioSpec<'a>> {
+        GpioSpec::from_args(&self.get_prop(name)?.phandle_args(b"#gpio-cells").next()?)
+    }
+
+    /// Returns a iterator over the keys of this `gpio-keys` node
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn gpio_keys(&self) -> impl Iterator<Item = GpioKey<'a>> {
+        self.into_iter()
+            .filter(|node| matches!(node, Token::BeginNode(_, _, _)))
+            .map(|node| GpioKey {
+                node,
+                label: node.get_prop(b"label").and_then(|prop| prop.prop_str()),
+                code: node.get_prop(b"linux,code").and_then(|prop| prop.prop_u32(0)),
+                gpio: node.gpio(b"gpios"),
+            })
+    }
+
+    /// Returns a iterator over the LEDs of this `gpio-leds` node
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn gpio_leds(&self) -> impl Iterator<Item = GpioLed<'a>> {
+        self.into_iter()
+            .filter(|node| matches!(node, Token::BeginNode(_, _, _)))
+            .map(|node| GpioLed {
+                node,
+                label: node.get_prop(b"label").and_then(|prop| prop.prop_str()),
+                gpio: node.gpio(b"gpios"),
+                default_state: match node.get_prop(b"default-state").and_then(|prop| prop.prop_str()) {
+                    Some(b"on") => Some(LedDefaultState::On),
+                    Some(b"off") => Some(LedDefaultState::Off),
+                    Some(b"keep") => Some(LedDefaultState::Keep),
+                    _ => None
+                },
+            })
+    }
+}
@@ -0,0 +1,93 @@
+//! # Hexdump
+//! Raw dump of a property value for values `PropValue::classify()` can only call `Bytes` or `Cells`,
+//! e.g. unknown vendor properties. Each line has the offset, the bytes in hex, the printable
+//! ASCII bytes and, if the value is a multiple of 4 bytes, the big-endian cells:
+//!
+//! ```text
+//! vendor,blob (12 bytes)
+//!   0000: 00 00 00 01 61 62 63 00  |....abc.|  0x00000001 0x61626300
+//!   0008: de ad be ef              |....|      0xdeadbeef
+//! ```
+//!
+//! Long values are cut after `HexdumpOptions::max_lines` lines with a line counting the bytes left out.
+//!
+
+use core::fmt;
+
+use crate::dts::write_name;
+use crate::{utils, Token};
+
+/// # HexdumpOptions
+/// Layout of `Token::hexdump_with()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HexdumpOptions {
+    /// Number of bytes per line, at least 1. Cells are only shown if it is a multiple of 4
+    pub bytes_per_line: usize,
+    /// Number of lines shown before the dump is cut
+    pub max_lines: usize,
+}
+
+impl Default for HexdumpOptions {
+    /// 8 bytes per line, at most 16 lines
+    fn default() -> Self {
+        HexdumpOptions { bytes_per_line: 8, max_lines: 16 }
+    }
+}
+
+/// Write one line of `line` at `offset`, padded to `width` bytes
+fn write_line(w: &mut dyn fmt::Write, offset: usize, line: &[u8], width: usize, cells: bool) -> fmt::Result {
+    write!(w, "  {:04x}:", offset)?;
+    for byte in line {
+        write!(w, " {:02x}", byte)?;
+    }
+    for _ in line.len()..width {
+        w.write_str("   ")?;
+    }
+
+    w.write_str("  |")?;
+    for byte in line {
+        w.write_char(if (0x20..0x7f).contains(byte) { *byte as char } else { '.' })?;
+    }
+    w.write_char('|')?;
+
+    if cells {
+        for _ in line.len()..width {
+            w.write_char(' ')?;
+        }
+        w.write_char(' ')?;
+        for cell in line.chunks_exact(4) {
+            write!(w, " 0x{:08x}", utils::read_fdt_u32(cell, 0))?;
+        }
+    }
+    w.write_char('\n')
+}
+
+impl<'a> Token<'a> {
+
+    /// Write a hexdump of a property to `w` with `bytes_per_line` bytes per line,
+    /// see the module documentation. Writes nothing if not a property.
+    pub fn hexdump<W: fmt::Write>(&self, w: &mut W, bytes_per_line: usize) -> fmt::Result {
+        self.hexdump_with(w, &HexdumpOptions { bytes_per_line, ..HexdumpOptions::default() })
+    }
+
+    /// Same as `hexdump()` but with the layout of `opts`
+    pub fn hexdump_with<W: fmt::Write>(&self, w: &mut W, opts: &HexdumpOptions) -> fmt::Result {
+        let (name, val) = match self {
+            Token::Property(_, name, val) => (*name, *val),
+            _ => return Ok(())
+        };
+
+        write_name(w, name)?;
+        writeln!(w, " ({} bytes)", val.len())?;
+
+        let width = opts.bytes_per_line.max(1);
+        let cells = val.len().is_multiple_of(4) && width.is_multiple_of(4);
+        for (n, line) in val.chunks(width).enumerate() {
+            if n == opts.max_lines {
+                return writeln!(w, "  ... {} more bytes", val.len() - n*width)
+            }
+            write_line(w, n*width, line, width, cells)?;
+        }
+        Ok(())
+    }
+}
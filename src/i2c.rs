@@ -0,0 +1,46 @@
+//! # I2C
+//! Enumeration of the devices on an I2C bus.
+//!
+
+use crate::{props, Token};
+
+/// # I2cChild
+/// A device on an I2C bus, see `Token::i2c_children()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct I2cChild<'a> {
+    pub node: Token<'a>,
+    /// The address with the flag bits masked off
+    pub address: u32,
+    /// The address is a 10-bit address
+    pub ten_bit: bool,
+    /// The address is one the controller responds to as a slave, not a device on the bus
+    pub own_slave: bool,
+}
+
+impl I2cChild<'_> {
+    /// Flag in `reg` marking a 10-bit address
+    pub const TEN_BIT_ADDRESS: u32 = 1 << 31;
+    /// Flag in `reg` marking an own slave address
+    pub const OWN_SLAVE_ADDRESS: u32 = 1 << 30;
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the enabled devices of this I2C controller node.
+    /// Children without a `reg` property are skipped.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn i2c_children(&self) -> impl Iterator<Item = I2cChild<'a>> {
+        self.into_iter()
+            .filter(|node| matches!(node, Token::BeginNode(_, _, _)) && node.is_enabled())
+            .filter_map(|node| {
+                let reg = node.get_prop(props::REG)?.prop_u32(0)?;
+                Some(I2cChild {
+                    node,
+                    address: reg & !(I2cChild::TEN_BIT_ADDRESS | I2cChild::OWN_SLAVE_ADDRESS),
+                    ten_bit: reg & I2cChild::TEN_BIT_ADDRESS != 0,
+                    own_slave: reg & I2cChild::OWN_SLAVE_ADDRESS != 0,
+                })
+            })
+    }
+}
@@ -0,0 +1,232 @@
+//! # Indexes
+//! Lookup tables built in one pass over the tree into caller provided storage.
+//!
+
+use crate::utils::dt_hash;
+use crate::{props, valid_phandle, DeviceTree, DtPath, Token, MAX_DEPTH};
+
+/// # Errors
+/// Errors which can be returned when building an index
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IndexError {
+    /// Storage is too small, contains the required number of entries
+    InsufficientStorage(usize),
+
+    /// The node at `offset` in the structure block is nested deeper than `MAX_DEPTH`
+    TooDeep { offset: usize },
+}
+
+/// # PhandleEntry
+/// Storage for one entry in a `PhandleIndex`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct PhandleEntry {
+    phandle: u32,
+    offset: u32
+}
+
+impl PhandleEntry {
+    pub(crate) fn new(phandle: u32, offset: usize) -> PhandleEntry {
+        PhandleEntry { phandle, offset: offset as u32 }
+    }
+
+    /// The phandle of this entry
+    pub fn phandle(&self) -> u32 {
+        self.phandle
+    }
+
+    /// Offset of the node, see `Token::node_offset()`
+    pub fn offset(&self) -> usize {
+        self.offset as usize
+    }
+}
+
+/// # PhandleIndex
+/// Maps phandles to node offsets (see `Token::node_offset()`) using a binary search,
+/// see `DeviceTree::build_phandle_index()`.
+#[derive(Debug)]
+pub struct PhandleIndex<'b> {
+    tree: usize,
+    entries: &'b [PhandleEntry]
+}
+
+impl<'b> PhandleIndex<'b> {
+    /// Returns the offset of the node with `phandle`, the first in document order if several have it
+    /// Returns None if there is no such node
+    pub fn lookup(&self, phandle: u32) -> Option<usize> {
+        let i = self.entries.partition_point(|entry| entry.phandle < phandle);
+        self.entries.get(i)
+            .filter(|entry| entry.phandle == phandle)
+            .map(|entry| entry.offset())
+    }
+
+    /// Returns all entries sorted by phandle, then offset
+    pub fn entries(&self) -> &'b [PhandleEntry] {
+        self.entries
+    }
+
+    /// Sort the first `count` entries of `storage`, found in `dt`, into an index.
+    /// Returns `IndexError::InsufficientStorage` if `storage` has fewer entries.
+    pub(crate) fn from_entries(dt: &DeviceTree, storage: &'b mut [PhandleEntry], count: usize) -> Result<PhandleIndex<'b>, IndexError> {
+        if count > storage.len() {
+            return Err(IndexError::InsufficientStorage(count))
+        }
+
+        let entries = &mut storage[..count];
+        /* Offsets break ties so duplicate phandles resolve to the first node like a scan */
+        entries.sort_unstable_by_key(|entry| (entry.phandle, entry.offset));
+        Ok(PhandleIndex { tree: dt.blob().as_ptr() as usize, entries })
+    }
+
+    /// Returns true if this index was built from `dt`
+    pub(crate) fn is_for(&self, dt: &DeviceTree) -> bool {
+        self.tree == dt.blob().as_ptr() as usize
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Build a index of all phandles in the tree in one pass, using `storage` for the entries.
+    /// Only valid phandles are indexed, see `max_phandle()`.
+    /// Returns `IndexError::InsufficientStorage` with the required number of entries if `storage` is too small.
+    ///
+    pub fn build_phandle_index<'b>(&self, storage: &'b mut [PhandleEntry]) -> Result<PhandleIndex<'b>, IndexError> {
+        let mut count = 0;
+        for (node, prop) in self.all_props() {
+            if !matches!(prop.name(), props::PHANDLE | props::LINUX_PHANDLE) {
+                continue
+            }
+
+            if let (Some(phandle), Some(offset)) = (valid_phandle(&prop), node.node_offset()) {
+                /* Keep counting to report the required size */
+                if let Some(entry) = storage.get_mut(count) {
+                    *entry = PhandleEntry::new(phandle, offset);
+                }
+                count += 1;
+            }
+        }
+
+        PhandleIndex::from_entries(self, storage, count)
+    }
+}
+
+/// Parent offset used for the root node
+const NO_PARENT: u32 = u32::MAX;
+
+/// # NodeIndexEntry
+/// Storage for one node in a `NodeIndex`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct NodeIndexEntry {
+    offset: u32,
+    parent: u32,
+    hash: u32
+}
+
+impl NodeIndexEntry {
+    /// Offset of the node, see `Token::node_offset()`
+    pub fn offset(&self) -> usize {
+        self.offset as usize
+    }
+
+    /// Offset of the parent node, None for the root node
+    pub fn parent(&self) -> Option<usize> {
+        if self.parent == NO_PARENT { None } else { Some(self.parent as usize) }
+    }
+
+    /// Hash of the node name, see `utils::dt_hash()`
+    pub fn hash(&self) -> u32 {
+        self.hash
+    }
+}
+
+/// # NodeIndex
+/// Resolves paths using a binary search per path component,
+/// see `DeviceTree::build_node_index()`.
+#[derive(Debug)]
+pub struct NodeIndex<'b> {
+    dt: &'b DeviceTree<'b>,
+    /* Sorted by (parent, hash, offset) */
+    entries: &'b [NodeIndexEntry],
+    /* Offset of the root node, None for a tree without nodes */
+    root: Option<u32>
+}
+
+impl<'b> NodeIndex<'b> {
+    /// Find a node by its full path, same as `DeviceTree::get_node_by_path()`
+    /// Returns None if path is invalid or there is no such node.
+    pub fn lookup_path(&self, path: &[u8]) -> Option<Token<'b>> {
+        let offset = DtPath::parse(path).ok()?
+            .components()
+            .try_fold(self.root?, |parent, component| self.lookup_child(parent, component))?;
+
+        self.dt.node_at_offset(offset as usize)
+    }
+
+    /// Returns all entries sorted by parent offset, name hash and offset
+    pub fn entries(&self) -> &'b [NodeIndexEntry] {
+        self.entries
+    }
+
+    fn lookup_child(&self, parent: u32, name: &[u8]) -> Option<u32> {
+        let hash = dt_hash(name);
+        let start = self.entries.partition_point(|entry| (entry.parent, entry.hash) < (parent, hash));
+
+        /* Compare names of all entries with the same hash */
+        self.entries[start..].iter()
+            .take_while(|entry| entry.parent == parent && entry.hash == hash)
+            .find(|entry| {
+                self.dt.node_at_offset(entry.offset())
+                    .is_some_and(|node| node.name() == name)
+            })
+            .map(|entry| entry.offset)
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Build a index of all nodes in the tree in one pass, using `storage` for the entries.
+    /// Returns `IndexError::InsufficientStorage` with the required number of entries if `storage` is too small,
+    /// or `IndexError::TooDeep` if the tree is deeper than `MAX_DEPTH`.
+    ///
+    pub fn build_node_index<'b>(&'b self, storage: &'b mut [NodeIndexEntry]) -> Result<NodeIndex<'b>, IndexError> {
+        let mut stack = [NO_PARENT; MAX_DEPTH];
+        let mut depth = 0;
+        let mut count = 0;
+        let mut root = None;
+
+        for tok in self.tokens() {
+            match tok {
+                Token::BeginNode(_, _, name) => {
+                    let offset = tok.node_offset().unwrap_or_default() as u32;
+                    if depth >= MAX_DEPTH {
+                        return Err(IndexError::TooDeep { offset: offset as usize })
+                    }
+                    let parent = if depth == 0 { NO_PARENT } else { stack[depth - 1] };
+                    root = root.or(Some(offset));
+
+                    /* Keep counting to report the required size */
+                    if let Some(entry) = storage.get_mut(count) {
+                        *entry = NodeIndexEntry { offset, parent, hash: dt_hash(name) };
+                    }
+                    count += 1;
+
+                    stack[depth] = offset;
+                    depth += 1;
+                },
+                Token::EndNode => {
+                    if depth == 0 { break }
+                    depth -= 1;
+                },
+                _ => ()
+            }
+        }
+
+        if count > storage.len() {
+            return Err(IndexError::InsufficientStorage(count))
+        }
+
+        let entries = &mut storage[..count];
+        /* Offsets break ties so duplicate names resolve to the first node like `Token::get_node()` */
+        entries.sort_unstable_by_key(|entry| (entry.parent, entry.hash, entry.offset));
+        Ok(NodeIndex { dt: self, entries, root })
+    }
+}
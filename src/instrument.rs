@@ -0,0 +1,19 @@
+//! # Instrument
+//! Counters for testing the cost of operations, enabled by the `instrument` feature.
+//! Not intended for use outside of tests.
+//!
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub(crate) static TOKEN_READS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of tokens decoded by `TokenIterator` since the last reset.
+/// Tokens passed over by `TokenIterator::skip_subtree()` are not counted.
+pub fn token_reads() -> usize {
+    TOKEN_READS.load(Ordering::Relaxed)
+}
+
+/// Reset the token counter
+pub fn reset() {
+    TOKEN_READS.store(0, Ordering::Relaxed)
+}
@@ -0,0 +1,239 @@
+//! # Interrupts
+//! Decoding of `interrupts` and `interrupts-extended` using the `#interrupt-cells` of the interrupt parent.
+//! Interrupt maps of nexus nodes are not followed, the interrupt parent is taken to be the controller.
+//!
+
+use core::fmt::{self, Write};
+
+use crate::{compat, props, DeviceTree, PhandleArgs, PhandleArgsIterator, Token, MAX_PHANDLE_ARGS};
+use crate::dts::write_name;
+
+/// # InterruptController
+/// A node taking part in interrupt routing, see `DeviceTree::interrupt_controllers()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InterruptController<'a> {
+    pub node: Token<'a>,
+    /// `#interrupt-cells`
+    pub cells: Option<u32>,
+    /// Value of `phandle` or `linux,phandle`
+    pub phandle: Option<u32>,
+    /// The node is an interrupt nexus, with `interrupt-map` but no `interrupt-controller`
+    pub nexus: bool,
+}
+
+/// Returns the `#interrupt-cells` of `parent`, 0 if there is no parent or property
+pub(crate) fn interrupt_cells(parent: Option<Token>) -> usize {
+    parent
+        .and_then(|parent| parent.get_prop(props::INTERRUPT_CELLS))
+        .and_then(|prop| prop.prop_u32(0))
+        .map_or(0, |cells| cells as usize)
+}
+
+/// # InterruptIterator
+/// Iterates over the interrupt specifiers of a node as `PhandleArgs` with the interrupt controller
+/// as provider, see `Token::interrupts()`.
+pub struct InterruptIterator<'a> {
+    /* Used for interrupts-extended */
+    extended: Option<PhandleArgsIterator<'a, 'static>>,
+
+    /* Used for interrupts */
+    prop: Option<Token<'a>>,
+    parent: Option<Token<'a>>,
+    cells: usize,
+    n: usize
+}
+
+impl<'a> Iterator for InterruptIterator<'a> {
+    type Item = PhandleArgs<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(extended) = &mut self.extended {
+            return extended.next()
+        }
+
+        let prop = self.prop?;
+        let mut args = [0; MAX_PHANDLE_ARGS];
+        for (i, arg) in args.iter_mut().take(self.cells).enumerate() {
+            *arg = prop.prop_u32(self.n + i)?;
+        }
+        self.n += self.cells;
+        Some(PhandleArgs::new(self.parent?, args, self.cells))
+    }
+}
+
+/// # IrqSpec
+/// A decoded interrupt: the interrupt controller and its specifier cells, see `Token::irqs()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct IrqSpec<'a> {
+    args: PhandleArgs<'a>,
+}
+
+impl<'a> From<PhandleArgs<'a>> for IrqSpec<'a> {
+    fn from(args: PhandleArgs<'a>) -> Self {
+        IrqSpec { args }
+    }
+}
+
+impl<'a> IrqSpec<'a> {
+
+    /// Returns the interrupt controller node and the specifier cells
+    pub fn raw(&self) -> (&Token<'a>, &[u32]) {
+        (&self.args.provider, self.args.args())
+    }
+
+    /// Returns a formatter of the interrupt, see `IrqDisplay`
+    pub fn display(&self) -> IrqDisplay<'a> {
+        IrqDisplay(*self)
+    }
+}
+
+/// # IrqDisplay
+/// `Display` formatter of a `IrqSpec`, see `IrqSpec::display()`.
+/// Known encodings are decoded based on the `compatible` of the controller:
+/// * ARM GIC with 3 cells, e.g. `GIC SPI 42 LEVEL_HIGH`
+/// * RISC-V PLIC with 1 cell, e.g. `PLIC 10`
+///
+/// Other interrupts are written as the controller name and the raw cells, e.g. `gpio@1000 <0x3 0x1>`.
+#[derive(Debug, Copy, Clone)]
+pub struct IrqDisplay<'a>(IrqSpec<'a>);
+
+/// Returns true if `compat` is a ARM GIC, e.g. `arm,gic-400`, `arm,gic-v3` or `arm,cortex-a15-gic`
+fn is_gic(compat: &[u8]) -> bool {
+    compat.starts_with(b"arm,gic") || (compat.starts_with(b"arm,") && compat.ends_with(b"-gic"))
+}
+
+/// Returns true if `compat` is a RISC-V PLIC
+fn is_plic(compat: &[u8]) -> bool {
+    matches!(compat, b"riscv,plic0" | b"sifive,plic-1.0.0")
+}
+
+/// Name of a GIC interrupt type
+fn gic_type(cell: u32) -> Option<&'static str> {
+    match cell {
+        0 => Some("SPI"),
+        1 => Some("PPI"),
+        2 => Some("ESPI"),
+        3 => Some("EPPI"),
+        _ => None
+    }
+}
+
+/// Name of the trigger type in the low 4 bits of GIC flags, the PPI CPU mask is ignored
+fn trigger(flags: u32) -> Option<&'static str> {
+    match flags & 0xf {
+        0 => Some("NONE"),
+        1 => Some("EDGE_RISING"),
+        2 => Some("EDGE_FALLING"),
+        3 => Some("EDGE_BOTH"),
+        4 => Some("LEVEL_HIGH"),
+        8 => Some("LEVEL_LOW"),
+        _ => None
+    }
+}
+
+impl fmt::Display for IrqDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (controller, cells) = self.0.raw();
+        let compatible = controller.get_prop(props::COMPATIBLE);
+        let has = |test: fn(&[u8]) -> bool| compatible.is_some_and(|prop| prop.prop_strings().any(test));
+
+        match *cells {
+            [kind, number, flags] if has(is_gic) => {
+                if let (Some(kind), Some(trigger)) = (gic_type(kind), trigger(flags)) {
+                    return write!(f, "GIC {} {} {}", kind, number, trigger)
+                }
+            },
+            [number] if has(is_plic) => return write!(f, "PLIC {}", number),
+            _ => ()
+        }
+
+        write_name(f, controller.name())?;
+        f.write_str(" <")?;
+        for (i, cell) in cells.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            write!(f, "{:#x}", cell)?;
+        }
+        f.write_char('>')
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns the interrupt parent of this node, given by the `interrupt-parent` of this node
+    /// or the closest ancestor which has one, see `compat::interrupt_parent()`.
+    /// Returns None if token is not a node or no interrupt parent was found.
+    ///
+    pub fn interrupt_parent(&self) -> Option<Token<'a>> {
+        compat::interrupt_parent(self).map(|(parent, _)| parent)
+    }
+
+    /// Returns a iterator over the interrupts of this node from `interrupts-extended`, or if missing,
+    /// from `interrupts` decoded with the `#interrupt-cells` of `interrupt_parent()`.
+    /// The iterator is empty if there are no interrupts or the interrupt parent could not be found,
+    /// and stops at a specifier with more than `MAX_PHANDLE_ARGS` cells.
+    ///
+    pub fn interrupts(&self) -> InterruptIterator<'a> {
+        self.interrupts_from(|node| {
+            let parent = node.interrupt_parent();
+            (parent, interrupt_cells(parent))
+        })
+    }
+
+    /// Decode `interrupts-extended`, or `interrupts` with the interrupt parent and its cells given by `parent`,
+    /// which is only called if needed
+    pub(crate) fn interrupts_from<F>(&self, parent: F) -> InterruptIterator<'a>
+        where F: FnOnce(&Token<'a>) -> (Option<Token<'a>>, usize) {
+        let mut iter = InterruptIterator { extended: None, prop: None, parent: None, cells: 0, n: 0 };
+
+        if let Some(prop) = self.get_prop(props::INTERRUPTS_EXTENDED) {
+            iter.extended = Some(prop.phandle_args(props::INTERRUPT_CELLS));
+        } else if let Some(prop) = self.get_prop(props::INTERRUPTS) {
+            let (parent, cells) = parent(self);
+            if cells > 0 && cells <= MAX_PHANDLE_ARGS {
+                iter.prop = Some(prop);
+                iter.parent = parent;
+                iter.cells = cells;
+            }
+        }
+        iter
+    }
+
+    /// Same as `interrupts()` but yields `IrqSpec`s, e.g. for logging with `IrqSpec::display()`
+    ///
+    pub fn irqs(&self) -> impl Iterator<Item = IrqSpec<'a>> {
+        self.interrupts().map(IrqSpec::from)
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a iterator over all interrupt controllers, i.e. nodes with an `interrupt-controller` property,
+    /// and interrupt nexus nodes, i.e. nodes with an `interrupt-map` property but no `interrupt-controller`.
+    ///
+    pub fn interrupt_controllers(&self) -> impl Iterator<Item = InterruptController<'_>> {
+        self.nodes().filter_map(|node| {
+            let controller = node.get_prop(b"interrupt-controller").is_some();
+            let nexus = !controller && node.get_prop(b"interrupt-map").is_some();
+            if !controller && !nexus {
+                return None
+            }
+            Some(InterruptController {
+                node,
+                cells: node.get_prop(props::INTERRUPT_CELLS).and_then(|prop| prop.prop_u32(0)),
+                phandle: node.get_prop(props::PHANDLE)
+                    .or_else(|| node.get_prop(props::LINUX_PHANDLE))
+                    .and_then(|prop| prop.prop_u32(0)),
+                nexus,
+            })
+        })
+    }
+
+    /// Returns the node referenced by the `interrupt-parent` of the root node
+    /// Returns None if there is no such property or node.
+    ///
+    pub fn default_interrupt_parent(&self) -> Option<Token<'_>> {
+        self.root().get_prop(props::INTERRUPT_PARENT)?.prop_phandle()
+    }
+}
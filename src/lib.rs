@@ -4,15 +4,141 @@
 //!
 //! `static-dt-rs` is a library to parse a static devicetree in an embedded environment without alloc.
 //!
+//! ## Ordering
+//! All iterators yield in document order, the order of the tokens in the structure block,
+//! including the relative order of properties and sub-nodes. Lookups returning the first match,
+//! e.g. `get_node()`, `get_phandle()` or `NodeIndex::lookup_path()`, return the first in document order
+//! if several nodes or properties match. tests/ordering.rs pins this.
+//!
+//! ## Names
+//! `get_node()`, `get_prop()`, `get_node_by_path()`, `find_compatible()`, `alias()` and `symbol()`
+//! accept anything that is `AsRef<[u8]>`, so both `b"chosen"` and `"chosen"` or a `&str` from configuration work.
+//! The generic part is an `#[inline]` wrapper which only calls `as_ref()` and a non-generic core taking `&[u8]`,
+//! so the search itself exists once no matter how many argument types are used. With optimizations
+//! (including `opt-level = "z"`) the wrappers are inlined away entirely, in unoptimized builds each
+//! argument type, e.g. every length of byte literal, adds a wrapper of a few instructions.
+//!
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+use core::convert::TryFrom;
+use core::ops::Range;
 
-use crate::utils::{read_fdt_u32, get_fdt_string};
+use crate::raw::Header;
+pub use crate::address::{CellSizes, RangeEntry, RangesIterator, RegEntry, RegIterator, UnitAddrCompare, UnitAddrFields};
+pub use crate::backend::{BackendError, DeviceTreeOn, FdtRead, RawToken, RawTokenIterator};
+pub use crate::builder::{BuildError, BuildReport, DtbBuilder, STRING_INDEX_CAPACITY};
+pub use crate::budget::{Budget, BudgetError, Resume};
+pub use crate::bus::{BusContext, MAX_BUS_WINDOWS};
+pub use crate::cells::{CellIterator, PropError};
+pub use crate::chosen::{AliasError, Stdout, StdoutSource};
+pub use crate::clocks::MAX_CLOCK_CHAIN;
+pub use crate::console::{EarlyConsole, Parity, UartOptions};
+pub use crate::contents::{ChildrenIter, ContentCursor, PropsIter};
+pub use crate::cursor::DtCursor;
+pub use crate::diff::{tree_diff, tree_diff_with, tree_eq, tree_eq_with, DiffEntry};
+pub use crate::driver::DtMatch;
+pub use crate::dts::{ClassifyOptions, DebugTree, PropValue};
+pub use crate::edit::{DeviceTreeMut, EditError, MAX_TRANSACTION_STEPS};
+pub use crate::framebuffer::{FramebufferInfo, PixelFormat};
+pub use crate::gpio::{GpioKey, GpioLed, GpioSpec, LedDefaultState};
+pub use crate::hexdump::HexdumpOptions;
+pub use crate::i2c::I2cChild;
+pub use crate::index::{IndexError, NodeIndex, NodeIndexEntry, PhandleEntry, PhandleIndex};
+pub use crate::interrupts::{InterruptController, InterruptIterator, IrqDisplay, IrqSpec};
+pub use crate::lossy::LossyTokenIterator;
+pub use crate::mmio::{CapacityError, MmioAttr, MmioRegion};
+pub use crate::nvmem::NvmemCell;
+pub use crate::opp::Opp;
+pub use crate::options::{ParseOptions, TokenError, TryTokenIterator, DEFAULT_MAX_PROP_SIZE};
+pub use crate::overlay::{overlay_dry_run, OverlayChange, MAX_OVERLAY_PATH};
+#[cfg(feature = "alloc")]
+pub use crate::owned::OwnedDeviceTree;
+pub use crate::partitions::{Partition, PartitionIterator};
+pub use crate::patch::{AppliedReport, Patch, PatchSet};
+pub use crate::path::{DtPath, DtValue, PathError, PathLookupError};
+pub use crate::pci::{PciRange, PciRangesIterator, PciSpace};
+pub use crate::platform::PlatformDevice;
+pub use crate::phandle::{PhandleArgs, PhandleArgsIterator, MAX_PHANDLE_ARGS};
+pub use crate::profile::{Check, ValidationProfile};
+pub use crate::props::{NodeProps, StdProp};
+pub use crate::psci::{PsciInfo, PsciMethod, PsciVersion};
+pub use crate::search::NodePredicate;
+pub use crate::shared::StaticDt;
+pub use crate::snapshot::NodeSnapshot;
+pub use crate::spi::{SpiChild, SpiMode};
+pub use crate::stats::{BootDigest, QuickFacts, TreeStats};
+pub use crate::thermal::{ThermalIterator, ThermalZone, Trip, TripType};
+pub use crate::typed::FromProperty;
+pub use crate::validate::ValidationError;
+pub use crate::warnings::{DtWarnSink, NoWarnings, Warning};
+use crate::utils::{read_fdt_u32, try_read_fdt_u32, get_fdt_string};
 
+mod address;
+mod backend;
+mod budget;
+mod builder;
+mod bus;
+mod cells;
+mod chosen;
+pub mod compat;
+#[cfg(feature = "classes")]
+pub mod classes;
+mod clocks;
+mod console;
+mod contents;
+mod crc;
+mod cursor;
+mod diff;
+mod driver;
+mod dts;
+mod edit;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod framebuffer;
+mod gpio;
+mod hexdump;
+mod i2c;
+mod index;
+#[cfg(feature = "instrument")]
+pub mod instrument;
+mod interrupts;
+mod lossy;
+mod macros;
+mod memory;
+mod mmio;
+mod nvmem;
+mod opp;
+mod options;
+mod overlay;
+#[cfg(feature = "alloc")]
+mod owned;
+mod partitions;
+mod patch;
+mod path;
+mod pci;
+mod platform;
+mod phandle;
+mod profile;
+pub mod props;
+mod psci;
+pub mod raw;
+mod search;
+mod shared;
+mod snapshot;
+mod spi;
+mod stats;
+mod thermal;
+mod typed;
 pub mod utils;
+mod validate;
+mod warnings;
 
 /// # Errors
-/// Errors which can be returned by DeviceTree::new()
+/// Errors which can be returned by DeviceTree::parse()
 ///
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Error {
 
     /// Magic does not match specification
@@ -20,6 +146,96 @@ pub enum Error {
 
     /// Last compatible version is not 16, contains read last compatible version
     UnsupportedVersion(u32),
+
+    /// Buffer is too small to contain the header or `totalsize` bytes
+    Truncated,
+
+    /// The structure or strings block lies outside of `totalsize`
+    BlockOutOfBounds,
+
+    /// The structure block does not begin with a node, NOPs excluded
+    NoRootNode,
+
+    /// `totalsize` is larger than the maximum given by the caller, contains `totalsize`
+    TooLarge(usize),
+
+    /// A destination buffer is smaller than `totalsize`, contains `totalsize`
+    BufferTooSmall(usize),
+
+    /// A destination buffer is not aligned to 8 bytes
+    Unaligned,
+
+    /// `StaticDt::init()` was already called
+    AlreadyInitialized,
+
+    /// The CRC32 footer does not match the blob, contains the computed CRC. See `DeviceTree::parse_with_footer()`
+    CrcMismatch(u32),
+}
+
+impl Error {
+    /// Returns a short static description of the error.
+    /// Usable in const context, see `include_dtb!`.
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Error::InvalidMagic => "devicetree magic does not match 0xd00dfeed",
+            Error::UnsupportedVersion(_) => "devicetree last compatible version is not 16",
+            Error::Truncated => "devicetree blob is truncated",
+            Error::BlockOutOfBounds => "devicetree structure or strings block is out of bounds",
+            Error::NoRootNode => "devicetree structure block does not begin with a node",
+            Error::TooLarge(_) => "devicetree totalsize is larger than allowed",
+            Error::BufferTooSmall(_) => "buffer is too small for the devicetree",
+            Error::Unaligned => "buffer is not aligned to 8 bytes",
+            Error::AlreadyInitialized => "devicetree is already initialized",
+            Error::CrcMismatch(_) => "devicetree CRC32 footer does not match",
+        }
+    }
+}
+
+/// Check that `fdt` begins with a valid header and that the structure and strings
+/// blocks lies within the blob. Usable in const context, see `include_dtb!`.
+///
+pub const fn check_header(fdt: &[u8]) -> Result<(), Error> {
+    match Header::parse(fdt) {
+        Some(header) => check_header_fields(&header, fdt.len()),
+        None => Err(Error::Truncated)
+    }
+}
+
+/// Check the fields of `header` of a blob of `len` bytes, see `check_header()`
+pub(crate) const fn check_header_fields(header: &Header, len: usize) -> Result<(), Error> {
+    /* Check the header */
+    if header.magic != raw::FDT_MAGIC {
+        return Err(Error::InvalidMagic)
+    }
+
+    /* Check that the compatible version is 16 */
+    if header.last_comp_version != 16 {
+        return Err(Error::UnsupportedVersion(header.last_comp_version))
+    }
+
+    /* Blocks must be inside totalsize which must be inside the buffer */
+    let totalsize = header.totalsize as usize;
+    if totalsize > len {
+        return Err(Error::Truncated)
+    }
+
+    let struct_end = (header.off_dt_struct as usize).checked_add(header.struct_size() as usize);
+    let strings_end = (header.off_dt_strings as usize).checked_add(header.size_dt_strings as usize);
+    match (struct_end, strings_end) {
+        (Some(a), Some(b)) if a <= totalsize && b <= totalsize => Ok(()),
+        _ => Err(Error::BlockOutOfBounds)
+    }
+}
+
+/// Returns true if `fdt` passes `check_header()`.
+/// Never panics, so it can be used for compile-time checks of an included blob:
+///
+/// ```
+/// const FDT: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.dtb"));
+/// const _: () = assert!(static_dt_rs::validate_header(FDT));
+/// ```
+pub const fn validate_header(fdt: &[u8]) -> bool {
+    check_header(fdt).is_ok()
 }
 
 /// # Tokens
@@ -28,9 +244,10 @@ pub enum Error {
 ///
 #[derive(Debug, Copy, Clone)]
 pub enum Token<'a> {
-    /// A token with an unknown or otherwise invalid ID.
-    /// Shouldn't be returned
-    Invalid(u32), // Shouldn't be returned
+    /// A token with an unknown or otherwise invalid ID, contains the ID.
+    /// Also returned with `FDT_PROP` for properties larger than `ParseOptions::max_prop_size`.
+    /// Iteration stops after this token.
+    Invalid(u32),
 
     /// Marks the beginning of a node
     ///
@@ -56,7 +273,7 @@ pub enum Token<'a> {
     /// This token means nothing.
     NoOperation,
 
-    /// Marks end of tokens
+    /// Marks end of tokens, iteration stops after this token.
     End
 }
 
@@ -74,30 +291,113 @@ impl<'a> Token<'a> {
         }
     }
 
+    /// Returns the hash of the name of a node or property, see `utils::dt_hash()`.
+    /// Returns None if token is not a node or property.
+    pub fn name_hash(&self) -> Option<u32> {
+        match self {
+            Token::BeginNode(_, _, name) | Token::Property(_, name, _) => Some(utils::dt_hash(name)),
+            _ => None
+        }
+    }
+
     /// Return length of the node
     /// If token is a property, return its length in bytes
     /// If token is a node, return the number of properties and sub-nodes
     ///
+    #[deprecated(since = "0.2.0", note = "use `prop_len()`, `child_count()` or `prop_count()` instead")]
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize{
         match self {
             /* If property, return its length in bytes */
             Token::Property(_, _, val) => val.len(),
             /* If node, return the number of properties and sub-nodes in it (single level) */
             Token::BeginNode(_, _, _) =>
-                self.into_iter().filter(|x| match x {
-                    Token::BeginNode(_,_,_) | Token::Property(_,_,_) => true,
-                    _ => false
-                }).count(),
+                self.counts().map_or(0, |(children, props)| children + props),
             /* Not a property or node */
             _ => 0
         }
     }
 
     /// Returns true if len() equals 0. See len() for exact behaviour.
+    #[deprecated(since = "0.2.0", note = "use `prop_len()`, `child_count()` or `prop_count()` instead")]
+    #[allow(deprecated)]
     pub fn empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns the length of a property value in bytes
+    /// Returns None if not a property
+    pub fn prop_len(&self) -> Option<usize> {
+        match self {
+            Token::Property(_, _, val) => Some(val.len()),
+            _ => None
+        }
+    }
+
+    /// Returns the value of a property.
+    /// Values always start 4-byte aligned within the structure block as they follow the aligned
+    /// FDT_PROP token and its len and nameoff cells. The address in memory is only 4-byte aligned
+    /// if the structure block is, which the blob alone does not guarantee.
+    /// Returns None if not a property
+    pub fn value(&self) -> Option<&'a [u8]> {
+        match self {
+            Token::Property(_, _, val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// Returns the offset of a property value in the structure block
+    /// Returns None if not a property
+    pub fn value_offset(&self) -> Option<usize> {
+        match self {
+            Token::Property(dt, _, val) => Some(val.as_ptr() as usize - dt.structs.as_ptr() as usize),
+            _ => None
+        }
+    }
+
+    /// Returns the offset of the name of a property in the strings block, as stored in the nameoff
+    /// cell before its value. Properties with the same name usually share it, a name may also
+    /// point into the tail of a longer one. Resolve it with `DeviceTree::name_at()`.
+    /// Returns None if not a property
+    pub fn prop_nameoff(&self) -> Option<u32> {
+        match self {
+            Token::Property(dt, _, _) => try_read_fdt_u32(dt.structs, self.value_offset()? - 4),
+            _ => None
+        }
+    }
+
+    /// Returns the number of sub-nodes and properties in this node (single level)
+    /// as (sub-nodes, properties), counted in one pass.
+    /// Unlike `prop_count()` this includes properties following a sub-node.
+    /// Returns None if not a node
+    pub fn counts(&self) -> Option<(usize, usize)> {
+        match self {
+            Token::BeginNode(_, _, _) => Some(self.into_iter().fold((0, 0), |(children, props), tok| {
+                match tok {
+                    Token::BeginNode(_, _, _) => (children + 1, props),
+                    Token::Property(_, _, _) => (children, props + 1),
+                    _ => (children, props)
+                }
+            })),
+            _ => None
+        }
+    }
+
+    /// Returns the number of sub-nodes in this node (single level)
+    /// Returns None if not a node
+    pub fn child_count(&self) -> Option<usize> {
+        self.counts().map(|(children, _)| children)
+    }
+
+    /// Returns the number of properties in this node, see `props()`
+    /// Returns None if not a node
+    pub fn prop_count(&self) -> Option<usize> {
+        match self {
+            Token::BeginNode(_, _, _) => Some(self.props().count()),
+            _ => None
+        }
+    }
+
     /// Read one byte from property at position n
     /// Returns None if not a property or out of range
     pub fn prop_u8(&self, n: usize) -> Option<u8>{
@@ -116,7 +416,7 @@ impl<'a> Token<'a> {
     pub fn prop_u32(&self, n: usize) -> Option<u32>{
         match self {
             Token::Property(_, _, val) => {
-                if n*4+4 > val.len() { return None }
+                if n >= val.len()/4 { return None }
                 Some(utils::read_fdt_u32(val, n*4))
             },
             /* Not a property */
@@ -124,6 +424,21 @@ impl<'a> Token<'a> {
         }
     }
 
+    /// Returns the value of a cell property as native u32 without copying.
+    /// Returns None if not a property, the target is little-endian, or the value is
+    /// not 4-byte aligned or not a multiple of 4 bytes long (see `utils::can_view_as_cells()`).
+    /// Callers must fall back on `prop_u32()` when None is returned.
+    pub fn cells_as_native(&self) -> Option<&'a [u32]> {
+        match self {
+            Token::Property(_, _, val) if cfg!(target_endian = "big") && utils::can_view_as_cells(val) => {
+                /* Safety: val is aligned for u32 and a multiple of 4 bytes long as checked above,
+                 * all bit patterns are valid u32 and the data is big-endian as the target. */
+                Some(unsafe { core::slice::from_raw_parts(val.as_ptr() as *const u32, val.len() / 4) })
+            },
+            _ => None
+        }
+    }
+
     /// Read one string from start of property
     /// Returns None if not a property
     ///
@@ -137,14 +452,31 @@ impl<'a> Token<'a> {
         }
     }
 
+    /// Returns a iterator over the NUL-separated strings of a string list property, e.g. `compatible`
+    /// Returns a empty iterator if not a property or the value is empty.
+    ///
+    pub fn prop_strings(&self) -> impl Iterator<Item = &'a [u8]> {
+        let val: &'a [u8] = match self {
+            Token::Property(_, _, val) => val,
+            _ => &[]
+        };
+        let list = val.strip_suffix(&[0]).unwrap_or(val);
+        (!val.is_empty()).then(|| list.split(|c| *c == 0)).into_iter().flatten()
+    }
+
     /// Read one phandle (one cell) at position 0
     /// Returns None if token is not a property, out of range or failed to find a matching node
     pub fn prop_phandle(&self) -> Option<Token<'a>> {
+        self.prop_phandle_with(None)
+    }
+
+    /// Same as `prop_phandle()` but uses `index` to find the node if given
+    pub fn prop_phandle_with(&self, index: Option<&PhandleIndex>) -> Option<Token<'a>> {
         match self {
-            Token::Property(dt,_, val) => {
+            Token::Property(dt, _, _) => {
                 /* Try to read one cell and search for its node */
                 match self.prop_u32(0) {
-                    Some(phandle) => dt.get_phandle(phandle),
+                    Some(phandle) => dt.get_phandle_with(phandle, index),
                     None => None //Out of range
                 }
             },
@@ -153,39 +485,286 @@ impl<'a> Token<'a> {
         }
     }
 
-    /// Find a node with `name` in this node (not recursive)
+    /// Find a node with `name` in this node (not recursive), the first in document order
     /// Returns None if there is no matching node.
     ///
-    pub fn get_node(&self, name: &'a [u8]) -> Option<Token<'a>>{
+    #[inline]
+    pub fn get_node<S: AsRef<[u8]>>(&self, name: S) -> Option<Token<'a>> {
+        self.get_node_bytes(name.as_ref())
+    }
+
+    /* Non-generic core of get_node(), see "Names" in the crate documentation */
+    fn get_node_bytes(&self, name: &[u8]) -> Option<Token<'a>> {
         for tok in self.into_iter() {
-            match tok {
-                Token::BeginNode(_, _, s) => if name.eq(s) { return Some(tok) },
-                _ => ()
+            if let Token::BeginNode(_, _, s) = tok {
+                if name.eq(s) { return Some(tok) }
             }
         }
         None
     }
 
+    /// Returns a iterator over the sub-nodes of this node (not recursive) in document order.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn children(&self) -> impl Iterator<Item = Token<'a>> {
+        self.into_iter().filter(|tok| matches!(tok, Token::BeginNode(_, _, _)))
+    }
+
+    /// Returns a iterator over the properties of this node (not recursive) in document order.
+    /// Stops at the first sub-node as the specification requires properties to precede sub-nodes,
+    /// properties following a sub-node are not returned.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn props(&self) -> impl Iterator<Item = Token<'a>> {
+        self.into_iter()
+            .take_while(|tok| !matches!(tok, Token::BeginNode(_, _, _)))
+            .filter(|tok| matches!(tok, Token::Property(_, _, _)))
+    }
+
     /// Find a property with `name` in this node (not recursive)
+    /// Only properties preceding the first sub-node are searched, see `props()`.
     /// Returns None if there is no matching property.
     ///
-    pub fn get_prop(&self, name: &'a [u8]) -> Option<Token<'a>>{
+    #[inline]
+    pub fn get_prop<S: AsRef<[u8]>>(&self, name: S) -> Option<Token<'a>> {
+        self.get_prop_bytes(name.as_ref())
+    }
+
+    /* Non-generic core of get_prop(), see "Names" in the crate documentation */
+    fn get_prop_bytes(&self, name: &[u8]) -> Option<Token<'a>> {
+        self.props().find(|tok| name.eq(tok.name()))
+    }
+
+    /// Same as `get_prop()` but also searches properties following a sub-node,
+    /// for trees which do not follow the specification.
+    /// Sub-nodes are skipped over, so a miss is O(size of node) rather than O(properties).
+    ///
+    pub fn get_prop_relaxed(&self, name: &[u8]) -> Option<Token<'a>>{
         for tok in self.into_iter() {
+            if let Token::Property(_, s, _) = tok {
+                if name.eq(s) { return Some(tok) }
+            }
+        }
+        None
+    }
+
+    /// Returns a iterator over the properties of this node whose name starts with `prefix`,
+    /// e.g. `b"qcom,"` for vendor properties, in document order. See `props()`.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn props_with_prefix<'p>(&self, prefix: &'p [u8]) -> impl Iterator<Item = Token<'a>> + 'p
+    where 'a: 'p
+    {
+        self.props().filter(move |tok| tok.name().starts_with(prefix))
+    }
+
+    /// Returns a iterator over the properties of this node named `stem` followed by a decimal index,
+    /// e.g. `pinctrl-0`, `pinctrl-1`... for the stem `b"pinctrl-"`, as (index, property).
+    /// Properties are yielded by increasing index regardless of their order in the node and gaps
+    /// in the numbering are skipped. If an index appears twice only the first property is yielded.
+    /// Each step scans the properties again, this is O(n²) in the number of properties
+    /// but needs no storage, meant for the handful of numbered properties of a node.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn numbered_props<'p>(&self, stem: &'p [u8]) -> impl Iterator<Item = (u32, Token<'a>)> + 'p
+    where 'a: 'p
+    {
+        let node = *self;
+        let mut next = Some(0u32);
+        core::iter::from_fn(move || {
+            let min = next?;
+            let found = node.props_with_prefix(stem)
+                .filter_map(|tok| Some((utils::parse_decimal(&tok.name()[stem.len()..])?, tok)))
+                .filter(|(index, _)| *index >= min)
+                .fold(None, |best: Option<(u32, Token<'a>)>, (index, tok)| match best {
+                    Some((best_index, _)) if best_index <= index => best,
+                    _ => Some((index, tok))
+                });
+            next = found.and_then(|(index, _)| index.checked_add(1));
+            found
+        })
+    }
+
+    /// Snapshot up to `N` properties of this node for repeated lookups, see `NodeProps`
+    /// Returns None if not a node
+    ///
+    pub fn props_cached<const N: usize>(&self) -> Option<NodeProps<'a, N>> {
+        NodeProps::new(self)
+    }
+
+    /// Returns true if this node has `compat` in its compatible string list
+    ///
+    pub fn is_compatible(&self, compat: &[u8]) -> bool {
+        match self.get_prop(props::COMPATIBLE) {
+            Some(prop) => prop_has_string(&prop, compat),
+            None => false
+        }
+    }
+
+    /// Returns true if the `status` of this node is "okay" (or "ok"), or it has no `status`.
+    /// Returns false if token is not a node.
+    ///
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Token::BeginNode(_, _, _) => match self.get_prop(props::STATUS) {
+                Some(prop) => matches!(prop.prop_str(), Some(b"okay") | Some(b"ok")),
+                None => true
+            },
+            _ => false
+        }
+    }
+
+    /// Returns the offset of the FDT_BEGIN_NODE token of this node in the structure block.
+    /// See `DeviceTree::node_at_offset()` for the reverse.
+    /// Returns None if token is not a node.
+    ///
+    pub fn node_offset(&self) -> Option<usize> {
+        match self {
+            Token::BeginNode(_, offs, name) => Some(node_offset(*offs, name)),
+            _ => None
+        }
+    }
+
+    /// Returns the range of this node in the structure block, from its FDT_BEGIN_NODE token
+    /// through its matching FDT_END_NODE token inclusive, e.g. to hash a subtree.
+    /// Returns None if token is not a node or the structure block ends before the node does.
+    ///
+    pub fn byte_span(&self) -> Option<Range<usize>> {
+        match self {
+            Token::BeginNode(dt, offs, name) => {
+                let mut tokens = TokenIterator::new_offs(dt, *offs);
+                tokens.skip_subtree().then(|| node_offset(*offs, name)..tokens.offset())
+            },
+            _ => None
+        }
+    }
+
+    /// Returns a iterator over all properties in this node and its descendants in document order
+    /// together with the node owning them as (node, property), see `DeviceTree::all_props()`.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn subtree_props(&self) -> PropertyIterator<'a> {
+        PropertyIterator { tokens: self.attributed_tokens() }
+    }
+
+    /// Returns a iterator over this node and its descendants in document order with the node owning each token,
+    /// see `DeviceTree::attributed_tokens()`.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn attributed_tokens(&self) -> AttributedTokens<'a> {
+        match (self, self.node_offset()) {
+            (Token::BeginNode(dt, _, _), Some(offs)) => AttributedTokens::new_offs(dt, offs),
+            _ => AttributedTokens::none()
+        }
+    }
+
+    /// Find the node with a `phandle` or `linux,phandle` property equal to `phandle`
+    /// in this node or its descendants.
+    /// Returns None if token is not a node or there is no such node in the subtree.
+    ///
+    pub fn get_phandle_in_subtree(&self, phandle: u32) -> Option<Token<'a>> {
+        match self {
+            Token::BeginNode(dt, _, _) => find_phandle(dt, self.attributed_tokens(), phandle),
+            _ => None
+        }
+    }
+
+    /// Returns the parent of this node by scanning the tree from the root.
+    /// Returns None if token is not a node or is the root node.
+    ///
+    pub fn parent(&self) -> Option<Token<'a>> {
+        let (dt, offset) = match self {
+            Token::BeginNode(dt, _, _) => (*dt, self.node_offset()?),
+            _ => return None
+        };
+
+        let mut stack = [0u32; MAX_DEPTH];
+        let mut depth = 0;
+        for tok in dt.tokens() {
             match tok {
-                Token::Property(_, s, _) => if name.eq(s) { return Some(tok) },
+                Token::BeginNode(_, _, _) => {
+                    if depth >= MAX_DEPTH { return None }
+                    let offs = tok.node_offset()?;
+                    if offs == offset {
+                        return if depth == 0 { None } else { dt.node_at_offset(stack[depth - 1] as usize) }
+                    }
+                    stack[depth] = offs as u32;
+                    depth += 1;
+                },
+                Token::EndNode => {
+                    if depth == 0 { return None }
+                    depth -= 1;
+                },
                 _ => ()
             }
         }
         None
     }
 
+    /// Returns a handle identifying this node in its tree.
+    /// Returns None if token is not a node.
+    ///
+    pub fn handle(&self) -> Option<NodeHandle> {
+        match self {
+            Token::BeginNode(dt, offs, _) => Some(NodeHandle {
+                tree: dt.fdt.as_ptr() as usize,
+                offset: *offs
+            }),
+            _ => None
+        }
+    }
+
+}
+
+/// Equality means "same element of the same tree", not structural equivalence.
+///
+/// Nodes are equal if they belong to the same backing blob and have the same offset,
+/// properties are equal if their name and value are the same slices of the same blob.
+/// Two identical trees backed by different buffers never compare equal.
+///
+impl<'a> PartialEq for Token<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Token::BeginNode(dt1, offs1, _), Token::BeginNode(dt2, offs2, _)) =>
+                core::ptr::eq(dt1.fdt, dt2.fdt) && offs1 == offs2,
+            (Token::Property(dt1, name1, val1), Token::Property(dt2, name2, val2)) =>
+                core::ptr::eq(dt1.fdt, dt2.fdt)
+                    && core::ptr::eq(*name1, *name2)
+                    && core::ptr::eq(*val1, *val2),
+            (Token::Invalid(x), Token::Invalid(y)) => x == y,
+            (Token::EndNode, Token::EndNode)
+            | (Token::NoOperation, Token::NoOperation)
+            | (Token::End, Token::End) => true,
+            _ => false
+        }
+    }
+}
+
+impl<'a> Eq for Token<'a> {}
+
+/// # NodeHandle
+/// A small copyable identity of a node, see `Token::handle()`.
+///
+/// Handles compare equal if they refer to the same node of the same backing blob,
+/// they are ordered by blob address and then by document order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeHandle {
+    tree: usize,
+    offset: usize
+}
+
+impl NodeHandle {
+    /// Offset of the node contents in the structure block
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
 }
 
 impl<'a> IntoIterator for Token<'a> {
     type Item = Token<'a>;
     type IntoIter = HierarchyTokenIterator<'a>;
 
-    /// Returns a hierarchical iterator over this node
+    /// Returns a hierarchical iterator over the properties and sub-nodes of this node in document order
     /// Returns a empty iterator if token is not a node.
     ///
     fn into_iter(self) -> Self::IntoIter {
@@ -200,6 +779,8 @@ impl<'a> IntoIterator for Token<'a> {
 /// # TokenIterator
 /// Iterates over FDT tokens (see Token) in a device tree.
 /// Doesn't care about which level it's in.
+/// The last token is always End, or Invalid if an unknown token was encountered.
+/// Iteration stops without End if a token runs out of the structure block.
 pub struct TokenIterator<'a> {
     dt: Option<&'a DeviceTree<'a>>,
     offs: usize
@@ -220,58 +801,347 @@ impl<'a> TokenIterator<'a> {
     fn none() -> Self {
         TokenIterator { dt: None, offs: 0 }
     }
+
+    /// Offset of the next unread token in the structure block, e.g. for logging where a lookup failed.
+    /// Once the iterator has stopped this is the offset it stopped at.
+    pub fn offset(&self) -> usize {
+        self.offs
+    }
+
+    /// Skip past the FDT_END_NODE token of the node whose contents begins at the current offset,
+    /// e.g. directly after a `Token::BeginNode` was returned.
+    /// Only token ids and sizes are read, names and values are not decoded.
+    /// Returns false and stops the iterator if the structure block ends before.
+    pub fn skip_subtree(&mut self) -> bool {
+        let dt = match self.dt {
+            Some(dt) => dt,
+            None => return false
+        };
+
+        let mut depth = 1;
+        while let Some(token_id) = try_read_fdt_u32(dt.structs, self.offs) {
+            self.offs += 4;
+
+            match token_id {
+                raw::FDT_BEGIN_NODE => {
+                    let name = match dt.structs.get(self.offs..).and_then(|s| s.iter().position(|c| *c == 0)) {
+                        Some(len) => len,
+                        None => break
+                    };
+                    self.offs += (name/4 + 1)*4;
+                    depth += 1;
+                },
+                raw::FDT_END_NODE => {
+                    depth -= 1;
+                    if depth == 0 { return true }
+                },
+                raw::FDT_PROP => {
+                    let len = match try_read_fdt_u32(dt.structs, self.offs) {
+                        Some(len) => len as usize,
+                        None => break
+                    };
+                    /* A corrupt length may overflow on 32-bit targets */
+                    match len.checked_next_multiple_of(4).and_then(|len| self.offs.checked_add(8)?.checked_add(len)) {
+                        Some(end) if end <= dt.structs.len() => self.offs = end,
+                        _ => break
+                    }
+                },
+                raw::FDT_NOP => (),
+                /* End or unknown token inside a node */
+                _ => break
+            }
+        }
+
+        self.dt = None;
+        false
+    }
 }
 
 impl<'a> Iterator for TokenIterator<'a> {
     type Item = Token<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        match self.next_checked()? {
+            Ok(tok) => Some(tok),
+            Err(_) => {
+                /* A oversized property is as good as corrupt, don't read past it */
+                self.dt = None;
+                Some(Token::Invalid(raw::FDT_PROP))
+            }
+        }
+    }
+}
+
+impl<'a> TokenIterator<'a> {
+    /// Same as `next()` but returns properties larger than `ParseOptions::max_prop_size` as errors
+    /// and continues after them
+    pub(crate) fn next_checked(&mut self) -> Option<Result<Token<'a>, TokenError>> {
+        let dt = self.dt?;
 
-        match self.dt {
-            Some(dt) => {
-                /* Read token id */
-                let token_id = read_fdt_u32(dt.structs, self.offs); self.offs += 4;
-
-                match token_id {
-                    1 => {
-                        let s = get_fdt_string(dt.structs, self.offs).unwrap();
-                        self.offs += (s.len()/4 + 1)*4;
-
-                        Some(Token::BeginNode(dt, self.offs, s))
-                    },
-                    2 => Some(Token::EndNode),
-                    3 => {
-                        let len = read_fdt_u32(dt.structs, self.offs) as usize; self.offs += 4;
-                        let nameoff = read_fdt_u32(dt.structs, self.offs) as usize; self.offs += 4;
-                        let name = get_fdt_string(dt.strings, nameoff).unwrap();
-                        let tmp = self.offs;
-                        self.offs += ((len + 3) / 4)*4;
-                        Some(Token::Property(dt, name, &dt.structs[tmp..tmp+(len as usize)]))
-                    },
-                    4 => Some(Token::NoOperation),
-                    9 => None,
-                    x => None //Some(Token::Invalid(x)) //useful for debugging
+        /* Skip the value of a oversized property without looking at it */
+        if try_read_fdt_u32(dt.structs, self.offs) == Some(raw::FDT_PROP) {
+            if let Some(len) = try_read_fdt_u32(dt.structs, self.offs + 4).map(|len| len as usize) {
+                if len > dt.max_prop_size {
+                    let offset = self.offs;
+                    self.offs = self.offs.saturating_add(3*raw::FDT_TAGSIZE).saturating_add(len.div_ceil(4).saturating_mul(4));
+                    return Some(Err(TokenError::OversizeProperty { offset, len }))
                 }
             }
-            None => None
         }
+
+        let tok = self.read_token(dt);
+
+        /* Stop for good if a token runs out of the structure block */
+        if tok.is_none() {
+            self.dt = None;
+        }
+        tok.map(Ok)
+    }
+
+    /// Read the token at the current offset and advance past it
+    fn read_token(&mut self, dt: &'a DeviceTree<'a>) -> Option<Token<'a>> {
+        #[cfg(feature = "instrument")]
+        instrument::TOKEN_READS.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+
+        /* Read token id */
+        let token_id = try_read_fdt_u32(dt.structs, self.offs)?; self.offs += 4;
+
+        match token_id {
+            raw::FDT_BEGIN_NODE => {
+                let s = get_fdt_string(dt.structs, self.offs)?;
+                self.offs += (s.len()/4 + 1)*4;
+
+                Some(Token::BeginNode(dt, self.offs, s))
+            },
+            raw::FDT_END_NODE => Some(Token::EndNode),
+            raw::FDT_PROP => {
+                let len = try_read_fdt_u32(dt.structs, self.offs)? as usize; self.offs += 4;
+                let nameoff = try_read_fdt_u32(dt.structs, self.offs)? as usize; self.offs += 4;
+                let name = get_fdt_string(dt.strings, nameoff)?;
+                debug_assert!(self.offs.is_multiple_of(raw::FDT_TAGSIZE), "property value at unaligned offset {}", self.offs);
+                let val = dt.structs.get(self.offs..self.offs.checked_add(len)?)?;
+                self.offs += len.div_ceil(4)*4;
+                Some(Token::Property(dt, name, val))
+            },
+            raw::FDT_NOP => Some(Token::NoOperation),
+            raw::FDT_END => {
+                self.dt = None;
+                Some(Token::End)
+            },
+            x => {
+                /* Can't know the size of an unknown token, stop here */
+                self.dt = None;
+                Some(Token::Invalid(x))
+            }
+        }
+    }
+}
+
+/// # StringIterator
+/// Iterates over all NUL-terminated strings in the strings block as (offset, string).
+/// A final string missing its terminator is not yielded.
+pub struct StringIterator<'a> {
+    strings: &'a [u8],
+    offs: usize
+}
+
+impl<'a> Iterator for StringIterator<'a> {
+    type Item = (usize, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offs >= self.strings.len() {
+            return None
+        }
+
+        let offs = self.offs;
+        match get_fdt_string(self.strings, offs) {
+            Some(s) => {
+                self.offs += s.len() + 1;
+                Some((offs, s))
+            },
+            None => {
+                /* Unterminated string at end of block */
+                self.offs = self.strings.len();
+                None
+            }
+        }
+    }
+}
+
+/// Maximum depth of nodes, the root node (or the node a traversal starts from) has depth 1.
+///
+/// Traversals which keep track of the open nodes do so in fixed arrays of `MAX_DEPTH` entries
+/// instead of recursing, so their stack usage does not grow with the depth of the tree.
+/// They all stop at the first node nested deeper: iterators such as `AttributedTokens` end,
+/// lookups such as `Token::parent()` or `DeviceTree::find_node_where()` return None and
+/// `validate()`, `tree_diff()`, `build_node_index()` and `warnings()` report it as too deep.
+/// Path lookups and flat scans which only count the depth, e.g. `get_node_by_path()`, `tokens()`, `nodes()`,
+/// `write_dts()` or `Token::debug_tree()`, are not limited.
+pub const MAX_DEPTH: usize = 64;
+
+/// # AttributedToken
+/// A token together with the node it belongs to, see `AttributedTokens`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AttributedToken<'a> {
+    /// Offset of the FDT_BEGIN_NODE token of the owning node, see `DeviceTree::node_at_offset()`.
+    /// For a `BeginNode` or `EndNode` this is the node itself.
+    pub node_offset: usize,
+    /// Depth of the owning node, the first node has depth 1
+    pub depth: usize,
+    pub token: Token<'a>,
+}
+
+/// # AttributedTokens
+/// Iterates over the nodes, properties and node ends of a device tree together with the node owning them.
+/// Properties following a closed sub-node are attributed to the correct node and NOPs are skipped.
+/// Stops after the end of the first node (i.e. the root node or the node of `Token::attributed_tokens()`),
+/// on unbalanced nodes or trees deeper than MAX_DEPTH.
+pub struct AttributedTokens<'a> {
+    tokeniter: TokenIterator<'a>,
+    /* Offsets of the FDT_BEGIN_NODE tokens of all open nodes */
+    stack: [u32; MAX_DEPTH],
+    depth: usize
+}
+
+impl<'a> AttributedTokens<'a> {
+    fn new(dt: &'a DeviceTree<'a>) -> Self {
+        Self::new_offs(dt, dt.root_offset)
+    }
+
+    /// Iterate over the subtree of the node whose FDT_BEGIN_NODE token is at `offs`
+    fn new_offs(dt: &'a DeviceTree<'a>, offs: usize) -> Self {
+        AttributedTokens { tokeniter: TokenIterator::new_offs(dt, offs), stack: [0; MAX_DEPTH], depth: 0 }
+    }
+
+    /// Create a empty iterator, will immediately return None
+    fn none() -> Self {
+        AttributedTokens { tokeniter: TokenIterator::none(), stack: [0; MAX_DEPTH], depth: 0 }
+    }
+}
+
+/// Result of reading one token with `AttributedTokens::step()`
+pub(crate) enum AttributedStep<'a> {
+    Item(AttributedToken<'a>),
+    /// A NOP
+    Skipped,
+    /// End of the first node, end of tree or malformed
+    Stopped,
+}
+
+impl<'a> AttributedTokens<'a> {
+    /// Read exactly one token, so a caller can bound the work done per call (see `Budget`)
+    pub(crate) fn step(&mut self) -> AttributedStep<'a> {
+        let token = match self.tokeniter.next() {
+            Some(token) => token,
+            None => return self.stop()
+        };
+
+        match token {
+            Token::BeginNode(_, offs, name) => {
+                if self.depth >= MAX_DEPTH { return self.stop() }
+                let node_offset = node_offset(offs, name);
+                self.stack[self.depth] = node_offset as u32;
+                self.depth += 1;
+                AttributedStep::Item(AttributedToken { node_offset, depth: self.depth, token })
+            },
+            Token::EndNode => {
+                if self.depth == 0 { return self.stop() }
+                let item = AttributedToken { node_offset: self.stack[self.depth - 1] as usize, depth: self.depth, token };
+                self.depth -= 1;
+                if self.depth == 0 {
+                    /* End of the first node */
+                    self.tokeniter = TokenIterator::none();
+                }
+                AttributedStep::Item(item)
+            },
+            Token::Property(_, _, _) => {
+                if self.depth == 0 { return self.stop() }
+                AttributedStep::Item(AttributedToken { node_offset: self.stack[self.depth - 1] as usize, depth: self.depth, token })
+            },
+            Token::NoOperation => AttributedStep::Skipped,
+            _ => self.stop()
+        }
+    }
+
+    /// Stop for good
+    fn stop(&mut self) -> AttributedStep<'a> {
+        self.tokeniter = TokenIterator::none();
+        AttributedStep::Stopped
+    }
+}
+
+impl<'a> Iterator for AttributedTokens<'a> {
+    type Item = AttributedToken<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.step() {
+                AttributedStep::Item(item) => return Some(item),
+                AttributedStep::Skipped => (),
+                AttributedStep::Stopped => return None
+            }
+        }
+    }
+}
+
+/// # PropertyIterator
+/// Iterates over all properties in a device tree together with the node owning them,
+/// see `AttributedTokens` for how they are attributed and when the iteration stops.
+pub struct PropertyIterator<'a> {
+    tokens: AttributedTokens<'a>,
+}
+
+impl<'a> Iterator for PropertyIterator<'a> {
+    type Item = (Token<'a>, Token<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let dt = self.tokens.tokeniter.dt?;
+        let attributed = self.tokens.find(|attributed| matches!(attributed.token, Token::Property(_, _, _)))?;
+        Some((dt.node_at_offset(attributed.node_offset)?, attributed.token))
+    }
+}
+
+/// Find the node defining `phandle` among the properties of `tokens`
+fn find_phandle<'a>(dt: &'a DeviceTree<'a>, mut tokens: AttributedTokens<'a>, phandle: u32) -> Option<Token<'a>> {
+    /* zero is not a valid phandle */
+    if phandle == 0 { return None; }
+
+    let attributed = tokens.find(|attributed| {
+        matches!(attributed.token.name(), props::PHANDLE | props::LINUX_PHANDLE)
+            && matches!(attributed.token, Token::Property(_, _, _))
+            && attributed.token.prop_u32(0) == Some(phandle)
+    })?;
+    dt.node_at_offset(attributed.node_offset)
+}
+
+/// Returns the value of a phandle property of exactly one cell, except the reserved 0 and 0xffffffff
+pub(crate) fn valid_phandle(prop: &Token) -> Option<u32> {
+    prop.prop_as::<u32>().filter(|phandle| !matches!(phandle, 0 | 0xffff_ffff))
+}
+
+/// Offset of the FDT_BEGIN_NODE token of a node with contents at `offs`
+fn node_offset(offs: usize, name: &[u8]) -> usize {
+    offs - (name.len()/4 + 1)*4 - raw::FDT_TAGSIZE
+}
+
+/// Returns true if property `prop` contains `s` in its string list
+fn prop_has_string(prop: &Token, s: &[u8]) -> bool {
+    match prop {
+        Token::Property(_, _, val) => val.split(|c| *c == 0).any(|x| x == s),
+        _ => false
     }
 }
 
 /// # HierarchyTokenIterator
 /// Iterates over FDT tokens but ignores token not in the current node
 /// (i.e. between a node-begin and -end pair).
+/// Sub-nodes are skipped over with `TokenIterator::skip_subtree()` without decoding their contents.
 pub struct HierarchyTokenIterator<'a> {
     tokeniter: TokenIterator<'a>,
     level: i16
 }
 
 impl<'a> HierarchyTokenIterator<'a> {
-    /// See `TokenIterator::new()`
-    fn new(dt: &'a DeviceTree<'a>) -> Self {
-        HierarchyTokenIterator {tokeniter: TokenIterator::new(dt), level: 0}
-    }
-
     /// See `TokenIterator::new_offs()`
     fn new_offs(dt: &'a DeviceTree<'a>, offs: usize) -> Self {
         HierarchyTokenIterator{ tokeniter: TokenIterator::new_offs(dt, offs), level: 0 }
@@ -281,6 +1151,12 @@ impl<'a> HierarchyTokenIterator<'a> {
     fn none() -> Self {
         HierarchyTokenIterator{ tokeniter: TokenIterator::none(), level: 0 }
     }
+
+    /// See `TokenIterator::offset()`.
+    /// The contents of the last returned sub-node count as unread until the next call skips them.
+    pub fn offset(&self) -> usize {
+        self.tokeniter.offset()
+    }
 }
 
 impl<'a> Iterator for HierarchyTokenIterator<'a> {
@@ -288,24 +1164,27 @@ impl<'a> Iterator for HierarchyTokenIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
 
-        while let Some(tok) = self.tokeniter.next() {
-            match tok {
-                Token::BeginNode(_, _, _) => {
-                    self.level += 1;
-                    if self.level <= 1 { return Some(tok) }
-                },
-                Token::EndNode => {
-                    self.level -= 1;
-                    if self.level == 0 { return Some(tok) }
-                    if self.level < 0 {return None}
-                },
-                _ => {
-                    if self.level == 0 { return Some(tok) }
-                }
+        /* Last token was a sub-node, skip its contents and return its end */
+        if self.level > 0 {
+            self.level = 0;
+            if self.tokeniter.skip_subtree() {
+                return Some(Token::EndNode)
             }
+            return None
         }
 
-        None
+        match self.tokeniter.next()? {
+            tok @ Token::BeginNode(_, _, _) => {
+                self.level = 1;
+                Some(tok)
+            },
+            Token::EndNode => {
+                /* End of this node, keep the offset for offset() */
+                self.tokeniter.dt = None;
+                None
+            },
+            tok => Some(tok)
+        }
 
     }
 }
@@ -314,10 +1193,42 @@ impl<'a> Iterator for HierarchyTokenIterator<'a> {
 ///
 #[derive(Debug)]
 pub struct DeviceTree<'a> {
-    pub fdt: &'a [u8],
+    fdt: &'a [u8],
+
+    structs: &'a [u8],
+    strings: &'a [u8],
 
-    pub structs: &'a [u8],
-    pub strings: &'a [u8]
+    /* Values read once by parse() */
+    root_offset: usize,
+    root_name_len: usize,
+    totalsize: usize,
+    version: u32,
+    /* Iteration stops at properties above this, see ParseOptions */
+    max_prop_size: usize
+}
+
+/// Find the FDT_BEGIN_NODE token of the root node, skipping leading NOPs.
+/// Returns the offset of the token and the length of the root node name.
+const fn find_root(structs: &[u8]) -> Result<(usize, usize), Error> {
+    let mut offs = 0;
+    loop {
+        match try_read_fdt_u32(structs, offs) {
+            Some(raw::FDT_NOP) => offs += raw::FDT_TAGSIZE,
+            Some(raw::FDT_BEGIN_NODE) => break,
+            _ => return Err(Error::NoRootNode)
+        }
+    }
+
+    /* Length of the NUL-terminated name */
+    let mut len = 0;
+    let name = offs + raw::FDT_TAGSIZE;
+    while name + len < structs.len() {
+        if structs[name + len] == 0 {
+            return Ok((offs, len))
+        }
+        len += 1;
+    }
+    Err(Error::NoRootNode)
 }
 
 impl<'a> DeviceTree<'a> {
@@ -325,84 +1236,371 @@ impl<'a> DeviceTree<'a> {
     /// Create a new DeviceTree with `fdt` as backing buffer.
     /// Returns Ok if header and version is correct. Respective Err() otherwise.
     ///
-    pub fn back(fdt: &'a [u8]) -> Result<DeviceTree<'a>, Error> {
+    /// Usable in const context, in which case the header accessors are as well.
+    ///
+    pub const fn parse(fdt: &'a [u8]) -> Result<DeviceTree<'a>, Error> {
+
+        /* Check magic, version and block bounds */
+        if let Err(e) = check_header(fdt) {
+            return Err(e)
+        }
 
-        let struct_offs = utils::read_fdt_u32(fdt, 8) as usize;
-        let strings_offs = utils::read_fdt_u32(fdt, 12) as usize;
-        let struct_size = utils::read_fdt_u32(fdt, 36) as usize;
-        let string_size = utils::read_fdt_u32(fdt, 32) as usize;
+        let header = match Header::parse(fdt) {
+            Some(header) => header,
+            None => return Err(Error::Truncated)
+        };
+        let struct_offs = header.off_dt_struct as usize;
+        let strings_offs = header.off_dt_strings as usize;
+        let struct_size = header.struct_size() as usize;
+        let string_size = header.size_dt_strings as usize;
 
-        let dt = DeviceTree { fdt,
-            structs: &fdt[struct_offs..struct_offs+struct_size],
-            strings: &fdt[strings_offs..strings_offs+string_size]
+        /* check_header() guarantees that the blocks are in bounds */
+        let structs = fdt.split_at(struct_offs).1.split_at(struct_size).0;
+        let (root_offset, root_name_len) = match find_root(structs) {
+            Ok(root) => root,
+            Err(e) => return Err(e)
         };
 
-        /* Check the header */
-        if dt.magic() != 0xD00DFEED_u32 {
+        /* All ok */
+        Ok(DeviceTree { fdt,
+            structs,
+            strings: fdt.split_at(strings_offs).1.split_at(string_size).0,
+            root_offset,
+            root_name_len,
+            totalsize: header.totalsize as usize,
+            version: header.version,
+            max_prop_size: usize::MAX
+        })
+    }
+
+    /// Create a DeviceTree from blocks the caller sliced out of `fdt`, without checking the header.
+    /// `structs` and `strings` are used as the structure and strings blocks as given, `totalsize()` is
+    /// the length of `fdt` and `version()` is read from the header.
+    ///
+    /// The header fields are not checked, prefer `parse()` which checks all of them.
+    /// Returns `Error::Truncated` if `fdt` is shorter than a header, `Error::BlockOutOfBounds`
+    /// if `structs` or `strings` is not a sub-slice of `fdt` and `Error::NoRootNode` if `structs`
+    /// does not begin with a node, NOPs excluded.
+    ///
+    pub fn from_blocks(fdt: &'a [u8], structs: &'a [u8], strings: &'a [u8]) -> Result<DeviceTree<'a>, Error> {
+        if fdt.len() < raw::HEADER_SIZE {
+            return Err(Error::Truncated)
+        }
+        if !utils::is_subslice(fdt, structs) || !utils::is_subslice(fdt, strings) {
+            return Err(Error::BlockOutOfBounds)
+        }
+        let (root_offset, root_name_len) = find_root(structs)?;
+
+        Ok(DeviceTree { fdt,
+            structs,
+            strings,
+            root_offset,
+            root_name_len,
+            totalsize: fdt.len(),
+            version: read_fdt_u32(fdt, raw::OFF_VERSION),
+            max_prop_size: usize::MAX
+        })
+    }
+
+    /// Old name of `parse()`.
+    ///
+    #[deprecated(since = "0.2.0", note = "renamed to `DeviceTree::parse()`")]
+    pub const fn back(fdt: &'a [u8]) -> Result<DeviceTree<'a>, Error> {
+        Self::parse(fdt)
+    }
+
+    /// Parse a devicetree at `ptr`, e.g. an address passed by the bootloader in a register.
+    /// The magic is checked and `totalsize` is read from the header before the rest of the blob
+    /// is accessed. Returns `Error::TooLarge` if `totalsize` is larger than `max_size`.
+    ///
+    /// # Safety
+    /// The first 8 bytes at `ptr` must be readable. If they hold a valid magic, the
+    /// `min(totalsize, max_size)` bytes at `ptr` must be mapped, readable and must not be
+    /// mutated or freed for the rest of the program.
+    pub unsafe fn from_ptr(ptr: *const u8, max_size: usize) -> Result<DeviceTree<'static>, Error> {
+        let prefix = core::ptr::read_unaligned(ptr as *const [u8; 8]);
+        if read_fdt_u32(&prefix, raw::OFF_MAGIC) != raw::FDT_MAGIC {
             return Err(Error::InvalidMagic)
         }
 
-        /* Check that the compatible version is 16 */
-        if dt.last_comp_version() != 16 {
-            return Err(Error::UnsupportedVersion(dt.last_comp_version()))
+        let totalsize = read_fdt_u32(&prefix, raw::OFF_TOTALSIZE) as usize;
+        if totalsize > max_size {
+            return Err(Error::TooLarge(totalsize))
+        }
+        if totalsize < raw::HEADER_SIZE {
+            return Err(Error::Truncated)
         }
 
-        /* All ok */
-        Ok(dt)
+        DeviceTree::parse(core::slice::from_raw_parts(ptr, totalsize))
+    }
+
+    /// Copy the blob into `dst`, e.g. before the memory it was passed in is reclaimed,
+    /// and parse the copy. Bytes in `dst` after `totalsize` are zeroed.
+    /// Returns `Error::BufferTooSmall` if `dst` is smaller than `totalsize`
+    /// or `Error::Unaligned` if it is not aligned to 8 bytes.
+    ///
+    pub fn copy_to<'b>(&self, dst: &'b mut [u8]) -> Result<DeviceTree<'b>, Error> {
+        if dst.len() < self.totalsize {
+            return Err(Error::BufferTooSmall(self.totalsize))
+        }
+        if !(dst.as_ptr() as usize).is_multiple_of(8) {
+            return Err(Error::Unaligned)
+        }
+
+        let (blob, slack) = dst.split_at_mut(self.totalsize);
+        blob.copy_from_slice(&self.fdt[..self.totalsize]);
+        slack.fill(0);
+        DeviceTree::parse(blob)
+    }
+
+    /// Returns the whole blob backing this tree
+    pub const fn blob(&self) -> &'a [u8] {
+        self.fdt
+    }
+
+    /// Returns the structure block
+    pub const fn struct_block(&self) -> &'a [u8] {
+        self.structs
+    }
+
+    /// Returns the range of the structure block in the blob, i.e. `&blob()[struct_block_range()] == struct_block()`
+    pub fn struct_block_range(&self) -> Range<usize> {
+        let start = self.structs.as_ptr() as usize - self.fdt.as_ptr() as usize;
+        start..start + self.structs.len()
+    }
+
+    /// Returns the strings block
+    pub const fn strings_block(&self) -> &'a [u8] {
+        self.strings
+    }
+
+    /// Returns the node whose FDT_BEGIN_NODE token is at `offset` in the structure block,
+    /// see `Token::node_offset()`.
+    /// Returns None if there is no FDT_BEGIN_NODE token at `offset`
+    pub fn node_at_offset(&self, offset: usize) -> Option<Token<'_>> {
+        if !offset.is_multiple_of(raw::FDT_TAGSIZE) || offset + raw::FDT_TAGSIZE > self.structs.len() {
+            return None
+        }
+        if read_fdt_u32(self.structs, offset) != raw::FDT_BEGIN_NODE {
+            return None
+        }
+        TokenIterator::new_offs(self, offset).next()
     }
 
     /// Returns the root node
     ///
-    pub fn root(&self) -> Token {
-        HierarchyTokenIterator::new(self).nth(0).unwrap()
+    pub fn root(&self) -> Token<'_> {
+        let name = self.root_offset + raw::FDT_TAGSIZE;
+        Token::BeginNode(self,
+            name + (self.root_name_len/4 + 1)*4,
+            &self.structs[name..name + self.root_name_len])
+    }
+
+    /// Find a node by its full path, e.g. `/node1/child-node1`.
+    /// The path is normalized by `DtPath::parse()`, see `get_node_by_path_verbose()` for why a lookup failed.
+    /// Returns None if path is invalid or there is no such node.
+    ///
+    #[inline]
+    pub fn get_node_by_path<S: AsRef<[u8]>>(&self, path: S) -> Option<Token<'_>> {
+        self.get_node_by_path_bytes(path.as_ref())
     }
 
-    /// Returns a iterator that will iterate over all tokens in the tree
-    pub fn tokens(&self) -> TokenIterator{
+    /* Non-generic core of get_node_by_path(), see "Names" in the crate documentation */
+    fn get_node_by_path_bytes(&self, path: &[u8]) -> Option<Token<'_>> {
+        self.get_node_by_dt_path(&DtPath::parse(path).ok()?)
+    }
+
+    /// Find a property or node by its full path, e.g. `/chosen/bootargs` or `/soc/uart@0`.
+    /// The last component is first looked up as a property of its parent node and then as a child node,
+    /// so a property is returned if the parent has both a property and a node of that name.
+    /// The path is normalized by `DtPath::parse()`.
+    /// Returns None if path is invalid or there is no such property or node.
+    ///
+    pub fn get(&self, path: &[u8]) -> Option<Token<'_>> {
+        let path = DtPath::parse(path).ok()?;
+        let (parent, name) = match path.split_last() {
+            Some(split) => split,
+            None => return Some(self.root())
+        };
+        let parent = self.get_node_by_dt_path(&parent)?;
+        parent.get_prop(name).or_else(|| parent.get_node(name))
+    }
+
+    /// Returns a iterator that will iterate over all tokens in the tree in document order, NOPs included
+    pub fn tokens(&self) -> TokenIterator<'_>{
         TokenIterator::new(self)
     }
 
-    pub fn get_phandle(&self, phandle: u32) -> Option<Token> {
+    /// Returns a iterator over all strings in the strings block as (offset, string)
+    pub fn strings(&self) -> StringIterator<'a> {
+        StringIterator { strings: self.strings, offs: 0 }
+    }
+
+    /// Returns the string at `nameoff` in the strings block without its NUL, e.g. the name of
+    /// a property from `Token::prop_nameoff()`. An offset into a string resolves to its tail.
+    /// Returns None if `nameoff` is outside of the strings block or the string is not terminated.
+    pub fn name_at(&self, nameoff: u32) -> Option<&'a [u8]> {
+        get_fdt_string(self.strings, nameoff as usize)
+    }
+
+    /// Returns a iterator over all nodes in the tree in document order
+    pub fn nodes(&self) -> impl Iterator<Item = Token<'_>> {
+        self.tokens().filter(|tok| matches!(tok, Token::BeginNode(_,_,_)))
+    }
+
+    /// Returns a iterator over all nodes in document order together with a sequential id,
+    /// starting with 0 for the root node. The ids are stable for a given blob, see `node_id()`.
+    pub fn enumerate_nodes(&self) -> impl Iterator<Item = (u32, Token<'_>)> {
+        (0..).zip(self.nodes())
+    }
+
+    /// Returns the id of `node` as assigned by `enumerate_nodes()`, by counting the nodes preceding it
+    /// Returns None if `node` is not a node of this tree
+    pub fn node_id(&self, node: &Token) -> Option<u32> {
+        let offset = match node {
+            Token::BeginNode(dt, _, _) if core::ptr::eq(dt.fdt, self.fdt) => node.node_offset()?,
+            _ => return None
+        };
+        self.enumerate_nodes()
+            .map(|(id, tok)| (id, tok.node_offset()))
+            .take_while(|(_, offs)| offs.is_some_and(|offs| offs <= offset))
+            .find(|(_, offs)| *offs == Some(offset))
+            .map(|(id, _)| id)
+    }
+
+    /// Returns a iterator over all properties in the tree in document order,
+    /// together with the node owning them as (node, property)
+    pub fn all_props(&self) -> PropertyIterator<'_> {
+        PropertyIterator { tokens: self.attributed_tokens() }
+    }
+
+    /// Returns a iterator over all nodes, properties and node ends in document order
+    /// together with the node owning them, see `AttributedTokens`
+    pub fn attributed_tokens(&self) -> AttributedTokens<'_> {
+        AttributedTokens::new(self)
+    }
+
+    /// Find the node with a `phandle` or `linux,phandle` property equal to `phandle`, the first in document order
+    /// Returns None if there is no such node
+    pub fn get_phandle(&self, phandle: u32) -> Option<Token<'_>> {
+        self.get_phandle_with(phandle, None)
+    }
+
+    /// Same as `get_phandle()` but looks up the node in `index` if given instead of scanning the tree.
+    /// An index built from another tree is ignored.
+    pub fn get_phandle_with(&self, phandle: u32, index: Option<&PhandleIndex>) -> Option<Token<'_>> {
         /* zero is not a valid phandle */
         if phandle == 0 { return None; }
 
-        let mut last_node = Token::Invalid(0);
-        for token in self.tokens() {
-            match token {
-                Token::BeginNode(_,_,_) => {
-                    last_node = token;
-                },
-                Token::Property(_,_,val) => {
-                    match token.prop_u32(0) {
-                        Some(x) => if x == phandle { return Some(last_node) }
-                        _ => ()
-                    }
-                },
-                _ => ()
+        if let Some(index) = index.filter(|index| index.is_for(self)) {
+            return index.lookup(phandle).and_then(|offset| self.node_at_offset(offset))
+        }
+
+        find_phandle(self, self.attributed_tokens(), phandle)
+    }
+
+    /// Same as `get_phandle()` but searches the subtree of `hint` first, where the node is expected to be,
+    /// before falling back to searching the whole tree.
+    pub fn get_phandle_near(&self, phandle: u32, hint: &Token) -> Option<Token<'_>> {
+        if let Token::BeginNode(dt, _, _) = hint {
+            if core::ptr::eq(dt.fdt, self.fdt) {
+                if let Some(node) = hint.node_offset()
+                    .and_then(|offs| find_phandle(self, AttributedTokens::new_offs(self, offs), phandle)) {
+                    return Some(node)
+                }
             }
         }
-        None
+        self.get_phandle(phandle)
+    }
+
+    /// Returns the largest phandle in use, from a single pass over all `phandle` and `linux,phandle` properties.
+    /// Properties which are not exactly one cell and the reserved values 0 and 0xffffffff are ignored.
+    /// Returns 0 if the tree has no phandles.
+    ///
+    pub fn max_phandle(&self) -> u32 {
+        self.tokens()
+            .filter(|tok| matches!(tok, Token::Property(_, props::PHANDLE, _) | Token::Property(_, props::LINUX_PHANDLE, _)))
+            .filter_map(|prop| valid_phandle(&prop))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the phandle of `node`, from `phandle` or else `linux,phandle`, see `compat::phandle()`
+    /// Returns None if `node` is not a node of this tree or has no valid phandle, see `max_phandle()`.
+    ///
+    pub fn phandle_of(&self, node: &Token) -> Option<u32> {
+        match node {
+            Token::BeginNode(dt, _, _) if core::ptr::eq(dt.fdt, self.fdt) => compat::phandle(node).map(|(phandle, _)| phandle),
+            _ => None
+        }
+    }
+
+    /// Find the first node compatible with `compat`, see `Token::is_compatible()`.
+    /// This is the node of the first matching property in document order, which is a descendant
+    /// of an earlier matching node only if that node has its properties after its sub-nodes.
+    /// Returns None if there is no such node
+    #[inline]
+    pub fn find_compatible<S: AsRef<[u8]>>(&self, compat: S) -> Option<Token<'_>> {
+        self.find_compatible_bytes(compat.as_ref())
+    }
+
+    /* Non-generic core of find_compatible(), see "Names" in the crate documentation */
+    fn find_compatible_bytes(&self, compat: &[u8]) -> Option<Token<'_>> {
+        self.all_props()
+            .find(|(_, prop)| prop.name() == props::COMPATIBLE && prop_has_string(prop, compat))
+            .map(|(node, _)| node)
+    }
+
+    /// Returns a iterator over every property named `name` in the tree in document order,
+    /// together with the node owning it as (node, property), see `all_props()`.
+    /// Properties following a sub-node are included and attributed to their node.
+    pub fn props_named<'s>(&'s self, name: &'s [u8]) -> impl Iterator<Item = (Token<'s>, Token<'s>)> {
+        self.all_props().filter(move |(_, prop)| prop.name() == name)
+    }
+
+    /// Find the first node with a property named `name`, ordered like `find_compatible()`
+    /// Returns None if there is no such node
+    pub fn find_with_property(&self, name: &[u8]) -> Option<Token<'_>> {
+        self.all_props()
+            .find(|(_, prop)| prop.name() == name)
+            .map(|(node, _)| node)
     }
 
     /* Methods to access header information*/
 
     /// This field shall contain the value 0xd00dfeed (big-endian).
-    pub fn magic(&self) -> u32 {
-        utils::read_fdt_u32(self.fdt, 0)
+    pub const fn magic(&self) -> u32 {
+        utils::read_fdt_u32(self.fdt, raw::OFF_MAGIC)
     }
 
     /// This field shall contain the total size in bytes of the devicetree data structure. This size shall encompass all
     /// sections of the structure: the header, the memory reservation block, structure block and strings block, as well as any
     /// free space gaps between the blocks or after the final block.
-    pub fn totalsize(&self) -> usize {
-        utils::read_fdt_u32(self.fdt, 4) as usize
+    pub const fn totalsize(&self) -> usize {
+        self.totalsize
+    }
+
+    /// This field shall contain the offset in bytes of the structure block from the beginning of the header.
+    pub const fn off_dt_struct(&self) -> usize {
+        utils::read_fdt_u32(self.fdt, raw::OFF_DT_STRUCT) as usize
+    }
+
+    /// This field shall contain the offset in bytes of the strings block from the beginning of the header.
+    pub const fn off_dt_strings(&self) -> usize {
+        utils::read_fdt_u32(self.fdt, raw::OFF_DT_STRINGS) as usize
+    }
+
+    /// This field shall contain the offset in bytes of the memory reservation block from the beginning of
+    /// the header.
+    pub const fn off_mem_rsvmap(&self) -> usize {
+        utils::read_fdt_u32(self.fdt, raw::OFF_MEM_RSVMAP) as usize
     }
 
     /// This field shall contain the version of the devicetree data structure. The version is 17 if using the structure as
     /// defined in this document. An DTSpec boot program may provide the devicetree of a later version, in which case
     /// this field shall contain the version number defined in whichever later document gives the details of that version.
-    pub fn version(&self) -> u32 {
-        utils::read_fdt_u32(self.fdt, 20)
+    pub const fn version(&self) -> u32 {
+        self.version
     }
 
     /// This field shall contain the lowest version of the devicetree data structure with which the version
@@ -410,14 +1608,37 @@ impl<'a> DeviceTree<'a> {
     /// 16 because version 17 is backwards compatible with version 16, but not earlier versions. As per section 5.1, a
     /// DTSpec boot program should provide a devicetree in a format which is backwards compatible with version 16, and
     /// thus this field shall always contain 16.
-    pub fn last_comp_version(&self) -> u32 {
-        utils::read_fdt_u32(self.fdt, 24)
+    pub const fn last_comp_version(&self) -> u32 {
+        utils::read_fdt_u32(self.fdt, raw::OFF_LAST_COMP_VERSION)
     }
 
     /// This field shall contain the physical ID of the system’s boot CPU. It shall be identical to the
     /// physical ID given in the reg property of that CPU node within the devicetree.
-    pub fn boot_cpuid_phys(&self) -> u32 {
-        utils::read_fdt_u32(self.fdt, 28)
+    pub const fn boot_cpuid_phys(&self) -> u32 {
+        utils::read_fdt_u32(self.fdt, raw::OFF_BOOT_CPUID_PHYS)
     }
 
-}
\ No newline at end of file
+    /// This field shall contain the length in bytes of the strings block section of the devicetree blob.
+    pub const fn size_dt_strings(&self) -> usize {
+        utils::read_fdt_u32(self.fdt, raw::OFF_SIZE_DT_STRINGS) as usize
+    }
+
+    /// This field shall contain the length in bytes of the structure block section of the devicetree blob.
+    /// Returns None if version is less than 17 where this field does not exist.
+    pub const fn size_dt_struct(&self) -> Option<usize> {
+        if self.version() < 17 {
+            return None
+        }
+        Some(utils::read_fdt_u32(self.fdt, raw::OFF_SIZE_DT_STRUCT) as usize)
+    }
+
+}
+
+impl<'a> TryFrom<&'a [u8]> for DeviceTree<'a> {
+    type Error = Error;
+
+    /// See `DeviceTree::parse()`
+    fn try_from(fdt: &'a [u8]) -> Result<Self, Self::Error> {
+        DeviceTree::parse(fdt)
+    }
+}
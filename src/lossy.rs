@@ -0,0 +1,82 @@
+//! # Lossy iteration
+//! Token iteration which resynchronizes after unknown token ids instead of stopping,
+//! for blobs from tools emitting nonstandard tokens.
+//!
+
+use crate::{raw, DeviceTree, DtWarnSink, Token, TokenError, TokenIterator, Warning};
+use crate::utils::{get_fdt_string, try_read_fdt_u32};
+
+/// # LossyTokenIterator
+/// Same as `TokenIterator` but skips unknown tokens, see `DeviceTree::tokens_lossy()`
+pub struct LossyTokenIterator<'a, 's> {
+    tokeniter: TokenIterator<'a>,
+    sink: &'s mut dyn DtWarnSink,
+}
+
+/// Returns true if a known token whose contents fit in the structure block begins at `offs`
+fn plausible_token(dt: &DeviceTree, offs: usize) -> bool {
+    let structs = dt.structs;
+    match try_read_fdt_u32(structs, offs) {
+        Some(raw::FDT_BEGIN_NODE) => get_fdt_string(structs, offs + raw::FDT_TAGSIZE).is_some(),
+        Some(raw::FDT_END_NODE) | Some(raw::FDT_NOP) | Some(raw::FDT_END) => true,
+        Some(raw::FDT_PROP) => {
+            match (try_read_fdt_u32(structs, offs + 4), try_read_fdt_u32(structs, offs + 8)) {
+                (Some(len), Some(nameoff)) => {
+                    (offs + 12).checked_add(len as usize).is_some_and(|end| end <= structs.len())
+                        && get_fdt_string(dt.strings, nameoff as usize).is_some()
+                },
+                _ => false
+            }
+        },
+        _ => false
+    }
+}
+
+impl<'a, 's> Iterator for LossyTokenIterator<'a, 's> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let dt = self.tokeniter.dt?;
+        let offset = self.tokeniter.offs;
+        let tok = match self.tokeniter.next_checked()? {
+            Ok(tok) => tok,
+            Err(TokenError::OversizeProperty { offset, len }) => {
+                /* The size is known, resume after the value */
+                self.sink.warn(Warning::OversizeProperty { offset, len });
+                return self.next()
+            },
+            Err(TokenError::Truncated { .. }) => return None
+        };
+        if !matches!(tok, Token::Invalid(_)) {
+            return Some(tok)
+        }
+
+        /* Scan for the next plausible token */
+        let mut offs = offset + raw::FDT_TAGSIZE;
+        while offs + raw::FDT_TAGSIZE <= dt.structs.len() {
+            if plausible_token(dt, offs) {
+                self.sink.warn(Warning::SkippedBytes { offset, len: offs - offset });
+                self.tokeniter = TokenIterator::new_offs(dt, offs);
+                return self.next()
+            }
+            offs += raw::FDT_TAGSIZE;
+        }
+
+        /* Nothing to resume at */
+        self.sink.warn(Warning::SkippedBytes { offset, len: dt.structs.len() - offset });
+        Some(tok)
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Same as `tokens()` but on an unknown token id, scans forward 4 bytes at a time for a known token
+    /// whose contents fit in the structure block and resumes there. Each skipped range is reported to `sink`.
+    /// `Token::Invalid` is only returned if no token follows the unknown one.
+    /// Properties larger than `ParseOptions::max_prop_size` are reported to `sink` and skipped.
+    /// The resumed token may itself be garbage which happens to look like a token.
+    ///
+    pub fn tokens_lossy<'s>(&'a self, sink: &'s mut dyn DtWarnSink) -> LossyTokenIterator<'a, 's> {
+        LossyTokenIterator { tokeniter: TokenIterator::new(self), sink }
+    }
+}
@@ -0,0 +1,81 @@
+/// Include a devicetree blob as a `&'static [u8]` aligned to 8 bytes.
+///
+/// The header of the blob (magic, version and block bounds) is checked at compile time
+/// using `check_header()`, a bad blob results in a compile error with the reason.
+/// The returned slice can be passed directly to `DeviceTree::parse()`.
+///
+/// ```
+/// use static_dt_rs::{include_dtb, DeviceTree};
+///
+/// static FDT: &[u8] = include_dtb!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.dtb"));
+///
+/// let dt = DeviceTree::parse(FDT).unwrap();
+/// assert_eq!(dt.version(), 17);
+/// ```
+///
+/// A blob with a broken header fails to compile, see tests/ui for the exact errors:
+///
+/// ```compile_fail
+/// use static_dt_rs::include_dtb;
+///
+/// static FDT: &[u8] = include_dtb!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bad_magic.dtb"));
+/// ```
+#[macro_export]
+macro_rules! include_dtb {
+    ($file:expr $(,)?) => {{
+        #[repr(C, align(8))]
+        struct Aligned<B: ?Sized>(B);
+
+        static ALIGNED: &Aligned<[u8]> = &Aligned(*include_bytes!($file));
+
+        const _: () = if let Err(e) = $crate::check_header(include_bytes!($file)) {
+            panic!("{}", e.description())
+        };
+
+        &ALIGNED.0
+    }};
+}
+
+/// Generate a static match table of probe functions and a function calling them for every matching node.
+///
+/// The table is a `&[DtMatch<fn(Token, &mut C)>]` where `C` is a context passed to every probe function.
+/// The generated probe function walks the enabled nodes in tree order and calls the function of the
+/// best matching entry once per node, see `DeviceTree::probe_matches()`.
+///
+/// ```
+/// use static_dt_rs::{dt_match_table, DeviceTree, Token};
+///
+/// fn probe_uart(node: Token, found: &mut Vec<String>) {
+///     found.push(String::from_utf8_lossy(node.name()).into_owned());
+/// }
+///
+/// dt_match_table! {
+///     static DRIVERS;
+///     fn probe_all(&mut Vec<String>);
+///     b"ns16550a" => probe_uart,
+/// }
+///
+/// let fdt = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/console.dtb"));
+/// let dt = DeviceTree::parse(fdt).unwrap();
+/// let mut found = Vec::new();
+/// probe_all(&dt, &mut found);
+/// assert_eq!(found, ["serial@2000", "serial@3000"]);
+/// ```
+#[macro_export]
+macro_rules! dt_match_table {
+    (
+        $tvis:vis static $table:ident;
+        $pvis:vis fn $probe:ident(&mut $ctx:ty);
+        $($compat:expr => $func:expr),* $(,)?
+    ) => {
+        $tvis static $table: &[$crate::DtMatch<fn($crate::Token<'_>, &mut $ctx)>] = &[
+            $($crate::DtMatch { compatible: $compat, data: $func }),*
+        ];
+
+        $pvis fn $probe(dt: &$crate::DeviceTree<'_>, ctx: &mut $ctx) {
+            for (node, entry) in dt.probe_matches($table) {
+                (entry.data)(node, ctx)
+            }
+        }
+    };
+}
@@ -0,0 +1,68 @@
+//! # Memory
+//! The RAM described by the `memory` nodes, and the restrictions passed to a crash kernel
+//! in `/chosen`: `linux,usable-memory-range` and `linux,elfcorehdr`.
+//! All are decoded with the `#address-cells` and `#size-cells` of the root node.
+//!
+
+use crate::{props, CellSizes, DeviceTree, RegEntry, RegIterator, Token};
+
+/// Returns the overlap of `region` and `window`
+/// Returns None if they do not overlap.
+fn clamp(region: RegEntry, window: RegEntry) -> Option<RegEntry> {
+    let start = region.address.max(window.address);
+    let end = region.address.saturating_add(region.size).min(window.address.saturating_add(window.size));
+    (start < end).then(|| RegEntry { address: start, size: end - start })
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a iterator over the `reg` entries of all children of the root with `device_type = "memory"`
+    ///
+    pub fn memory_regions(&self) -> impl Iterator<Item = RegEntry> + '_ {
+        let sizes = CellSizes::of_parent(&self.root());
+        self.root().into_iter()
+            .filter(|node| {
+                matches!(node, Token::BeginNode(..))
+                    && node.get_prop(props::DEVICE_TYPE).and_then(|prop| prop.prop_str()) == Some(b"memory")
+            })
+            .flat_map(move |node| node.reg_with(&sizes).into_iter().flatten())
+    }
+
+    /// Same as `memory_regions()` but clamped to `usable_memory_range()` if present.
+    /// Regions outside of the usable range are skipped.
+    ///
+    pub fn memory_regions_clamped(&self) -> impl Iterator<Item = RegEntry> + '_ {
+        let usable = self.usable_memory_range().map(|(address, size)| RegEntry { address, size });
+        self.memory_regions().filter_map(move |region| match usable {
+            Some(window) => clamp(region, window),
+            None => Some(region)
+        })
+    }
+
+    /// Returns the first (base, size) of `linux,usable-memory-range` in `/chosen`,
+    /// the memory a crash kernel may use
+    /// Returns None if there is no such property or it is too short.
+    ///
+    pub fn usable_memory_range(&self) -> Option<(u64, u64)> {
+        self.chosen_reg(props::USABLE_MEMORY_RANGE)
+    }
+
+    /// Returns the (base, size) of `linux,elfcorehdr` in `/chosen`,
+    /// the ELF core header of the crashed kernel
+    /// Returns None if there is no such property or it is too short.
+    ///
+    pub fn elfcorehdr(&self) -> Option<(u64, u64)> {
+        self.chosen_reg(props::ELFCOREHDR)
+    }
+
+    /// Read the first (address, size) of the `/chosen` property `name` with the cell sizes of the root
+    fn chosen_reg(&self, name: &[u8]) -> Option<(u64, u64)> {
+        let val = match self.chosen()?.get_prop(name)? {
+            Token::Property(_, _, val) => val,
+            _ => return None
+        };
+        RegIterator::new(val, &CellSizes::of_parent(&self.root()))?
+            .next()
+            .map(|entry| (entry.address, entry.size))
+    }
+}
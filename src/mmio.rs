@@ -0,0 +1,138 @@
+//! # MMIO regions
+//! The register windows of all available devices as a short list of CPU address ranges,
+//! e.g. to program a MPU or the device mappings of a MMU before any driver runs.
+//!
+//! Every `reg` entry of an enabled node whose ancestors are all enabled is translated to a CPU
+//! address. Entries which are not memory mapped (no `ranges` on the way to the root), empty entries
+//! and `memory` nodes are left out. Overlapping and adjacent windows with the same attributes are
+//! coalesced, windows with different attributes are kept apart even if they overlap.
+//!
+
+use crate::{props, DeviceTree, Token};
+
+/// # MmioAttr
+/// Memory attributes of a `MmioRegion`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MmioAttr {
+    /// Device memory, the node is below a `simple-bus`
+    Device,
+    /// Normal memory, the node is not below a `simple-bus`, e.g. SRAM or a controller at the root
+    Normal,
+}
+
+/// # MmioRegion
+/// A range of CPU addresses, see `DeviceTree::mmio_regions()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MmioRegion {
+    pub base: u64,
+    pub size: u64,
+    pub attr: MmioAttr,
+}
+
+impl MmioRegion {
+    /// Returns the first address after the region, saturated at `u64::MAX`
+    pub fn end(&self) -> u64 {
+        self.base.saturating_add(self.size)
+    }
+
+    /// Returns true if `other` has the same attributes and overlaps or touches this region
+    fn joins(&self, other: &MmioRegion) -> bool {
+        self.attr == other.attr && self.base <= other.end() && other.base <= self.end()
+    }
+}
+
+/// # CapacityError
+/// Storage given to `DeviceTree::mmio_regions()` is too small
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CapacityError {
+    /// Number of regions required
+    pub required: usize,
+}
+
+/// Returns the attributes of `node` if it and all its ancestors are enabled
+fn availability(node: &Token) -> Option<MmioAttr> {
+    let mut attr = MmioAttr::Normal;
+    let mut node = *node;
+    loop {
+        if !node.is_enabled() {
+            return None
+        }
+        node = match node.parent() {
+            Some(parent) => parent,
+            None => return Some(attr)
+        };
+        if node.is_compatible(b"simple-bus") {
+            attr = MmioAttr::Device;
+        }
+    }
+}
+
+/// Merge `region` into the sorted and coalesced `regions[..count]`.
+/// Returns the new count, None if `regions` is full and `region` joins none of them.
+fn insert(regions: &mut [MmioRegion], count: usize, region: MmioRegion) -> Option<usize> {
+    let mut region = region;
+    let mut count = count;
+
+    /* A merged region may join more of its neighbours, merge until none is left */
+    while let Some(i) = regions[..count].iter().position(|other| other.joins(&region)) {
+        let other = regions[i];
+        let end = region.end().max(other.end());
+        region.base = region.base.min(other.base);
+        region.size = end - region.base;
+        regions.copy_within(i + 1..count, i);
+        count -= 1;
+    }
+
+    if count == regions.len() {
+        return None
+    }
+    let at = regions[..count].partition_point(|other| (other.base, other.attr) < (region.base, region.attr));
+    regions.copy_within(at..count, at + 1);
+    regions[at] = region;
+    Some(count + 1)
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a iterator over the translated `reg` entries of all available devices in document order,
+    /// not coalesced, see the module documentation
+    fn mmio_windows(&self) -> impl Iterator<Item = MmioRegion> + '_ {
+        self.nodes()
+            .filter(|node| node.get_prop(props::DEVICE_TYPE).and_then(|prop| prop.prop_str()) != Some(b"memory"))
+            .filter_map(|node| Some((node, availability(&node)?)))
+            .flat_map(|(node, attr)| node.reg().into_iter().flatten().filter_map(move |reg| {
+                let base = node.translate_address(reg.address)?;
+                (reg.size > 0).then_some(MmioRegion { base, size: reg.size, attr })
+            }))
+    }
+
+    /// Write the coalesced register windows of all available devices to `out`, sorted by base address,
+    /// see the module documentation. Returns the number of regions written.
+    ///
+    /// Returns `CapacityError` with the number of regions required if `out` is too small,
+    /// the contents of `out` are unspecified then. Counting them takes time quadratic in the number of windows.
+    ///
+    pub fn mmio_regions(&self, out: &mut [MmioRegion]) -> Result<usize, CapacityError> {
+        let mut count = 0;
+        for region in self.mmio_windows() {
+            count = match insert(out, count, region) {
+                Some(count) => count,
+                None => return Err(CapacityError { required: self.mmio_region_count() })
+            };
+        }
+        Ok(count)
+    }
+
+    /// Returns the number of regions `mmio_regions()` writes without storing them.
+    /// A coalesced region is counted at the window with its lowest base address, the first one
+    /// in document order if several start there.
+    fn mmio_region_count(&self) -> usize {
+        self.mmio_windows().enumerate().filter(|(i, region)| {
+            self.mmio_windows().enumerate().all(|(j, other)| {
+                let starts_before = other.base < region.base && other.end() >= region.base;
+                let same_start = other.base == region.base && j < *i;
+                other.attr != region.attr || !(starts_before || same_start)
+            })
+        }).count()
+    }
+}
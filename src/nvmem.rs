@@ -0,0 +1,59 @@
+//! # NVMEM
+//! Consumer side of the nvmem bindings, e.g. a MAC address or calibration data
+//! stored in an eeprom or OTP fuses.
+//!
+
+use crate::Token;
+
+/// # NvmemCell
+/// A cell of a nvmem provider, see `Token::nvmem_cell()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NvmemCell<'a> {
+    /// The cell node
+    pub node: Token<'a>,
+    /// The device holding the data, the parent of the cell (or of its `fixed-layout` node)
+    pub provider: Token<'a>,
+    /// Offset in bytes from the start of the provider
+    pub offset: u64,
+    /// Length in bytes
+    pub length: u64,
+    /// Bit offset and number of bits within the cell, from the `bits` property
+    pub bits: Option<(u32, u32)>,
+}
+
+impl<'a> NvmemCell<'a> {
+    /// Decode the cell `node`
+    /// Returns None if `node` has no `reg` or no parent.
+    pub fn new(node: Token<'a>) -> Option<NvmemCell<'a>> {
+        let reg = node.reg()?.next()?;
+        let mut provider = node.parent()?;
+        if provider.is_compatible(b"fixed-layout") {
+            provider = provider.parent()?;
+        }
+        let bits = node.get_prop(b"bits")
+            .and_then(|prop| Some((prop.prop_u32(0)?, prop.prop_u32(1)?)));
+        Some(NvmemCell { node, provider, offset: reg.address, length: reg.size, bits })
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns the cell called `name` in `nvmem-cell-names` of this consumer node
+    /// Returns None if there is no such name, `nvmem-cells` and `nvmem-cell-names` differ in length,
+    /// the phandle does not exist or the cell has no `reg`.
+    ///
+    pub fn nvmem_cell(&self, name: &[u8]) -> Option<NvmemCell<'a>> {
+        let cells = self.get_prop(b"nvmem-cells")?;
+        let names = self.get_prop(b"nvmem-cell-names")?;
+        if cells.prop_len()? != names.prop_strings().count()*4 {
+            return None
+        }
+
+        let index = names.prop_strings().position(|n| n == name)?;
+        let phandle = cells.prop_u32(index)?;
+        match cells {
+            Token::Property(dt, _, _) => NvmemCell::new(dt.get_phandle(phandle)?),
+            _ => None
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! # Operating points
+//! Frequency and voltage pairs used for CPU and device frequency scaling,
+//! from the legacy `operating-points` or from an `operating-points-v2` table.
+//!
+
+use crate::Token;
+
+/// # Opp
+/// One operating point, see `Token::operating_points()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Opp {
+    pub frequency_hz: u64,
+    /// Target voltage, None if the table does not specify voltages
+    pub microvolt: Option<u32>,
+}
+
+impl Opp {
+    /// Decode a node of a `operating-points-v2` table
+    /// Returns None if `node` has no valid `opp-hz`
+    pub fn from_node(node: &Token) -> Option<Opp> {
+        let hz = node.get_prop(b"opp-hz")?;
+        Some(Opp {
+            frequency_hz: hz.prop_as::<u64>().or_else(|| hz.prop_as::<u32>().map(u64::from))?,
+            /* Either <target> or <target min max> */
+            microvolt: node.get_prop(b"opp-microvolt").and_then(|prop| prop.prop_u32(0)),
+        })
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the operating points of this node, read from the (kHz, uV) pairs of
+    /// `operating-points` or from the nodes of the table referenced by `operating-points-v2`
+    /// in document order. An incomplete pair at the end of `operating-points` and table nodes
+    /// without `opp-hz` are skipped.
+    /// Returns a empty iterator if there are no operating points or token is not a node.
+    ///
+    pub fn operating_points(&self) -> impl Iterator<Item = Opp> + 'a {
+        let legacy = self.get_prop(b"operating-points");
+        let table = match legacy {
+            Some(_) => None,
+            None => self.get_prop(b"operating-points-v2").and_then(|prop| prop.prop_phandle())
+        };
+
+        let pairs = legacy.into_iter().flat_map(|prop| {
+            (0..prop.prop_len().unwrap_or(0)/8).filter_map(move |i| Some(Opp {
+                frequency_hz: prop.prop_u32(2*i)? as u64 * 1000,
+                microvolt: Some(prop.prop_u32(2*i + 1)?),
+            }))
+        });
+        let nodes = table.into_iter()
+            .flatten()
+            .filter(|node| matches!(node, Token::BeginNode(_, _, _)))
+            .filter_map(|node| Opp::from_node(&node));
+        pairs.chain(nodes)
+    }
+}
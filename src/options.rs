@@ -0,0 +1,83 @@
+//! # Parse options
+//! Limits applied by `DeviceTree::parse_with_options()`. A corrupted `len` field can claim a property
+//! of gigabytes which, if it happens to fit the buffer, every consumer of the value would process.
+//! Properties above `ParseOptions::max_prop_size` are reported to the warning sink, returned as
+//! `TokenError::OversizeProperty` by `DeviceTree::try_tokens()`. All other iterators and lookups stop
+//! at them with `Token::Invalid(FDT_PROP)`, except `DeviceTree::tokens_lossy()` which skips their value.
+//! `DeviceTree::parse()` applies no limit.
+//!
+
+use crate::{DeviceTree, DtWarnSink, Error, Token, TokenIterator};
+
+/// Default of `ParseOptions::max_prop_size`, 1 MiB
+pub const DEFAULT_MAX_PROP_SIZE: usize = 1 << 20;
+
+/// # ParseOptions
+/// Options of `DeviceTree::parse_with_options()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Largest property value in bytes
+    pub max_prop_size: usize,
+}
+
+impl Default for ParseOptions {
+    /// `DEFAULT_MAX_PROP_SIZE`
+    fn default() -> Self {
+        ParseOptions { max_prop_size: DEFAULT_MAX_PROP_SIZE }
+    }
+}
+
+/// # TokenError
+/// Errors returned by `DeviceTree::try_tokens()`, offsets are relative to the structure block
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    /// The property at `offset` has a value of `len` bytes, more than `ParseOptions::max_prop_size`.
+    /// Iteration continues after the value.
+    OversizeProperty { offset: usize, len: usize },
+
+    /// The token at `offset` runs past the end of the structure block, iteration stops
+    Truncated { offset: usize },
+}
+
+/// # TryTokenIterator
+/// Same as `TokenIterator` but reports problems as errors, see `DeviceTree::try_tokens()`
+pub struct TryTokenIterator<'a> {
+    tokeniter: TokenIterator<'a>,
+}
+
+impl<'a> TryTokenIterator<'a> {
+    /// See `TokenIterator::offset()`
+    pub fn offset(&self) -> usize {
+        self.tokeniter.offset()
+    }
+}
+
+impl<'a> Iterator for TryTokenIterator<'a> {
+    type Item = Result<Token<'a>, TokenError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.tokeniter.dt?;
+        let offset = self.tokeniter.offs;
+        match self.tokeniter.next_checked() {
+            Some(result) => Some(result),
+            None => Some(Err(TokenError::Truncated { offset }))
+        }
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Same as `parse_with_warnings()` but with the limits of `options`
+    pub fn parse_with_options(fdt: &'a [u8], options: &ParseOptions, sink: &mut dyn DtWarnSink) -> Result<DeviceTree<'a>, Error> {
+        let mut dt = DeviceTree::parse(fdt)?;
+        dt.max_prop_size = options.max_prop_size;
+        dt.warnings(sink);
+        Ok(dt)
+    }
+
+    /// Returns a iterator over all tokens like `tokens()`, with properties above `ParseOptions::max_prop_size`
+    /// and tokens running out of the structure block returned as errors
+    pub fn try_tokens(&self) -> TryTokenIterator<'_> {
+        TryTokenIterator { tokeniter: TokenIterator::new(self) }
+    }
+}
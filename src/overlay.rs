@@ -0,0 +1,281 @@
+//! # Overlays
+//! Applying devicetree overlays to a blob in a mutable buffer, and a dry run reporting what
+//! applying one would change without writing anything.
+//!
+//! An overlay has one `fragment@N` node per target, each with a `target = <phandle>` or a
+//! `target-path = "/path"` property and an `__overlay__` node. The properties and sub-nodes of
+//! `__overlay__` are merged into the target: existing properties are replaced and missing
+//! properties and nodes are added. Phandles must already be resolved, `__fixups__` and
+//! `__local_fixups__` are not processed.
+//!
+//! `overlay_dry_run()` and `DeviceTreeMut::apply_overlay()` resolve targets and compare with the
+//! base tree with the same code, so the dry run reports exactly the edits applying makes.
+//! Properties set to the value they already have are not reported and not written.
+//! Paths are built in a buffer of `MAX_OVERLAY_PATH` bytes.
+//!
+
+use crate::{DeviceTree, DeviceTreeMut, DtPath, EditError, Token, TokenIterator, MAX_DEPTH};
+
+/// Longest path of a node changed by an overlay, in bytes
+pub const MAX_OVERLAY_PATH: usize = 256;
+
+/// # OverlayChange
+/// A change applying an overlay makes to the base tree, see `overlay_dry_run()`.
+/// Paths are full paths in the base tree without repeated or trailing separators,
+/// names and new values are taken from the overlay.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverlayChange<'a> {
+    /// The property `name` with `value` is added to the node at `path`
+    PropAdded { path: &'a [u8], name: &'a [u8], value: &'a [u8] },
+
+    /// The value of property `name` of the node at `path` is replaced
+    PropReplaced { path: &'a [u8], name: &'a [u8], old: &'a [u8], new: &'a [u8] },
+
+    /// An empty node is created at `path`, its properties and sub-nodes are reported after it
+    NodeCreated { path: &'a [u8] },
+
+    /// The target of the fragment named `fragment` does not exist in the base tree,
+    /// or its `target-path` is not a valid path, see `DtPath::parse()`.
+    /// Nothing of the fragment is applied, `DeviceTreeMut::apply_overlay()` fails
+    TargetNotFound { fragment: &'a [u8] },
+}
+
+/// A node or property of an overlay to merge at the path of an `OverlayWalk`
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum OverlayItem<'b> {
+    /// The fragment node has no target in the base tree
+    NoTarget(Token<'b>),
+    /// A node to merge at the path
+    Node(Token<'b>),
+    /// A property to merge into the node at the path
+    Prop(Token<'b>),
+}
+
+/// Walk of the fragments of an overlay, keeping the base tree path of the current node
+pub(crate) struct OverlayWalk<'b> {
+    fragments: TokenIterator<'b>,
+    body: Option<TokenIterator<'b>>,
+    path: [u8; MAX_OVERLAY_PATH],
+    len: usize,
+    /* Path lengths of the open nodes of the current fragment */
+    lens: [usize; MAX_DEPTH],
+    depth: usize,
+}
+
+impl<'b> OverlayWalk<'b> {
+    pub(crate) fn new(overlay: &'b DeviceTree<'b>) -> Self {
+        let fragments = match overlay.root() {
+            Token::BeginNode(dt, offs, _) => TokenIterator::new_offs(dt, offs),
+            _ => TokenIterator::none()
+        };
+        OverlayWalk { fragments, body: None, path: [0; MAX_OVERLAY_PATH], len: 0, lens: [0; MAX_DEPTH], depth: 0 }
+    }
+
+    /// Returns the path in the base tree of the node of the last item
+    pub(crate) fn path(&self) -> &[u8] {
+        &self.path[..self.len]
+    }
+
+    /// Returns the path of the parent of the node of the last `OverlayItem::Node`
+    pub(crate) fn parent_path(&self) -> &[u8] {
+        &self.path[..self.lens[self.depth - 1].max(1)]
+    }
+
+    /// Replace the path with `path`
+    fn set_path(&mut self, path: &[u8]) -> Result<(), EditError> {
+        self.path.get_mut(..path.len()).ok_or(EditError::PathTooLong)?.copy_from_slice(path);
+        self.len = path.len();
+        Ok(())
+    }
+
+    /// Append the node `name` to the path
+    fn push(&mut self, name: &[u8]) -> Result<(), EditError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(EditError::PathTooLong)
+        }
+        let start = if self.len == 1 { 1 } else { self.len + 1 };
+        let dst = self.path.get_mut(start - 1..start + name.len()).ok_or(EditError::PathTooLong)?;
+        dst[0] = b'/';
+        dst[1..].copy_from_slice(name);
+        self.lens[self.depth] = self.len;
+        self.depth += 1;
+        self.len = start + name.len();
+        Ok(())
+    }
+
+    /// Write the full path of `node` to the path
+    fn set_node_path(&mut self, base: &DeviceTree, node: &Token) -> Result<(), EditError> {
+        let target = node.node_offset().ok_or(EditError::NodeNotFound)?;
+        let mut stack = [0usize; MAX_DEPTH];
+        let mut depth = 0;
+        for tok in base.tokens() {
+            match tok {
+                Token::BeginNode(_, _, _) => {
+                    let offs = tok.node_offset().ok_or(EditError::NodeNotFound)?;
+                    *stack.get_mut(depth).ok_or(EditError::PathTooLong)? = offs;
+                    depth += 1;
+                    if offs == target {
+                        break
+                    }
+                },
+                Token::EndNode => depth -= 1,
+                _ => ()
+            }
+        }
+
+        self.set_path(b"/")?;
+        for offs in &stack[1..depth] {
+            let name = base.node_at_offset(*offs).ok_or(EditError::NodeNotFound)?.name();
+            self.push(name)?;
+        }
+        self.depth = 0;
+        Ok(())
+    }
+
+    /// Resolve the target of `fragment` in `base` into the path.
+    /// Returns false if it has none.
+    fn resolve(&mut self, base: &DeviceTree, fragment: &Token) -> Result<bool, EditError> {
+        if let Some(path) = fragment.get_prop(b"target-path").and_then(|prop| prop.prop_str()) {
+            let path = match DtPath::parse(path) {
+                Ok(path) if base.get_node_by_dt_path(&path).is_some() => path,
+                _ => return Ok(false)
+            };
+            /* Collapsed like every other path this walk builds */
+            self.set_path(b"/")?;
+            for name in path.components() {
+                self.push(name)?;
+            }
+            self.depth = 0;
+            return Ok(true)
+        }
+        match fragment.get_prop(b"target").and_then(|prop| prop.prop_u32(0)).and_then(|phandle| base.get_phandle(phandle)) {
+            Some(node) => self.set_node_path(base, &node).map(|_| true),
+            None => Ok(false)
+        }
+    }
+
+    /// Returns the next item of the overlay, resolving fragment targets in `base`
+    pub(crate) fn next(&mut self, base: &DeviceTree) -> Option<Result<OverlayItem<'b>, EditError>> {
+        loop {
+            if let Some(body) = &mut self.body {
+                match body.next() {
+                    Some(tok @ Token::Property(_, _, _)) => return Some(Ok(OverlayItem::Prop(tok))),
+                    Some(tok @ Token::BeginNode(_, _, name)) => {
+                        return Some(self.push(name).map(|_| OverlayItem::Node(tok)))
+                    },
+                    Some(Token::EndNode) if self.depth > 0 => {
+                        self.depth -= 1;
+                        self.len = self.lens[self.depth];
+                    },
+                    Some(Token::NoOperation) => (),
+                    _ => self.body = None
+                }
+                continue
+            }
+
+            let fragment = match self.fragments.next()? {
+                tok @ Token::BeginNode(_, _, _) => tok,
+                Token::Property(_, _, _) | Token::NoOperation => continue,
+                _ => return None
+            };
+            self.fragments.skip_subtree();
+            let body = match fragment.get_node(b"__overlay__") {
+                Some(Token::BeginNode(dt, offs, _)) => TokenIterator::new_offs(dt, offs),
+                _ => continue
+            };
+            match self.resolve(base, &fragment) {
+                Ok(true) => {
+                    self.depth = 0;
+                    self.body = Some(body);
+                },
+                Ok(false) => return Some(Ok(OverlayItem::NoTarget(fragment))),
+                Err(e) => return Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Compare `item` at `path` of `walk` with `base`.
+/// Returns None if merging it changes nothing.
+pub(crate) fn classify<'s>(base: &'s DeviceTree, walk: &'s OverlayWalk, item: &OverlayItem<'s>) -> Option<OverlayChange<'s>> {
+    let path = walk.path();
+    match item {
+        OverlayItem::NoTarget(fragment) => Some(OverlayChange::TargetNotFound { fragment: fragment.name() }),
+        OverlayItem::Node(node) => {
+            let exists = base.get_node_by_path(walk.parent_path()).and_then(|parent| parent.get_node(node.name())).is_some();
+            if exists { None } else { Some(OverlayChange::NodeCreated { path }) }
+        },
+        OverlayItem::Prop(Token::Property(_, name, new)) => {
+            match base.get_node_by_path(path).and_then(|node| node.get_prop_relaxed(name)) {
+                Some(Token::Property(_, _, old)) if old == *new => None,
+                Some(Token::Property(_, _, old)) => Some(OverlayChange::PropReplaced { path, name, old, new }),
+                _ => Some(OverlayChange::PropAdded { path, name, value: new })
+            }
+        },
+        OverlayItem::Prop(_) => None
+    }
+}
+
+/// Report every change applying `overlay` to `base` would make to `sink`, in the order
+/// `DeviceTreeMut::apply_overlay()` makes them, see the module documentation.
+///
+/// Every fragment is resolved against the unmodified base: if two fragments set the same property
+/// both are reported as added, where applying replaces the value added by the first.
+/// Returns `EditError::PathTooLong` if a path does not fit in `MAX_OVERLAY_PATH` bytes.
+///
+pub fn overlay_dry_run(base: &DeviceTree, overlay: &DeviceTree, sink: &mut dyn FnMut(OverlayChange)) -> Result<(), EditError> {
+    let mut walk = OverlayWalk::new(overlay);
+    while let Some(item) = walk.next(base) {
+        if let Some(change) = classify(base, &walk, &item?) {
+            sink(change);
+        }
+    }
+    Ok(())
+}
+
+/// Edit made for an `OverlayChange`
+enum Edit {
+    AddNode,
+    SetProp,
+}
+
+impl<'a> DeviceTreeMut<'a> {
+
+    /// Merge the fragments of `overlay` into this tree, see the module documentation and
+    /// `overlay_dry_run()` for the changes made.
+    ///
+    /// All targets are resolved before anything is written, returns `EditError::NodeNotFound`
+    /// with the blob unchanged if one is missing. On `EditError::NoSpace` the changes made so far are kept,
+    /// reserve room with `ensure_slack()` first to apply an overlay of known size.
+    ///
+    pub fn apply_overlay(&mut self, overlay: &DeviceTree) -> Result<(), EditError> {
+        let mut missing = false;
+        overlay_dry_run(&self.tree(), overlay, &mut |change| {
+            missing |= matches!(change, OverlayChange::TargetNotFound { .. });
+        })?;
+        if missing {
+            return Err(EditError::NodeNotFound)
+        }
+
+        let mut walk = OverlayWalk::new(overlay);
+        loop {
+            let tree = self.tree();
+            let item = match walk.next(&tree) {
+                Some(item) => item?,
+                None => return Ok(())
+            };
+            let edit = match classify(&tree, &walk, &item) {
+                Some(OverlayChange::NodeCreated { .. }) => Edit::AddNode,
+                Some(OverlayChange::PropAdded { .. }) | Some(OverlayChange::PropReplaced { .. }) => Edit::SetProp,
+                Some(OverlayChange::TargetNotFound { .. }) => return Err(EditError::NodeNotFound),
+                None => continue
+            };
+
+            match (edit, item) {
+                (Edit::AddNode, OverlayItem::Node(node)) => self.add_node(walk.parent_path(), node.name())?,
+                (Edit::SetProp, OverlayItem::Prop(Token::Property(_, name, value))) => self.set_prop(walk.path(), name, value)?,
+                _ => ()
+            }
+        }
+    }
+}
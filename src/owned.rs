@@ -0,0 +1,98 @@
+//! # Alloc conveniences
+//! Owning devicetree and collecting helpers, available with the `alloc` feature.
+//!
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{DeviceTree, Error, PhandleArgs, RegEntry, Token};
+
+/// # OwnedDeviceTree
+/// A devicetree owning its blob.
+/// The blob is checked once when created, borrow it as a `DeviceTree` with `tree()`.
+#[derive(Debug, Clone)]
+pub struct OwnedDeviceTree {
+    fdt: Box<[u8]>
+}
+
+impl OwnedDeviceTree {
+    /// Take ownership of `fdt` and check that it is a valid devicetree, see `DeviceTree::parse()`
+    pub fn new(fdt: impl Into<Box<[u8]>>) -> Result<OwnedDeviceTree, Error> {
+        let fdt = fdt.into();
+        DeviceTree::parse(&fdt)?;
+        Ok(OwnedDeviceTree { fdt })
+    }
+
+    /// Copy `fdt` and check that it is a valid devicetree, see `DeviceTree::parse()`
+    pub fn from_slice(fdt: &[u8]) -> Result<OwnedDeviceTree, Error> {
+        DeviceTree::parse(fdt)?;
+        Ok(OwnedDeviceTree { fdt: fdt.into() })
+    }
+
+    /// Borrow the devicetree
+    pub fn tree(&self) -> DeviceTree<'_> {
+        DeviceTree::parse(&self.fdt).expect("blob was checked by new()")
+    }
+
+    /// Returns the blob
+    pub fn blob(&self) -> &[u8] {
+        &self.fdt
+    }
+
+    /// Returns the blob, giving up ownership
+    pub fn into_blob(self) -> Box<[u8]> {
+        self.fdt
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns the strings of a string list property, see `prop_strings()`
+    /// Returns None if not a property
+    ///
+    pub fn prop_string_list_vec(&self) -> Option<Vec<&'a [u8]>> {
+        match self {
+            Token::Property(_, _, _) => Some(self.prop_strings().collect()),
+            _ => None
+        }
+    }
+
+    /// Returns the full path of this node, e.g. "/soc/serial@10000000".
+    /// Names which are not valid UTF-8 are converted lossily.
+    /// Returns None if token is not a node.
+    ///
+    pub fn full_path_string(&self) -> Option<String> {
+        let mut names = Vec::new();
+        let mut node = *self;
+        while let Some(parent) = node.parent() {
+            names.push(node.name());
+            node = parent;
+        }
+        if !matches!(node, Token::BeginNode(_, _, _)) {
+            return None
+        }
+        if names.is_empty() {
+            return Some(String::from("/"))
+        }
+
+        let mut path = String::new();
+        for name in names.iter().rev() {
+            path.push('/');
+            path.push_str(&String::from_utf8_lossy(name));
+        }
+        Some(path)
+    }
+
+    /// Returns the entries of the `reg` property, see `reg()`
+    ///
+    pub fn reg_vec(&self) -> Option<Vec<RegEntry>> {
+        self.reg().map(|reg| reg.collect())
+    }
+
+    /// Returns the interrupt specifiers of this node, see `interrupts()`
+    ///
+    pub fn interrupts_vec(&self) -> Vec<PhandleArgs<'a>> {
+        self.interrupts().collect()
+    }
+}
@@ -0,0 +1,78 @@
+//! # Partitions
+//! Decoding of flash partition tables described by `fixed-partitions` nodes.
+//!
+
+use crate::{props, CellSizes, HierarchyTokenIterator, Token};
+
+/// # Partition
+/// One partition of a flash node, see `Token::partitions()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Partition<'a> {
+    /// The partition node
+    pub node: Token<'a>,
+    /// Offset from the start of the flash in bytes
+    pub offset: u64,
+    /// Size in bytes
+    pub size: u64,
+    /// `label` property
+    pub label: Option<&'a [u8]>,
+    /// True if the partition has a `read-only` property
+    pub read_only: bool,
+}
+
+/// # PartitionIterator
+/// Iterates over the partitions of a flash node, see `Token::partitions()`.
+/// Children without a valid `reg` property are skipped.
+pub struct PartitionIterator<'a> {
+    children: HierarchyTokenIterator<'a>,
+    sizes: CellSizes,
+    legacy: bool
+}
+
+impl<'a> Iterator for PartitionIterator<'a> {
+    type Item = Partition<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for node in &mut self.children {
+            if let Token::BeginNode(_, _, _) = node {
+                /* Other devices may live next to legacy partitions, like Linux skip anything with a compatible */
+                if self.legacy && node.get_prop(props::COMPATIBLE).is_some() {
+                    continue
+                }
+                let reg = match node.reg_with(&self.sizes).and_then(|mut reg| reg.next()) {
+                    Some(reg) => reg,
+                    None => continue
+                };
+                return Some(Partition {
+                    node,
+                    offset: reg.address,
+                    size: reg.size,
+                    label: node.get_prop(b"label").and_then(|prop| prop.prop_str()),
+                    read_only: node.get_prop(b"read-only").is_some(),
+                })
+            }
+        }
+        None
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the partitions of this flash node.
+    /// Partitions are the children of a sub-node compatible with "fixed-partitions",
+    /// or for legacy layouts the children of this node with a `reg` and without a `compatible` property.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn partitions(&self) -> PartitionIterator<'a> {
+        let (container, legacy) = match self.into_iter().find(|node| node.is_compatible(b"fixed-partitions")) {
+            Some(partitions) => (partitions, false),
+            None => (*self, true)
+        };
+
+        PartitionIterator {
+            children: container.into_iter(),
+            sizes: CellSizes::of_parent(&container),
+            legacy
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! # Patch sets
+//! Edits recorded while the mutable buffer is not yet available and applied later in one go,
+//! e.g. a boot flow which computes the memory size, MAC address and bootargs before copying the blob.
+//! The edits are stored in fixed storage so no allocator is needed.
+//!
+
+use crate::{DeviceTreeMut, DtPath, EditError};
+
+/// # Patch
+/// A single pending edit, see `PatchSet`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Patch<'a> {
+    /// Overwrite cell `index` of an existing property, see `DeviceTreeMut::set_prop_u32()`
+    SetU32 { path: DtPath<'a>, prop: &'a [u8], index: usize, value: u32 },
+
+    /// Set or add a property, see `DeviceTreeMut::set_prop()`
+    SetBytes { path: DtPath<'a>, prop: &'a [u8], value: &'a [u8] },
+
+    /// Delete a property, see `DeviceTreeMut::delete_prop()`
+    DeleteProp { path: DtPath<'a>, prop: &'a [u8] },
+
+    /// Delete a node and its sub-nodes, see `DeviceTreeMut::delete_node()`
+    DeleteNode { path: DtPath<'a> },
+
+    /// Set `bootargs` of `/chosen` to `value`, without the NUL terminator
+    SetBootargs { value: &'a [u8] },
+}
+
+impl<'a> Patch<'a> {
+
+    /// Apply this edit to `dt`
+    pub fn apply(&self, dt: &mut DeviceTreeMut) -> Result<(), EditError> {
+        match *self {
+            Patch::SetU32 { path, prop, index, value } => dt.set_prop_u32(path.as_bytes(), prop, index, value),
+            Patch::SetBytes { path, prop, value } => dt.set_prop(path.as_bytes(), prop, value),
+            Patch::DeleteProp { path, prop } => dt.delete_prop(path.as_bytes(), prop),
+            Patch::DeleteNode { path } => dt.delete_node(path.as_bytes()),
+            Patch::SetBootargs { value } => dt.set_prop_str(b"/chosen", b"bootargs", value),
+        }
+    }
+}
+
+/// # PatchSet
+/// Up to `N` pending edits applied in the order they were pushed, see `PatchSet::apply()`
+#[derive(Debug, Clone)]
+pub struct PatchSet<'a, const N: usize> {
+    patches: [Option<Patch<'a>>; N],
+    len: usize,
+    stop_on_error: bool,
+}
+
+impl<'a, const N: usize> Default for PatchSet<'a, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> PatchSet<'a, N> {
+
+    /// Create a empty set which applies all edits even if some fail
+    pub fn new() -> Self {
+        PatchSet { patches: [None; N], len: 0, stop_on_error: false }
+    }
+
+    /// Make `apply()` stop and return the error at the first failing edit
+    pub fn stop_on_error(mut self, stop: bool) -> Self {
+        self.stop_on_error = stop;
+        self
+    }
+
+    /// Append `patch`
+    /// Returns the patch back if the set is full
+    pub fn push(&mut self, patch: Patch<'a>) -> Result<(), Patch<'a>> {
+        if self.len == N {
+            return Err(patch)
+        }
+        self.patches[self.len] = Some(patch);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns the number of pending edits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no pending edits
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a iterator over the pending edits in order
+    pub fn iter(&self) -> impl Iterator<Item = &Patch<'a>> {
+        self.patches[..self.len].iter().flatten()
+    }
+
+    /// Apply all edits to `dt` in order, recording the result of each.
+    /// A failed edit leaves the blob as it was before it, the following edits are still applied.
+    /// Returns the first error instead if `stop_on_error(true)` was set,
+    /// in that case the edits before the failing one remain applied.
+    pub fn apply(&self, dt: &mut DeviceTreeMut) -> Result<AppliedReport<N>, EditError> {
+        let mut report = AppliedReport { results: [Ok(()); N], len: self.len };
+        for (result, patch) in report.results.iter_mut().zip(self.iter()) {
+            *result = patch.apply(dt);
+            if let (Err(e), true) = (*result, self.stop_on_error) {
+                return Err(e)
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// # AppliedReport
+/// The result of each edit of a `PatchSet`, in the same order
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AppliedReport<const N: usize> {
+    results: [Result<(), EditError>; N],
+    len: usize,
+}
+
+impl<const N: usize> AppliedReport<N> {
+
+    /// Returns the result of each edit
+    pub fn results(&self) -> &[Result<(), EditError>] {
+        &self.results[..self.len]
+    }
+
+    /// Returns the number of edits which were applied
+    pub fn applied(&self) -> usize {
+        self.results().iter().filter(|result| result.is_ok()).count()
+    }
+
+    /// Returns a iterator over the index and error of each failed edit
+    pub fn failures(&self) -> impl Iterator<Item = (usize, EditError)> + '_ {
+        self.results().iter().enumerate().filter_map(|(i, result)| Some((i, result.err()?)))
+    }
+
+    /// Returns true if all edits were applied
+    pub fn is_ok(&self) -> bool {
+        self.applied() == self.len
+    }
+}
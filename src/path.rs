@@ -0,0 +1,192 @@
+//! # Paths
+//! Parsing of absolute node paths such as `/soc/uart@0`, used by all path lookups.
+//! Paths copied from sources or shell pipelines are normalized: ASCII whitespace around the
+//! path is trimmed and repeated or trailing separators are ignored. Names are compared exactly.
+//!
+//! `Token::get()` takes a path relative to a node, which may end in a property or a cell of one.
+//!
+
+use crate::utils::{dt_hash, fnv1a};
+use crate::{DeviceTree, Token, UnitAddrCompare};
+
+/// # PathError
+/// Errors returned by `DtPath::parse()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PathError {
+    /// The path is empty or only whitespace
+    Empty,
+
+    /// The path does not start with '/'
+    NotAbsolute,
+
+    /// ASCII whitespace inside the path at `offset` in the given bytes
+    Whitespace { offset: usize },
+}
+
+/// # PathLookupError
+/// Errors returned by `DeviceTree::get_node_by_path_verbose()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PathLookupError {
+    /// The path could not be parsed
+    Invalid(PathError),
+
+    /// The component at `start..end` of the given path does not exist.
+    /// `resolved` components before it were found, the last of them is the node at `node_offset`
+    /// in the structure block (the root node if none).
+    NotFound { resolved: usize, node_offset: usize, start: usize, end: usize },
+}
+
+/// # DtPath
+/// A parsed absolute path, borrowing the bytes it was parsed from, see `DtPath::parse()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DtPath<'p> {
+    /* Trimmed, starts with '/' */
+    path: &'p [u8]
+}
+
+impl<'p> DtPath<'p> {
+
+    /// Parse `path`, trimming ASCII whitespace at both ends.
+    /// Returns `PathError::Whitespace` for whitespace between the first and last non-whitespace byte.
+    pub fn parse(path: &'p [u8]) -> Result<DtPath<'p>, PathError> {
+        let start = path.iter().position(|c| !c.is_ascii_whitespace()).ok_or(PathError::Empty)?;
+        let end = path.iter().rposition(|c| !c.is_ascii_whitespace()).map_or(start, |i| i + 1);
+        let trimmed = &path[start..end];
+
+        if trimmed.first() != Some(&b'/') {
+            return Err(PathError::NotAbsolute)
+        }
+        if let Some(i) = trimmed.iter().position(|c| c.is_ascii_whitespace()) {
+            return Err(PathError::Whitespace { offset: start + i })
+        }
+        Ok(DtPath { path: trimmed })
+    }
+
+    /// Returns the path without surrounding whitespace, separators are not collapsed
+    pub fn as_bytes(&self) -> &'p [u8] {
+        self.path
+    }
+
+    /// Returns a iterator over the node names of the path, empty for the root node
+    pub fn components(&self) -> impl DoubleEndedIterator<Item = &'p [u8]> {
+        self.path.split(|c| *c == b'/').filter(|component| !component.is_empty())
+    }
+
+    /// Returns true if this is the path of the root node, e.g. `/` or `//`
+    pub fn is_root(&self) -> bool {
+        self.components().next().is_none()
+    }
+
+    /// Returns the hash of the path with separators collapsed, e.g. `/soc//uart@0/` hashes like `/soc/uart@0`.
+    /// It is `utils::dt_hash()` of the collapsed path, stable across releases.
+    pub fn hash(&self) -> u32 {
+        if self.is_root() {
+            return dt_hash(b"/")
+        }
+        self.components().fold(dt_hash(b""), |hash, component| fnv1a(fnv1a(hash, b"/"), component))
+    }
+
+    /// Split off the last component, e.g. `/soc/uart@0` into `/soc` and `uart@0`
+    /// Returns None for the root node
+    pub fn split_last(&self) -> Option<(DtPath<'p>, &'p [u8])> {
+        let name = self.components().next_back()?;
+        let split = name.as_ptr() as usize - self.path.as_ptr() as usize;
+        Some((DtPath { path: &self.path[..split] }, name))
+    }
+}
+
+/// # DtValue
+/// What a relative path resolves to, see `Token::get()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DtValue<'a> {
+    Node(Token<'a>),
+    Prop(Token<'a>),
+    /// A cell of a property
+    Cell(u32),
+}
+
+impl<'a> Token<'a> {
+
+    /// Find a node, property or cell by a path relative to this node, e.g. `child-node1/a-string-property`
+    /// or `clocks/0` for the first cell of `clocks`. Repeated and trailing separators are ignored,
+    /// an empty path is this node.
+    ///
+    /// The last component is looked up as a property before a child node like in `DeviceTree::get()`,
+    /// any other as a child node. A component which is not a child node may be a property followed by
+    /// the decimal index of a cell as the last component.
+    /// Returns None if token is not a node, a component does not exist or the index is past the last complete cell.
+    ///
+    pub fn get(&self, rel_path: &[u8]) -> Option<DtValue<'a>> {
+        if !matches!(self, Token::BeginNode(_, _, _)) {
+            return None
+        }
+
+        let mut components = rel_path.split(|c| *c == b'/').filter(|component| !component.is_empty()).peekable();
+        let mut node = *self;
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                return node.get_prop(component).map(DtValue::Prop)
+                    .or_else(|| node.get_node(component).map(DtValue::Node))
+            }
+
+            node = match node.get_node(component) {
+                Some(child) => child,
+                None => {
+                    let index = components.next()?;
+                    if components.next().is_some() || !index.iter().all(u8::is_ascii_digit) {
+                        return None
+                    }
+                    let index = core::str::from_utf8(index).ok()?.parse().ok()?;
+                    return node.get_prop(component)?.prop_u32(index).map(DtValue::Cell)
+                }
+            };
+        }
+        Some(DtValue::Node(node))
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Find the node at `path`
+    /// Returns None if there is no such node.
+    ///
+    pub fn get_node_by_dt_path(&self, path: &DtPath) -> Option<Token<'_>> {
+        path.components().try_fold(self.root(), |node, component| node.get_node(component))
+    }
+
+    /// Same as `get_node_by_dt_path()` with node names compared as given by `compare`, see `Token::get_node_with()`
+    ///
+    pub fn get_node_by_dt_path_with(&self, path: &DtPath, compare: UnitAddrCompare) -> Option<Token<'_>> {
+        path.components().try_fold(self.root(), |node, component| node.get_node_with(component, compare))
+    }
+
+    /// Same as `get_node_by_path()` with node names compared as given by `compare`, e.g. to find
+    /// `/soc/uart@1000` by `/soc/uart@0x1000` with `UnitAddrCompare::NumericHex`
+    ///
+    pub fn get_node_by_path_with(&self, path: &[u8], compare: UnitAddrCompare) -> Option<Token<'_>> {
+        self.get_node_by_dt_path_with(&DtPath::parse(path).ok()?, compare)
+    }
+
+    /// Find the node at `path` like `get_node_by_path()`, reporting how far the lookup got if it fails
+    ///
+    pub fn get_node_by_path_verbose(&self, path: &[u8]) -> Result<Token<'_>, PathLookupError> {
+        let components = DtPath::parse(path).map_err(PathLookupError::Invalid)?.components();
+
+        let mut node = self.root();
+        for (resolved, component) in components.enumerate() {
+            node = match node.get_node(component) {
+                Some(child) => child,
+                None => {
+                    let start = component.as_ptr() as usize - path.as_ptr() as usize;
+                    return Err(PathLookupError::NotFound {
+                        resolved,
+                        node_offset: node.node_offset().unwrap_or_default(),
+                        start,
+                        end: start + component.len()
+                    })
+                }
+            };
+        }
+        Ok(node)
+    }
+}
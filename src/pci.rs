@@ -0,0 +1,111 @@
+//! # PCI
+//! Decoding of PCI host bridge `ranges` with 3-cell PCI addresses and `bus-range`.
+//!
+
+use crate::address::read_cells;
+use crate::{props, CellSizes, Token};
+
+/// # PciSpace
+/// Address space of a PCI address, the `ss` bits of the first address cell
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PciSpace {
+    Config,
+    Io,
+    Memory32,
+    Memory64,
+}
+
+/// # PciRange
+/// One entry of the `ranges` property of a PCI host bridge, see `Token::pci_ranges()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PciRange {
+    pub space: PciSpace,
+    pub prefetchable: bool,
+    /// Address on the PCI bus
+    pub pci_address: u64,
+    /// CPU address, translated through the `ranges` of the ancestors of the host bridge.
+    /// None if the address is not mapped.
+    pub cpu_address: Option<u64>,
+    pub size: u64,
+}
+
+/// Number of cells of a PCI address
+const PCI_ADDRESS_CELLS: usize = 3;
+
+/// # PciRangesIterator
+/// Iterates over the entries of the `ranges` property of a PCI host bridge, see `Token::pci_ranges()`.
+/// An incomplete entry at the end is ignored.
+pub struct PciRangesIterator<'a> {
+    node: Token<'a>,
+    val: &'a [u8],
+    parent_address_cells: u32,
+    size_cells: u32,
+    n: usize
+}
+
+impl<'a> Iterator for PciRangesIterator<'a> {
+    type Item = PciRange;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut n = self.n;
+        let hi = read_cells(self.val, n, 1)? as u32;
+        let pci_address = read_cells(self.val, n + 1, 2)?;
+        n += PCI_ADDRESS_CELLS;
+        let parent_address = read_cells(self.val, n, self.parent_address_cells)?;
+        n += self.parent_address_cells as usize;
+        let size = read_cells(self.val, n, self.size_cells)?;
+        self.n = n + self.size_cells as usize;
+
+        Some(PciRange {
+            space: match (hi >> 24) & 0x3 {
+                0 => PciSpace::Config,
+                1 => PciSpace::Io,
+                2 => PciSpace::Memory32,
+                _ => PciSpace::Memory64
+            },
+            prefetchable: hi & (1 << 30) != 0,
+            pci_address,
+            cpu_address: self.node.translate_address(parent_address),
+            size,
+        })
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the entries of the `ranges` property of this PCI host bridge.
+    /// Returns None if there is no `ranges` property, the node has no parent, `#address-cells`
+    /// of this node is not 3, or the other cell sizes are larger than 2.
+    ///
+    pub fn pci_ranges(&self) -> Option<PciRangesIterator<'a>> {
+        let sizes = CellSizes::of_parent(self);
+        let parent = CellSizes::of_parent(&self.parent()?);
+        if sizes.address_cells as usize != PCI_ADDRESS_CELLS || sizes.size_cells > 2 || parent.address_cells > 2 {
+            return None
+        }
+
+        match self.get_prop(props::RANGES)? {
+            Token::Property(_, _, val) => Some(PciRangesIterator {
+                node: *self,
+                val,
+                parent_address_cells: parent.address_cells,
+                size_cells: sizes.size_cells,
+                n: 0
+            }),
+            _ => None
+        }
+    }
+
+    /// Returns the first and last bus number from the `bus-range` property
+    /// Returns None if there is no `bus-range` property or a bus number is larger than 255.
+    ///
+    pub fn pci_bus_range(&self) -> Option<(u8, u8)> {
+        let prop = self.get_prop(b"bus-range")?;
+        let first = prop.prop_u32(0)?;
+        let last = prop.prop_u32(1)?;
+        if first > u8::MAX as u32 || last > u8::MAX as u32 {
+            return None
+        }
+        Some((first as u8, last as u8))
+    }
+}
@@ -0,0 +1,135 @@
+//! # Phandle arguments
+//! Decoding of phandle + argument lists such as `gpios = <&gpio0 5 1>, <&gpio1 2 0>`,
+//! where the number of arguments is given by a `#...-cells` property of the referenced node,
+//! and the reverse: finding the properties referring to a phandle.
+//!
+
+use crate::{compat, props, DeviceTree, Token};
+
+/// Maximum number of argument cells of one specifier
+pub const MAX_PHANDLE_ARGS: usize = 8;
+
+/// # PhandleArgs
+/// One specifier of a phandle + arguments list, see `Token::phandle_args()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PhandleArgs<'a> {
+    /// The node referenced by the phandle
+    pub provider: Token<'a>,
+    args: [u32; MAX_PHANDLE_ARGS],
+    count: usize
+}
+
+impl<'a> PhandleArgs<'a> {
+    /// Create a specifier from the first `count` cells of `args`
+    pub(crate) fn new(provider: Token<'a>, args: [u32; MAX_PHANDLE_ARGS], count: usize) -> Self {
+        PhandleArgs { provider, args, count }
+    }
+
+    /// Returns the argument cells
+    pub fn args(&self) -> &[u32] {
+        &self.args[..self.count]
+    }
+
+    /// Returns argument `n`
+    /// Returns None if out of range
+    pub fn arg(&self, n: usize) -> Option<u32> {
+        self.args().get(n).copied()
+    }
+}
+
+/// # PhandleArgsIterator
+/// Iterates over the specifiers of a phandle + arguments list, see `Token::phandle_args()`.
+/// Empty entries (a phandle of 0) are skipped. Iteration stops at a phandle which does not exist,
+/// a provider without the cells property, more than `MAX_PHANDLE_ARGS` arguments or a truncated entry.
+/// A `gpio-controller` without `#gpio-cells` takes `compat::DEFAULT_GPIO_CELLS`.
+pub struct PhandleArgsIterator<'a, 'b> {
+    prop: Token<'a>,
+    cells_name: &'b [u8],
+    n: usize
+}
+
+/// Returns the number of argument cells of `provider` from its `cells_name` property,
+/// with the legacy default of `compat::gpio_cells()` for `#gpio-cells`
+fn provider_cells(provider: &Token, cells_name: &[u8]) -> Option<u32> {
+    if cells_name == props::GPIO_CELLS {
+        return compat::gpio_cells(provider).map(|(cells, _)| cells)
+    }
+    provider.get_prop(cells_name)?.prop_u32(0)
+}
+
+impl<'a, 'b> Iterator for PhandleArgsIterator<'a, 'b> {
+    type Item = PhandleArgs<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let dt = match self.prop {
+            Token::Property(dt, _, _) => dt,
+            _ => return None
+        };
+
+        loop {
+            let phandle = self.prop.prop_u32(self.n)?;
+            self.n += 1;
+            if phandle == 0 {
+                continue
+            }
+
+            let provider = dt.get_phandle(phandle)?;
+            let count = provider_cells(&provider, self.cells_name)? as usize;
+            if count > MAX_PHANDLE_ARGS {
+                return None
+            }
+
+            let mut args = [0; MAX_PHANDLE_ARGS];
+            for (i, arg) in args.iter_mut().take(count).enumerate() {
+                *arg = self.prop.prop_u32(self.n + i)?;
+            }
+            self.n += count;
+            return Some(PhandleArgs { provider, args, count })
+        }
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the specifiers of this phandle + arguments property
+    /// where the number of arguments is given by the property `cells_name` (e.g. `#gpio-cells`)
+    /// of each referenced node.
+    /// The iterator is empty if token is not a property.
+    ///
+    pub fn phandle_args<'b>(&self, cells_name: &'b [u8]) -> PhandleArgsIterator<'a, 'b> {
+        PhandleArgsIterator { prop: *self, cells_name, n: 0 }
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a iterator over the references to `phandle` as (referring node, property, cell index)
+    /// for every cell equal to `phandle` in properties which conventionally hold phandles,
+    /// see `compat::holds_phandles()`.
+    ///
+    /// Cells are compared without decoding the specifiers, an argument cell equal to `phandle` is
+    /// reported as well. The iterator is empty for the reserved phandles 0 and 0xffffffff.
+    ///
+    pub fn phandle_references(&self, phandle: u32) -> impl Iterator<Item = (Token<'_>, Token<'_>, usize)> + '_ {
+        self.references(phandle, |prop| compat::holds_phandles(prop.name()))
+    }
+
+    /// Same as `phandle_references()` but searches every property whose length is a multiple of 4,
+    /// except `phandle` and `linux,phandle`, for trees using properties not in the conventional list
+    pub fn phandle_references_raw(&self, phandle: u32) -> impl Iterator<Item = (Token<'_>, Token<'_>, usize)> + '_ {
+        self.references(phandle, |prop| {
+            !matches!(prop.name(), props::PHANDLE | props::LINUX_PHANDLE) && prop.value().is_some_and(|v| v.len().is_multiple_of(4))
+        })
+    }
+
+    fn references(&self, phandle: u32, filter: impl Fn(&Token) -> bool + 'a)
+        -> impl Iterator<Item = (Token<'_>, Token<'_>, usize)> + '_ {
+        let valid = phandle != 0 && phandle != u32::MAX;
+        self.all_props()
+            .filter(move |(_, prop)| valid && filter(prop))
+            .flat_map(move |(node, prop)| {
+                let cells = prop.value().map_or(0, |v| v.len() / 4);
+                (0..cells).filter(move |n| prop.prop_u32(*n) == Some(phandle)).map(move |n| (node, prop, n))
+            })
+    }
+}
@@ -0,0 +1,49 @@
+//! # Platform devices
+//! Enumeration of the memory mapped devices on `simple-bus` nodes,
+//! the loop at the heart of a driver model.
+//!
+
+use crate::{props, DeviceTree, PhandleArgs, Token};
+
+/// # PlatformDevice
+/// Summary of a device on a `simple-bus`, see `DeviceTree::platform_devices()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PlatformDevice<'a> {
+    pub node: Token<'a>,
+    /// First string of `compatible`
+    pub compatible: Option<&'a [u8]>,
+    /// First `reg` address translated to a CPU address, None if missing or not mapped
+    pub base: Option<u64>,
+    /// First `reg` size
+    pub size: Option<u64>,
+    /// First interrupt specifier
+    pub interrupt: Option<PhandleArgs<'a>>,
+}
+
+impl<'a> PlatformDevice<'a> {
+    /// Assemble the summary of `node`
+    pub fn new(node: Token<'a>) -> PlatformDevice<'a> {
+        let reg = node.reg().and_then(|mut reg| reg.next());
+        PlatformDevice {
+            node,
+            compatible: node.get_prop(props::COMPATIBLE).and_then(|prop| prop.prop_str()),
+            base: reg.and_then(|reg| node.translate_address(reg.address)),
+            size: reg.map(|reg| reg.size),
+            interrupt: node.interrupts().next(),
+        }
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a iterator over the enabled children of all enabled `simple-bus` nodes, nested buses included.
+    /// A nested bus is itself yielded as a device of its parent bus.
+    ///
+    pub fn platform_devices(&self) -> impl Iterator<Item = PlatformDevice<'_>> {
+        self.nodes()
+            .filter(|bus| bus.is_compatible(b"simple-bus") && bus.is_enabled())
+            .flat_map(|bus| bus.into_iter())
+            .filter(|node| matches!(node, Token::BeginNode(_, _, _)) && node.is_enabled())
+            .map(PlatformDevice::new)
+    }
+}
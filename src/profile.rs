@@ -0,0 +1,119 @@
+//! # Validation profiles
+//! Which checks `DeviceTree::validate_with()` and `DeviceTree::lint()` run, from "won't crash the parser"
+//! to every lint. Start from a preset and toggle single checks, e.g.
+//! `ValidationProfile::standard().without(Check::PropertyOrder)`.
+//!
+//! | Check                 | Reports                                             | Minimal | Standard | Pedantic |
+//! |-----------------------|-----------------------------------------------------|---------|----------|----------|
+//! | `Structure`           | `ValidationError`s of the structure block            | x       | x        | x        |
+//! | `Reservations`        | `ValidationError::UnterminatedReservations`          |         | x        | x        |
+//! | `PropertyOrder`       | `Warning::PropertyAfterNode`                         |         | x        | x        |
+//! | `OversizeProperty`    | `Warning::OversizeProperty`                          |         | x        | x        |
+//! | `CellLength`          | `Warning::TrailingBytes`                             |         |          | x        |
+//! | `NopRuns`             | `Warning::NopRun`                                    |         |          | x        |
+//! | `UnreferencedStrings` | `Warning::UnreferencedString`                        |         |          | x        |
+//! | `Deprecated`          | `Warning::Deprecated`                                |         |          | x        |
+//! | `NameCharset`         | `Warning::NameCharset`                               |         |          | x        |
+//!
+//! Minimal is enough to walk the tree safely, Standard is what a bootloader passing the blob on
+//! should check and Pedantic is meant for CI. `DeviceTree::validate()` runs the structural checks
+//! of Standard, `DeviceTree::warnings()` the lints of Pedantic except `NameCharset`.
+//!
+
+/// # Check
+/// Identifier of a check, see the module documentation for what each reports
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Check {
+    Structure,
+    Reservations,
+    PropertyOrder,
+    OversizeProperty,
+    CellLength,
+    NopRuns,
+    UnreferencedStrings,
+    Deprecated,
+    NameCharset,
+}
+
+impl Check {
+    /// Every check, in the order they are listed in the module documentation
+    pub const ALL: [Check; 9] = [
+        Check::Structure,
+        Check::Reservations,
+        Check::PropertyOrder,
+        Check::OversizeProperty,
+        Check::CellLength,
+        Check::NopRuns,
+        Check::UnreferencedStrings,
+        Check::Deprecated,
+        Check::NameCharset,
+    ];
+
+    const fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// # ValidationProfile
+/// A set of checks, see the module documentation
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ValidationProfile {
+    checks: u16
+}
+
+impl ValidationProfile {
+
+    /// No checks
+    pub const fn empty() -> Self {
+        ValidationProfile { checks: 0 }
+    }
+
+    /// Only what is needed to walk the tree without errors
+    pub const fn minimal() -> Self {
+        Self::empty().with(Check::Structure)
+    }
+
+    /// Structural correctness and the lints which change what lookups find
+    pub const fn standard() -> Self {
+        Self::minimal()
+            .with(Check::Reservations)
+            .with(Check::PropertyOrder)
+            .with(Check::OversizeProperty)
+    }
+
+    /// Every check
+    pub const fn pedantic() -> Self {
+        Self::standard()
+            .with(Check::CellLength)
+            .with(Check::NopRuns)
+            .with(Check::UnreferencedStrings)
+            .with(Check::Deprecated)
+            .with(Check::NameCharset)
+    }
+
+    /// Same profile with `check` enabled
+    pub const fn with(self, check: Check) -> Self {
+        ValidationProfile { checks: self.checks | check.bit() }
+    }
+
+    /// Same profile with `check` disabled
+    pub const fn without(self, check: Check) -> Self {
+        ValidationProfile { checks: self.checks & !check.bit() }
+    }
+
+    /// Returns true if `check` is enabled
+    pub const fn contains(&self, check: Check) -> bool {
+        self.checks & check.bit() != 0
+    }
+
+    /// Returns the enabled checks
+    pub fn checks(&self) -> impl Iterator<Item = Check> + '_ {
+        Check::ALL.iter().copied().filter(move |check| self.contains(*check))
+    }
+}
+
+impl Default for ValidationProfile {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
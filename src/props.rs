@@ -0,0 +1,227 @@
+//! # Properties
+//! Names of standard properties, to avoid typos in byte literals, and a snapshot
+//! of the properties of a node for repeated lookups, e.g. while probing a driver.
+//!
+//! ```
+//! use static_dt_rs::{props, DeviceTree, StdProp};
+//!
+//! let dt = DeviceTree::parse(include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.dtb"))).unwrap();
+//! let node = dt.get_node_by_path(b"/node1/child-node1").unwrap();
+//! assert_eq!(node.get_prop(props::PHANDLE), node.get_std_prop(StdProp::Phandle));
+//! ```
+//!
+
+use crate::{DeviceTree, Token};
+
+/// `compatible`
+pub const COMPATIBLE: &[u8] = b"compatible";
+/// `model`
+pub const MODEL: &[u8] = b"model";
+/// `reg`
+pub const REG: &[u8] = b"reg";
+/// `ranges`
+pub const RANGES: &[u8] = b"ranges";
+/// `status`
+pub const STATUS: &[u8] = b"status";
+/// `phandle`
+pub const PHANDLE: &[u8] = b"phandle";
+/// `linux,phandle`, the old name of `phandle`
+pub const LINUX_PHANDLE: &[u8] = b"linux,phandle";
+/// `#address-cells`
+pub const ADDRESS_CELLS: &[u8] = b"#address-cells";
+/// `#size-cells`
+pub const SIZE_CELLS: &[u8] = b"#size-cells";
+/// `interrupts`
+pub const INTERRUPTS: &[u8] = b"interrupts";
+/// `interrupts-extended`
+pub const INTERRUPTS_EXTENDED: &[u8] = b"interrupts-extended";
+/// `interrupt-parent`
+pub const INTERRUPT_PARENT: &[u8] = b"interrupt-parent";
+/// `#interrupt-cells`
+pub const INTERRUPT_CELLS: &[u8] = b"#interrupt-cells";
+/// `device_type`
+pub const DEVICE_TYPE: &[u8] = b"device_type";
+/// `#gpio-cells`
+pub const GPIO_CELLS: &[u8] = b"#gpio-cells";
+/// `gpio-controller`
+pub const GPIO_CONTROLLER: &[u8] = b"gpio-controller";
+
+/// `bootargs` of `/chosen`
+pub const BOOTARGS: &[u8] = b"bootargs";
+/// `stdout-path` of `/chosen`
+pub const STDOUT_PATH: &[u8] = b"stdout-path";
+/// `linux,stdout-path` of `/chosen`, the old name of `stdout-path`
+pub const LINUX_STDOUT_PATH: &[u8] = b"linux,stdout-path";
+/// `linux,initrd-start` of `/chosen`
+pub const INITRD_START: &[u8] = b"linux,initrd-start";
+/// `linux,initrd-end` of `/chosen`
+pub const INITRD_END: &[u8] = b"linux,initrd-end";
+/// `kaslr-seed` of `/chosen`
+pub const KASLR_SEED: &[u8] = b"kaslr-seed";
+/// `rng-seed` of `/chosen`
+pub const RNG_SEED: &[u8] = b"rng-seed";
+/// `linux,usable-memory-range` of `/chosen`, the memory a crash kernel may use
+pub const USABLE_MEMORY_RANGE: &[u8] = b"linux,usable-memory-range";
+/// `linux,elfcorehdr` of `/chosen`, the ELF core header of the crashed kernel
+pub const ELFCOREHDR: &[u8] = b"linux,elfcorehdr";
+
+/// # StdProp
+/// The standard properties with constants in this module, for exhaustive matching
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum StdProp {
+    Compatible,
+    Model,
+    Reg,
+    Ranges,
+    Status,
+    Phandle,
+    LinuxPhandle,
+    AddressCells,
+    SizeCells,
+    Interrupts,
+    InterruptsExtended,
+    InterruptParent,
+    InterruptCells,
+    DeviceType,
+    Bootargs,
+    StdoutPath,
+    LinuxStdoutPath,
+    InitrdStart,
+    InitrdEnd,
+    KaslrSeed,
+    RngSeed,
+}
+
+impl StdProp {
+    /// All standard properties
+    pub const ALL: [StdProp; 21] = [
+        StdProp::Compatible, StdProp::Model, StdProp::Reg, StdProp::Ranges, StdProp::Status,
+        StdProp::Phandle, StdProp::LinuxPhandle, StdProp::AddressCells, StdProp::SizeCells,
+        StdProp::Interrupts, StdProp::InterruptsExtended, StdProp::InterruptParent, StdProp::InterruptCells,
+        StdProp::DeviceType, StdProp::Bootargs, StdProp::StdoutPath, StdProp::LinuxStdoutPath,
+        StdProp::InitrdStart, StdProp::InitrdEnd, StdProp::KaslrSeed, StdProp::RngSeed,
+    ];
+
+    /// Returns the property name
+    pub const fn name(&self) -> &'static [u8] {
+        match self {
+            StdProp::Compatible => COMPATIBLE,
+            StdProp::Model => MODEL,
+            StdProp::Reg => REG,
+            StdProp::Ranges => RANGES,
+            StdProp::Status => STATUS,
+            StdProp::Phandle => PHANDLE,
+            StdProp::LinuxPhandle => LINUX_PHANDLE,
+            StdProp::AddressCells => ADDRESS_CELLS,
+            StdProp::SizeCells => SIZE_CELLS,
+            StdProp::Interrupts => INTERRUPTS,
+            StdProp::InterruptsExtended => INTERRUPTS_EXTENDED,
+            StdProp::InterruptParent => INTERRUPT_PARENT,
+            StdProp::InterruptCells => INTERRUPT_CELLS,
+            StdProp::DeviceType => DEVICE_TYPE,
+            StdProp::Bootargs => BOOTARGS,
+            StdProp::StdoutPath => STDOUT_PATH,
+            StdProp::LinuxStdoutPath => LINUX_STDOUT_PATH,
+            StdProp::InitrdStart => INITRD_START,
+            StdProp::InitrdEnd => INITRD_END,
+            StdProp::KaslrSeed => KASLR_SEED,
+            StdProp::RngSeed => RNG_SEED,
+        }
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Find the standard property `p` in this node, same as `get_prop(p.name())`
+    /// Returns None if there is no matching property.
+    ///
+    pub fn get_std_prop(&self, p: StdProp) -> Option<Token<'a>> {
+        self.get_prop(p.name())
+    }
+}
+
+/// # NodeProps
+/// Up to `N` (name, value) pairs of a node collected in one pass, see `Token::props_cached()`.
+/// Lookups compare names without re-walking the token stream.
+/// If the node has more than `N` properties, names not found in the snapshot
+/// are looked up with `Token::get_prop()` instead.
+#[derive(Debug, Clone)]
+pub struct NodeProps<'a, const N: usize> {
+    dt: &'a DeviceTree<'a>,
+    offset: usize,
+    name: &'a [u8],
+    props: [(&'a [u8], &'a [u8]); N],
+    len: usize,
+    complete: bool,
+}
+
+impl<'a, const N: usize> NodeProps<'a, N> {
+
+    /// Snapshot the properties of `node`
+    /// Returns None if not a node
+    pub fn new(node: &Token<'a>) -> Option<Self> {
+        let (dt, offset, name) = match *node {
+            Token::BeginNode(dt, offset, name) => (dt, offset, name),
+            _ => return None
+        };
+
+        let mut cache = NodeProps { dt, offset, name, props: [(&[][..], &[][..]); N], len: 0, complete: true };
+        for tok in node.props() {
+            if let Token::Property(_, name, val) = tok {
+                if cache.len == N {
+                    cache.complete = false;
+                    break;
+                }
+                cache.props[cache.len] = (name, val);
+                cache.len += 1;
+            }
+        }
+        Some(cache)
+    }
+
+    /// Returns the node of this snapshot
+    pub fn node(&self) -> Token<'a> {
+        Token::BeginNode(self.dt, self.offset, self.name)
+    }
+
+    /// Returns the value of the property with `name`
+    /// Returns None if there is no matching property.
+    pub fn get(&self, name: &[u8]) -> Option<&'a [u8]> {
+        match self.get_prop(name)? {
+            Token::Property(_, _, val) => Some(val),
+            _ => None
+        }
+    }
+
+    /// Find a property with `name`, same as `Token::get_prop()` on the node
+    /// Returns None if there is no matching property.
+    #[inline]
+    pub fn get_prop<S: AsRef<[u8]>>(&self, name: S) -> Option<Token<'a>> {
+        self.get_prop_bytes(name.as_ref())
+    }
+
+    /* Non-generic core of get_prop(), see "Names" in the crate documentation */
+    fn get_prop_bytes(&self, name: &[u8]) -> Option<Token<'a>> {
+        for (s, val) in &self.props[..self.len] {
+            if name.eq(*s) { return Some(Token::Property(self.dt, s, val)) }
+        }
+
+        /* Not all properties fit, search the rest in the tree */
+        if self.complete { None } else { self.node().get_prop(name) }
+    }
+
+    /// Returns the number of properties in the snapshot
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the snapshot contains no properties
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns true if all properties of the node fit in the snapshot
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
@@ -0,0 +1,93 @@
+//! # PSCI
+//! Decoding of the ARM Power State Coordination Interface firmware node.
+//!
+
+use crate::{DeviceTree, Token};
+
+/// # PsciMethod
+/// Conduit used to call the PSCI firmware, from the `method` property
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PsciMethod {
+    /// Secure monitor call, `method = "smc"`
+    Smc,
+    /// Hypervisor call, `method = "hvc"`
+    Hvc,
+}
+
+/// # PsciVersion
+/// Lowest PSCI version supported by the firmware, from the most recent compatible string of the node
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PsciVersion {
+    /// "arm,psci", function ids must be given by the node
+    V0_1,
+    /// "arm,psci-0.2"
+    V0_2,
+    /// "arm,psci-1.0"
+    V1_0,
+}
+
+/// Compatible strings of the PSCI node, most recent first
+const PSCI_COMPATIBLE: [(&[u8], PsciVersion); 3] = [
+    (b"arm,psci-1.0", PsciVersion::V1_0),
+    (b"arm,psci-0.2", PsciVersion::V0_2),
+    (b"arm,psci", PsciVersion::V0_1),
+];
+
+/// # PsciInfo
+/// The PSCI firmware node, see `DeviceTree::psci()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PsciInfo<'a> {
+    /// The PSCI node
+    pub node: Token<'a>,
+    pub version: PsciVersion,
+    pub method: PsciMethod,
+    /// Function id of CPU_SUSPEND, `cpu_suspend` property
+    pub cpu_suspend: Option<u32>,
+    /// Function id of CPU_OFF, `cpu_off` property
+    pub cpu_off: Option<u32>,
+    /// Function id of CPU_ON, `cpu_on` property
+    pub cpu_on: Option<u32>,
+    /// Function id of MIGRATE, `migrate` property
+    pub migrate: Option<u32>,
+}
+
+impl<'a> PsciInfo<'a> {
+
+    /// Decode a PSCI node.
+    /// Returns None if `node` is not compatible with PSCI or its `method` is missing or unknown.
+    ///
+    pub fn from_node(node: &Token<'a>) -> Option<PsciInfo<'a>> {
+        let version = PSCI_COMPATIBLE.iter()
+            .find(|(compat, _)| node.is_compatible(compat))
+            .map(|(_, version)| *version)?;
+
+        let method = match node.get_prop(b"method")?.prop_str()? {
+            b"smc" => PsciMethod::Smc,
+            b"hvc" => PsciMethod::Hvc,
+            _ => return None
+        };
+
+        let function_id = |name: &[u8]| node.get_prop(name).and_then(|prop| prop.prop_u32(0));
+        Some(PsciInfo {
+            node: *node,
+            version,
+            method,
+            cpu_suspend: function_id(b"cpu_suspend"),
+            cpu_off: function_id(b"cpu_off"),
+            cpu_on: function_id(b"cpu_on"),
+            migrate: function_id(b"migrate"),
+        })
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Find the PSCI node by its compatible string and decode it, see `PsciInfo::from_node()`.
+    /// Returns None if there is no PSCI node or it is unusable.
+    ///
+    pub fn psci(&self) -> Option<PsciInfo<'_>> {
+        PSCI_COMPATIBLE.iter()
+            .find_map(|(compat, _)| self.find_compatible(compat))
+            .and_then(|node| PsciInfo::from_node(&node))
+    }
+}
@@ -0,0 +1,104 @@
+//! # Raw
+//! Constants and header layout of the flattened devicetree format,
+//! for writing specialized scanners or interfacing with other tools.
+//!
+
+use crate::utils::read_fdt_u32;
+
+/// Value of the `magic` header field
+pub const FDT_MAGIC: u32 = 0xD00DFEED;
+
+/// Token marking the beginning of a node
+pub const FDT_BEGIN_NODE: u32 = 0x1;
+/// Token marking the end of a node
+pub const FDT_END_NODE: u32 = 0x2;
+/// Token marking a property
+pub const FDT_PROP: u32 = 0x3;
+/// Token which should be ignored
+pub const FDT_NOP: u32 = 0x4;
+/// Token marking the end of the structure block
+pub const FDT_END: u32 = 0x9;
+
+/// Size in bytes of a token id, tokens are aligned to this
+pub const FDT_TAGSIZE: usize = 4;
+/// Alignment of the structure block and all tokens in it
+pub const FDT_STRUCT_ALIGN: usize = 4;
+/// Alignment of the memory reservation block
+pub const FDT_RSVMAP_ALIGN: usize = 8;
+
+/// Offset of the `magic` header field
+pub const OFF_MAGIC: usize = 0;
+/// Offset of the `totalsize` header field
+pub const OFF_TOTALSIZE: usize = 4;
+/// Offset of the `off_dt_struct` header field
+pub const OFF_DT_STRUCT: usize = 8;
+/// Offset of the `off_dt_strings` header field
+pub const OFF_DT_STRINGS: usize = 12;
+/// Offset of the `off_mem_rsvmap` header field
+pub const OFF_MEM_RSVMAP: usize = 16;
+/// Offset of the `version` header field
+pub const OFF_VERSION: usize = 20;
+/// Offset of the `last_comp_version` header field
+pub const OFF_LAST_COMP_VERSION: usize = 24;
+/// Offset of the `boot_cpuid_phys` header field
+pub const OFF_BOOT_CPUID_PHYS: usize = 28;
+/// Offset of the `size_dt_strings` header field
+pub const OFF_SIZE_DT_STRINGS: usize = 32;
+/// Offset of the `size_dt_struct` header field
+pub const OFF_SIZE_DT_STRUCT: usize = 36;
+
+/// Size in bytes of a version 17 header
+pub const HEADER_SIZE: usize = 40;
+
+/// # Header
+/// All fields of a devicetree header, read at once.
+/// The fields are not validated, see `check_header()` for that.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub magic: u32,
+    pub totalsize: u32,
+    pub off_dt_struct: u32,
+    pub off_dt_strings: u32,
+    pub off_mem_rsvmap: u32,
+    pub version: u32,
+    pub last_comp_version: u32,
+    pub boot_cpuid_phys: u32,
+    pub size_dt_strings: u32,
+    pub size_dt_struct: u32,
+}
+
+impl Header {
+    /// Read all header fields from the start of `fdt`.
+    /// Returns None if `fdt` is shorter than a header.
+    pub const fn parse(fdt: &[u8]) -> Option<Header> {
+        if fdt.len() < HEADER_SIZE {
+            return None
+        }
+
+        Some(Header {
+            magic: read_fdt_u32(fdt, OFF_MAGIC),
+            totalsize: read_fdt_u32(fdt, OFF_TOTALSIZE),
+            off_dt_struct: read_fdt_u32(fdt, OFF_DT_STRUCT),
+            off_dt_strings: read_fdt_u32(fdt, OFF_DT_STRINGS),
+            off_mem_rsvmap: read_fdt_u32(fdt, OFF_MEM_RSVMAP),
+            version: read_fdt_u32(fdt, OFF_VERSION),
+            last_comp_version: read_fdt_u32(fdt, OFF_LAST_COMP_VERSION),
+            boot_cpuid_phys: read_fdt_u32(fdt, OFF_BOOT_CPUID_PHYS),
+            size_dt_strings: read_fdt_u32(fdt, OFF_SIZE_DT_STRINGS),
+            size_dt_struct: read_fdt_u32(fdt, OFF_SIZE_DT_STRUCT),
+        })
+    }
+
+    /// Size of the structure block.
+    /// `size_dt_struct` only exists since version 17, before that the block is assumed to
+    /// end at the strings block if it follows, or else at `totalsize`.
+    pub const fn struct_size(&self) -> u32 {
+        if self.version >= 17 {
+            self.size_dt_struct
+        } else if self.off_dt_strings > self.off_dt_struct {
+            self.off_dt_strings - self.off_dt_struct
+        } else {
+            self.totalsize.saturating_sub(self.off_dt_struct)
+        }
+    }
+}
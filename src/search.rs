@@ -0,0 +1,152 @@
+//! # Search
+//! Scans over the nodes of a tree, either for a common `NodePredicate` within a subtree
+//! or with a stateful closure. The closure receives tokens which borrow the tree
+//! for `'a`, so it may keep them (e.g. the best match so far) in variables of the caller
+//! while also holding `&mut` references to other state.
+//!
+//! ```
+//! use static_dt_rs::{DeviceTree, Token};
+//!
+//! let dt = DeviceTree::parse(include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bus.dtb"))).unwrap();
+//! let bus = dt.get_node_by_path(b"/bus@80000000").unwrap();
+//!
+//! /* Node with the largest total reg size, scanning every child */
+//! let mut best: Option<(u64, Token)> = None;
+//! let mut scanned = 0;
+//! bus.find_child_where(|node| {
+//!     scanned += 1;
+//!     let size = node.reg().map_or(0, |reg| reg.map(|entry| entry.size).sum());
+//!     if best.map_or(true, |(max, _)| size > max) {
+//!         best = Some((size, *node));
+//!     }
+//!     false
+//! });
+//! let (size, node) = best.unwrap();
+//! assert_eq!(node.name(), b"device@9,9000");
+//! assert_eq!(size, 0x1a0);
+//! assert_eq!(scanned, 11);
+//!
+//! /* Stop at the third node with a reg property */
+//! let mut remaining = 3;
+//! let node = dt.find_node_where(|node| {
+//!     if node.get_prop(b"reg").is_some() {
+//!         remaining -= 1;
+//!     }
+//!     remaining == 0
+//! });
+//! assert_eq!(node.unwrap().name(), b"device@2,2000");
+//! ```
+//!
+
+use crate::{prop_has_string, props, AttributedTokens, DeviceTree, Token};
+
+/// # NodePredicate
+/// Common conditions on a node for `DeviceTree::find_from()`, evaluated while streaming
+/// the tokens of the subtree so each token is decoded once.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NodePredicate<'p> {
+    /// The node has a property with this name
+    HasProp(&'p [u8]),
+
+    /// The node has this string in its `compatible` list
+    Compatible(&'p [u8]),
+
+    /// The node has this name, either the full name (e.g. `serial@1000`)
+    /// or the name without unit address (e.g. `serial`)
+    Named(&'p [u8]),
+
+    /// The node has this `device_type`
+    DeviceType(&'p [u8]),
+}
+
+impl<'p> NodePredicate<'p> {
+
+    /// Returns true if `node` matches
+    pub fn matches(&self, node: &Token) -> bool {
+        match self {
+            NodePredicate::HasProp(name) => node.get_prop(name).is_some(),
+            NodePredicate::Compatible(compat) => node.is_compatible(compat),
+            NodePredicate::Named(_) => self.matches_name(node.name()),
+            NodePredicate::DeviceType(_) => node.get_prop(props::DEVICE_TYPE).is_some_and(|prop| self.matches_prop(&prop)),
+        }
+    }
+
+    fn matches_name(&self, name: &[u8]) -> bool {
+        match self {
+            NodePredicate::Named(wanted) => {
+                let base = name.split(|c| *c == b'@').next().unwrap_or(name);
+                name == *wanted || base == *wanted
+            },
+            _ => false
+        }
+    }
+
+    fn matches_prop(&self, prop: &Token) -> bool {
+        match (self, prop) {
+            (NodePredicate::HasProp(wanted), Token::Property(_, name, _)) => name == wanted,
+            (NodePredicate::Compatible(compat), Token::Property(_, props::COMPATIBLE, _)) => prop_has_string(prop, compat),
+            (NodePredicate::DeviceType(wanted), Token::Property(_, props::DEVICE_TYPE, _)) => prop.prop_str() == Some(wanted),
+            _ => false
+        }
+    }
+}
+
+/// Returns the first node at `depth` (any depth if None) of `tokens` for which `f` returns true
+fn find_where<'a, F>(tokens: AttributedTokens<'a>, depth: Option<usize>, mut f: F) -> Option<Token<'a>>
+    where F: FnMut(&Token<'a>) -> bool
+{
+    tokens
+        .filter(|tok| matches!(tok.token, Token::BeginNode(..)) && depth.is_none_or(|depth| tok.depth == depth))
+        .map(|tok| tok.token)
+        .find(|node| f(node))
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns the first descendant, in document order, of the node at `start_path` matching `pred`.
+    /// Only the subtree of the start node is scanned, siblings along the path are skipped over.
+    /// Returns None if `start_path` does not resolve or no descendant matches.
+    ///
+    /// ```
+    /// use static_dt_rs::{DeviceTree, NodePredicate};
+    ///
+    /// let dt = DeviceTree::parse(include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/test.dtb"))).unwrap();
+    /// let node = dt.find_from(b"/node1", NodePredicate::HasProp(b"phandle")).unwrap();
+    /// assert_eq!(node.name(), b"child-node1");
+    /// assert!(dt.find_from(b"/node2", NodePredicate::HasProp(b"phandle")).is_none());
+    /// ```
+    pub fn find_from(&self, start_path: &[u8], pred: NodePredicate) -> Option<Token<'_>> {
+        let found = self.get_node_by_path(start_path)?
+            .attributed_tokens()
+            .filter(|tok| tok.depth > 1)
+            .find(|tok| match tok.token {
+                Token::BeginNode(_, _, name) => pred.matches_name(name),
+                Token::Property(..) => pred.matches_prop(&tok.token),
+                _ => false
+            })?;
+        self.node_at_offset(found.node_offset)
+    }
+
+    /// Returns the first node, in document order starting with the root, for which `f` returns true.
+    /// `f` is called once per node until it returns true.
+    /// Returns None if `f` returns false for every node.
+    ///
+    pub fn find_node_where<'b, F>(&'b self, f: F) -> Option<Token<'b>>
+        where F: FnMut(&Token<'b>) -> bool
+    {
+        find_where(self.attributed_tokens(), None, f)
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns the first direct child of this node (not recursive) for which `f` returns true.
+    /// `f` is called once per child until it returns true.
+    /// Returns None if `f` returns false for every child or token is not a node.
+    ///
+    pub fn find_child_where<F>(&self, f: F) -> Option<Token<'a>>
+        where F: FnMut(&Token<'a>) -> bool
+    {
+        find_where(self.attributed_tokens(), Some(2), f)
+    }
+}
@@ -0,0 +1,85 @@
+//! # Sharing
+//! A once-initialized holder for sharing one parsed devicetree through a static, e.g. across cores.
+//!
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::{DeviceTree, Error, Token};
+
+/* DeviceTree and Token are shared references to immutable bytes and must stay Send + Sync */
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<DeviceTree<'static>>();
+    assert_send_sync::<Token<'static>>();
+};
+
+const UNINIT: u8 = 0;
+const INITIALIZING: u8 = 1;
+const READY: u8 = 2;
+
+/// # StaticDt
+/// A devicetree parsed once by `init()` and then available to all users of the static through `get()`.
+///
+/// ```
+/// use static_dt_rs::StaticDt;
+///
+/// static DT: StaticDt = StaticDt::new();
+/// static FDT: &[u8] = include_bytes!("../tests/test.dtb");
+///
+/// DT.init(FDT).unwrap();
+/// assert_eq!(DT.get().unwrap().version(), 17);
+/// ```
+pub struct StaticDt {
+    state: AtomicU8,
+    dt: UnsafeCell<MaybeUninit<DeviceTree<'static>>>
+}
+
+/* Safety: dt is written once, before state is set to READY with release ordering,
+ * and only read after READY is observed with acquire ordering. */
+unsafe impl Sync for StaticDt {}
+
+impl StaticDt {
+    /// Create an empty holder
+    pub const fn new() -> StaticDt {
+        StaticDt { state: AtomicU8::new(UNINIT), dt: UnsafeCell::new(MaybeUninit::uninit()) }
+    }
+
+    /// Parse `fdt` and store it.
+    /// Returns `Error::AlreadyInitialized` without waiting if `init()` was called before,
+    /// or any error of `DeviceTree::parse()` in which case `init()` may be called again.
+    pub fn init(&self, fdt: &'static [u8]) -> Result<(), Error> {
+        if self.state.compare_exchange(UNINIT, INITIALIZING, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return Err(Error::AlreadyInitialized)
+        }
+
+        match DeviceTree::parse(fdt) {
+            Ok(dt) => {
+                /* Safety: only the caller which moved the state to INITIALIZING writes */
+                unsafe { (*self.dt.get()).write(dt); }
+                self.state.store(READY, Ordering::Release);
+                Ok(())
+            },
+            Err(e) => {
+                self.state.store(UNINIT, Ordering::Release);
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the devicetree or None if `init()` has not completed
+    pub fn get(&self) -> Option<&DeviceTree<'static>> {
+        if self.state.load(Ordering::Acquire) != READY {
+            return None
+        }
+        /* Safety: dt was initialized before READY was stored and is never written again */
+        Some(unsafe { (*self.dt.get()).assume_init_ref() })
+    }
+}
+
+impl Default for StaticDt {
+    fn default() -> Self {
+        StaticDt::new()
+    }
+}
@@ -0,0 +1,117 @@
+//! # Snapshots
+//! Owned copies of what identifies a node, for diagnostics which outlive the borrow of the tree,
+//! e.g. stored in a panic payload or passed out of an interrupt handler.
+//!
+
+use core::fmt::{self, Write};
+
+use crate::dts::write_name;
+use crate::{Token, UnitAddrFields};
+
+/// # NodeSnapshot
+/// Name, unit address, offset and depth of a node, see `Token::snapshot()`.
+/// At most `N` bytes of the name are kept, `is_truncated()` tells if there were more.
+///
+/// Displays as `serial@10000000 (offset 0x1f4, depth 3)`, a truncated name is followed by `...`
+/// and, if its unit address was cut off, the parsed unit address.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct NodeSnapshot<const N: usize = 32> {
+    name: [u8; N],
+    name_len: usize,
+    truncated: bool,
+    unit_address: UnitAddrFields,
+    offset: usize,
+    depth: usize,
+}
+
+impl<const N: usize> NodeSnapshot<N> {
+    /// Returns the stored name, at most `N` bytes
+    pub fn name(&self) -> &[u8] {
+        &self.name[..self.name_len]
+    }
+
+    /// Returns true if the name was longer than `N` bytes and is cut off
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the unit address, parsed from the full name
+    pub fn unit_address(&self) -> &UnitAddrFields {
+        &self.unit_address
+    }
+
+    /// Returns the offset of the node in the structure block, see `Token::node_offset()`
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the depth of the node, the root node has depth 1
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+}
+
+impl<const N: usize> fmt::Display for NodeSnapshot<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.depth == 1 {
+            f.write_char('/')?;
+        }
+        write_name(f, self.name())?;
+        if self.truncated {
+            f.write_str("...")?;
+            if !self.name().contains(&b'@') && !self.unit_address.is_empty() {
+                for (i, field) in self.unit_address.as_slice().iter().enumerate() {
+                    f.write_char(if i == 0 { '@' } else { ',' })?;
+                    write!(f, "{:x}", field)?;
+                }
+            }
+        }
+        write!(f, " (offset {:#x}, depth {})", self.offset, self.depth)
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Take a `NodeSnapshot` of this node keeping up to 32 bytes of its name, see `snapshot_with()`
+    /// Returns None if token is not a node.
+    ///
+    pub fn snapshot(&self) -> Option<NodeSnapshot> {
+        self.snapshot_with()
+    }
+
+    /// Take a `NodeSnapshot` of this node keeping up to `N` bytes of its name.
+    /// Finding the depth scans the tree from the root.
+    /// Returns None if token is not a node.
+    ///
+    pub fn snapshot_with<const N: usize>(&self) -> Option<NodeSnapshot<N>> {
+        let (dt, name) = match self {
+            Token::BeginNode(dt, _, name) => (*dt, *name),
+            _ => return None
+        };
+        let offset = self.node_offset()?;
+
+        let mut depth = 0usize;
+        for tok in dt.tokens() {
+            match tok {
+                Token::BeginNode(_, _, _) => {
+                    depth += 1;
+                    if tok.node_offset() == Some(offset) { break }
+                },
+                Token::EndNode => depth = depth.saturating_sub(1),
+                _ => ()
+            }
+        }
+
+        let name_len = name.len().min(N);
+        let mut snapshot = NodeSnapshot {
+            name: [0; N],
+            name_len,
+            truncated: name_len < name.len(),
+            unit_address: UnitAddrFields::parse(name),
+            offset,
+            depth,
+        };
+        snapshot.name[..name_len].copy_from_slice(&name[..name_len]);
+        Some(snapshot)
+    }
+}
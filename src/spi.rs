@@ -0,0 +1,88 @@
+//! # SPI
+//! Enumeration of the devices on an SPI bus.
+//!
+
+use core::ops::BitOr;
+
+use crate::{props, Token};
+
+/// # SpiMode
+/// Mode flags of an SPI device from its marker properties, with the bit values used by Linux
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SpiMode(u32);
+
+impl SpiMode {
+    /// Clock phase, `spi-cpha`
+    pub const CPHA: SpiMode = SpiMode(0x01);
+    /// Clock polarity, `spi-cpol`
+    pub const CPOL: SpiMode = SpiMode(0x02);
+    /// Chip select active high, `spi-cs-high`
+    pub const CS_HIGH: SpiMode = SpiMode(0x04);
+    /// Shared data in/out line, `spi-3wire`
+    pub const THREE_WIRE: SpiMode = SpiMode(0x10);
+
+    /// No flags set, mode 0
+    pub const fn empty() -> SpiMode {
+        SpiMode(0)
+    }
+
+    /// Returns the raw bits
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns true if all flags of `other` are set
+    pub const fn contains(&self, other: SpiMode) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Read the marker properties of `node`
+    pub fn of_node(node: &Token) -> SpiMode {
+        [
+            (&b"spi-cpha"[..], SpiMode::CPHA),
+            (b"spi-cpol", SpiMode::CPOL),
+            (b"spi-cs-high", SpiMode::CS_HIGH),
+            (b"spi-3wire", SpiMode::THREE_WIRE),
+        ].iter()
+            .filter(|(name, _)| node.get_prop(name).is_some())
+            .fold(SpiMode::empty(), |mode, (_, flag)| mode | *flag)
+    }
+}
+
+impl BitOr for SpiMode {
+    type Output = SpiMode;
+
+    fn bitor(self, rhs: SpiMode) -> SpiMode {
+        SpiMode(self.0 | rhs.0)
+    }
+}
+
+/// # SpiChild
+/// A device on an SPI bus, see `Token::spi_children()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SpiChild<'a> {
+    pub node: Token<'a>,
+    /// Chip select, the first cell of `reg`
+    pub chip_select: u32,
+    /// `spi-max-frequency` in Hz
+    pub max_frequency: Option<u32>,
+    pub mode: SpiMode,
+}
+
+impl<'a> Token<'a> {
+
+    /// Returns a iterator over the enabled devices of this SPI controller node.
+    /// Children without a `reg` property are skipped.
+    /// Returns a empty iterator if token is not a node.
+    ///
+    pub fn spi_children(&self) -> impl Iterator<Item = SpiChild<'a>> {
+        self.into_iter()
+            .filter(|node| matches!(node, Token::BeginNode(_, _, _)) && node.is_enabled())
+            .filter_map(|node| Some(SpiChild {
+                node,
+                chip_select: node.get_prop(props::REG)?.prop_u32(0)?,
+                max_frequency: node.get_prop(b"spi-max-frequency").and_then(|prop| prop.prop_u32(0)),
+                mode: SpiMode::of_node(&node),
+            }))
+    }
+}
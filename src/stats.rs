@@ -0,0 +1,334 @@
+//! # Statistics
+//! Counts over the whole tree, for sizing index storage at boot,
+//! and a fixed set of facts for feature gating in early startup code.
+//! `BootDigest` packs the facts a later boot stage needs into a fixed layout to pass along.
+//!
+
+use crate::{compat, props, valid_phandle, DeviceTree, Token};
+
+/// # TreeStats
+/// Statistics of a tree, see `DeviceTree::stats()`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    /// Number of nodes, including the root node
+    pub nodes: usize,
+    /// Number of properties
+    pub props: usize,
+    /// Maximum nesting depth, the root node has depth 1
+    pub max_depth: usize,
+    /// Length in bytes of the longest node name
+    pub max_name_len: usize,
+    /// Total length in bytes of all property values
+    pub prop_bytes: usize,
+    /// Largest value of any `phandle` or `linux,phandle` property, 0 if none
+    pub max_phandle: u32,
+}
+
+/// # QuickFacts
+/// Presence flags and counts of well-known nodes, see `DeviceTree::quick_facts()`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct QuickFacts {
+    /// There is a `/chosen` node
+    pub chosen: bool,
+    /// There is a `/aliases` node
+    pub aliases: bool,
+    /// There is a `/psci` node
+    pub psci: bool,
+    /// There is a `/reserved-memory` node
+    pub reserved_memory: bool,
+    /// `/chosen` has a `stdout-path` or `linux,stdout-path`
+    pub stdout_path: bool,
+    /// Number of CPUs, see `DeviceTree::cpus()`
+    pub cpus: usize,
+    /// Number of children of the root with `device_type = "memory"`
+    pub memory_nodes: usize,
+    /// Largest phandle in use, 0 if none, see `DeviceTree::max_phandle()`
+    pub max_phandle: u32,
+}
+
+/// # BootDigest
+/// A fixed layout digest of the tree for handing to a later boot stage, see `DeviceTree::boot_digest()`
+///
+/// All fields are naturally aligned at the offsets given in the `OFFSET_*` constants with explicit
+/// reserved words instead of padding, integers are in native byte order.
+/// The receiving stage should check `validate()` before trusting any field.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BootDigest {
+    /// `BootDigest::MAGIC`
+    pub magic: u32,
+    /// `BootDigest::VERSION`
+    pub version: u32,
+    /// Number of cpus, see `QuickFacts::cpus`
+    pub cpus: u32,
+    /// Number of valid entries in `memory`
+    pub memory_count: u32,
+    /// Largest phandle in use, 0 if none
+    pub max_phandle: u32,
+    /// `FLAG_*` bits
+    pub flags: u32,
+    /// Address of the early console registers, valid if `FLAG_EARLYCON` is set
+    pub earlycon_address: u64,
+    /// Usable memory as (address, size) pairs, see `DeviceTree::memory_regions_clamped()`
+    pub memory: [[u64; 2]; BootDigest::MAX_MEMORY],
+    /// Checksum of all other words, see `checksum()`
+    pub checksum: u32,
+    /// Reserved, 0
+    pub reserved: u32,
+}
+
+/* The layout is part of the handoff ABI */
+const _: () = assert!(core::mem::size_of::<BootDigest>() == BootDigest::SIZE);
+
+impl BootDigest {
+    /// Magic value, "DTBD" in big-endian
+    pub const MAGIC: u32 = 0x4454_4244;
+    /// Layout version
+    pub const VERSION: u32 = 1;
+    /// Maximum number of memory regions
+    pub const MAX_MEMORY: usize = 8;
+    /// Size in bytes
+    pub const SIZE: usize = 168;
+
+    /// `earlycon_address` is valid
+    pub const FLAG_EARLYCON: u32 = 1 << 0;
+    /// There were more than `MAX_MEMORY` memory regions, the rest are missing
+    pub const FLAG_MEMORY_TRUNCATED: u32 = 1 << 1;
+
+    /* Byte offsets of the fields, checked against the struct in tests */
+    pub const OFFSET_MAGIC: usize = 0;
+    pub const OFFSET_VERSION: usize = 4;
+    pub const OFFSET_CPUS: usize = 8;
+    pub const OFFSET_MEMORY_COUNT: usize = 12;
+    pub const OFFSET_MAX_PHANDLE: usize = 16;
+    pub const OFFSET_FLAGS: usize = 20;
+    pub const OFFSET_EARLYCON_ADDRESS: usize = 24;
+    pub const OFFSET_MEMORY: usize = 32;
+    pub const OFFSET_CHECKSUM: usize = 160;
+    pub const OFFSET_RESERVED: usize = 164;
+
+    /// Memory regions in use
+    pub fn memory(&self) -> &[[u64; 2]] {
+        &self.memory[..(self.memory_count as usize).min(Self::MAX_MEMORY)]
+    }
+
+    /// Address of the early console registers
+    ///
+    /// Returns None if there is no early console or it has no address.
+    ///
+    pub fn earlycon_address(&self) -> Option<u64> {
+        if self.flags & Self::FLAG_EARLYCON != 0 {
+            Some(self.earlycon_address)
+        } else {
+            None
+        }
+    }
+
+    /// Rotating sum of every 32-bit word except `checksum`, detects corruption but not tampering
+    pub fn checksum(&self) -> u32 {
+        let bytes = self.to_bytes();
+        bytes.chunks_exact(4)
+            .enumerate()
+            .filter(|(i, _)| i * 4 != Self::OFFSET_CHECKSUM)
+            .fold(0u32, |sum, (_, word)| {
+                let mut buf = [0u8; 4];
+                buf.copy_from_slice(word);
+                sum.rotate_left(1).wrapping_add(u32::from_ne_bytes(buf))
+            })
+    }
+
+    /// Check magic, version, memory count and checksum
+    pub fn validate(&self) -> bool {
+        self.magic == Self::MAGIC
+            && self.version == Self::VERSION
+            && self.memory_count as usize <= Self::MAX_MEMORY
+            && self.checksum == self.checksum()
+    }
+
+    /// Raw bytes with the same layout as the struct in memory
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0u8; Self::SIZE];
+        let mut put = |offs: usize, val: &[u8]| bytes[offs..offs + val.len()].copy_from_slice(val);
+        put(Self::OFFSET_MAGIC, &self.magic.to_ne_bytes());
+        put(Self::OFFSET_VERSION, &self.version.to_ne_bytes());
+        put(Self::OFFSET_CPUS, &self.cpus.to_ne_bytes());
+        put(Self::OFFSET_MEMORY_COUNT, &self.memory_count.to_ne_bytes());
+        put(Self::OFFSET_MAX_PHANDLE, &self.max_phandle.to_ne_bytes());
+        put(Self::OFFSET_FLAGS, &self.flags.to_ne_bytes());
+        put(Self::OFFSET_EARLYCON_ADDRESS, &self.earlycon_address.to_ne_bytes());
+        for (i, [address, size]) in self.memory.iter().enumerate() {
+            put(Self::OFFSET_MEMORY + i * 16, &address.to_ne_bytes());
+            put(Self::OFFSET_MEMORY + i * 16 + 8, &size.to_ne_bytes());
+        }
+        put(Self::OFFSET_CHECKSUM, &self.checksum.to_ne_bytes());
+        put(Self::OFFSET_RESERVED, &self.reserved.to_ne_bytes());
+        bytes
+    }
+
+    /// Read a digest from raw bytes, as written by `to_bytes()` or copied from the struct in memory.
+    /// The digest is not validated.
+    ///
+    /// Returns None if `bytes` is shorter than `SIZE`.
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..Self::SIZE)?;
+        let u32_at = |offs: usize| {
+            let mut word = [0u8; 4];
+            word.copy_from_slice(&bytes[offs..offs + 4]);
+            u32::from_ne_bytes(word)
+        };
+        let u64_at = |offs: usize| {
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&bytes[offs..offs + 8]);
+            u64::from_ne_bytes(word)
+        };
+        let mut memory = [[0u64; 2]; Self::MAX_MEMORY];
+        for (i, region) in memory.iter_mut().enumerate() {
+            *region = [u64_at(Self::OFFSET_MEMORY + i * 16), u64_at(Self::OFFSET_MEMORY + i * 16 + 8)];
+        }
+        Some(BootDigest {
+            magic: u32_at(Self::OFFSET_MAGIC),
+            version: u32_at(Self::OFFSET_VERSION),
+            cpus: u32_at(Self::OFFSET_CPUS),
+            memory_count: u32_at(Self::OFFSET_MEMORY_COUNT),
+            max_phandle: u32_at(Self::OFFSET_MAX_PHANDLE),
+            flags: u32_at(Self::OFFSET_FLAGS),
+            earlycon_address: u64_at(Self::OFFSET_EARLYCON_ADDRESS),
+            memory,
+            checksum: u32_at(Self::OFFSET_CHECKSUM),
+            reserved: u32_at(Self::OFFSET_RESERVED),
+        })
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Fill a `BootDigest` from `quick_facts()`, the usable memory and the early console
+    ///
+    pub fn boot_digest(&self) -> BootDigest {
+        let facts = self.quick_facts();
+        let mut digest = BootDigest {
+            magic: BootDigest::MAGIC,
+            version: BootDigest::VERSION,
+            cpus: facts.cpus as u32,
+            memory_count: 0,
+            max_phandle: facts.max_phandle,
+            flags: 0,
+            earlycon_address: 0,
+            memory: [[0; 2]; BootDigest::MAX_MEMORY],
+            checksum: 0,
+            reserved: 0,
+        };
+
+        for region in self.memory_regions_clamped() {
+            match digest.memory.get_mut(digest.memory_count as usize) {
+                Some(slot) => {
+                    *slot = [region.address, region.size];
+                    digest.memory_count += 1;
+                },
+                None => digest.flags |= BootDigest::FLAG_MEMORY_TRUNCATED,
+            }
+        }
+
+        if let Some(address) = self.earlycon().and_then(|console| console.address) {
+            digest.flags |= BootDigest::FLAG_EARLYCON;
+            digest.earlycon_address = address;
+        }
+
+        digest.checksum = digest.checksum();
+        digest
+    }
+
+    /// Returns a iterator over the CPU nodes, the children of `/cpus` recognized by `compat::cpu()`
+    /// Returns a empty iterator if there is no `/cpus` node.
+    ///
+    pub fn cpus(&self) -> impl Iterator<Item = Token<'_>> {
+        self.root().get_node(b"cpus").into_iter()
+            .flat_map(|cpus| cpus.children())
+            .filter(|node| compat::cpu(node).is_some())
+    }
+
+    /// Gather `QuickFacts` in a single pass, instead of one search per fact
+    ///
+    pub fn quick_facts(&self) -> QuickFacts {
+        let mut facts = QuickFacts::default();
+        let mut depth = 0usize;
+        /* Name of the open child of the root */
+        let mut top: &[u8] = &[];
+
+        for tok in self.tokens() {
+            match tok {
+                Token::BeginNode(_, _, name) => {
+                    depth += 1;
+                    if depth == 3 && top == b"cpus" && compat::cpu(&tok).is_some() {
+                        facts.cpus += 1;
+                    }
+                    if depth == 2 {
+                        top = name;
+                        match name {
+                            b"chosen" => facts.chosen = true,
+                            b"aliases" => facts.aliases = true,
+                            b"psci" => facts.psci = true,
+                            b"reserved-memory" => facts.reserved_memory = true,
+                            _ => ()
+                        }
+                    }
+                },
+                Token::EndNode => {
+                    depth = depth.saturating_sub(1);
+                    if depth < 2 {
+                        top = &[];
+                    }
+                },
+                Token::Property(_, name, _) => {
+                    match (depth, top, name) {
+                        (2, b"chosen", props::STDOUT_PATH | props::LINUX_STDOUT_PATH) => facts.stdout_path = true,
+                        (2, _, props::DEVICE_TYPE) if tok.prop_str() == Some(b"memory") => facts.memory_nodes += 1,
+                        (_, _, props::PHANDLE | props::LINUX_PHANDLE) => {
+                            if let Some(phandle) = valid_phandle(&tok) {
+                                facts.max_phandle = facts.max_phandle.max(phandle);
+                            }
+                        },
+                        _ => ()
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        facts
+    }
+
+    /// Compute statistics of the tree in a single pass
+    ///
+    pub fn stats(&self) -> TreeStats {
+        let mut stats = TreeStats::default();
+        let mut depth = 0;
+
+        for tok in self.tokens() {
+            match tok {
+                Token::BeginNode(_, _, name) => {
+                    depth += 1;
+                    stats.nodes += 1;
+                    stats.max_depth = stats.max_depth.max(depth);
+                    stats.max_name_len = stats.max_name_len.max(name.len());
+                },
+                Token::EndNode => {
+                    depth = depth.saturating_sub(1);
+                },
+                Token::Property(_, name, val) => {
+                    stats.props += 1;
+                    stats.prop_bytes += val.len();
+                    if matches!(name, props::PHANDLE | props::LINUX_PHANDLE) {
+                        if let Some(phandle) = tok.prop_u32(0) {
+                            stats.max_phandle = stats.max_phandle.max(phandle);
+                        }
+                    }
+                },
+                _ => ()
+            }
+        }
+
+        stats
+    }
+}
@@ -0,0 +1,158 @@
+//! # Thermal
+//! Thermal zones under `/thermal-zones` and their trip points.
+//! Zones and trips missing a mandatory property are skipped, optionally reporting
+//! `Warning::InvalidNode` to a sink.
+//!
+
+use crate::{DeviceTree, DtWarnSink, HierarchyTokenIterator, PhandleArgs, Token, Warning};
+
+/// # ThermalZone
+/// A child of `/thermal-zones`, see `DeviceTree::thermal_zones()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ThermalZone<'a> {
+    pub node: Token<'a>,
+    /// Polling interval in ms, 0 for interrupt driven sensors
+    pub polling_delay: u32,
+    /// Polling interval in ms while passive cooling is active
+    pub polling_delay_passive: Option<u32>,
+    /// The first entry of `thermal-sensors`
+    pub sensor: PhandleArgs<'a>,
+}
+
+/// # TripType
+/// The `type` of a trip point
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TripType {
+    /// Enable active cooling, e.g. a fan
+    Active,
+    /// Enable passive cooling, e.g. throttling
+    Passive,
+    /// Notify the OS
+    Hot,
+    /// Shut down
+    Critical,
+}
+
+impl TripType {
+    /// Parse a `type` string
+    /// Returns None if unknown
+    pub fn from_name(name: &[u8]) -> Option<TripType> {
+        match name {
+            b"active" => Some(TripType::Active),
+            b"passive" => Some(TripType::Passive),
+            b"hot" => Some(TripType::Hot),
+            b"critical" => Some(TripType::Critical),
+            _ => None
+        }
+    }
+}
+
+/// # Trip
+/// A trip point of a thermal zone, see `ThermalZone::trips()`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Trip<'a> {
+    pub node: Token<'a>,
+    /// Temperature in millicelsius
+    pub temperature: i32,
+    /// Hysteresis in millicelsius
+    pub hysteresis: u32,
+    pub kind: TripType,
+}
+
+/// Decodes a node, or returns the name of the missing or invalid property
+type Decoder<'a, T> = fn(Token<'a>) -> Result<T, &'static [u8]>;
+
+/// # ThermalIterator
+/// Iterates over the valid zones or trips of a node, see `DeviceTree::thermal_zones()` and `ThermalZone::trips()`.
+/// Invalid nodes are reported to the sink, if any, and skipped.
+pub struct ThermalIterator<'a, 's, T> {
+    nodes: HierarchyTokenIterator<'a>,
+    decode: Decoder<'a, T>,
+    sink: Option<&'s mut dyn DtWarnSink>,
+}
+
+impl<'a, 's, T> ThermalIterator<'a, 's, T> {
+    fn new(parent: Option<Token<'a>>, decode: Decoder<'a, T>, sink: Option<&'s mut dyn DtWarnSink>) -> Self {
+        let nodes = match parent {
+            Some(parent) => parent.into_iter(),
+            None => HierarchyTokenIterator::none()
+        };
+        ThermalIterator { nodes, decode, sink }
+    }
+}
+
+impl<'a, 's, T> Iterator for ThermalIterator<'a, 's, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for node in self.nodes.by_ref() {
+            if !matches!(node, Token::BeginNode(_, _, _)) {
+                continue
+            }
+            match (self.decode)(node) {
+                Ok(item) => return Some(item),
+                Err(property) => if let (Some(sink), Some(offset)) = (self.sink.as_mut(), node.node_offset()) {
+                    sink.warn(Warning::InvalidNode { offset, property });
+                }
+            }
+        }
+        None
+    }
+}
+
+fn decode_zone(node: Token) -> Result<ThermalZone, &'static [u8]> {
+    let polling_delay = node.get_prop(b"polling-delay")
+        .and_then(|prop| prop.prop_as::<u32>())
+        .ok_or(&b"polling-delay"[..])?;
+    let sensor = node.get_prop(b"thermal-sensors")
+        .and_then(|prop| prop.phandle_args(b"#thermal-sensor-cells").next())
+        .ok_or(&b"thermal-sensors"[..])?;
+    Ok(ThermalZone {
+        node,
+        polling_delay,
+        polling_delay_passive: node.get_prop(b"polling-delay-passive").and_then(|prop| prop.prop_as::<u32>()),
+        sensor
+    })
+}
+
+fn decode_trip(node: Token) -> Result<Trip, &'static [u8]> {
+    let temperature = node.get_prop(b"temperature")
+        .and_then(|prop| prop.prop_as::<u32>())
+        .ok_or(&b"temperature"[..])?;
+    let hysteresis = node.get_prop(b"hysteresis")
+        .and_then(|prop| prop.prop_as::<u32>())
+        .ok_or(&b"hysteresis"[..])?;
+    let kind = node.get_prop(b"type")
+        .and_then(|prop| prop.prop_str())
+        .and_then(TripType::from_name)
+        .ok_or(&b"type"[..])?;
+    Ok(Trip { node, temperature: temperature as i32, hysteresis, kind })
+}
+
+impl<'a> ThermalZone<'a> {
+
+    /// Returns a iterator over the valid trip points in the `trips` node of this zone
+    pub fn trips(&self) -> ThermalIterator<'a, 'static, Trip<'a>> {
+        ThermalIterator::new(self.node.get_node(b"trips"), decode_trip, None)
+    }
+
+    /// Same as `trips()` but reports skipped trip points to `sink`
+    pub fn trips_with_warnings<'s>(&self, sink: &'s mut dyn DtWarnSink) -> ThermalIterator<'a, 's, Trip<'a>> {
+        ThermalIterator::new(self.node.get_node(b"trips"), decode_trip, Some(sink))
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Returns a iterator over the valid thermal zones in `/thermal-zones`.
+    /// Zones without `polling-delay` or a resolvable `thermal-sensors` are skipped.
+    ///
+    pub fn thermal_zones(&self) -> ThermalIterator<'_, 'static, ThermalZone<'_>> {
+        ThermalIterator::new(self.get_node_by_path(b"/thermal-zones"), decode_zone, None)
+    }
+
+    /// Same as `thermal_zones()` but reports skipped zones to `sink`
+    pub fn thermal_zones_with_warnings<'s>(&self, sink: &'s mut dyn DtWarnSink) -> ThermalIterator<'_, 's, ThermalZone<'_>> {
+        ThermalIterator::new(self.get_node_by_path(b"/thermal-zones"), decode_zone, Some(sink))
+    }
+}
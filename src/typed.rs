@@ -0,0 +1,70 @@
+//! # Typed properties
+//! Conversion of property values to Rust types with `FromProperty`.
+//!
+
+use crate::{utils, DeviceTree, Token};
+
+/// # FromProperty
+/// Conversion of a property value, see `Token::prop_as()` and `DeviceTree::get_as()`
+pub trait FromProperty<'a>: Sized {
+    /// Convert `val`
+    /// Returns None if `val` does not have the expected size or format.
+    fn from_property(val: &'a [u8]) -> Option<Self>;
+}
+
+/// A single cell, the value must be exactly 4 bytes
+impl<'a> FromProperty<'a> for u32 {
+    fn from_property(val: &'a [u8]) -> Option<Self> {
+        match val.len() {
+            4 => Some(utils::read_fdt_u32(val, 0)),
+            _ => None
+        }
+    }
+}
+
+/// Two cells, the value must be exactly 8 bytes
+impl<'a> FromProperty<'a> for u64 {
+    fn from_property(val: &'a [u8]) -> Option<Self> {
+        match val.len() {
+            8 => Some(utils::read_fdt_u64(val, 0)),
+            _ => None
+        }
+    }
+}
+
+/// The raw value
+impl<'a> FromProperty<'a> for &'a [u8] {
+    fn from_property(val: &'a [u8]) -> Option<Self> {
+        Some(val)
+    }
+}
+
+/// The first NUL-terminated string, which must be valid UTF-8
+impl<'a> FromProperty<'a> for &'a str {
+    fn from_property(val: &'a [u8]) -> Option<Self> {
+        core::str::from_utf8(utils::get_fdt_string(val, 0)?).ok()
+    }
+}
+
+impl<'a> Token<'a> {
+
+    /// Convert the value of this property to `T`
+    /// Returns None if not a property or the value could not be converted.
+    ///
+    pub fn prop_as<T: FromProperty<'a>>(&self) -> Option<T> {
+        match self {
+            Token::Property(_, _, val) => T::from_property(val),
+            _ => None
+        }
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Same as `get()` but converts the value of the property to `T`
+    /// Returns None if the path does not name a property or the value could not be converted.
+    ///
+    pub fn get_as<'s, T: FromProperty<'s>>(&'s self, path: &[u8]) -> Option<T> {
+        self.get(path)?.prop_as()
+    }
+}
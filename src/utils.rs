@@ -1,27 +1,93 @@
+/// Read a big-endian u32 at `offs`, usable in const context.
+/// Returns None if `offs + 4` is out of range.
+pub const fn try_read_fdt_u32(buf: &[u8], offs: usize) -> Option<u32> {
+    match buf.split_at_checked(offs) {
+        Some((_, rest)) => match rest.first_chunk::<4>() {
+            Some(bytes) => Some(u32::from_be_bytes(*bytes)),
+            None => None
+        },
+        None => None
+    }
+}
+
+/// Read a big-endian u64 at `offs`, usable in const context.
+/// Returns None if `offs + 8` is out of range.
+pub const fn try_read_fdt_u64(buf: &[u8], offs: usize) -> Option<u64> {
+    match buf.split_at_checked(offs) {
+        Some((_, rest)) => match rest.first_chunk::<8>() {
+            Some(bytes) => Some(u64::from_be_bytes(*bytes)),
+            None => None
+        },
+        None => None
+    }
+}
+
+/// Read a big-endian u32 at `offs`, usable in const context.
+/// Panics if `offs + 4` is out of range.
+pub const fn read_fdt_u32(buf: &[u8], offs: usize) -> u32 {
+    match try_read_fdt_u32(buf, offs) {
+        Some(val) => val,
+        None => panic!("read_fdt_u32 out of range")
+    }
+}
+
+/// Read a big-endian u64 at `offs`, usable in const context.
+/// Panics if `offs + 8` is out of range.
+pub const fn read_fdt_u64(buf: &[u8], offs: usize) -> u64 {
+    match try_read_fdt_u64(buf, offs) {
+        Some(val) => val,
+        None => panic!("read_fdt_u64 out of range")
+    }
+}
+
+/// Offset basis of `dt_hash()`
+const FNV_OFFSET_BASIS: u32 = 0x811c9dc5;
 
-pub fn read_fdt_u32(buf: &[u8], offs: usize) -> u32 {
-    (buf[offs+0] as u32) << 24
-        | (buf[offs+1] as u32) << 16
-        | (buf[offs+2] as u32) << 8
-        | (buf[offs+3] as u32) << 0
+/// Continue the FNV-1a hash `hash` over `bytes`
+pub(crate) const fn fnv1a(mut hash: u32, bytes: &[u8]) -> u32 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = (hash ^ bytes[i] as u32).wrapping_mul(0x01000193);
+        i += 1;
+    }
+    hash
 }
 
-pub fn read_fdt_u64(buf: &[u8], offs: usize) -> u64 {
-    (buf[offs + 0] as u64) << 56
-        | (buf[offs + 1] as u64) << 48
-        | (buf[offs + 2] as u64) << 40
-        | (buf[offs + 3] as u64) << 32
-        | (buf[offs + 4] as u64) << 24
-        | (buf[offs + 5] as u64) << 16
-        | (buf[offs + 6] as u64) << 8
-        | (buf[offs + 7] as u64) << 0
+/// 32-bit FNV-1a hash of `bytes`, used by `NodeIndex`, `DtbBuilder`, `Token::name_hash()` and `DtPath::hash()`.
+/// The algorithm is part of the stable API: the same bytes hash to the same value in every release,
+/// so hashes may be persisted, e.g. in prebuilt index tables.
+pub const fn dt_hash(bytes: &[u8]) -> u32 {
+    fnv1a(FNV_OFFSET_BASIS, bytes)
 }
 
+/// Read a NUL-terminated string at `offs`, not including the terminator.
+/// Returns None if `offs` is out of range or the string is not terminated.
 pub fn get_fdt_string(buf: &[u8], offs: usize) -> Option<&[u8]> {
-    for (i, c) in buf[offs..].iter().enumerate() {
-        if *c == 0u8 {
-            return Some(&buf[offs..offs+i])
-        }
+    let s = buf.get(offs..)?;
+    s.iter().position(|c| *c == 0u8).map(|i| &s[..i])
+}
+
+/// Returns true if `inner` lies within `outer`
+pub(crate) fn is_subslice(outer: &[u8], inner: &[u8]) -> bool {
+    let start = outer.as_ptr() as usize;
+    let inner_start = inner.as_ptr() as usize;
+    inner_start >= start && inner_start + inner.len() <= start + outer.len()
+}
+
+/// Returns true if `val` is aligned to 4 bytes and a multiple of 4 bytes long,
+/// i.e. it can be reinterpreted as a slice of u32.
+pub fn can_view_as_cells(val: &[u8]) -> bool {
+    (val.as_ptr() as usize).is_multiple_of(core::mem::align_of::<u32>()) && val.len().is_multiple_of(4)
+}
+
+/// Parse a decimal number without sign or leading zeros (except "0" itself)
+/// Returns None if `s` is empty, contains other characters or overflows u32.
+pub fn parse_decimal(s: &[u8]) -> Option<u32> {
+    if s.is_empty() || (s.len() > 1 && s[0] == b'0') {
+        return None
     }
-    None
-}
\ No newline at end of file
+    s.iter().try_fold(0u32, |acc, c| match c {
+        b'0'..=b'9' => acc.checked_mul(10)?.checked_add((c - b'0') as u32),
+        _ => None
+    })
+}
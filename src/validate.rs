@@ -0,0 +1,188 @@
+//! # Validation
+//! Full structural check of a parsed tree. `DeviceTree::parse()` only checks the header,
+//! `validate()` walks every token once so later lookups can rely on a well-formed tree.
+//! `validate_with()` and `lint()` select the checks with a `ValidationProfile`.
+//!
+
+use crate::{raw, Check, DeviceTree, DtWarnSink, ValidationProfile, MAX_DEPTH};
+use crate::utils::{get_fdt_string, try_read_fdt_u32, try_read_fdt_u64};
+
+/// # ValidationError
+/// Structural errors found by `DeviceTree::validate()`.
+/// Offsets of tokens are relative to the structure block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The memory reservation block has no terminating entry before the next block,
+    /// `offset` is where the terminating entry was expected, relative to the start of the blob
+    UnterminatedReservations { offset: usize },
+
+    /// The token at `offset` runs past the end of the structure block
+    Truncated { offset: usize },
+
+    /// Unknown token id at `offset`
+    UnknownToken { offset: usize, id: u32 },
+
+    /// The name of the property at `offset` is outside of the strings block or not terminated
+    BadNameOffset { offset: usize },
+
+    /// The token at `offset` does not fit the nesting: an END_NODE without an open node,
+    /// a property outside of the root node, a second root node or FDT_END with open nodes
+    UnbalancedNodes { offset: usize },
+
+    /// The node at `offset` is nested deeper than `MAX_DEPTH`
+    TooDeep { offset: usize },
+
+    /// The structure block ends at `offset` without FDT_END
+    MissingEnd { offset: usize },
+}
+
+/// Position of `DeviceTree::validate_token()` in the structure block
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct StructureWalk {
+    pub offs: usize,
+    pub depth: usize,
+    pub root_done: bool,
+}
+
+impl ValidationError {
+    /// Returns the check reporting this error
+    pub fn check(&self) -> Check {
+        match self {
+            ValidationError::UnterminatedReservations { .. } => Check::Reservations,
+            _ => Check::Structure
+        }
+    }
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Check the memory reservation block and every token of the structure block,
+    /// same as `validate_with()` with `ValidationProfile::standard()`.
+    /// Returns the first error found.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.validate_with(&ValidationProfile::standard())
+    }
+
+    /// Run the structural checks of `profile`, `Check::Reservations` and `Check::Structure`.
+    /// Returns the first error found.
+    pub fn validate_with(&self, profile: &ValidationProfile) -> Result<(), ValidationError> {
+        if profile.contains(Check::Reservations) {
+            self.validate_reservations()?;
+        }
+        if profile.contains(Check::Structure) {
+            self.validate_structure()?;
+        }
+        Ok(())
+    }
+
+    /// Run every check of `profile`: the structural checks as `validate_with()`, then if they pass
+    /// the lints, reporting to `sink`.
+    /// Returns the first structural error found, no lints are reported then.
+    pub fn lint(&self, profile: &ValidationProfile, sink: &mut dyn DtWarnSink) -> Result<(), ValidationError> {
+        self.validate_with(profile)?;
+        self.report(profile, sink);
+        Ok(())
+    }
+
+    /// The reservation block must be terminated before the following block, or `totalsize` if it is last
+    fn validate_reservations(&self) -> Result<(), ValidationError> {
+        let mut offs = 0;
+        loop {
+            if let Some(result) = self.validate_reservation(offs) {
+                return result
+            }
+            offs += 16;
+        }
+    }
+
+    /// Check the reservation entry at `offs` in the reservation block.
+    /// Returns None if it is not the terminating entry, the next one is at `offs + 16`.
+    pub(crate) fn validate_reservation(&self, offs: usize) -> Option<Result<(), ValidationError>> {
+        let start = self.off_mem_rsvmap();
+        let end = [self.off_dt_struct(), self.off_dt_strings()].iter()
+            .copied()
+            .filter(|offs| *offs >= start)
+            .fold(self.totalsize, usize::min);
+        let block = self.fdt.get(start..end).unwrap_or(&[]);
+
+        match (try_read_fdt_u64(block, offs), try_read_fdt_u64(block, offs + 8)) {
+            (Some(0), Some(0)) => Some(Ok(())),
+            (Some(_), Some(_)) => None,
+            _ => Some(Err(ValidationError::UnterminatedReservations { offset: start + offs }))
+        }
+    }
+
+    fn validate_structure(&self) -> Result<(), ValidationError> {
+        let mut walk = StructureWalk::default();
+        loop {
+            if let Some(result) = self.validate_token(&mut walk) {
+                return result
+            }
+        }
+    }
+
+    /// Check the token at `walk.offs` and advance past it.
+    /// Returns None if it is not the last token.
+    pub(crate) fn validate_token(&self, walk: &mut StructureWalk) -> Option<Result<(), ValidationError>> {
+        let structs = self.structs;
+        let offset = walk.offs;
+        let token_id = match try_read_fdt_u32(structs, offset) {
+            Some(id) => id,
+            None if offset >= structs.len() => return Some(Err(ValidationError::MissingEnd { offset })),
+            None => return Some(Err(ValidationError::Truncated { offset }))
+        };
+        let mut offs = offset + raw::FDT_TAGSIZE;
+
+        match token_id {
+            raw::FDT_BEGIN_NODE => {
+                if walk.depth == 0 && walk.root_done {
+                    return Some(Err(ValidationError::UnbalancedNodes { offset }))
+                }
+                let name = match get_fdt_string(structs, offs) {
+                    Some(name) => name,
+                    None => return Some(Err(ValidationError::Truncated { offset }))
+                };
+                offs += (name.len()/4 + 1)*4;
+                walk.depth += 1;
+                if walk.depth > MAX_DEPTH {
+                    return Some(Err(ValidationError::TooDeep { offset }))
+                }
+            },
+            raw::FDT_END_NODE => {
+                if walk.depth == 0 {
+                    return Some(Err(ValidationError::UnbalancedNodes { offset }))
+                }
+                walk.depth -= 1;
+                walk.root_done |= walk.depth == 0;
+            },
+            raw::FDT_PROP => {
+                if walk.depth == 0 {
+                    return Some(Err(ValidationError::UnbalancedNodes { offset }))
+                }
+                let (len, nameoff) = match (try_read_fdt_u32(structs, offs), try_read_fdt_u32(structs, offs + 4)) {
+                    (Some(len), Some(nameoff)) => (len as usize, nameoff as usize),
+                    _ => return Some(Err(ValidationError::Truncated { offset }))
+                };
+                offs += 8;
+                if offs.checked_add(len).is_none_or(|end| end > structs.len()) {
+                    return Some(Err(ValidationError::Truncated { offset }))
+                }
+                if get_fdt_string(self.strings, nameoff).is_none() {
+                    return Some(Err(ValidationError::BadNameOffset { offset }))
+                }
+                offs += len.div_ceil(4)*4;
+            },
+            raw::FDT_NOP => (),
+            raw::FDT_END => {
+                if walk.depth != 0 || !walk.root_done {
+                    return Some(Err(ValidationError::UnbalancedNodes { offset }))
+                }
+                return Some(Ok(()))
+            },
+            id => return Some(Err(ValidationError::UnknownToken { offset, id }))
+        }
+
+        walk.offs = offs;
+        None
+    }
+}
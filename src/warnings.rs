@@ -0,0 +1,244 @@
+//! # Warnings
+//! Recoverable oddities in a devicetree, reported to a caller-provided sink without formatting or alloc.
+//!
+
+use crate::{compat, props, Check, DeviceTree, Error, Token, TokenError, TokenIterator, ValidationProfile, MAX_DEPTH};
+use crate::compat::Spelling;
+use crate::cells::is_cell_property;
+
+/// # Warning
+/// A recoverable oddity found in a devicetree.
+/// Offsets of tokens are relative to the structure block, offsets of strings to the strings block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// Unknown token id at `offset`, iteration stops there
+    UnknownToken { offset: usize, id: u32 },
+
+    /// `count` consecutive NOP tokens starting at `offset`
+    NopRun { offset: usize, count: usize },
+
+    /// Property at `offset` following a sub-node, not found by `Token::get_prop()`
+    PropertyAfterNode { offset: usize },
+
+    /// String at `offset` not used as the name of any property
+    UnreferencedString { offset: usize },
+
+    /// `len` bytes at `offset` skipped by `DeviceTree::tokens_lossy()` after an unknown token
+    SkippedBytes { offset: usize, len: usize },
+
+    /// Node at `offset` skipped as `property` is missing or invalid, e.g. by `DeviceTree::thermal_zones_with_warnings()`
+    InvalidNode { offset: usize, property: &'static [u8] },
+
+    /// Property at `offset` of the node at `node_offset`, named by the string at `nameoff`,
+    /// which always holds cells (e.g. `reg`) but has `count` bytes after its last complete cell
+    TrailingBytes { offset: usize, node_offset: usize, nameoff: usize, count: usize },
+
+    /// The node at `offset` is nested deeper than `MAX_DEPTH`, the rest of the structure block is not checked
+    TooDeep { offset: usize },
+
+    /// Property at `offset` with a value of `len` bytes, more than `ParseOptions::max_prop_size`
+    OversizeProperty { offset: usize, len: usize },
+
+    /// The node at `offset` relies on a legacy spelling, see the `compat` module. `property` is the legacy
+    /// property used (`linux,phandle`, `interrupt-parent` of the root node or `device_type`)
+    /// or the missing one a default stands in for (`#gpio-cells`)
+    Deprecated { offset: usize, property: &'static [u8] },
+
+    /// The name of the node or property at `offset` has characters the devicetree specification does not allow,
+    /// or a node name has more than one `@`
+    NameCharset { offset: usize },
+}
+
+impl Warning {
+    /// Returns the check reporting this warning, see `ValidationProfile`
+    /// Returns None if it is not reported by `DeviceTree::lint()`, e.g. `SkippedBytes`.
+    ///
+    pub fn check(&self) -> Option<Check> {
+        match self {
+            Warning::UnknownToken { .. } | Warning::TooDeep { .. } => Some(Check::Structure),
+            Warning::NopRun { .. } => Some(Check::NopRuns),
+            Warning::PropertyAfterNode { .. } => Some(Check::PropertyOrder),
+            Warning::UnreferencedString { .. } => Some(Check::UnreferencedStrings),
+            Warning::TrailingBytes { .. } => Some(Check::CellLength),
+            Warning::OversizeProperty { .. } => Some(Check::OversizeProperty),
+            Warning::Deprecated { .. } => Some(Check::Deprecated),
+            Warning::NameCharset { .. } => Some(Check::NameCharset),
+            Warning::SkippedBytes { .. } | Warning::InvalidNode { .. } => None
+        }
+    }
+}
+
+/// Characters allowed in node names and unit addresses besides letters and digits
+const NODE_NAME_CHARS: &[u8] = b",._+-";
+
+/// Characters allowed in property names besides letters and digits
+const PROP_NAME_CHARS: &[u8] = b",._+?#-";
+
+/// Returns true if `name` of a node is empty (the root) or a node name and an optional unit address
+fn valid_node_name(name: &[u8]) -> bool {
+    let mut parts = name.splitn(2, |c| *c == b'@');
+    let node_name = parts.next().unwrap_or(&[]);
+    let unit_address = parts.next().unwrap_or(&[]);
+    (!node_name.is_empty() || unit_address.is_empty())
+        && node_name.iter().chain(unit_address).all(|c| c.is_ascii_alphanumeric() || NODE_NAME_CHARS.contains(c))
+}
+
+/// Returns true if `name` of a property is non-empty and has only allowed characters
+fn valid_prop_name(name: &[u8]) -> bool {
+    !name.is_empty() && name.iter().all(|c| c.is_ascii_alphanumeric() || PROP_NAME_CHARS.contains(c))
+}
+
+/// # DtWarnSink
+/// Receiver of warnings, e.g. to print them to a boot log
+pub trait DtWarnSink {
+    fn warn(&mut self, w: Warning);
+}
+
+/// # NoWarnings
+/// A sink discarding all warnings
+#[derive(Debug, Copy, Clone, Default)]
+pub struct NoWarnings;
+
+impl DtWarnSink for NoWarnings {
+    fn warn(&mut self, _w: Warning) {}
+}
+
+impl<'a> DeviceTree<'a> {
+
+    /// Same as `parse()` but also reports recoverable oddities of the tree to `sink`, see `warnings()`
+    pub fn parse_with_warnings(fdt: &'a [u8], sink: &mut dyn DtWarnSink) -> Result<DeviceTree<'a>, Error> {
+        let dt = DeviceTree::parse(fdt)?;
+        dt.warnings(sink);
+        Ok(dt)
+    }
+
+    /// Scan the whole tree and report recoverable oddities to `sink`.
+    /// Name characters are only checked by `lint()`.
+    pub fn warnings(&self, sink: &mut dyn DtWarnSink) {
+        self.report(&ValidationProfile::pedantic().without(Check::NameCharset), sink)
+    }
+
+    /// Report the warnings of the checks in `profile` to `sink`
+    pub(crate) fn report(&self, profile: &ValidationProfile, sink: &mut dyn DtWarnSink) {
+        let mut iter = TokenIterator::new(self);
+        let mut seen_child = [false; MAX_DEPTH];
+        let mut nodes = [0usize; MAX_DEPTH];
+        let mut depth = 0;
+        let mut nops: Option<(usize, usize)> = None;
+
+        loop {
+            let offset = iter.offs;
+            let tok = match iter.next_checked() {
+                Some(Ok(tok)) => tok,
+                Some(Err(TokenError::OversizeProperty { offset, len })) => {
+                    if profile.contains(Check::OversizeProperty) {
+                        sink.warn(Warning::OversizeProperty { offset, len });
+                    }
+                    continue
+                },
+                Some(Err(TokenError::Truncated { .. })) | None => break
+            };
+
+            /* Report a run of NOPs when it ends */
+            match (&tok, nops) {
+                (Token::NoOperation, Some((start, count))) => nops = Some((start, count + 1)),
+                (Token::NoOperation, None) => nops = Some((offset, 1)),
+                (_, Some((start, count))) => {
+                    if profile.contains(Check::NopRuns) {
+                        sink.warn(Warning::NopRun { offset: start, count });
+                    }
+                    nops = None;
+                },
+                _ => ()
+            }
+
+            match tok {
+                Token::BeginNode(_, _, name) => {
+                    if depth >= MAX_DEPTH {
+                        if profile.contains(Check::Structure) {
+                            sink.warn(Warning::TooDeep { offset });
+                        }
+                        break
+                    }
+                    if profile.contains(Check::NameCharset) && !valid_node_name(name) {
+                        sink.warn(Warning::NameCharset { offset });
+                    }
+                    if depth > 0 { seen_child[depth - 1] = true; }
+                    seen_child[depth] = false;
+                    nodes[depth] = offset;
+                    depth += 1;
+                },
+                Token::EndNode => depth = depth.saturating_sub(1),
+                Token::Property(_, name, val) => {
+                    if profile.contains(Check::PropertyOrder) && depth > 0 && seen_child[depth - 1] {
+                        sink.warn(Warning::PropertyAfterNode { offset });
+                    }
+                    if profile.contains(Check::NameCharset) && !valid_prop_name(name) {
+                        sink.warn(Warning::NameCharset { offset });
+                    }
+                    if profile.contains(Check::CellLength) && val.len() % 4 != 0 && is_cell_property(name) {
+                        sink.warn(Warning::TrailingBytes {
+                            offset,
+                            node_offset: nodes[depth.saturating_sub(1)],
+                            nameoff: name.as_ptr() as usize - self.strings.as_ptr() as usize,
+                            count: val.len() % 4
+                        });
+                    }
+                },
+                Token::Invalid(id) if profile.contains(Check::Structure) => sink.warn(Warning::UnknownToken { offset, id }),
+                _ => ()
+            }
+        }
+
+        if profile.contains(Check::Deprecated) {
+            self.deprecated_spellings(sink);
+        }
+        if !profile.contains(Check::UnreferencedStrings) {
+            return
+        }
+
+        /* Names of oversized properties count as references */
+        let unlimited = DeviceTree { max_prop_size: usize::MAX, ..*self };
+        for (offset, s) in self.strings() {
+            let referenced = unlimited.tokens().any(|tok| match tok {
+                Token::Property(_, name, _) => {
+                    /* Names may point into the middle of a string */
+                    let nameoff = name.as_ptr() as usize - self.strings.as_ptr() as usize;
+                    nameoff >= offset && nameoff <= offset + s.len()
+                },
+                _ => false
+            });
+            if !referenced {
+                sink.warn(Warning::UnreferencedString { offset });
+            }
+        }
+    }
+
+    /// Report the nodes relying on a legacy spelling, see the `compat` module.
+    /// The `interrupt-parent` of the root node is reported once, for the root node, if any node relies on it.
+    fn deprecated_spellings(&self, sink: &mut dyn DtWarnSink) {
+        let mut root_parent_used = false;
+        for node in self.nodes() {
+            let offset = node.node_offset().unwrap_or(0);
+            if let Some((_, Spelling::Legacy)) = compat::phandle(&node) {
+                sink.warn(Warning::Deprecated { offset, property: props::LINUX_PHANDLE });
+            }
+            if let Some((_, Spelling::Legacy)) = compat::gpio_cells(&node) {
+                sink.warn(Warning::Deprecated { offset, property: props::GPIO_CELLS });
+            }
+            if !root_parent_used && node.get_prop(props::INTERRUPTS).is_some() && node.get_prop(props::INTERRUPTS_EXTENDED).is_none() {
+                root_parent_used = matches!(compat::interrupt_parent(&node), Some((_, Spelling::Legacy)));
+            }
+        }
+        if root_parent_used {
+            sink.warn(Warning::Deprecated { offset: self.root().node_offset().unwrap_or(0), property: props::INTERRUPT_PARENT });
+        }
+
+        for cpu in self.cpus() {
+            if compat::cpu(&cpu) == Some(Spelling::Legacy) {
+                sink.warn(Warning::Deprecated { offset: cpu.node_offset().unwrap_or(0), property: props::DEVICE_TYPE });
+            }
+        }
+    }
+}
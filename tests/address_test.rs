@@ -0,0 +1,287 @@
+use static_dt_rs::{CellSizes, DeviceTree, RangeEntry, RegEntry, Token, UnitAddrCompare, UnitAddrFields};
+
+mod common;
+
+static BUS_FDT: &[u8] = include_bytes!("bus.dtb");
+
+#[test]
+fn test_cell_sizes() {
+    let dt = DeviceTree::parse(BUS_FDT).unwrap();
+    let bus = dt.get_node_by_path(b"/bus@80000000").unwrap();
+
+    assert_eq!(CellSizes::of_parent(&dt.root()), CellSizes { address_cells: 1, size_cells: 1 });
+    assert_eq!(CellSizes::of_parent(&bus), CellSizes { address_cells: 2, size_cells: 1 });
+
+    /* Defaults */
+    let no_reg = bus.get_node(b"no-reg").unwrap();
+    assert_eq!(CellSizes::of_parent(&no_reg), CellSizes::default());
+    assert_eq!(CellSizes::default(), CellSizes { address_cells: 2, size_cells: 1 });
+}
+
+#[test]
+fn test_reg() {
+    let dt = DeviceTree::parse(BUS_FDT).unwrap();
+
+    let mmio = dt.get_node_by_path(b"/mmio@10000000").unwrap();
+    let mut reg = mmio.reg().unwrap();
+    assert_eq!(reg.next(), Some(RegEntry { address: 0x10000000, size: 0x1000 }));
+    assert_eq!(reg.next(), None);
+
+    let device = dt.get_node_by_path(b"/bus@80000000/device@3,3000").unwrap();
+    let reg: Vec<RegEntry> = device.reg().unwrap().collect();
+    assert_eq!(reg, vec![
+        RegEntry { address: 0x3_0000_3000, size: 0x100 },
+        RegEntry { address: 0x3_0000_3800, size: 0x40 },
+    ]);
+
+    /* No reg property */
+    assert!(dt.get_node_by_path(b"/bus@80000000/no-reg").unwrap().reg().is_none());
+    /* Root has no parent */
+    assert!(dt.root().reg().is_none());
+}
+
+#[test]
+fn test_reg_with_bus_context() {
+    let dt = DeviceTree::parse(BUS_FDT).unwrap();
+    let bus = dt.get_node_by_path(b"/bus@80000000").unwrap();
+    let sizes = CellSizes::of_parent(&bus);
+
+    let mut devices = 0;
+    for child in bus {
+        if let Token::BeginNode(_, _, _) = child {
+            let cached: Option<Vec<RegEntry>> = child.reg_with(&sizes).map(|reg| reg.collect());
+            let uncached: Option<Vec<RegEntry>> = child.reg().map(|reg| reg.collect());
+            assert_eq!(cached, uncached);
+
+            if let Some(reg) = cached {
+                assert_eq!(reg.len(), 2);
+                devices += 1;
+            }
+        }
+    }
+    assert_eq!(devices, 10);
+}
+
+#[test]
+fn test_ranges() {
+    let dt = DeviceTree::parse(BUS_FDT).unwrap();
+    let bus = dt.get_node_by_path(b"/bus@80000000").unwrap();
+
+    let ranges: Vec<RangeEntry> = bus.ranges().unwrap().collect();
+    assert_eq!(ranges, vec![
+        RangeEntry { child_address: 0x0, parent_address: 0x80000000, size: 0x1000000 },
+        RangeEntry { child_address: 0x1_0000_0000, parent_address: 0x90000000, size: 0x1000000 },
+    ]);
+
+    let with: Vec<RangeEntry> = bus.ranges_with(&CellSizes::of_parent(&bus), &CellSizes::of_parent(&dt.root()))
+        .unwrap().collect();
+    assert_eq!(with, ranges);
+
+    assert_eq!(ranges[1].translate(0x1_0000_0800), Some(0x90000800));
+    assert_eq!(ranges[1].translate(0x1_0100_0000), None);
+    assert_eq!(ranges[0].translate(0x1_0000_0000), None);
+
+    /* No ranges property */
+    assert!(dt.get_node_by_path(b"/mmio@10000000").unwrap().ranges().is_none());
+}
+
+#[test]
+fn test_zero_cells() {
+    let fdt = common::zero_cells_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let bus = dt.get_node_by_path(b"/bus").unwrap();
+    let device = dt.get_node_by_path(b"/bus/device").unwrap();
+
+    /* Entries of zero cells end the iteration instead of repeating forever */
+    assert_eq!(bus.reg().unwrap().count(), 0);
+    assert_eq!(device.reg().unwrap().count(), 0);
+    assert_eq!(bus.ranges().unwrap().count(), 0);
+    assert_eq!(dt.memory_regions().count(), 0);
+    let zero = CellSizes { address_cells: 0, size_cells: 0 };
+    assert_eq!(device.reg_with(&zero).unwrap().count(), 0);
+
+    /* Empty ranges at every level, an identity mapping */
+    assert_eq!(device.translate_address(0x1000), Some(0x1000));
+
+    /* Entries of only size cells still advance */
+    let sizes = CellSizes { address_cells: 0, size_cells: 1 };
+    let reg: Vec<RegEntry> = device.reg_with(&sizes).unwrap().collect();
+    assert_eq!(reg, vec![RegEntry { address: 0, size: 0x1000 }, RegEntry { address: 0, size: 0x100 }]);
+}
+
+#[test]
+fn test_translate_address() {
+    let dt = DeviceTree::parse(BUS_FDT).unwrap();
+
+    let device = dt.get_node_by_path(b"/bus@80000000/device@1,1000").unwrap();
+    let reg = device.reg().unwrap().next().unwrap();
+    assert_eq!(device.translate_address(reg.address), Some(0x90001000));
+    /* Outside of the bus ranges */
+    let device = dt.get_node_by_path(b"/bus@80000000/device@2,2000").unwrap();
+    assert_eq!(device.translate_address(0x2_0000_2000), None);
+
+    /* Directly under the root */
+    let mmio = dt.get_node_by_path(b"/mmio@10000000").unwrap();
+    assert_eq!(mmio.translate_address(0x10000000), Some(0x10000000));
+    assert_eq!(dt.root().translate_address(0), None);
+}
+
+#[test]
+fn test_child_by_address() {
+    let fdt = common::i2c_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let i2c = dt.get_node_by_path(b"/i2c@1000").unwrap();
+
+    assert_eq!(i2c.child_by_unit_address(0x50).unwrap().name(), b"eeprom@50");
+    assert_eq!(i2c.child_by_unit_address(0x1a).unwrap().name(), b"sensor@1a");
+    assert!(i2c.child_by_unit_address(0x1b).is_none());
+    assert!(i2c.child_by_unit_address(0).is_none());
+
+    /* reg and unit address of sensor@1a disagree */
+    assert_eq!(i2c.child_by_reg(0x50).unwrap().name(), b"eeprom@50");
+    assert_eq!(i2c.child_by_reg(0x1b).unwrap().name(), b"sensor@1a");
+    assert!(i2c.child_by_reg(0x1a).is_none());
+
+    /* Only direct children */
+    assert!(dt.root().child_by_unit_address(0x50).is_none());
+    assert_eq!(dt.root().child_by_unit_address(0x1000).unwrap().name(), b"i2c@1000");
+
+    /* Unit addresses with several numbers are skipped */
+    let dt = DeviceTree::parse(BUS_FDT).unwrap();
+    let bus = dt.get_node_by_path(b"/bus@80000000").unwrap();
+    assert!(bus.child_by_unit_address(0x3000).is_none());
+    assert_eq!(bus.child_by_reg(0x3_0000_3000).unwrap().name(), b"device@3,3000");
+}
+
+#[test]
+fn test_unit_address_fields() {
+    let fdt = common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"pci@40000000").unwrap();
+        builder.begin_node(b"port@0,0").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"port@1,0").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"port@1,1").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"flash-controller@1000").unwrap();
+        builder.begin_node(b"flash@0,400000").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"flash@2,0").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"odd@1,zz,3").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"many@1,2,3,4,5").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"vendor@abc").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"bad@xyz").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let fields = |path: &[u8]| dt.get_node_by_path(path).unwrap().unit_address_fields();
+
+    /* PCI device, function */
+    let pci = dt.get_node_by_path(b"/pci@40000000").unwrap();
+    assert_eq!(fields(b"/pci@40000000/port@1,1").as_slice(), &[1, 1]);
+    assert_eq!(pci.child_by_unit_address_first(1).unwrap().name(), b"port@1,0");
+    assert!(pci.child_by_unit_address_first(2).is_none());
+    assert!(pci.child_by_unit_address(1).is_none());
+
+    /* Chip-select, offset */
+    let flash = dt.get_node_by_path(b"/flash-controller@1000").unwrap();
+    let cs0 = fields(b"/flash-controller@1000/flash@0,400000");
+    assert_eq!(cs0.as_slice(), &[0, 0x400000]);
+    assert_eq!((cs0.len(), cs0.get(0), cs0.get(1), cs0.get(2)), (2, Some(0), Some(0x400000), None));
+    assert_eq!(flash.child_by_unit_address_first(2).unwrap().name(), b"flash@2,0");
+
+    /* Partially parseable, too many fields and single fields */
+    assert_eq!(fields(b"/odd@1,zz,3").as_slice(), &[1]);
+    assert_eq!(fields(b"/many@1,2,3,4,5").as_slice(), &[1, 2, 3, 4]);
+    assert_eq!(fields(b"/vendor@abc").as_slice(), &[0xabc]);
+    assert!(fields(b"/bad@xyz").is_empty());
+    assert!(dt.root().unit_address_fields().is_empty());
+    assert_eq!(UnitAddrFields::parse(b"port@1,0"), fields(b"/pci@40000000/port@1,0"));
+    assert_eq!(dt.root().child_by_unit_address_first(0xabc).unwrap().name(), b"vendor@abc");
+}
+
+/// Unit addresses spelled with leading zeros, a 0x prefix, as composite and not as a number
+fn unit_names_fdt() -> Vec<u8> {
+    common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"soc").unwrap();
+        for name in [&b"uart@01000"[..], b"serial@0x2000", b"timer@3000", b"timer@03000", b"port@1,0", b"gpio@zz"] {
+            builder.begin_node(name).unwrap();
+            builder.end_node().unwrap();
+        }
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+#[test]
+fn test_unit_address_compare() {
+    let fdt = unit_names_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let find = |path: &[u8], compare| dt.get_node_by_path_with(path, compare).map(|node| node.name());
+    assert_eq!(UnitAddrCompare::default(), UnitAddrCompare::Exact);
+
+    for compare in [UnitAddrCompare::Exact, UnitAddrCompare::NumericHex, UnitAddrCompare::Lenient] {
+        assert_eq!(find(b"/soc/uart@01000", compare), Some(&b"uart@01000"[..]));
+        assert_eq!(find(b"/soc/gpio@zz", compare), Some(&b"gpio@zz"[..]));
+        assert_eq!(find(b"/soc/timer@03000", compare), Some(&b"timer@03000"[..]));
+        assert_eq!(find(b"/soc/serial@2001", compare), None);
+        assert_eq!(find(b"/soc/gpio@0zz", compare), None);
+    }
+
+    /* Exact is what get_node_by_path() does */
+    assert_eq!(find(b"/soc/uart@1000", UnitAddrCompare::Exact), None);
+    assert_eq!(find(b"/soc/serial@2000", UnitAddrCompare::Exact), None);
+    assert_eq!(dt.get_node_by_path(b"/soc/uart@1000"), None);
+
+    /* Numeric matches, the first one in document order unless there is an exact match */
+    for compare in [UnitAddrCompare::NumericHex, UnitAddrCompare::Lenient] {
+        assert_eq!(find(b"/soc/uart@1000", compare), Some(&b"uart@01000"[..]));
+        assert_eq!(find(b"/soc/uart@0x1000", compare), Some(&b"uart@01000"[..]));
+        assert_eq!(find(b"/soc/uart@0X001000", compare), Some(&b"uart@01000"[..]));
+        assert_eq!(find(b"/soc/serial@2000", compare), Some(&b"serial@0x2000"[..]));
+        assert_eq!(find(b"/soc/timer@003000", compare), Some(&b"timer@3000"[..]));
+        assert_eq!(find(b"/soc/port@01,0x0", compare), Some(&b"port@1,0"[..]));
+        assert_eq!(find(b"/soc/port@1", compare), None);
+        assert_eq!(find(b"/sOc/uart@1000", compare), None);
+    }
+
+    /* Only Lenient matches names without a unit address */
+    assert_eq!(find(b"/soc/uart", UnitAddrCompare::NumericHex), None);
+    assert_eq!(find(b"/soc/uart", UnitAddrCompare::Lenient), Some(&b"uart@01000"[..]));
+    assert_eq!(find(b"/soc/port", UnitAddrCompare::Lenient), Some(&b"port@1,0"[..]));
+
+    let soc = dt.get_node_by_path(b"/soc").unwrap();
+    let by_addr = |addr, compare| soc.child_by_unit_address_with(addr, compare).map(|node| node.name());
+    assert_eq!(by_addr(0x1000, UnitAddrCompare::Exact), None);
+    assert_eq!(by_addr(0x3000, UnitAddrCompare::Exact), Some(&b"timer@3000"[..]));
+    assert_eq!(by_addr(0x2000, UnitAddrCompare::NumericHex), Some(&b"serial@0x2000"[..]));
+    assert_eq!(by_addr(1, UnitAddrCompare::NumericHex), None);
+    assert_eq!(by_addr(1, UnitAddrCompare::Lenient), None);
+}
+
+#[test]
+fn test_unit_address_compare_agree() {
+    let fdt = unit_names_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let soc = dt.get_node_by_path(b"/soc").unwrap();
+
+    /* A lookup by address finds what a lookup by the canonical name finds */
+    let lookups: [(u64, &[u8]); 5] = [
+        (0x1000, b"uart@1000"), (0x2000, b"serial@2000"), (0x3000, b"timer@3000"), (1, b"port@1"), (0, b"gpio@0")
+    ];
+    for compare in [UnitAddrCompare::Exact, UnitAddrCompare::NumericHex, UnitAddrCompare::Lenient] {
+        for (addr, name) in lookups {
+            let by_name = soc.get_node_with(name, compare).map(|node| node.name());
+            assert_eq!(soc.child_by_unit_address_with(addr, compare).map(|node| node.name()), by_name);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "alloc")]
+
+use static_dt_rs::{DeviceTree, Error, OwnedDeviceTree, RegEntry};
+
+mod common;
+static BOARD_FDT: &[u8] = include_bytes!("board.dtb");
+static INTERRUPTS_FDT: &[u8] = include_bytes!("interrupts.dtb");
+
+#[test]
+fn test_owned_device_tree() {
+    let owned = OwnedDeviceTree::new(common::test_blob().to_vec()).unwrap();
+    let dt = owned.tree();
+    assert_eq!(dt.blob(), common::test_blob());
+    assert_eq!(dt.root().counts(), DeviceTree::parse(common::test_blob()).unwrap().root().counts());
+
+    assert_eq!(OwnedDeviceTree::from_slice(BOARD_FDT).unwrap().blob(), BOARD_FDT);
+    assert_eq!(OwnedDeviceTree::new(&common::test_blob()[..20]).unwrap_err(), Error::Truncated);
+    assert_eq!(owned.into_blob().len(), common::test_blob().len());
+}
+
+#[test]
+fn test_alloc_helpers() {
+    let owned = OwnedDeviceTree::from_slice(BOARD_FDT).unwrap();
+    let dt = owned.tree();
+
+    let uart = dt.get_node_by_path(b"/soc/serial@10001000").unwrap();
+    assert_eq!(uart.full_path_string().unwrap(), "/soc/serial@10001000");
+    assert_eq!(dt.root().full_path_string().unwrap(), "/");
+    assert_eq!(uart.get_prop(b"reg").unwrap().full_path_string(), None);
+
+    assert_eq!(uart.get_prop(b"compatible").unwrap().prop_string_list_vec().unwrap(), vec![&b"test,uart"[..], b"ns16550a"]);
+    assert_eq!(uart.prop_string_list_vec(), None);
+
+    assert_eq!(uart.reg_vec().unwrap(), vec![RegEntry { address: 0x10001000, size: 0x100 }]);
+    assert_eq!(dt.root().reg_vec(), None);
+
+    let dt = DeviceTree::parse(INTERRUPTS_FDT).unwrap();
+    let keys = dt.get_node_by_path(b"/soc/keys@9040000").unwrap();
+    let interrupts: Vec<Vec<u32>> = keys.interrupts_vec().iter().map(|spec| spec.args().to_vec()).collect();
+    assert_eq!(interrupts, vec![vec![3, 1], vec![4, 2]]);
+}
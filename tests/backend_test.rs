@@ -0,0 +1,117 @@
+use std::cell::Cell;
+
+use static_dt_rs::{BackendError, DeviceTree, DeviceTreeOn, Error, FdtRead, RawToken, Token};
+
+mod common;
+static BOARD_FDT: &[u8] = include_bytes!("board.dtb");
+
+/// In-memory backend counting reads, failing once `fail_after` reads were done
+struct Flash<'a> {
+    data: &'a [u8],
+    reads: Cell<usize>,
+    fail_after: usize
+}
+
+impl<'a> Flash<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Flash { data, reads: Cell::new(0), fail_after: usize::MAX }
+    }
+}
+
+impl<'a> FdtRead for Flash<'a> {
+    type Error = &'static str;
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if self.reads.get() >= self.fail_after {
+            return Err("read error")
+        }
+        self.reads.set(self.reads.get() + 1);
+        self.data.read(offset, buf).map_err(|_| "unreachable")
+    }
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+}
+
+#[test]
+fn test_backend_tokens() {
+    for fdt in [common::test_blob(), BOARD_FDT] {
+        let dt = DeviceTree::parse(fdt).unwrap();
+        let on = DeviceTreeOn::parse(Flash::new(fdt)).unwrap();
+        assert_eq!(on.header().totalsize as usize, dt.totalsize());
+
+        let mut count = 0;
+        for (tok, raw) in dt.tokens().zip(on.tokens()) {
+            let raw = raw.unwrap();
+            let mut name = [0u8; 64];
+            let mut value = [0u8; 64];
+            match (tok, raw) {
+                (Token::BeginNode(_, _, s), RawToken::BeginNode { offset, .. }) => {
+                    assert_eq!(on.name(&raw, &mut name).unwrap(), s);
+                    assert_eq!(Some(offset), tok.node_offset());
+                },
+                (Token::Property(_, s, val), RawToken::Property { .. }) => {
+                    assert_eq!(on.name(&raw, &mut name).unwrap(), s);
+                    assert!(on.name_eq(&raw, s).unwrap());
+                    assert_eq!(on.value(&raw, &mut value).unwrap(), val);
+                },
+                (Token::EndNode, RawToken::EndNode) | (Token::End, RawToken::End) => (),
+                (tok, raw) => panic!("{:?} != {:?}", tok, raw)
+            }
+            count += 1;
+        }
+        assert_eq!(count, dt.tokens().count());
+        assert_eq!(on.tokens().count(), count);
+    }
+}
+
+#[test]
+fn test_backend_lookup() {
+    let dt = DeviceTree::parse(BOARD_FDT).unwrap();
+    let on = DeviceTreeOn::parse(Flash::new(BOARD_FDT)).unwrap();
+
+    for path in [&b"/"[..], b"/soc", b"/soc/serial@10000000", b"/soc/serial@10001000"] {
+        let node = dt.get_node_by_path(path).unwrap();
+        let offset = on.get_node_by_path(path).unwrap().unwrap();
+        assert_eq!(Some(offset), node.node_offset());
+
+        for prop in node.props() {
+            let raw = on.get_prop(offset, prop.name()).unwrap().unwrap();
+            let mut value = [0u8; 64];
+            if let Token::Property(_, _, val) = prop {
+                assert_eq!(on.value(&raw, &mut value).unwrap(), val);
+            }
+        }
+        assert_eq!(on.get_prop(offset, b"no-such-prop").unwrap(), None);
+    }
+
+    assert_eq!(on.get_node_by_path(b"/soc/serial").unwrap(), None);
+    assert_eq!(on.get_node_by_path(b"/soc/serial@10000000/x").unwrap(), None);
+    assert_eq!(on.get_node_by_path(b"soc").unwrap(), None);
+}
+
+#[test]
+fn test_backend_errors() {
+    let on = DeviceTreeOn::parse(Flash::new(BOARD_FDT)).unwrap();
+    let node = on.get_node_by_path(b"/soc/serial@10000000").unwrap().unwrap();
+    let compatible = on.get_prop(node, b"compatible").unwrap().unwrap();
+
+    let mut small = [0u8; 4];
+    assert_eq!(on.value(&compatible, &mut small), Err(BackendError::BufferTooSmall(9)));
+    assert!(matches!(on.name(&compatible, &mut small), Err(BackendError::BufferTooSmall(_))));
+
+    /* Storage errors are passed on */
+    let mut flash = Flash::new(BOARD_FDT);
+    flash.fail_after = 3;
+    let on = DeviceTreeOn::parse(flash).unwrap();
+    assert_eq!(on.get_node_by_path(b"/soc/serial@10000000"), Err(BackendError::Read("read error")));
+    let mut flash = Flash::new(BOARD_FDT);
+    flash.fail_after = 0;
+    assert!(matches!(DeviceTreeOn::parse(flash), Err(BackendError::Read("read error"))));
+
+    /* Header checks */
+    assert!(matches!(DeviceTreeOn::parse(&BOARD_FDT[..20]), Err(BackendError::Format(Error::Truncated))));
+    assert!(matches!(DeviceTreeOn::parse(&BOARD_FDT[..100]), Err(BackendError::Format(Error::Truncated))));
+    assert!(matches!(DeviceTreeOn::parse(include_bytes!("bad_magic.dtb") as &[u8]), Err(BackendError::Format(Error::InvalidMagic))));
+}
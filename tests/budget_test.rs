@@ -0,0 +1,106 @@
+use static_dt_rs::{Budget, BudgetError, DeviceTree, IndexError, PhandleEntry, Resume, ValidationError};
+
+mod common;
+static BOARD_FDT: &[u8] = include_bytes!("board.dtb");
+
+/// Steps taken by a budgeted validation running at once
+fn validate_steps(dt: &DeviceTree) -> usize {
+    let mut budget = Budget::new(usize::MAX);
+    let _ = dt.validate_budgeted(&mut budget, &mut Resume::new());
+    usize::MAX - budget.steps_remaining
+}
+
+/// Run `op` with budgets of `steps` until it finishes, returns the result and the number of installments
+fn installments<T, E>(steps: usize, mut op: impl FnMut(&mut Budget, &mut Resume) -> Result<T, BudgetError<E>>) -> (Result<T, E>, usize) {
+    let mut resume = Resume::new();
+    for n in 1.. {
+        match op(&mut Budget::new(steps), &mut resume) {
+            Ok(result) => return (Ok(result), n),
+            Err(BudgetError::Failed(e)) => return (Err(e), n),
+            Err(BudgetError::Exhausted { .. }) => ()
+        }
+    }
+    unreachable!()
+}
+
+#[test]
+fn test_validate_budgeted() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let total = validate_steps(&dt);
+    assert!(total > 3);
+
+    /* Three installments, each stopping further along */
+    let mut resume = Resume::new();
+    let mut budget = Budget::new(total / 3);
+    let first = match dt.validate_budgeted(&mut budget, &mut resume) {
+        Err(BudgetError::Exhausted { offset }) => offset,
+        other => panic!("{:?}", other)
+    };
+    assert!(budget.is_exhausted());
+    assert_eq!(resume.offset(), first);
+    let second = match dt.validate_budgeted(&mut Budget::new(total / 3), &mut resume) {
+        Err(BudgetError::Exhausted { offset }) => offset,
+        other => panic!("{:?}", other)
+    };
+    assert!(second > first);
+    let mut budget = Budget::new(total - 2*(total / 3));
+    assert_eq!(dt.validate_budgeted(&mut budget, &mut resume), Ok(()));
+    assert_eq!(budget.steps_remaining, 0);
+    assert_eq!(dt.validate(), Ok(()));
+    assert_eq!(resume, Resume::new());
+
+    /* One step short */
+    assert!(matches!(dt.validate_budgeted(&mut Budget::new(total - 1), &mut Resume::new()), Err(BudgetError::Exhausted { .. })));
+
+    /* Errors are the same as without a budget */
+    let mut buf = common::test_blob().to_vec();
+    buf[0x170..0x174].copy_from_slice(&[0, 0, 0, 0x0b]);
+    let dt = DeviceTree::parse(&buf).unwrap();
+    let expected = dt.validate();
+    assert!(matches!(expected, Err(ValidationError::UnknownToken { .. })));
+    assert_eq!(installments(validate_steps(&dt) / 3, |budget, resume| dt.validate_budgeted(budget, resume)).0, expected);
+}
+
+#[test]
+fn test_lookups_budgeted() {
+    let dt = DeviceTree::parse(BOARD_FDT).unwrap();
+    for compat in [&b"ns16550a"[..], b"test,uart", b"simple-bus", b"missing"] {
+        let (found, n) = installments(3, |budget, resume| dt.find_compatible_budgeted(compat, budget, resume));
+        assert_eq!(found, Ok(dt.find_compatible(compat)));
+        assert!(n > 1);
+    }
+
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let phandle = dt.get(b"/node1/child-node1/phandle").unwrap().prop_u32(0).unwrap();
+    let (found, n) = installments(2, |budget, resume| dt.get_phandle_budgeted(phandle, budget, resume));
+    assert_eq!(found, Ok(dt.get_phandle(phandle)));
+    assert!(found.unwrap().is_some() && n > 1);
+    assert_eq!(installments(2, |budget, resume| dt.get_phandle_budgeted(0, budget, resume)), (Ok(None), 1));
+
+    /* A position of another operation starts over */
+    let mut resume = Resume::new();
+    assert!(dt.validate_budgeted(&mut Budget::new(5), &mut resume).is_err());
+    assert_eq!(dt.get_phandle_budgeted(phandle, &mut Budget::new(usize::MAX), &mut resume), Ok(dt.get_phandle(phandle)));
+}
+
+#[test]
+fn test_phandle_index_budgeted() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let mut expected = [PhandleEntry::default(); 4];
+    let expected = dt.build_phandle_index(&mut expected).unwrap().entries().to_vec();
+
+    let mut storage = [PhandleEntry::default(); 4];
+    let mut resume = Resume::new();
+    let index = loop {
+        match dt.build_phandle_index_budgeted(&mut storage, &mut Budget::new(2), &mut resume) {
+            Ok(index) => break index,
+            Err(BudgetError::Exhausted { .. }) => (),
+            Err(BudgetError::Failed(e)) => panic!("{:?}", e)
+        }
+    };
+    assert_eq!(index.entries(), &expected[..]);
+
+    let mut empty: [PhandleEntry; 0] = [];
+    assert_eq!(installments(4, |budget, resume| dt.build_phandle_index_budgeted(&mut empty, budget, resume).map(|_| ())).0,
+               Err(IndexError::InsufficientStorage(expected.len())));
+}
@@ -0,0 +1,120 @@
+use static_dt_rs::{BuildError, BuildReport, DeviceTree, DtbBuilder, Error};
+
+mod common;
+
+static FDT: &[u8] = include_bytes!("test.dtb");
+
+#[test]
+fn test_builder_matches_dtc() {
+    /* The suites use the generated fixture, test.dtb only shows it matches what dtc emits */
+    assert_eq!(common::test_fdt(), FDT);
+}
+
+#[test]
+fn test_builder_v16() {
+    let fdt = common::v16_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(dt.version(), 16);
+    assert_eq!(dt.size_dt_struct(), None);
+    assert_eq!(dt.struct_block().len(), 0x13c);
+
+    let prop = dt.get_node_by_path(b"/node2").unwrap().get_prop(b"a-cell-property").unwrap();
+    assert_eq!(prop.prop_u32(3), Some(4));
+    assert_eq!(dt.get_phandle(1).unwrap().name(), b"child-node1");
+}
+
+#[test]
+fn test_builder_deep() {
+    let fdt = common::deep_fdt(64);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let deepest = dt.nodes().last().unwrap();
+    assert_eq!(deepest.get_prop(b"level").unwrap().prop_u32(0), Some(63));
+    assert_eq!(dt.nodes().count(), 65);
+}
+
+#[test]
+fn test_builder_huge_prop() {
+    let fdt = common::huge_prop_fdt(100_001);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let prop = dt.root().get_prop(b"data").unwrap();
+    assert_eq!(prop.prop_len(), Some(100_001));
+    assert_eq!(prop.prop_u8(100_000), Some(100_000u32 as u8));
+    assert_eq!(prop.prop_u8(100_001), None);
+}
+
+#[test]
+fn test_builder_broken() {
+    assert_eq!(DeviceTree::parse(&common::bad_magic_fdt()).unwrap_err(), Error::InvalidMagic);
+    assert_eq!(DeviceTree::parse(&common::strings_out_of_bounds_fdt()).unwrap_err(), Error::BlockOutOfBounds);
+    assert_eq!(DeviceTree::parse(&common::no_root_fdt()).unwrap_err(), Error::NoRootNode);
+    assert_eq!(DeviceTree::parse(&common::test_fdt()[..200]).unwrap_err(), Error::Truncated);
+}
+
+#[test]
+fn test_builder_errors() {
+    let mut buf = [0u8; 128];
+    let mut builder = DtbBuilder::new(&mut buf);
+    assert_eq!(builder.prop_u32(b"x", 1), Err(BuildError::InvalidState));
+    assert_eq!(builder.end_node(), Err(BuildError::InvalidState));
+    builder.add_reservation(0x1000, 0x100).unwrap();
+    builder.begin_node(b"").unwrap();
+    assert_eq!(builder.add_reservation(0, 0), Err(BuildError::InvalidState));
+    assert_eq!(builder.begin_node(b"a\0b"), Err(BuildError::InvalidName));
+    assert_eq!(builder.property(b"big", &[0; 128]), Err(BuildError::NoSpace));
+
+    /* A failed property leaves the structure block untouched, only its name is kept */
+    let len = builder.len();
+    assert_eq!(builder.prop_str(b"s", &[b'x'; 100]), Err(BuildError::NoSpace));
+    assert_eq!(builder.len(), len + 2);
+    builder.prop_u32(b"x", 1).unwrap();
+    builder.prop_u32(b"x", 2).unwrap();
+    builder.end_node().unwrap();
+    assert_eq!(builder.begin_node(b"second-root"), Err(BuildError::InvalidState));
+
+    let fdt = builder.finish().unwrap();
+    let dt = DeviceTree::parse(fdt).unwrap();
+    /* Both properties share one name, the string from the failed prop_str() remains */
+    assert_eq!(dt.strings_block(), b"big\0s\0x\0");
+    assert_eq!(dt.root().props().count(), 2);
+}
+
+/// 25 nodes with 20 properties each, all nodes using the same 20 names
+fn build_repeated<const N: usize>(buf: &mut [u8]) -> (Vec<u8>, BuildReport) {
+    let mut builder = DtbBuilder::<N>::with_string_index(buf);
+    builder.begin_node(b"").unwrap();
+    for node in 0..25 {
+        builder.begin_node(format!("node@{}", node).as_bytes()).unwrap();
+        for prop in 0..20u32 {
+            builder.prop_u32(format!("prop-{}", prop).as_bytes(), node * 20 + prop).unwrap();
+        }
+        builder.end_node().unwrap();
+    }
+    builder.end_node().unwrap();
+    let (fdt, report) = builder.finish_with_report().unwrap();
+    (fdt.to_vec(), report)
+}
+
+#[test]
+fn test_builder_string_index() {
+    let mut buf = vec![0u8; 16384];
+    let (fdt, report) = build_repeated::<{ static_dt_rs::STRING_INDEX_CAPACITY }>(&mut buf);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(dt.strings().count(), 20);
+    assert_eq!(report, BuildReport { totalsize: fdt.len(), strings: 20, unindexed: 0, string_compares: report.string_compares });
+    assert!(!report.index_overflowed());
+    /* One compare for each of the 480 repeated names */
+    assert!(report.string_compares <= 500, "{:?}", report);
+    for prop in 0..20 {
+        assert_eq!(dt.props_named(format!("prop-{}", prop).as_bytes()).count(), 25);
+    }
+
+    /* An index too small falls back to scanning with the same result */
+    let (small, report) = build_repeated::<4>(&mut buf);
+    assert_eq!(small, fdt);
+    assert_eq!((report.strings, report.unindexed), (20, 16));
+    assert!(report.index_overflowed());
+    assert!(report.string_compares > 500);
+    let (none, report) = build_repeated::<0>(&mut buf);
+    assert_eq!(none, fdt);
+    assert_eq!(report.unindexed, 20);
+}
@@ -0,0 +1,85 @@
+use static_dt_rs::{DeviceTree, IrqSpec, RangeEntry, RegEntry, Token, MAX_BUS_WINDOWS};
+
+mod common;
+
+/// Decode every child of `bus` with and without a `BusContext` and compare,
+/// returns the translated addresses
+fn compare_children(bus: Token) -> Vec<Option<u64>> {
+    let ctx = bus.bus_context().unwrap();
+    let mut addresses = Vec::new();
+    for child in bus.children() {
+        let cached: Option<Vec<RegEntry>> = child.reg_in(&ctx).map(|reg| reg.collect());
+        let uncached: Option<Vec<RegEntry>> = child.reg().map(|reg| reg.collect());
+        assert_eq!(cached, uncached);
+
+        for reg in cached.unwrap_or_default() {
+            let translated = ctx.translate(reg.address);
+            assert_eq!(translated, child.translate_address(reg.address), "{:x}", reg.address);
+            addresses.push(translated);
+        }
+
+        let cached: Vec<IrqSpec> = child.irqs_in(&ctx).collect();
+        let uncached: Vec<IrqSpec> = child.irqs().collect();
+        assert_eq!(cached, uncached);
+    }
+    addresses
+}
+
+#[test]
+fn test_bus_context() {
+    let fdt = common::uart_bus_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    let bus = dt.get_node_by_path("/soc/bus@10000").unwrap();
+    let ctx = bus.bus_context().unwrap();
+    assert_eq!(ctx.bus(), bus);
+    assert_eq!(ctx.interrupt_parent(), dt.get_phandle(1));
+    assert_eq!(ctx.windows(), Some(&[
+        RangeEntry { child_address: 0x0, parent_address: 0x40010000, size: 0x20000 },
+        RangeEntry { child_address: 0x80000, parent_address: 0x80010000, size: 0x1000 },
+    ][..]));
+
+    let addresses = compare_children(bus);
+    assert_eq!(addresses.len(), 16 + 2 + 1);
+    assert_eq!(addresses[0], Some(0x40010000));
+    assert_eq!(addresses[15], Some(0x4001f000));
+    assert_eq!(&addresses[16..], &[Some(0x80010000), Some(0x80010800), None]);
+
+    /* Own interrupt-parent and interrupts-extended are still honored */
+    let uart = |n: usize| bus.children().nth(n).unwrap();
+    let irq = uart(3).irqs_in(&ctx).next().unwrap();
+    assert_eq!((*irq.raw().0, irq.raw().1), (dt.get_phandle(2).unwrap(), &[7][..]));
+    assert_eq!(uart(5).irqs_in(&ctx).count(), 2);
+    let irq = uart(7).irqs_in(&ctx).next().unwrap();
+    assert_eq!(irq.raw().1, &[0, 39, 4]);
+}
+
+#[test]
+fn test_bus_context_fallbacks() {
+    let fdt = common::uart_bus_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* More windows than kept, translated by walking */
+    let wide = dt.get_node_by_path("/wide").unwrap();
+    assert!(wide.ranges().unwrap().count() > MAX_BUS_WINDOWS);
+    assert_eq!(wide.bus_context().unwrap().windows(), None);
+    assert_eq!(compare_children(wide), vec![Some(0x90000010), Some(0x90040010), Some(0x90090010), None]);
+
+    /* Not mapped */
+    let isolated = dt.get_node_by_path("/isolated").unwrap();
+    assert_eq!(isolated.bus_context().unwrap().windows(), None);
+    assert_eq!(compare_children(isolated), vec![None]);
+
+    /* The root is the CPU address space */
+    let root = dt.root().bus_context().unwrap();
+    assert_eq!(root.translate(0x1234), Some(0x1234));
+    compare_children(dt.root());
+    compare_children(dt.get_node_by_path("/soc").unwrap());
+
+    let fdt = common::soc_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    compare_children(dt.get_node_by_path("/soc").unwrap());
+    compare_children(dt.get_node_by_path("/soc/bus@100000").unwrap());
+
+    assert!(dt.root().get_prop("#address-cells").unwrap().bus_context().is_none());
+}
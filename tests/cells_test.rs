@@ -0,0 +1,110 @@
+use static_dt_rs::{DeviceTree, DtWarnSink, PropError, Token, Warning};
+
+mod common;
+
+#[derive(Default)]
+struct Collect(Vec<Warning>);
+
+impl DtWarnSink for Collect {
+    fn warn(&mut self, w: Warning) {
+        self.0.push(w)
+    }
+}
+
+#[test]
+fn test_cells_lenient() {
+    let fdt = common::odd_cells_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let reg = dt.get(b"/device@1000/reg").unwrap();
+
+    /* The incomplete cell at the end is ignored */
+    assert_eq!(reg.prop_u32(0), Some(0x1000));
+    assert_eq!(reg.prop_u32(1), None);
+    assert_eq!(reg.prop_cells().collect::<Vec<_>>(), vec![0x1000]);
+    assert_eq!(reg.prop_cells().len(), 1);
+
+    let interrupts = dt.get(b"/device@1000/interrupts").unwrap();
+    assert_eq!(interrupts.prop_cells().collect::<Vec<_>>(), vec![5, 4]);
+    assert_eq!(dt.root().prop_cells().count(), 0);
+}
+
+#[test]
+fn test_cells_strict() {
+    let fdt = common::odd_cells_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let reg = dt.get(b"/device@1000/reg").unwrap();
+
+    let trailing = PropError::TrailingBytes { offset: reg.value_offset().unwrap(), count: 2 };
+    assert_eq!(reg.prop_cell_count(), Err(trailing));
+    assert!(matches!(reg.prop_cells_strict(), Err(e) if e == trailing));
+    assert_eq!(reg.prop_u32_strict(0), Err(trailing));
+
+    let interrupts = dt.get(b"/device@1000/interrupts").unwrap();
+    assert_eq!(interrupts.prop_cell_count(), Ok(2));
+    assert_eq!(interrupts.prop_cells_strict().unwrap().collect::<Vec<_>>(), vec![5, 4]);
+    assert_eq!(interrupts.prop_u32_strict(1), Ok(4));
+    assert_eq!(interrupts.prop_u32_strict(2), Err(PropError::OutOfRange));
+
+    assert_eq!(dt.root().prop_cell_count(), Err(PropError::NotAProperty));
+    assert_eq!(Token::EndNode.prop_u32_strict(0), Err(PropError::NotAProperty));
+}
+
+#[test]
+fn test_cells_exact() {
+    let fdt = common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_cells(b"one", &[1]).unwrap();
+        builder.prop_cells(b"two", &[0x1000, 0x100]).unwrap();
+        builder.prop_cells(b"three", &[0x8200_0000, 0, 0x4000_0000]).unwrap();
+        builder.prop_cells(b"four", &[0, 0x8000_0000, 0, 0x1000_0000]).unwrap();
+        builder.property(b"odd", &[0, 0, 0, 1, 0, 0]).unwrap();
+        builder.prop_empty(b"empty").unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let prop = |name: &str| dt.root().get_prop(name).unwrap();
+
+    assert_eq!(prop("one").cells_exact::<1>(), Some([1]));
+    assert_eq!(prop("two").cells_exact::<2>(), Some([0x1000, 0x100]));
+    assert_eq!(prop("three").cells_exact::<3>(), Some([0x8200_0000, 0, 0x4000_0000]));
+    assert_eq!(prop("four").cells_exact::<4>(), Some([0, 0x8000_0000, 0, 0x1000_0000]));
+    assert_eq!(prop("empty").cells_exact::<0>(), Some([]));
+
+    /* Too short, too long or not whole cells */
+    assert_eq!(prop("two").cells_exact::<3>(), None);
+    assert_eq!(prop("one").cells_exact::<2>(), None);
+    assert_eq!(prop("four").cells_exact::<3>(), None);
+    assert_eq!(prop("odd").cells_exact::<1>(), None);
+    assert_eq!(prop("odd").cells_exact::<2>(), None);
+    assert_eq!(prop("empty").cells_exact::<1>(), None);
+    assert_eq!(dt.root().cells_exact::<1>(), None);
+
+    /* Values are aligned within the structure block */
+    for tok in dt.root().props() {
+        assert_eq!(tok.value_offset().unwrap() % 4, 0);
+        assert_eq!(tok.value(), Some(&dt.struct_block()[tok.value_offset().unwrap()..][..tok.prop_len().unwrap()]));
+    }
+    assert_eq!(dt.root().value(), None);
+}
+
+#[test]
+fn test_cells_lint() {
+    let fdt = common::odd_cells_fdt();
+    let mut sink = Collect::default();
+    let dt = DeviceTree::parse_with_warnings(&fdt, &mut sink).unwrap();
+    let device = dt.get_node_by_path(b"/device@1000").unwrap();
+    let reg = device.get_prop(b"reg").unwrap();
+    let (nameoff, _) = dt.strings().find(|(_, s)| *s == b"reg").unwrap();
+
+    /* Only reg, the MAC address and label are not cell properties */
+    let offset = match reg {
+        Token::Property(_, _, val) => val.as_ptr() as usize - dt.struct_block().as_ptr() as usize - 12,
+        _ => unreachable!()
+    };
+    assert_eq!(sink.0, vec![Warning::TrailingBytes {
+        offset,
+        node_offset: device.node_offset().unwrap(),
+        nameoff,
+        count: 2
+    }]);
+}
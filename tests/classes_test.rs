@@ -0,0 +1,58 @@
+use static_dt_rs::classes::{device_class_table, DeviceClass};
+use static_dt_rs::DeviceTree;
+
+mod common;
+
+#[test]
+fn test_serial_ports() {
+    let fdt = common::riscv_virt_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* The disabled sifive,uart0 and the unknown vendor,uart are skipped */
+    let uarts: Vec<_> = dt.serial_ports().collect();
+    assert_eq!(uarts.len(), 1);
+    assert_eq!(uarts[0].node.name(), b"serial@10000000");
+    assert_eq!(uarts[0].compatible, Some(&b"ns16550a"[..]));
+    assert_eq!(uarts[0].base, Some(0x10000000));
+    assert_eq!(uarts[0].interrupt.unwrap().args(), &[10]);
+
+    /* Plausible by name */
+    let names: Vec<&[u8]> = dt.devices_of_class(DeviceClass::Serial, true).map(|dev| dev.node.name()).collect();
+    assert_eq!(names, [&b"serial@10000000"[..], b"uart@10001000"]);
+}
+
+#[test]
+fn test_timers() {
+    let fdt = common::riscv_virt_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    let timers: Vec<_> = dt.timers().collect();
+    assert_eq!(timers.len(), 1);
+    assert_eq!(timers[0].node.name(), b"clint@2000000");
+    assert_eq!(timers[0].base, Some(0x2000000));
+    assert_eq!(timers[0].size, Some(0x10000));
+    assert!(timers[0].interrupt.is_none());
+
+    let names: Vec<&[u8]> = dt.devices_of_class(DeviceClass::Timer, true).map(|dev| dev.node.name()).collect();
+    assert_eq!(names, [&b"clint@2000000"[..], b"timer@10003000"]);
+}
+
+#[test]
+fn test_device_class_table() {
+    let fdt = common::riscv_virt_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let class = |path: &[u8]| dt.get_node_by_path(path).unwrap().device_class();
+
+    assert_eq!(class(b"/soc/clint@2000000"), Some(DeviceClass::Timer));
+    assert_eq!(class(b"/soc/serial@10002000"), Some(DeviceClass::Serial));
+    assert_eq!(class(b"/soc/uart@10001000"), None);
+    assert_eq!(class(b"/soc/rtc@101000"), None);
+
+    /* The table works with the generic matcher, each string listed once */
+    let table = device_class_table();
+    let clint = dt.get_node_by_path(b"/soc/clint@2000000").unwrap();
+    assert_eq!(clint.match_compatible(table).unwrap().compatible, b"sifive,clint0");
+    for (i, entry) in table.iter().enumerate() {
+        assert!(table[..i].iter().all(|other| other.compatible != entry.compatible));
+    }
+}
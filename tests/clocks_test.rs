@@ -0,0 +1,56 @@
+use static_dt_rs::DeviceTree;
+
+mod common;
+
+#[test]
+fn test_clock_providers() {
+    let fdt = common::clocks_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let osc = dt.get_node_by_path(b"/osc").unwrap();
+    let factor = dt.get_node_by_path(b"/factor").unwrap();
+    let pll = dt.get_node_by_path(b"/pll").unwrap();
+
+    assert_eq!(osc.clock_output_names().collect::<Vec<_>>(), vec![&b"osc24m"[..]]);
+    assert_eq!(pll.clock_output_names().collect::<Vec<_>>(), vec![&b"pll-cpu"[..], b"pll-periph"]);
+    assert_eq!(factor.clock_output_names().count(), 0);
+
+    assert_eq!(factor.fixed_factor(), Some((5, 2)));
+    assert_eq!(osc.fixed_factor(), None);
+}
+
+#[test]
+fn test_clock_rate_simple() {
+    let fdt = common::clocks_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let rate = |path: &[u8]| dt.get_node_by_path(path).unwrap().clock_rate_simple();
+
+    /* osc -> factor -> uart */
+    assert_eq!(rate(b"/osc"), Some(24_000_000));
+    assert_eq!(rate(b"/factor"), Some(60_000_000));
+    assert_eq!(rate(b"/uart"), Some(60_000_000));
+
+    /* The PLL is not a fixed clock */
+    assert_eq!(rate(b"/pll"), None);
+    assert_eq!(rate(b"/pll-factor"), None);
+    assert_eq!(rate(b"/timer"), None);
+    assert_eq!(dt.root().clock_rate_simple(), None);
+}
+
+#[test]
+fn test_clock_rate_loop() {
+    /* A fixed factor clock feeding itself stops after MAX_CLOCK_CHAIN providers */
+    let fdt = common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"loop").unwrap();
+        builder.prop_str(b"compatible", b"fixed-factor-clock").unwrap();
+        builder.prop_u32(b"#clock-cells", 0).unwrap();
+        builder.prop_u32(b"clocks", 1).unwrap();
+        builder.prop_u32(b"clock-mult", 1).unwrap();
+        builder.prop_u32(b"clock-div", 1).unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(dt.get_node_by_path(b"/loop").unwrap().clock_rate_simple(), None);
+}
@@ -0,0 +1,999 @@
+//! Fixture trees generated with `DtbBuilder`, shared by the integration tests.
+//! Each test binary only uses some of them.
+#![allow(dead_code)]
+
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use static_dt_rs::{DtbBuilder, raw};
+
+/// Run the tests of a binary which reads the global `instrument` counter one at a time:
+/// every test takes this lock first so no other test decodes tokens meanwhile
+pub fn serial() -> MutexGuard<'static, ()> {
+    static SERIAL: Mutex<()> = Mutex::new(());
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Build a blob with `f` in a buffer of `size` bytes
+pub fn build<F>(size: usize, f: F) -> Vec<u8>
+    where F: FnOnce(&mut DtbBuilder)
+{
+    let mut buf = vec![0u8; size];
+    let mut builder = DtbBuilder::new(&mut buf);
+    f(&mut builder);
+    builder.finish().unwrap().to_vec()
+}
+
+fn canonical(builder: &mut DtbBuilder) {
+    builder.begin_node(b"").unwrap();
+
+    builder.begin_node(b"node1").unwrap();
+    builder.prop_str(b"a-string-property", b"A string").unwrap();
+    builder.property(b"a-string-list-property", b"first string\0second string\0").unwrap();
+    builder.property(b"a-byte-data-property", &[0x01, 0x23, 0x34, 0x56]).unwrap();
+    builder.begin_node(b"child-node1").unwrap();
+    builder.prop_empty(b"first-child-property").unwrap();
+    builder.prop_u32(b"second-child-property", 1).unwrap();
+    builder.prop_str(b"a-string-property", b"Hello, world").unwrap();
+    builder.prop_u32(b"phandle", 1).unwrap();
+    builder.end_node().unwrap();
+    builder.begin_node(b"child-node2").unwrap();
+    builder.end_node().unwrap();
+    builder.end_node().unwrap();
+
+    builder.begin_node(b"node2").unwrap();
+    builder.prop_empty(b"an-empty-property").unwrap();
+    builder.prop_cells(b"a-cell-property", &[1, 2, 3, 4]).unwrap();
+    builder.prop_u32(b"a-phandle-property", 1).unwrap();
+    builder.begin_node(b"child-node1").unwrap();
+    builder.end_node().unwrap();
+    builder.end_node().unwrap();
+
+    builder.end_node().unwrap();
+}
+
+/// The tree of test.dts, byte for byte what dtc emits for it
+pub fn test_fdt() -> Vec<u8> {
+    build(1024, canonical)
+}
+
+/// `test_fdt()` built once, for suites which share it between tests
+pub fn test_blob() -> &'static [u8] {
+    static BLOB: OnceLock<Vec<u8>> = OnceLock::new();
+    BLOB.get_or_init(test_fdt)
+}
+
+/// The tree of test.dts with a version 16 header, without `size_dt_struct`
+pub fn v16_fdt() -> Vec<u8> {
+    build(1024, |builder| {
+        builder.set_version(16);
+        canonical(builder)
+    })
+}
+
+/// A chain of `depth` nodes named "sub" below the root, each with a `level` property
+pub fn deep_fdt(depth: usize) -> Vec<u8> {
+    build(64 + depth * 32, |builder| {
+        builder.begin_node(b"").unwrap();
+        for level in 0..depth {
+            builder.begin_node(b"sub").unwrap();
+            builder.prop_u32(b"level", level as u32).unwrap();
+        }
+        for _ in 0..depth + 1 {
+            builder.end_node().unwrap();
+        }
+    })
+}
+
+/// `/bus` with two properties and three `child@N` nodes, each the top of
+/// a chain of 16 `sub` nodes with a `level` and `label` property, ending in a `leaf`
+pub fn deep_bus_fdt() -> Vec<u8> {
+    build(4096, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"bus").unwrap();
+        builder.prop_str(b"compatible", b"test,bus").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        for child in [&b"child@0"[..], b"child@1", b"child@2"] {
+            builder.begin_node(child).unwrap();
+            for level in (1..=16).rev() {
+                if level != 16 {
+                    builder.begin_node(b"sub").unwrap();
+                }
+                builder.prop_u32(b"level", level).unwrap();
+                builder.prop_str(b"label", b"deep").unwrap();
+            }
+            builder.begin_node(b"sub").unwrap();
+            builder.prop_u32(b"leaf", 1).unwrap();
+            for _ in 0..17 {
+                builder.end_node().unwrap();
+            }
+        }
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// Properties after closed sub-nodes, which dtc never emits: `/parent` has `first`, the node
+/// `child` (with `inner` after its sub-node `grandchild`), then `phandle` and `last`
+pub fn attribution_fdt() -> Vec<u8> {
+    build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"parent").unwrap();
+        builder.prop_u32(b"first", 1).unwrap();
+        builder.begin_node(b"child").unwrap();
+        builder.begin_node(b"grandchild").unwrap();
+        builder.prop_u32(b"deep", 3).unwrap();
+        builder.end_node().unwrap();
+        builder.prop_u32(b"inner", 2).unwrap();
+        builder.end_node().unwrap();
+        builder.prop_u32(b"phandle", 5).unwrap();
+        builder.prop_str(b"last", b"parent").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"other").unwrap();
+        builder.prop_u32(b"phandle", 6).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// `test_fdt()` with two NOPs before the root node
+pub fn leading_nop_fdt() -> Vec<u8> {
+    let mut fdt = test_fdt();
+    let header = |fdt: &[u8], field: usize| u32::from_be_bytes([fdt[field], fdt[field + 1], fdt[field + 2], fdt[field + 3]]);
+    let structs = header(&fdt, raw::OFF_DT_STRUCT) as usize;
+    let nops = [raw::FDT_NOP.to_be_bytes(), raw::FDT_NOP.to_be_bytes()].concat();
+    fdt.splice(structs..structs, nops.iter().copied());
+    for field in [raw::OFF_TOTALSIZE, raw::OFF_DT_STRINGS, raw::OFF_SIZE_DT_STRUCT] {
+        let value = header(&fdt, field) + nops.len() as u32;
+        fdt[field..field + 4].copy_from_slice(&value.to_be_bytes());
+    }
+    fdt
+}
+
+/// A root node with a single property `data` of `len` bytes, byte `i` being `i as u8`
+pub fn huge_prop_fdt(len: usize) -> Vec<u8> {
+    let value: Vec<u8> = (0..len).map(|i| i as u8).collect();
+    build(128 + len, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.property(b"data", &value).unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// `test_fdt()` with the header field at `offset` set to `value`
+pub fn patched_fdt(offset: usize, value: u32) -> Vec<u8> {
+    let mut fdt = test_fdt();
+    fdt[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    fdt
+}
+
+/// `test_fdt()` with a corrupted magic
+pub fn bad_magic_fdt() -> Vec<u8> {
+    patched_fdt(raw::OFF_MAGIC, 0xdeadbeef)
+}
+
+/// `test_fdt()` with the strings block moved past `totalsize`
+pub fn strings_out_of_bounds_fdt() -> Vec<u8> {
+    let fdt = test_fdt();
+    let off = fdt.len() as u32;
+    patched_fdt(raw::OFF_DT_STRINGS, off)
+}
+
+/// `test_fdt()` with a structure block beginning with END_NODE instead of the root node
+pub fn no_root_fdt() -> Vec<u8> {
+    let mut fdt = test_fdt();
+    let off = u32::from_be_bytes([fdt[8], fdt[9], fdt[10], fdt[11]]) as usize;
+    fdt[off..off + 4].copy_from_slice(&raw::FDT_END_NODE.to_be_bytes());
+    fdt
+}
+
+/// A node with one property for each `PropValue` classification
+pub fn classify_fdt() -> Vec<u8> {
+    build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"values").unwrap();
+        builder.prop_empty(b"empty").unwrap();
+        builder.prop_str(b"string", b"say \"hi\" \\o/").unwrap();
+        builder.property(b"string-list", b"first\0second\0third\0").unwrap();
+        builder.prop_u32(b"cell", 0xdeadbeef).unwrap();
+        builder.prop_cells(b"cells", &[0, 1, 0x80000000]).unwrap();
+        builder.property(b"bytes", &[0x01, 0x02, 0x03]).unwrap();
+        builder.property(b"not-a-string", b"bin\x01\0").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// An I2C bus with two addressed children, the second with a `reg` differing from its name, one unaddressed child,
+/// a 10-bit address, an own slave address and a disabled child
+pub fn i2c_fdt() -> Vec<u8> {
+    build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"i2c@1000").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 0).unwrap();
+        builder.begin_node(b"eeprom@50").unwrap();
+        builder.prop_u32(b"reg", 0x50).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"sensor@1a").unwrap();
+        builder.prop_u32(b"reg", 0x1b).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"mux").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"adc@a0").unwrap();
+        builder.prop_u32(b"reg", 0x800000a0).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"slave@64").unwrap();
+        builder.prop_u32(b"reg", 0x40000064).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"rtc@68").unwrap();
+        builder.prop_u32(b"reg", 0x68).unwrap();
+        builder.prop_str(b"status", b"disabled").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// An SPI bus with one child per mode marker, a child without markers or frequency,
+/// one with all markers, a disabled child and a child without `reg`
+pub fn spi_fdt() -> Vec<u8> {
+    build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"spi@2000").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 0).unwrap();
+        let markers: [&[u8]; 4] = [b"spi-cpha", b"spi-cpol", b"spi-cs-high", b"spi-3wire"];
+        for (cs, marker) in markers.iter().enumerate() {
+            builder.begin_node(format!("dev@{}", cs).as_bytes()).unwrap();
+            builder.prop_u32(b"reg", cs as u32).unwrap();
+            builder.prop_u32(b"spi-max-frequency", 1_000_000 * (cs as u32 + 1)).unwrap();
+            builder.prop_empty(marker).unwrap();
+            builder.end_node().unwrap();
+        }
+        builder.begin_node(b"plain@4").unwrap();
+        builder.prop_u32(b"reg", 4).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"all@5").unwrap();
+        builder.prop_u32(b"reg", 5).unwrap();
+        for marker in markers.iter() {
+            builder.prop_empty(marker).unwrap();
+        }
+        builder.end_node().unwrap();
+        builder.begin_node(b"off@6").unwrap();
+        builder.prop_u32(b"reg", 6).unwrap();
+        builder.prop_str(b"status", b"disabled").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"no-reg").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// A SoC with a GIC, a translating `simple-bus` with devices, a disabled device,
+/// a device without properties and a nested bus with its own translation
+pub fn soc_fdt() -> Vec<u8> {
+    build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_u32(b"interrupt-parent", 1).unwrap();
+
+        builder.begin_node(b"interrupt-controller@8000000").unwrap();
+        builder.prop_str(b"compatible", b"arm,gic-400").unwrap();
+        builder.prop_cells(b"reg", &[0x8000000, 0x10000]).unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 3).unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"soc").unwrap();
+        builder.property(b"compatible", b"vendor,soc\0simple-bus\0").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_cells(b"ranges", &[0x0, 0x40000000, 0x10000000]).unwrap();
+
+        builder.begin_node(b"serial@1000").unwrap();
+        builder.property(b"compatible", b"vendor,uart\0ns16550a\0").unwrap();
+        builder.prop_cells(b"reg", &[0x1000, 0x100]).unwrap();
+        builder.prop_cells(b"interrupts", &[0, 33, 4]).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"timer@2000").unwrap();
+        builder.prop_str(b"compatible", b"vendor,timer").unwrap();
+        builder.prop_cells(b"reg", &[0x2000, 0x40]).unwrap();
+        builder.prop_str(b"status", b"disabled").unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"bare").unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"bus@100000").unwrap();
+        builder.prop_str(b"compatible", b"simple-bus").unwrap();
+        builder.prop_cells(b"reg", &[0x100000, 0x10000]).unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_cells(b"ranges", &[0x0, 0x100000, 0x10000]).unwrap();
+
+        builder.begin_node(b"i2c@800").unwrap();
+        builder.prop_str(b"compatible", b"vendor,i2c").unwrap();
+        builder.prop_cells(b"reg", &[0x800, 0x80]).unwrap();
+        builder.prop_cells(b"interrupts", &[0, 40, 4]).unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// A GIC, a GPIO interrupt controller with `linux,phandle` and a PCI interrupt nexus
+pub fn nexus_fdt() -> Vec<u8> {
+    build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_u32(b"interrupt-parent", 1).unwrap();
+
+        builder.begin_node(b"interrupt-controller@8000000").unwrap();
+        builder.prop_str(b"compatible", b"arm,gic-400").unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 3).unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"gpio@9030000").unwrap();
+        builder.prop_empty(b"gpio-controller").unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 2).unwrap();
+        builder.prop_u32(b"linux,phandle", 2).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"pcie@10000000").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 1).unwrap();
+        builder.prop_cells(b"interrupt-map-mask", &[0, 0, 0, 7]).unwrap();
+        builder.prop_cells(b"interrupt-map", &[0, 0, 0, 1, 1, 0, 100, 4]).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"uart@9000000").unwrap();
+        builder.prop_cells(b"interrupts", &[0, 1, 4]).unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+    })
+}
+
+/// A 24 MHz oscillator, a fixed factor clock of 5/2 fed by it, a PLL and consumers of each
+pub fn clocks_fdt() -> Vec<u8> {
+    build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+
+        builder.begin_node(b"osc").unwrap();
+        builder.prop_str(b"compatible", b"fixed-clock").unwrap();
+        builder.prop_u32(b"#clock-cells", 0).unwrap();
+        builder.prop_u32(b"clock-frequency", 24_000_000).unwrap();
+        builder.prop_str(b"clock-output-names", b"osc24m").unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"factor").unwrap();
+        builder.prop_str(b"compatible", b"fixed-factor-clock").unwrap();
+        builder.prop_u32(b"#clock-cells", 0).unwrap();
+        builder.prop_u32(b"clocks", 1).unwrap();
+        builder.prop_u32(b"clock-mult", 5).unwrap();
+        builder.prop_u32(b"clock-div", 2).unwrap();
+        builder.prop_u32(b"phandle", 2).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"pll").unwrap();
+        builder.prop_str(b"compatible", b"vendor,pll").unwrap();
+        builder.prop_u32(b"#clock-cells", 1).unwrap();
+        builder.prop_u32(b"clocks", 1).unwrap();
+        builder.property(b"clock-output-names", b"pll-cpu\0pll-periph\0").unwrap();
+        builder.prop_u32(b"phandle", 3).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"pll-factor").unwrap();
+        builder.prop_str(b"compatible", b"fixed-factor-clock").unwrap();
+        builder.prop_u32(b"#clock-cells", 0).unwrap();
+        builder.prop_cells(b"clocks", &[3, 1]).unwrap();
+        builder.prop_u32(b"clock-mult", 1).unwrap();
+        builder.prop_u32(b"clock-div", 4).unwrap();
+        builder.prop_u32(b"phandle", 4).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"uart").unwrap();
+        builder.prop_u32(b"clocks", 2).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"timer").unwrap();
+        builder.prop_u32(b"clocks", 4).unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+    })
+}
+
+/// An eeprom with a MAC address and a calibration cell, and consumers of them
+pub fn nvmem_fdt() -> Vec<u8> {
+    build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+
+        builder.begin_node(b"eeprom@50").unwrap();
+        builder.prop_str(b"compatible", b"atmel,24c32").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.begin_node(b"mac@0").unwrap();
+        builder.prop_cells(b"reg", &[0x0, 0x6]).unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"calib@10").unwrap();
+        builder.prop_cells(b"reg", &[0x10, 0x2]).unwrap();
+        builder.prop_cells(b"bits", &[2, 10]).unwrap();
+        builder.prop_u32(b"phandle", 2).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"no-reg").unwrap();
+        builder.prop_u32(b"phandle", 3).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"ethernet").unwrap();
+        builder.prop_cells(b"nvmem-cells", &[1, 2]).unwrap();
+        builder.property(b"nvmem-cell-names", b"mac-address\0calibration\0").unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"mismatched").unwrap();
+        builder.prop_cells(b"nvmem-cells", &[1, 2]).unwrap();
+        builder.prop_str(b"nvmem-cell-names", b"mac-address").unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"broken").unwrap();
+        builder.prop_cells(b"nvmem-cells", &[3, 9]).unwrap();
+        builder.property(b"nvmem-cell-names", b"no-reg\0missing\0").unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+    })
+}
+
+/// A cpu with legacy `operating-points`, the last pair is incomplete
+pub fn opp_legacy_fdt() -> Vec<u8> {
+    build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"cpus").unwrap();
+        builder.begin_node(b"cpu@0").unwrap();
+        builder.prop_cells(b"operating-points", &[
+            1_200_000, 1_250_000,
+            800_000, 1_100_000,
+            400_000, 950_000,
+            200_000,
+        ]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// Two cpus sharing a `operating-points-v2` table
+pub fn opp_v2_fdt() -> Vec<u8> {
+    build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+
+        builder.begin_node(b"opp-table").unwrap();
+        builder.prop_str(b"compatible", b"operating-points-v2").unwrap();
+        builder.prop_empty(b"opp-shared").unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.begin_node(b"opp-400000000").unwrap();
+        builder.prop_u64(b"opp-hz", 400_000_000).unwrap();
+        builder.prop_u32(b"opp-microvolt", 950_000).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"opp-2000000000").unwrap();
+        builder.prop_u64(b"opp-hz", 2_000_000_000).unwrap();
+        builder.prop_cells(b"opp-microvolt", &[1_300_000, 1_250_000, 1_350_000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"opp-800000000").unwrap();
+        builder.prop_u64(b"opp-hz", 800_000_000).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"broken").unwrap();
+        builder.prop_u32(b"opp-microvolt", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"cpus").unwrap();
+        builder.begin_node(b"cpu@0").unwrap();
+        builder.prop_u32(b"operating-points-v2", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpu@1").unwrap();
+        builder.prop_u32(b"operating-points-v2", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpu@2").unwrap();
+        builder.prop_u32(b"operating-points-v2", 7).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+    })
+}
+
+/// Thermal zones of a soc sensor with two channels, one zone and one trip invalid
+pub fn thermal_fdt() -> Vec<u8> {
+    build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+
+        builder.begin_node(b"tsens@4a0000").unwrap();
+        builder.prop_u32(b"#thermal-sensor-cells", 1).unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"thermal-zones").unwrap();
+
+        builder.begin_node(b"cpu-thermal").unwrap();
+        builder.prop_u32(b"polling-delay", 1000).unwrap();
+        builder.prop_u32(b"polling-delay-passive", 250).unwrap();
+        builder.prop_cells(b"thermal-sensors", &[1, 0]).unwrap();
+        builder.begin_node(b"trips").unwrap();
+        builder.begin_node(b"cpu-alert").unwrap();
+        builder.prop_u32(b"temperature", 85_000).unwrap();
+        builder.prop_u32(b"hysteresis", 2_000).unwrap();
+        builder.prop_str(b"type", b"passive").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpu-no-type").unwrap();
+        builder.prop_u32(b"temperature", 90_000).unwrap();
+        builder.prop_u32(b"hysteresis", 2_000).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpu-crit").unwrap();
+        builder.prop_u32(b"temperature", 105_000).unwrap();
+        builder.prop_u32(b"hysteresis", 0).unwrap();
+        builder.prop_str(b"type", b"critical").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"no-sensor-thermal").unwrap();
+        builder.prop_u32(b"polling-delay", 0).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"battery-thermal").unwrap();
+        builder.prop_u32(b"polling-delay", 0).unwrap();
+        builder.prop_cells(b"thermal-sensors", &[1, 1]).unwrap();
+        builder.begin_node(b"trips").unwrap();
+        builder.begin_node(b"battery-cold").unwrap();
+        builder.prop_u32(b"temperature", -10_000i32 as u32).unwrap();
+        builder.prop_u32(b"hysteresis", 1_000).unwrap();
+        builder.prop_str(b"type", b"hot").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// Phandles 1, 4 and 9 with gaps, plus malformed and reserved phandles
+pub fn phandle_gaps_fdt() -> Vec<u8> {
+    build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"a").unwrap();
+        builder.prop_u32(b"phandle", 4).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"b").unwrap();
+        builder.prop_u32(b"linux,phandle", 9).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"c").unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.prop_u32(b"linux,phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"too-long").unwrap();
+        builder.prop_u64(b"phandle", 100).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"too-short").unwrap();
+        builder.property(b"phandle", &[0, 50]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"reserved").unwrap();
+        builder.prop_u32(b"phandle", 0xffff_ffff).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"zero").unwrap();
+        builder.prop_u32(b"phandle", 0).unwrap();
+        builder.prop_u32(b"linux,phandle", 2).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// A device whose `reg` is 6 bytes, one complete cell and 2 trailing bytes
+pub fn odd_cells_fdt() -> Vec<u8> {
+    build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"device@1000").unwrap();
+        builder.property(b"reg", &[0, 0, 0x10, 0, 0, 0x01]).unwrap();
+        builder.property(b"local-mac-address", &[0, 0x11, 0x22, 0x33, 0x44, 0x55]).unwrap();
+        builder.prop_str(b"label", b"odd").unwrap();
+        builder.prop_cells(b"interrupts", &[5, 4]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// Devices for driver matching: a node listing both drivers with the less specific one first in the table,
+/// a node of each driver, a disabled node and a node no driver matches
+pub fn drivers_fdt() -> Vec<u8> {
+    build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"soc").unwrap();
+        builder.property(b"compatible", b"simple-bus\0").unwrap();
+        builder.begin_node(b"timer@100").unwrap();
+        builder.property(b"compatible", b"acme,timer\0").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@200").unwrap();
+        builder.property(b"compatible", b"acme,uart-v2\0acme,timer\0").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@300").unwrap();
+        builder.property(b"compatible", b"acme,uart-v2\0").unwrap();
+        builder.prop_str(b"status", b"disabled").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"gpio@400").unwrap();
+        builder.property(b"compatible", b"acme,gpio\0").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// A GICv3 and a PLIC with one device each, and a device with a 4-cell GIC specifier on a 4-cell GIC
+pub fn irq_fdt() -> Vec<u8> {
+    build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"gic@1000").unwrap();
+        builder.property(b"compatible", b"arm,gic-v3\0").unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 3).unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"plic@2000").unwrap();
+        builder.property(b"compatible", b"sifive,fu540-c000-plic\0sifive,plic-1.0.0\0").unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 1).unwrap();
+        builder.prop_u32(b"phandle", 2).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"gic@3000").unwrap();
+        builder.property(b"compatible", b"arm,gic-v3\0").unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 4).unwrap();
+        builder.prop_u32(b"phandle", 3).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"timer").unwrap();
+        builder.prop_u32(b"interrupt-parent", 1).unwrap();
+        builder.prop_cells(b"interrupts", &[1, 13, 0xf08, 1, 14, 0x104, 2, 5, 1, 7, 1, 4, 0, 1, 5]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"uart").unwrap();
+        builder.prop_cells(b"interrupts-extended", &[2, 10, 3, 0, 3, 4, 0]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// A tree with every node `DeviceTree::quick_facts()` looks for: two CPUs next to a `cpu-map`,
+/// two memory nodes, a memory-like node below the root level and a `linux,phandle`
+pub fn boot_fdt() -> Vec<u8> {
+    build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"chosen").unwrap();
+        builder.prop_str(b"stdout-path", b"serial0:115200n8").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"aliases").unwrap();
+        builder.prop_str(b"serial0", b"/serial@1000").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpus").unwrap();
+        builder.begin_node(b"cpu@0").unwrap();
+        builder.prop_str(b"device_type", b"cpu").unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpu@1").unwrap();
+        builder.prop_str(b"device_type", b"cpu").unwrap();
+        builder.prop_u32(b"phandle", 2).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpu-map").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"memory@80000000").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.prop_cells(b"reg", &[0x80000000, 0x40000000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"memory@100000000").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"reserved-memory").unwrap();
+        builder.begin_node(b"buffer@90000000").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"psci").unwrap();
+        builder.prop_str(b"method", b"smc").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@1000").unwrap();
+        builder.prop_u32(b"linux,phandle", 7).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// A crash kernel tree with 2/2 cells: two memory nodes and a `/chosen` restricting the kernel
+/// to a window spanning the end of the first and the start of the second
+pub fn kexec_fdt() -> Vec<u8> {
+    build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 2).unwrap();
+        builder.prop_u32(b"#size-cells", 2).unwrap();
+        builder.begin_node(b"chosen").unwrap();
+        builder.prop_cells(b"linux,usable-memory-range", &[0x0, 0xb0000000, 0x0, 0x60000000]).unwrap();
+        builder.prop_cells(b"linux,elfcorehdr", &[0x1, 0x3ff00000, 0x0, 0x10000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"memory@80000000").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.prop_cells(b"reg", &[0x0, 0x80000000, 0x0, 0x40000000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"memory@100000000").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.prop_cells(b"reg", &[0x1, 0x00000000, 0x0, 0x40000000, 0x2, 0x00000000, 0x0, 0x1000]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// Two UARTs with phandles 1 and 2 and aliases `serial0`/`serial1`, with the given raw properties
+/// in `/chosen` and an optional `/aliases/stdout`. Lower priority console sources point elsewhere.
+fn stdout_tree(chosen: &[(&[u8], &[u8])], stdout_alias: Option<&[u8]>) -> Vec<u8> {
+    build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"chosen").unwrap();
+        for (name, value) in chosen {
+            builder.property(name, value).unwrap();
+        }
+        builder.end_node().unwrap();
+        builder.begin_node(b"aliases").unwrap();
+        builder.prop_str(b"serial0", b"/serial@1000").unwrap();
+        builder.prop_str(b"serial1", b"/serial@2000").unwrap();
+        if let Some(stdout) = stdout_alias {
+            builder.prop_str(b"stdout", stdout).unwrap();
+        }
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@1000").unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@2000").unwrap();
+        builder.prop_u32(b"phandle", 2).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// Console in `stdout-path` as an alias with options, shadowing the other sources
+pub fn stdout_path_fdt() -> Vec<u8> {
+    stdout_tree(&[
+        (b"linux,stdout-path", b"/serial@2000\0"),
+        (b"stdout-path", b"serial0:115200n8\0"),
+    ], Some(b"serial1"))
+}
+
+/// Console in the legacy `linux,stdout-path`, shadowing `/aliases/stdout`
+pub fn linux_stdout_path_fdt() -> Vec<u8> {
+    stdout_tree(&[(b"linux,stdout-path", b"/serial@2000:9600\0")], Some(b"serial0"))
+}
+
+/// Console only in `/aliases/stdout`
+pub fn alias_stdout_fdt() -> Vec<u8> {
+    stdout_tree(&[], Some(b"serial1"))
+}
+
+/// Console in `stdout-path` as a phandle
+pub fn phandle_stdout_fdt() -> Vec<u8> {
+    stdout_tree(&[(b"stdout-path", &2u32.to_be_bytes())], Some(b"serial0"))
+}
+
+/// Dangling `stdout-path` with a valid `linux,stdout-path`
+pub fn dangling_stdout_fdt() -> Vec<u8> {
+    stdout_tree(&[
+        (b"stdout-path", b"/serial@3000\0"),
+        (b"linux,stdout-path", b"/serial@2000\0"),
+    ], None)
+}
+
+/// A small RISC-V virt-like board: a CLINT, a PLIC and UARTs on a `simple-bus` with 2/2 cells,
+/// a UART with an unknown `compatible`, a disabled UART and a timer only recognizable by name
+pub fn riscv_virt_fdt() -> Vec<u8> {
+    build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 2).unwrap();
+        builder.prop_u32(b"#size-cells", 2).unwrap();
+        builder.property(b"compatible", b"riscv-virtio\0").unwrap();
+        builder.begin_node(b"soc").unwrap();
+        builder.prop_u32(b"#address-cells", 2).unwrap();
+        builder.prop_u32(b"#size-cells", 2).unwrap();
+        builder.property(b"compatible", b"simple-bus\0").unwrap();
+        builder.prop_empty(b"ranges").unwrap();
+        builder.begin_node(b"clint@2000000").unwrap();
+        builder.property(b"compatible", b"sifive,clint0\0riscv,clint0\0").unwrap();
+        builder.prop_cells(b"reg", &[0, 0x2000000, 0, 0x10000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"plic@c000000").unwrap();
+        builder.property(b"compatible", b"sifive,plic-1.0.0\0riscv,plic0\0").unwrap();
+        builder.prop_cells(b"reg", &[0, 0xc000000, 0, 0x600000]).unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 1).unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@10000000").unwrap();
+        builder.property(b"compatible", b"ns16550a\0").unwrap();
+        builder.prop_cells(b"reg", &[0, 0x10000000, 0, 0x100]).unwrap();
+        builder.prop_u32(b"interrupt-parent", 1).unwrap();
+        builder.prop_u32(b"interrupts", 10).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"uart@10001000").unwrap();
+        builder.property(b"compatible", b"vendor,uart\0").unwrap();
+        builder.prop_cells(b"reg", &[0, 0x10001000, 0, 0x100]).unwrap();
+        builder.prop_u32(b"interrupt-parent", 1).unwrap();
+        builder.prop_u32(b"interrupts", 11).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@10002000").unwrap();
+        builder.property(b"compatible", b"sifive,fu540-c000-uart\0sifive,uart0\0").unwrap();
+        builder.prop_cells(b"reg", &[0, 0x10002000, 0, 0x100]).unwrap();
+        builder.prop_str(b"status", b"disabled").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"timer@10003000").unwrap();
+        builder.property(b"compatible", b"vendor,timer\0").unwrap();
+        builder.prop_cells(b"reg", &[0, 0x10003000, 0, 0x100]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"rtc@101000").unwrap();
+        builder.property(b"compatible", b"google,goldfish-rtc\0").unwrap();
+        builder.prop_cells(b"reg", &[0, 0x101000, 0, 0x1000]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// A GIC and a PLIC, a soc bus with two ranges containing a bus with two ranges and 16 UARTs,
+/// one with its own `interrupt-parent` and one with `interrupts-extended`,
+/// a bus with more ranges than a `BusContext` keeps and a bus without ranges
+pub fn uart_bus_fdt() -> Vec<u8> {
+    build(8192, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_u32(b"interrupt-parent", 1).unwrap();
+
+        builder.begin_node(b"interrupt-controller@8000000").unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 3).unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"interrupt-controller@c000000").unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 1).unwrap();
+        builder.prop_u32(b"phandle", 2).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"soc").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_cells(b"ranges", &[0x0, 0x40000000, 0x100000, 0x200000, 0x80000000, 0x100000]).unwrap();
+        builder.begin_node(b"bus@10000").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_cells(b"ranges", &[0x0, 0x10000, 0x20000, 0x80000, 0x210000, 0x1000]).unwrap();
+        for i in 0..16u32 {
+            builder.begin_node(format!("serial@{:x}", i * 0x1000).as_bytes()).unwrap();
+            builder.prop_str(b"compatible", b"ns16550a").unwrap();
+            builder.prop_cells(b"reg", &[i * 0x1000, 0x100]).unwrap();
+            match i {
+                3 => {
+                    builder.prop_u32(b"interrupt-parent", 2).unwrap();
+                    builder.prop_cells(b"interrupts", &[7]).unwrap();
+                },
+                5 => builder.prop_cells(b"interrupts-extended", &[2, 9, 1, 0, 50, 4]).unwrap(),
+                _ => builder.prop_cells(b"interrupts", &[0, 32 + i, 4]).unwrap()
+            }
+            builder.end_node().unwrap();
+        }
+        builder.begin_node(b"serial@80000").unwrap();
+        builder.prop_cells(b"reg", &[0x80000, 0x100, 0x80800, 0x100]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@70000").unwrap();
+        builder.prop_cells(b"reg", &[0x70000, 0x100]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"wide").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        let ranges: Vec<u32> = (0..10).flat_map(|i| [i * 0x1000, 0x90000000 + i * 0x10000, 0x1000]).collect();
+        builder.prop_cells(b"ranges", &ranges).unwrap();
+        for i in [0u32, 4, 9, 12] {
+            builder.begin_node(format!("timer@{:x}", i * 0x1000).as_bytes()).unwrap();
+            builder.prop_cells(b"reg", &[i * 0x1000 + 0x10, 0x10]).unwrap();
+            builder.prop_cells(b"interrupts", &[1, i, 8]).unwrap();
+            builder.end_node().unwrap();
+        }
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"isolated").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.begin_node(b"device@0").unwrap();
+        builder.prop_cells(b"reg", &[0x0, 0x10]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+    })
+}
+
+/// A tree using only legacy spellings: `linux,phandle`, a GPIO controller without `#gpio-cells`,
+/// the root `interrupt-parent` as global default and a CPU recognized only by `device_type`.
+/// With `modern` the same tree with the current spellings.
+pub fn legacy_fdt(modern: bool) -> Vec<u8> {
+    let phandle: &[u8] = if modern { b"phandle" } else { b"linux,phandle" };
+    build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        if !modern {
+            builder.prop_u32(b"interrupt-parent", 1).unwrap();
+        }
+        builder.begin_node(b"cpus").unwrap();
+        builder.begin_node(if modern { &b"cpu@0"[..] } else { &b"PowerPC,970@0"[..] }).unwrap();
+        builder.prop_str(b"device_type", b"cpu").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpu-map").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"pic@1000").unwrap();
+        builder.prop_empty(b"interrupt-controller").unwrap();
+        builder.prop_u32(b"#interrupt-cells", 1).unwrap();
+        builder.prop_u32(phandle, 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"gpio@2000").unwrap();
+        builder.prop_empty(b"gpio-controller").unwrap();
+        if modern {
+            builder.prop_u32(b"#gpio-cells", 2).unwrap();
+        }
+        builder.prop_u32(phandle, 2).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"soc").unwrap();
+        if modern {
+            builder.prop_u32(b"interrupt-parent", 1).unwrap();
+        }
+        builder.begin_node(b"ethernet@3000").unwrap();
+        builder.prop_cells(b"interrupts", &[5]).unwrap();
+        builder.prop_cells(b"reset-gpios", &[2, 3, 1]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/// A bus and its parent with zero address and size cells, and `reg` values which are not empty
+pub fn zero_cells_fdt() -> Vec<u8> {
+    build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 0).unwrap();
+        builder.prop_u32(b"#size-cells", 0).unwrap();
+        builder.begin_node(b"memory").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.prop_cells(b"reg", &[0x80000000, 0x1000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"bus").unwrap();
+        builder.prop_str(b"compatible", b"simple-bus").unwrap();
+        builder.prop_u32(b"#address-cells", 0).unwrap();
+        builder.prop_u32(b"#size-cells", 0).unwrap();
+        builder.prop_empty(b"ranges").unwrap();
+        builder.prop_u32(b"reg", 0x1000).unwrap();
+        builder.begin_node(b"device").unwrap();
+        builder.prop_cells(b"reg", &[0x1000, 0x100]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
@@ -0,0 +1,96 @@
+use static_dt_rs::compat::{self, Spelling, DEFAULT_GPIO_CELLS};
+use static_dt_rs::{DeviceTree, DtWarnSink, Warning};
+
+mod common;
+
+#[derive(Default)]
+struct Collect(Vec<Warning>);
+
+impl DtWarnSink for Collect {
+    fn warn(&mut self, w: Warning) {
+        self.0.push(w)
+    }
+}
+
+fn deprecations(dt: &DeviceTree) -> Vec<Warning> {
+    let mut sink = Collect::default();
+    dt.warnings(&mut sink);
+    sink.0.into_iter().filter(|w| matches!(w, Warning::Deprecated { .. })).collect()
+}
+
+#[test]
+fn test_legacy_spellings() {
+    let fdt = common::legacy_fdt(false);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let pic = dt.get_node_by_path("/pic@1000").unwrap();
+    let gpio = dt.get_node_by_path("/gpio@2000").unwrap();
+    let eth = dt.get_node_by_path("/soc/ethernet@3000").unwrap();
+    let cpu = dt.get_node_by_path("/cpus/PowerPC,970@0").unwrap();
+
+    /* linux,phandle */
+    assert_eq!(compat::phandle(&pic), Some((1, Spelling::Legacy)));
+    assert_eq!(dt.phandle_of(&pic), Some(1));
+    assert_eq!(dt.get_phandle(1), Some(pic));
+
+    /* No #gpio-cells */
+    assert_eq!(compat::gpio_cells(&gpio), Some((DEFAULT_GPIO_CELLS, Spelling::Legacy)));
+    let reset = eth.gpio(b"reset-gpios").unwrap();
+    assert_eq!((reset.controller, reset.pin, reset.flags), (gpio, 3, 1));
+
+    /* interrupt-parent of the root */
+    assert_eq!(compat::interrupt_parent(&eth), Some((pic, Spelling::Legacy)));
+    assert_eq!(eth.interrupt_parent(), Some(pic));
+    assert_eq!(eth.interrupts().next().unwrap().args(), &[5]);
+    assert_eq!(compat::interrupt_parent(&dt.root()), Some((pic, Spelling::Modern)));
+
+    /* device_type = "cpu" */
+    assert_eq!(compat::cpu(&cpu), Some(Spelling::Legacy));
+    assert_eq!(dt.cpus().collect::<Vec<_>>(), vec![cpu]);
+    assert_eq!(dt.quick_facts().cpus, 1);
+
+    let offset = |node: static_dt_rs::Token| node.node_offset().unwrap();
+    assert_eq!(deprecations(&dt), vec![
+        Warning::Deprecated { offset: offset(pic), property: b"linux,phandle" },
+        Warning::Deprecated { offset: offset(gpio), property: b"linux,phandle" },
+        Warning::Deprecated { offset: offset(gpio), property: b"#gpio-cells" },
+        Warning::Deprecated { offset: offset(dt.root()), property: b"interrupt-parent" },
+        Warning::Deprecated { offset: offset(cpu), property: b"device_type" },
+    ]);
+}
+
+#[test]
+fn test_modern_spellings() {
+    let fdt = common::legacy_fdt(true);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let pic = dt.get_node_by_path("/pic@1000").unwrap();
+    let gpio = dt.get_node_by_path("/gpio@2000").unwrap();
+    let eth = dt.get_node_by_path("/soc/ethernet@3000").unwrap();
+    let cpu = dt.get_node_by_path("/cpus/cpu@0").unwrap();
+
+    assert_eq!(compat::phandle(&pic), Some((1, Spelling::Modern)));
+    assert_eq!(compat::gpio_cells(&gpio), Some((2, Spelling::Modern)));
+    assert_eq!(compat::interrupt_parent(&eth), Some((pic, Spelling::Modern)));
+    assert_eq!(compat::cpu(&cpu), Some(Spelling::Modern));
+    assert_eq!(eth.gpio(b"reset-gpios").unwrap().pin, 3);
+    assert_eq!(eth.interrupts().next().unwrap().args(), &[5]);
+    assert_eq!(dt.cpus().count(), 1);
+    assert_eq!(deprecations(&dt), vec![]);
+
+    /* Not applicable */
+    let cpu_map = dt.get_node_by_path("/cpus/cpu-map").unwrap();
+    assert_eq!(compat::cpu(&cpu_map), None);
+    assert_eq!(compat::gpio_cells(&pic), None);
+    assert_eq!(compat::phandle(&eth), None);
+    assert_eq!(compat::interrupt_parent(&cpu), None);
+    assert_eq!(compat::phandle(&pic.get_prop("phandle").unwrap()), None);
+}
+
+#[test]
+fn test_holds_phandles() {
+    for name in [&b"clocks"[..], b"interrupt-parent", b"vdd-supply", b"reset-gpios", b"enable-gpio", b"pinctrl-0", b"pinctrl-12"] {
+        assert!(compat::holds_phandles(name), "{:?}", name);
+    }
+    for name in [&b"reg"[..], b"pinctrl-names", b"pinctrl-", b"#gpio-cells", b"gpio-controller", b"clock-names"] {
+        assert!(!compat::holds_phandles(name), "{:?}", name);
+    }
+}
@@ -0,0 +1,175 @@
+use static_dt_rs::{AliasError, DeviceTree, Parity, PathLookupError, Stdout, StdoutSource, UartOptions};
+
+mod common;
+
+static CONSOLE_FDT: &[u8] = include_bytes!("console.dtb");
+static BOARD_FDT: &[u8] = include_bytes!("board.dtb");
+
+#[test]
+fn test_aliases() {
+    let dt = DeviceTree::parse(CONSOLE_FDT).unwrap();
+    let uart = dt.get_node_by_path(b"/soc/serial@2000").unwrap();
+
+    assert_eq!(dt.alias(b"serial0"), Some(&b"/soc/serial@2000"[..]));
+    assert_eq!(dt.alias(b"serial2"), None);
+    assert_eq!(dt.find_node(b"serial0"), Some(uart));
+    assert_eq!(dt.find_node(b"soc/serial@2000"), Some(uart));
+    assert_eq!(dt.find_node(b"/soc/serial@2000"), Some(uart));
+    assert_eq!(dt.find_node(b"soc/serial@4000"), None);
+    assert_eq!(dt.find_node(b"serial2"), None);
+    assert_eq!(dt.find_node(b""), None);
+
+    let (node, options) = dt.stdout().unwrap();
+    assert_eq!(node, uart);
+    assert_eq!(options, Some(&b"1500000n8"[..]));
+}
+
+#[test]
+fn test_earlycon() {
+    let dt = DeviceTree::parse(CONSOLE_FDT).unwrap();
+
+    let console = dt.earlycon().unwrap();
+    assert_eq!(console.node.name(), b"serial@2000");
+    assert_eq!(console.compatible, Some(&b"snps,dw-apb-uart"[..]));
+    assert_eq!(console.address, Some(0xfe602000));
+    assert_eq!(console.options, Some(UartOptions { baud: 1500000, ..UartOptions::default() }));
+    assert_eq!(console.reg_shift, Some(2));
+    assert_eq!(console.reg_io_width, Some(4));
+    assert_eq!(console.clock_frequency, Some(24000000));
+
+    assert_eq!(DeviceTree::parse(BOARD_FDT).unwrap().earlycon(), None);
+}
+
+/// Console of `fdt` as (node name, options, source)
+fn stdout(fdt: &[u8]) -> Option<(Vec<u8>, Option<Vec<u8>>, StdoutSource)> {
+    let dt = DeviceTree::parse(fdt).unwrap();
+    let stdout = dt.resolve_stdout()?;
+    assert_eq!(dt.stdout(), Some((stdout.node, stdout.options)));
+
+    let Stdout { node, options, source } = stdout;
+    Some((node.name().to_vec(), options.map(<[u8]>::to_vec), source))
+}
+
+#[test]
+fn test_stdout_path() {
+    let fdt = common::stdout_path_fdt();
+    assert_eq!(stdout(&fdt), Some((b"serial@1000".to_vec(), Some(b"115200n8".to_vec()), StdoutSource::StdoutPath)));
+}
+
+#[test]
+fn test_linux_stdout_path() {
+    let fdt = common::linux_stdout_path_fdt();
+    assert_eq!(stdout(&fdt), Some((b"serial@2000".to_vec(), Some(b"9600".to_vec()), StdoutSource::LinuxStdoutPath)));
+}
+
+#[test]
+fn test_alias_stdout() {
+    let fdt = common::alias_stdout_fdt();
+    assert_eq!(stdout(&fdt), Some((b"serial@2000".to_vec(), None, StdoutSource::Alias)));
+}
+
+#[test]
+fn test_phandle_stdout() {
+    let fdt = common::phandle_stdout_fdt();
+    assert_eq!(stdout(&fdt), Some((b"serial@2000".to_vec(), None, StdoutSource::StdoutPath)));
+}
+
+#[test]
+fn test_dangling_stdout() {
+    /* The first present source is used even if it leads nowhere */
+    let fdt = common::dangling_stdout_fdt();
+    assert_eq!(stdout(&fdt), None);
+    assert_eq!(DeviceTree::parse(&fdt).unwrap().earlycon(), None);
+}
+
+#[test]
+fn test_uart_options() {
+    assert_eq!(UartOptions::parse(b"115200"), Some(UartOptions::default()));
+    assert_eq!(UartOptions::parse(b"9600e7r"), Some(UartOptions { baud: 9600, parity: Parity::Even, bits: 7, flow_control: true }));
+    assert_eq!(UartOptions::parse(b"38400o"), Some(UartOptions { baud: 38400, parity: Parity::Odd, ..UartOptions::default() }));
+    assert_eq!(UartOptions::parse(b""), None);
+    assert_eq!(UartOptions::parse(b"n8"), None);
+    assert_eq!(UartOptions::parse(b"115200x8"), None);
+    assert_eq!(UartOptions::parse(b"115200n9"), None);
+    assert_eq!(UartOptions::parse(b"115200n8rx"), None);
+    assert_eq!(UartOptions::parse(b"99999999999"), None);
+}
+
+#[test]
+fn test_resolve_alias() {
+    let dt = DeviceTree::parse(CONSOLE_FDT).unwrap();
+    assert_eq!(dt.resolve_alias(b"serial0"), Ok(dt.get_node_by_path(b"/soc/serial@2000").unwrap()));
+    assert_eq!(dt.resolve_alias(b"serial2"), Err(AliasError::NoAlias));
+
+    let dt = DeviceTree::parse(BOARD_FDT).unwrap();
+    assert_eq!(dt.resolve_alias(b"serial0"), Err(AliasError::NoAliasesNode));
+
+    let fdt = common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"aliases").unwrap();
+        builder.prop_str(b"serial0", b"/soc/serial@9000").unwrap();
+        builder.prop_str(b"relative", b"soc").unwrap();
+        builder.property(b"binary", &[1, 2]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"soc").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let soc = dt.get_node_by_path(b"/soc").unwrap();
+
+    /* Indices are into the stored path */
+    assert_eq!(dt.resolve_alias(b"serial0"), Err(AliasError::Dangling(PathLookupError::NotFound {
+        resolved: 1,
+        node_offset: soc.node_offset().unwrap(),
+        start: 5,
+        end: 16
+    })));
+    assert!(matches!(dt.resolve_alias(b"relative"), Err(AliasError::Dangling(PathLookupError::Invalid(_)))));
+    assert_eq!(dt.resolve_alias(b"binary"), Err(AliasError::NotAString));
+}
+
+#[test]
+fn test_string_map() {
+    let fdt = common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"config").unwrap();
+        builder.prop_str(b"mode", b"fast").unwrap();
+        builder.prop_u32(b"count", 5).unwrap();
+        builder.property(b"list", b"first\0second\0").unwrap();
+        builder.prop_u32(b"flags", 0x100).unwrap();
+        builder.prop_empty(b"enabled").unwrap();
+        builder.property(b"unterminated", b"abc").unwrap();
+        builder.prop_str(b"empty", b"").unwrap();
+        builder.prop_str(b"name", b"board").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"aliases").unwrap();
+        builder.prop_str(b"serial0", b"/uart@1000").unwrap();
+        builder.prop_u32(b"bogus", 1).unwrap();
+        builder.prop_str(b"i2c0", b"/i2c@2000").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"__symbols__").unwrap();
+        builder.prop_str(b"uart0", b"/uart@1000").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* Cells, empty values and unterminated or empty strings are skipped, lists give their first string */
+    let config: Vec<(&[u8], &[u8])> = dt.get_node_by_path(b"/config").unwrap().as_string_map().collect();
+    assert_eq!(config, [(&b"mode"[..], &b"fast"[..]), (b"list", b"first"), (b"name", b"board")]);
+    assert_eq!(dt.get(b"/config/mode").unwrap().as_string_map().count(), 0);
+
+    let aliases: Vec<(&[u8], &[u8])> = dt.aliases().collect();
+    assert_eq!(aliases, [(&b"serial0"[..], &b"/uart@1000"[..]), (b"i2c0", b"/i2c@2000")]);
+    assert_eq!(dt.alias(b"i2c0"), Some(&b"/i2c@2000"[..]));
+    assert_eq!(dt.alias(b"bogus"), None);
+
+    assert_eq!(dt.symbols().count(), 1);
+    assert_eq!(dt.symbol(b"uart0"), Some(&b"/uart@1000"[..]));
+    assert_eq!(dt.symbol(b"serial0"), None);
+
+    let dt = DeviceTree::parse(CONSOLE_FDT).unwrap();
+    assert_eq!(dt.symbols().count(), 0);
+    assert!(dt.aliases().any(|(alias, _)| alias == b"serial0"));
+}
@@ -0,0 +1,85 @@
+use static_dt_rs::{instrument, ContentCursor, DeviceTree};
+
+mod common;
+
+/* The counter is global, every test takes `common::serial()` */
+#[test]
+fn test_split_contents_reads_once() {
+    let _serial = common::serial();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node = dt.get_node_by_path(b"/node1").unwrap();
+    let cursor = ContentCursor::new();
+
+    instrument::reset();
+    let (props, children) = node.split_contents(&cursor);
+    assert_eq!(props.count(), 3);
+    assert_eq!(children.count(), 2);
+
+    /* The 3 properties, 2 children and the end of the node, sub-nodes are skipped */
+    assert_eq!(instrument::token_reads(), 3 + 2 + 1);
+
+    /* Same when the properties are skipped over by the children iterator */
+    instrument::reset();
+    let (_, children) = node.split_contents(&cursor);
+    assert_eq!(children.count(), 2);
+    assert_eq!(instrument::token_reads(), 3 + 2 + 1);
+
+    /* Two independent passes decode the properties and the first child twice */
+    instrument::reset();
+    assert_eq!(node.props().count(), 3);
+    assert_eq!(node.into_iter().filter(|tok| tok.name().starts_with(b"child")).count(), 2);
+    assert!(instrument::token_reads() > 3 + 2 + 1);
+}
+
+#[test]
+fn test_split_contents() {
+    let _serial = common::serial();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node = dt.get_node_by_path(b"/node1").unwrap();
+    let cursor = ContentCursor::new();
+    assert_eq!(cursor.offset(), None);
+
+    let (props, children) = node.split_contents(&cursor);
+    let names: Vec<_> = props.map(|prop| prop.name()).collect();
+    assert_eq!(names, [&b"a-string-property"[..], b"a-string-list-property", b"a-byte-data-property"]);
+    let child = dt.get_node_by_path(b"/node1/child-node1").unwrap();
+    assert_eq!(cursor.offset(), Some(child.node_offset().unwrap()));
+
+    let children: Vec<_> = children.collect();
+    assert_eq!(children, [child, dt.get_node_by_path(b"/node1/child-node2").unwrap()]);
+    assert_eq!(cursor.offset(), None);
+
+    /* Node without children, the cursor is reset for each node. The phandle was added by dtc */
+    let (props, mut children) = child.split_contents(&cursor);
+    assert_eq!(props.count(), 4);
+    assert_eq!(children.next(), None);
+
+    let (mut props, mut children) = dt.get_node_by_path(b"/node1/child-node2").unwrap().split_contents(&cursor);
+    assert_eq!(props.next(), None);
+    assert_eq!(children.next(), None);
+
+    /* Not a node */
+    let (mut props, mut children) = child.get_prop(b"a-string-property").unwrap().split_contents(&cursor);
+    assert_eq!(props.next(), None);
+    assert_eq!(children.next(), None);
+}
+
+#[test]
+fn test_split_contents_children_first() {
+    let _serial = common::serial();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node = dt.get_node_by_path(b"/node2").unwrap();
+    let cursor = ContentCursor::new();
+
+    /* Remaining properties are skipped when children are visited first */
+    let (mut props, mut children) = node.split_contents(&cursor);
+    assert_eq!(props.next().unwrap().name(), b"an-empty-property");
+    assert_eq!(children.next(), dt.get_node_by_path(b"/node2/child-node1"));
+    assert_eq!(props.next(), None);
+    assert_eq!(children.next(), None);
+    assert_eq!(props.next(), None);
+
+    let (mut props, children) = node.split_contents(&cursor);
+    assert_eq!(children.count(), 1);
+    assert_eq!(props.next(), None);
+}
@@ -0,0 +1,92 @@
+//! Regression corpus of malformed blobs in tests/corpus/.
+//! Every blob must be listed in tests/corpus/manifest.txt with the expected result,
+//! adding a blob and a manifest line is enough to cover a new case.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use static_dt_rs::{DeviceTree, NoWarnings, Token};
+
+mod common;
+
+/// Name of the variant in the `Debug` output, e.g. "UnknownToken" for "UnknownToken { offset: 8, id: 119 }"
+fn variant<T: std::fmt::Debug>(value: &T) -> String {
+    let s = format!("{:?}", value);
+    s.split([' ', '(']).next().unwrap().to_string()
+}
+
+/// Use the tree the way a consumer would, none of this may panic
+fn exercise(dt: &DeviceTree) {
+    let tokens = dt.tokens().count();
+    assert!(tokens > 0);
+    assert!(dt.nodes().count() <= tokens);
+    let _ = dt.all_props().count();
+    let _ = dt.stats();
+    let _ = dt.get_node_by_path(b"/c");
+    let _ = dt.root().get_prop(b"a").map(|prop| prop.prop_u32(0));
+    let _ = dt.root().counts();
+    let _ = dt.get_phandle(1);
+    dt.warnings(&mut NoWarnings);
+    for tok in dt.root() {
+        if let Token::BeginNode(..) = tok {
+            let _ = tok.into_iter().count();
+        }
+    }
+}
+
+fn result(fdt: &[u8]) -> String {
+    let dt = match DeviceTree::parse(fdt) {
+        Ok(dt) => dt,
+        Err(e) => return format!("Error::{}", variant(&e))
+    };
+    exercise(&dt);
+    match dt.validate() {
+        Ok(()) => "ok".to_string(),
+        Err(e) => format!("ValidationError::{}", variant(&e))
+    }
+}
+
+#[test]
+fn test_corpus() {
+    let dir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "corpus"].iter().collect();
+    let manifest = std::fs::read_to_string(dir.join("manifest.txt")).unwrap();
+    let mut expected: BTreeMap<String, String> = manifest.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut fields = line.split_whitespace();
+            (fields.next().unwrap().to_string(), fields.next().expect("missing result").to_string())
+        })
+        .collect();
+
+    let mut blobs = 0;
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "dtb") {
+            continue
+        }
+        let name = path.file_name().unwrap().to_str().unwrap().to_string();
+        let expected = expected.remove(&name).unwrap_or_else(|| panic!("{} is missing in manifest.txt", name));
+        let fdt = std::fs::read(&path).unwrap();
+        assert_eq!(result(&fdt), expected, "{}", name);
+        blobs += 1;
+    }
+
+    assert!(expected.is_empty(), "listed in manifest.txt but missing: {:?}", expected.keys());
+    assert!(blobs >= 10);
+}
+
+#[test]
+fn test_validate_fixtures() {
+    /* All dtc generated fixtures are well-formed */
+    for name in ["test.dtb", "board.dtb", "bus.dtb", "pci.dtb"] {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", name].iter().collect();
+        let fdt = std::fs::read(&path).unwrap();
+        assert_eq!(DeviceTree::parse(&fdt).unwrap().validate(), Ok(()), "{}", name);
+    }
+
+    /* And so are the generated ones outside of what dtc emits */
+    for (name, fdt) in [("deep_bus", common::deep_bus_fdt()), ("attribution", common::attribution_fdt()), ("leading_nop", common::leading_nop_fdt())] {
+        assert_eq!(DeviceTree::parse(&fdt).unwrap().validate(), Ok(()), "{}", name);
+    }
+}
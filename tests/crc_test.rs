@@ -0,0 +1,59 @@
+use static_dt_rs::{DeviceTree, Error};
+
+mod common;
+
+/// CRC32 of test.dtb, computed with zlib
+const FDT_CRC: u32 = 0x0ae2cd21;
+
+fn with_footer(crc: u32) -> Vec<u8> {
+    let mut buf = common::test_blob().to_vec();
+    buf.extend_from_slice(&crc.to_be_bytes());
+    buf
+}
+
+#[test]
+fn test_crc32() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    assert_eq!(dt.crc32(), FDT_CRC);
+    assert!(dt.verify_crc32(FDT_CRC));
+    assert!(!dt.verify_crc32(!FDT_CRC));
+
+    /* Only totalsize bytes are covered */
+    let mut buf = with_footer(0);
+    buf.extend_from_slice(&[0xff; 12]);
+    assert_eq!(DeviceTree::parse(&buf).unwrap().crc32(), FDT_CRC);
+
+    /* Any change in the blob is detected */
+    let mut buf = common::test_blob().to_vec();
+    buf[0x100] ^= 1;
+    assert!(!DeviceTree::parse(&buf).unwrap().verify_crc32(FDT_CRC));
+}
+
+#[test]
+fn test_parse_with_footer() {
+    let buf = with_footer(FDT_CRC);
+    let dt = DeviceTree::parse_with_footer(&buf).unwrap();
+    assert_eq!(dt.totalsize(), common::test_blob().len());
+
+    /* Bytes after the footer are ignored */
+    let mut padded = buf.clone();
+    padded.extend_from_slice(&[0; 8]);
+    assert!(DeviceTree::parse_with_footer(&padded).is_ok());
+
+    assert_eq!(DeviceTree::parse_with_footer(&with_footer(FDT_CRC ^ 0x100)).unwrap_err(), Error::CrcMismatch(FDT_CRC));
+    assert_eq!(DeviceTree::parse_with_footer(&buf[..buf.len() - 1]).unwrap_err(), Error::Truncated);
+    assert_eq!(DeviceTree::parse_with_footer(common::test_blob()).unwrap_err(), Error::Truncated);
+
+    let mut corrupt = buf.clone();
+    corrupt[0x100] ^= 1;
+    assert!(matches!(DeviceTree::parse_with_footer(&corrupt), Err(Error::CrcMismatch(_))));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_back_with_footer() {
+    let buf = with_footer(FDT_CRC);
+    assert_eq!(DeviceTree::back_with_footer(&buf).unwrap().totalsize(), common::test_blob().len());
+    assert_eq!(DeviceTree::back_with_footer(&with_footer(FDT_CRC ^ 1)).unwrap_err(), Error::CrcMismatch(FDT_CRC));
+    assert_eq!(DeviceTree::back_with_footer(common::test_blob()).unwrap_err(), Error::Truncated);
+}
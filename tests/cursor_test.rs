@@ -0,0 +1,74 @@
+use static_dt_rs::{DeviceTree, DtCursor};
+
+mod common;
+
+fn uart_fdt() -> Vec<u8> {
+    common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"soc").unwrap();
+        builder.begin_node(b"serial@1000").unwrap();
+        builder.prop_str(b"status", b"okay").unwrap();
+        builder.prop_u32(b"clock-frequency", 1_843_200).unwrap();
+        builder.begin_node(b"bluetooth").unwrap();
+        builder.prop_u32(b"max-speed", 3_000_000).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+/* A layer which only gets the tree and a cursor */
+fn clock_of(dt: &DeviceTree, cursor: &DtCursor) -> Option<u32> {
+    cursor.prop_u32(dt, "clock-frequency")
+}
+
+#[test]
+fn test_cursor() {
+    let fdt = uart_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let uart = dt.get_node_by_path(b"/soc/serial@1000").unwrap();
+
+    let cursor = dt.cursor_at(b"/soc/serial@1000").unwrap();
+    assert_eq!(Some(cursor), uart.cursor());
+    assert_eq!(cursor.offset(), uart.node_offset().unwrap());
+    assert!(cursor.is_of(&dt));
+    assert_eq!(cursor.node(&dt), Some(uart));
+    assert_eq!(clock_of(&dt, &cursor), Some(1_843_200));
+    assert_eq!(cursor.prop_str(&dt, "status"), Some(&b"okay"[..]));
+    assert_eq!(cursor.prop_u32(&dt, "missing"), None);
+
+    let child = cursor.child(&dt, "bluetooth").unwrap();
+    assert_eq!(child.prop_u32(&dt, b"max-speed"), Some(3_000_000));
+    assert_eq!(cursor.child(&dt, "wifi"), None);
+
+    assert_eq!(dt.cursor_at("/soc/serial@2000"), None);
+    assert_eq!(uart.get_prop("status").unwrap().cursor(), None);
+}
+
+#[test]
+fn test_cursor_mismatch() {
+    let fdt = uart_fdt();
+    let copy = fdt.clone();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let other = DeviceTree::parse(&copy).unwrap();
+
+    /* An identical tree in another buffer has a node at the same offset, but is not the same tree */
+    let cursor = dt.cursor_at("/soc/serial@1000").unwrap();
+    assert_eq!(other.cursor_at("/soc/serial@1000").unwrap().offset(), cursor.offset());
+    assert!(!cursor.is_of(&other));
+    assert_eq!(cursor.node(&other), None);
+    assert_eq!(cursor.prop_u32(&other, "clock-frequency"), None);
+    assert_eq!(cursor.prop_str(&other, "status"), None);
+    assert_eq!(cursor.child(&other, "bluetooth"), None);
+    assert_eq!(clock_of(&other, &cursor), None);
+
+    /* A cursor from the other tree does not work on the first either */
+    let child = other.cursor_at("/soc/serial@1000/bluetooth").unwrap();
+    assert_eq!(child.prop_u32(&other, "max-speed"), Some(3_000_000));
+    assert_eq!(child.prop_u32(&dt, "max-speed"), None);
+
+    /* The same buffer parsed again is the same tree */
+    let again = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(clock_of(&again, &cursor), Some(1_843_200));
+}
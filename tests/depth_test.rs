@@ -0,0 +1,157 @@
+//! Traversals over trees at and past `MAX_DEPTH`, and with a small stack over a much deeper tree
+//! to check that nothing recurses once per level.
+
+use std::fmt;
+
+use static_dt_rs::{tree_diff, tree_eq, DeviceTree, DiffEntry, DtWarnSink, IndexError, NodeIndexEntry, NodePredicate,
+    Token, ValidationError, Warning, MAX_DEPTH};
+
+mod common;
+
+#[derive(Default)]
+struct Collect(Vec<Warning>);
+
+impl DtWarnSink for Collect {
+    fn warn(&mut self, w: Warning) {
+        self.0.push(w)
+    }
+}
+
+/// Path of the node `levels` below the root of `common::deep_fdt()`
+fn deep_path(levels: usize) -> Vec<u8> {
+    b"/sub".repeat(levels)
+}
+
+fn level(node: &Token) -> Option<u32> {
+    node.get_prop(b"level")?.prop_u32(0)
+}
+
+/// Recursive reference of `Token::debug_tree()` using `debug_map()`
+struct Reference<'a>(Token<'a>);
+
+impl fmt::Debug for Reference<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for tok in self.0 {
+            match tok {
+                Token::Property(_, name, _) => { map.key(&format_args!("{}", String::from_utf8_lossy(name))).value(&tok.prop_value().unwrap()); },
+                Token::BeginNode(_, _, name) => { map.key(&format_args!("{}", String::from_utf8_lossy(name))).value(&Reference(tok)); },
+                _ => ()
+            }
+        }
+        map.finish()
+    }
+}
+
+#[test]
+fn test_debug_tree_layout() {
+    for fdt in [common::test_fdt(), common::classify_fdt(), common::deep_fdt(8)] {
+        let dt = DeviceTree::parse(&fdt).unwrap();
+        for node in dt.nodes() {
+            assert_eq!(format!("{:?}", node.debug_tree()), format!("{:?}", Reference(node)));
+            assert_eq!(format!("{:#?}", node.debug_tree()), format!("{:#?}", Reference(node)));
+        }
+    }
+}
+
+#[test]
+fn test_traversal_at_max_depth() {
+    let fdt = common::deep_fdt(MAX_DEPTH - 1);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let path = deep_path(MAX_DEPTH - 1);
+    let deepest = dt.get_node_by_path(&path).unwrap();
+    assert_eq!(level(&deepest), Some(MAX_DEPTH as u32 - 2));
+
+    assert_eq!(dt.validate(), Ok(()));
+    assert_eq!(dt.stats().max_depth, MAX_DEPTH);
+    assert_eq!(dt.nodes().count(), MAX_DEPTH);
+
+    assert_eq!(dt.attributed_tokens().map(|tok| tok.depth).max(), Some(MAX_DEPTH));
+    assert_eq!(dt.attributed_tokens().count(), 3 * MAX_DEPTH - 1);
+    assert_eq!(dt.all_props().count(), MAX_DEPTH - 1);
+    assert_eq!(dt.root().subtree_props().count(), MAX_DEPTH - 1);
+    assert_eq!(dt.find_node_where(|node| level(node) == level(&deepest)), Some(deepest));
+    assert_eq!(dt.find_from(&deep_path(MAX_DEPTH - 2), NodePredicate::HasProp(b"level")), Some(deepest));
+    assert_eq!(deepest.parent(), dt.get_node_by_path(deep_path(MAX_DEPTH - 2)));
+    assert_eq!(deepest.full_path_string().unwrap().as_bytes(), &path[..]);
+
+    let mut storage = [NodeIndexEntry::default(); MAX_DEPTH];
+    let index = dt.build_node_index(&mut storage).unwrap();
+    assert_eq!(index.lookup_path(&path), Some(deepest));
+
+    let mut sink = Collect::default();
+    dt.warnings(&mut sink);
+    assert_eq!(sink.0, vec![]);
+    assert!(tree_eq(&dt, &dt));
+
+    let mut dts = String::new();
+    dt.write_dts(&mut dts).unwrap();
+    assert!(dts.contains(&format!("{}sub {{", "\t".repeat(MAX_DEPTH - 1))));
+    assert_eq!(format!("{:?}", dt.root().debug_tree()).matches('{').count(), MAX_DEPTH);
+}
+
+#[test]
+fn test_traversal_past_max_depth() {
+    let fdt = common::deep_fdt(MAX_DEPTH);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let deepest = dt.get_node_by_path(deep_path(MAX_DEPTH)).unwrap();
+    let offset = deepest.node_offset().unwrap();
+    let last = dt.get_node_by_path(deep_path(MAX_DEPTH - 1)).unwrap();
+
+    /* Every traversal tracking the open nodes stops at the same node */
+    assert_eq!(dt.validate(), Err(ValidationError::TooDeep { offset }));
+    let mut storage = [NodeIndexEntry::default(); MAX_DEPTH + 1];
+    assert_eq!(dt.build_node_index(&mut storage).unwrap_err(), IndexError::TooDeep { offset });
+    let mut sink = Collect::default();
+    dt.warnings(&mut sink);
+    assert_eq!(sink.0, vec![Warning::TooDeep { offset }]);
+    assert_eq!(tree_diff(&dt, &dt), Some(DiffEntry::TooDeep(deepest)));
+
+    assert!(dt.attributed_tokens().all(|tok| tok.depth <= MAX_DEPTH && tok.node_offset != offset));
+    assert_eq!(dt.all_props().count(), MAX_DEPTH - 1);
+    assert_eq!(dt.find_node_where(|node| level(node) == level(&deepest)), None);
+    assert_eq!(dt.find_from(&deep_path(MAX_DEPTH - 2), NodePredicate::HasProp(b"level")), Some(last));
+    assert_eq!(deepest.parent(), None);
+    assert_eq!(deepest.full_path_string(), Some(String::from("/")));
+
+    /* The node at the limit is still reachable, and depths are relative to where a traversal starts */
+    assert_eq!(last.parent(), dt.get_node_by_path(deep_path(MAX_DEPTH - 2)));
+    assert_eq!(dt.find_node_where(|node| level(node) == level(&last)), Some(last));
+    assert_eq!(dt.find_from(&deep_path(MAX_DEPTH - 1), NodePredicate::HasProp(b"level")), Some(deepest));
+
+    /* Flat scans are not limited */
+    assert_eq!(dt.nodes().count(), MAX_DEPTH + 1);
+    assert_eq!(dt.stats().max_depth, MAX_DEPTH + 1);
+}
+
+#[test]
+fn test_traversal_stack_usage() {
+    const DEPTH: usize = 5000;
+    let fdt = common::deep_fdt(DEPTH);
+
+    /* A recursive walk needs several frames per level, far more than this */
+    let result = std::thread::Builder::new().stack_size(64 * 1024).spawn(move || {
+        let dt = DeviceTree::parse(&fdt).unwrap();
+        let deepest = dt.get_node_by_path(deep_path(DEPTH)).unwrap();
+        assert_eq!(level(&deepest), Some(DEPTH as u32 - 1));
+
+        assert_eq!(dt.nodes().count(), DEPTH + 1);
+        assert_eq!(dt.stats().max_depth, DEPTH + 1);
+        assert_eq!(dt.root().counts(), Some((1, 0)));
+        assert!(matches!(dt.validate(), Err(ValidationError::TooDeep { .. })));
+        assert!(matches!(tree_diff(&dt, &dt), Some(DiffEntry::TooDeep(_))));
+        /* Stops at the limit before any node has ended */
+        assert_eq!(dt.attributed_tokens().count(), 2 * MAX_DEPTH - 1);
+        assert_eq!(dt.find_node_where(|node| node == &deepest), None);
+        assert_eq!(deepest.parent(), None);
+        let mut sink = Collect::default();
+        dt.warnings(&mut sink);
+        assert_eq!(sink.0.len(), 1);
+
+        let mut dts = String::new();
+        dt.write_dts(&mut dts).unwrap();
+        assert_eq!(dts.matches("};").count(), DEPTH + 1);
+        assert_eq!(format!("{:#?}", dt.root().debug_tree()).matches('}').count(), DEPTH + 1);
+    }).unwrap().join();
+    assert!(result.is_ok());
+}
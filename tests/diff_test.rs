@@ -0,0 +1,163 @@
+use static_dt_rs::{tree_diff, tree_diff_with, tree_eq, tree_eq_with, DeviceTree, DiffEntry, DtbBuilder, Token, UnitAddrCompare};
+
+mod common;
+
+/// A small tree, siblings and properties in the order given by `swap`
+fn bus_fdt(swap: bool, rate: u32) -> Vec<u8> {
+    common::build(512, |builder| {
+        let uart = |builder: &mut DtbBuilder| {
+            builder.begin_node(b"uart@1000").unwrap();
+            builder.prop_str(b"compatible", b"ns16550a").unwrap();
+            builder.prop_u32(b"clock-frequency", rate).unwrap();
+            builder.end_node().unwrap();
+        };
+        let timer = |builder: &mut DtbBuilder| {
+            builder.begin_node(b"timer@2000").unwrap();
+            builder.prop_empty(b"always-on").unwrap();
+            builder.end_node().unwrap();
+        };
+
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"soc").unwrap();
+        match swap {
+            false => {
+                builder.prop_u32(b"#address-cells", 1).unwrap();
+                builder.prop_u32(b"#size-cells", 1).unwrap();
+                uart(builder);
+                timer(builder);
+            },
+            true => {
+                builder.prop_u32(b"#size-cells", 1).unwrap();
+                builder.prop_u32(b"#address-cells", 1).unwrap();
+                timer(builder);
+                uart(builder);
+            }
+        }
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+#[test]
+fn test_tree_eq_permuted() {
+    let (a, b) = (bus_fdt(false, 115200), bus_fdt(true, 115200));
+    assert_ne!(a, b);
+    let (a, b) = (DeviceTree::parse(&a).unwrap(), DeviceTree::parse(&b).unwrap());
+    assert!(tree_eq(&a, &b));
+    assert!(tree_eq(&b, &a));
+    assert!(tree_eq(&a, &a));
+    assert!(tree_diff(&a, &b).is_none());
+}
+
+#[test]
+fn test_tree_eq_changed_byte() {
+    let a = bus_fdt(false, 115200);
+    let mut b = bus_fdt(true, 115200);
+    let dt_a = DeviceTree::parse(&a).unwrap();
+
+    /* Flip the last byte of clock-frequency */
+    let offs = {
+        let dt = DeviceTree::parse(&b).unwrap();
+        let val = dt.get_as::<&[u8]>(b"/soc/uart@1000/clock-frequency").unwrap();
+        val.as_ptr() as usize - b.as_ptr() as usize + 3
+    };
+    b[offs] ^= 1;
+
+    let dt_b = DeviceTree::parse(&b).unwrap();
+    assert!(!tree_eq(&dt_a, &dt_b));
+    match tree_diff(&dt_a, &dt_b) {
+        Some(DiffEntry::ValueDiffers(x, y)) => {
+            assert_eq!(x.name(), b"clock-frequency");
+            assert_eq!(x.prop_u32(0), Some(115200));
+            assert_eq!(y.prop_u32(0), Some(115201));
+        },
+        other => panic!("unexpected {:?}", other)
+    }
+}
+
+#[test]
+fn test_tree_diff_missing() {
+    let fdt = common::test_fdt();
+    let test = DeviceTree::parse(&fdt).unwrap();
+    let fdt = common::i2c_fdt();
+    let i2c = DeviceTree::parse(&fdt).unwrap();
+
+    match tree_diff(&test, &i2c) {
+        Some(DiffEntry::OnlyInA(tok)) => assert!(matches!(tok, Token::Property(..) | Token::BeginNode(..))),
+        other => panic!("unexpected {:?}", other)
+    }
+
+    /* A tree with an extra property only differs in b */
+    let a = bus_fdt(false, 1);
+    let b = common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"soc").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.begin_node(b"timer@2000").unwrap();
+        builder.prop_empty(b"always-on").unwrap();
+        builder.prop_empty(b"extra").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"uart@1000").unwrap();
+        builder.prop_str(b"compatible", b"ns16550a").unwrap();
+        builder.prop_u32(b"clock-frequency", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let (a, b) = (DeviceTree::parse(&a).unwrap(), DeviceTree::parse(&b).unwrap());
+    match tree_diff(&a, &b) {
+        Some(DiffEntry::OnlyInB(tok)) => assert_eq!(tok.name(), b"extra"),
+        other => panic!("unexpected {:?}", other)
+    }
+    match tree_diff(&b, &a) {
+        Some(DiffEntry::OnlyInA(tok)) => assert_eq!(tok.name(), b"extra"),
+        other => panic!("unexpected {:?}", other)
+    }
+}
+
+#[test]
+fn test_tree_diff_too_deep() {
+    let fdt = common::deep_fdt(64);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert!(matches!(tree_diff(&dt, &dt), Some(DiffEntry::TooDeep(_))));
+    let fdt = common::deep_fdt(63);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert!(tree_eq(&dt, &dt));
+}
+
+/// A tree with a single node named `name` below the root
+fn named_fdt(name: &[u8]) -> Vec<u8> {
+    common::build(256, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(name).unwrap();
+        builder.prop_u32(b"reg", 0x1000).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+#[test]
+fn test_tree_eq_unit_address() {
+    let plain = named_fdt(b"uart@1000");
+    let plain = DeviceTree::parse(&plain).unwrap();
+    for (other, numeric, lenient) in [(&b"uart@0x1000"[..], true, true), (b"uart@01000", true, true), (b"uart", false, true), (b"uart@1001", false, false)] {
+        let other = named_fdt(other);
+        let other = DeviceTree::parse(&other).unwrap();
+        assert!(!tree_eq(&plain, &other));
+        assert!(!tree_eq_with(&plain, &other, UnitAddrCompare::Exact));
+        assert_eq!(tree_eq_with(&plain, &other, UnitAddrCompare::NumericHex), numeric);
+        assert_eq!(tree_eq_with(&other, &plain, UnitAddrCompare::NumericHex), numeric);
+        assert_eq!(tree_eq_with(&plain, &other, UnitAddrCompare::Lenient), lenient);
+        assert_eq!(tree_eq_with(&other, &plain, UnitAddrCompare::Lenient), lenient);
+    }
+
+    /* Values are still compared */
+    let other = named_fdt(b"uart@0x1000");
+    let mut other = other.clone();
+    let dt = DeviceTree::parse(&other).unwrap();
+    let at = dt.get(b"/uart@0x1000/reg").unwrap().value().unwrap().as_ptr() as usize - other.as_ptr() as usize;
+    other[at + 3] = 1;
+    let other = DeviceTree::parse(&other).unwrap();
+    assert!(matches!(tree_diff_with(&plain, &other, UnitAddrCompare::NumericHex), Some(DiffEntry::ValueDiffers(_, _))));
+}
@@ -0,0 +1,76 @@
+use static_dt_rs::{dt_match_table, DeviceTree, DtMatch, Token};
+
+mod common;
+
+#[derive(Default)]
+struct Probed {
+    calls: Vec<(&'static str, usize)>,
+}
+
+fn probe_timer(node: Token, ctx: &mut Probed) {
+    ctx.calls.push(("timer", node.node_offset().unwrap()));
+}
+
+fn probe_uart(node: Token, ctx: &mut Probed) {
+    ctx.calls.push(("uart", node.node_offset().unwrap()));
+}
+
+dt_match_table! {
+    static DRIVERS;
+    fn probe_all(&mut Probed);
+    b"acme,timer" => probe_timer,
+    b"acme,uart-v2" => probe_uart,
+}
+
+#[test]
+fn test_match_compatible() {
+    let fdt = common::drivers_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let table = [
+        DtMatch { compatible: b"acme,timer", data: 1 },
+        DtMatch { compatible: b"acme,uart-v2", data: 2 },
+        DtMatch { compatible: b"acme,uart-v2", data: 3 },
+    ];
+
+    /* The most specific string wins, not the first entry of the table */
+    let serial = dt.get_node_by_path(b"/soc/serial@200").unwrap();
+    assert_eq!(serial.match_compatible(&table).map(|m| m.data), Some(2));
+    let timer = dt.get_node_by_path(b"/soc/timer@100").unwrap();
+    assert_eq!(timer.match_compatible(&table).map(|m| m.data), Some(1));
+
+    assert_eq!(dt.get_node_by_path(b"/soc/gpio@400").unwrap().match_compatible(&table), None);
+    assert_eq!(dt.root().match_compatible(&table), None);
+    assert_eq!(serial.get_prop(b"compatible").unwrap().match_compatible(&table), None);
+    assert_eq!(timer.match_compatible::<u32>(&[]), None);
+}
+
+#[test]
+fn test_probe_matches() {
+    let fdt = common::drivers_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let table = [
+        DtMatch { compatible: b"acme,timer", data: 1 },
+        DtMatch { compatible: b"acme,uart-v2", data: 2 },
+    ];
+
+    let matched: Vec<_> = dt.probe_matches(&table)
+        .map(|(node, m)| (node.name(), m.data))
+        .collect();
+    assert_eq!(matched, vec![(&b"timer@100"[..], 1), (&b"serial@200"[..], 2)]);
+}
+
+#[test]
+fn test_probe_all() {
+    let fdt = common::drivers_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let offset = |path: &[u8]| dt.get_node_by_path(path).unwrap().node_offset().unwrap();
+
+    let mut ctx = Probed::default();
+    probe_all(&dt, &mut ctx);
+    assert_eq!(ctx.calls, vec![
+        ("timer", offset(b"/soc/timer@100")),
+        ("uart", offset(b"/soc/serial@200")),
+    ]);
+    assert_eq!(DRIVERS.len(), 2);
+    assert_eq!(DRIVERS[1].compatible, b"acme,uart-v2");
+}
@@ -0,0 +1,183 @@
+//! Snapshot tests of `write_dts()` and `Token::debug_tree()`.
+//! The expected output is in tests/snapshots/, after an intentional format change
+//! regenerate it with `UPDATE_SNAPSHOTS=1 cargo test --test dts_test` and review the diff.
+
+use std::path::PathBuf;
+
+use static_dt_rs::{ClassifyOptions, DeviceTree, HexdumpOptions, PropValue};
+
+mod common;
+
+/// Strip trailing whitespace of every line
+fn normalize(s: &str) -> String {
+    s.lines().map(|line| line.trim_end()).collect::<Vec<_>>().join("\n")
+}
+
+fn check_snapshot(name: &str, actual: &str) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", name].iter().collect();
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {}, run with UPDATE_SNAPSHOTS=1 to create it", path.display(), e));
+    assert_eq!(normalize(actual), normalize(&expected), "{} differs, run with UPDATE_SNAPSHOTS=1 to update it", name);
+}
+
+fn render(fdt: &[u8]) -> (String, String) {
+    let dt = DeviceTree::parse(fdt).unwrap();
+    let mut dts = String::new();
+    dt.write_dts(&mut dts).unwrap();
+    (dts, format!("{:#?}\n", dt.root().debug_tree()))
+}
+
+#[test]
+fn test_snapshot_canonical() {
+    let (dts, debug) = render(&common::test_fdt());
+    check_snapshot("test.dts", &dts);
+    check_snapshot("test.debug", &debug);
+}
+
+#[test]
+fn test_snapshot_classify() {
+    let (dts, debug) = render(&common::classify_fdt());
+    check_snapshot("classify.dts", &dts);
+    check_snapshot("classify.debug", &debug);
+}
+
+#[test]
+fn test_classify() {
+    let fdt = common::classify_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let node = dt.get_node_by_path(b"/values").unwrap();
+    let value = |name: &[u8]| node.get_prop(name).unwrap().prop_value().unwrap();
+
+    assert_eq!(value(b"empty"), PropValue::Empty);
+    assert_eq!(value(b"string"), PropValue::String(b"say \"hi\" \\o/"));
+    assert_eq!(value(b"string-list").strings().collect::<Vec<_>>(), [&b"first"[..], b"second", b"third"]);
+    assert_eq!(value(b"cell"), PropValue::Cell(0xdeadbeef));
+    assert_eq!(value(b"cells").cells().collect::<Vec<_>>(), [0, 1, 0x80000000]);
+    assert_eq!(value(b"bytes"), PropValue::Bytes(&[1, 2, 3]));
+    assert!(matches!(value(b"not-a-string"), PropValue::Bytes(_)));
+
+    /* Values which are not strings because of empty strings or missing NUL */
+    assert_eq!(PropValue::classify(b"\0\0\0\0"), PropValue::Cell(0));
+    assert_eq!(PropValue::classify(b"a\0\0b\0"), PropValue::Bytes(b"a\0\0b\0"));
+    assert_eq!(PropValue::classify(b"abcd"), PropValue::Cell(0x61626364));
+    assert_eq!(node.prop_value(), None);
+}
+
+#[test]
+fn test_classify_options() {
+    let strict = ClassifyOptions { min_string_len: 5, single_string: true, allow_high_bytes: false };
+    let utf8 = ClassifyOptions { allow_high_bytes: true, ..ClassifyOptions::default() };
+    let classify = |val: &'static [u8]| (PropValue::classify(val), PropValue::classify_with(val, &strict), PropValue::classify_with(val, &utf8));
+
+    /* Short binary values looking like strings */
+    assert_eq!(classify(b"abcd\0"), (PropValue::String(b"abcd"), PropValue::Bytes(b"abcd\0"), PropValue::String(b"abcd")));
+    assert_eq!(classify(b"ab\0"), (PropValue::String(b"ab"), PropValue::Bytes(b"ab\0"), PropValue::String(b"ab")));
+    assert_eq!(classify(b"abc\0"), (PropValue::String(b"abc"), PropValue::Cell(0x61626300), PropValue::String(b"abc")));
+
+    /* String lists */
+    assert_eq!(classify(b"okay\0fail\0"),
+        (PropValue::StringList(b"okay\0fail\0"), PropValue::Bytes(b"okay\0fail\0"), PropValue::StringList(b"okay\0fail\0")));
+    assert_eq!(classify(b"first\0second\0"),
+        (PropValue::StringList(b"first\0second\0"), PropValue::Bytes(b"first\0second\0"), PropValue::StringList(b"first\0second\0")));
+
+    /* High bytes: UTF-8 "grüße" */
+    assert_eq!(classify("grüße\0".as_bytes()),
+        (PropValue::Cells("grüße\0".as_bytes()), PropValue::Cells("grüße\0".as_bytes()), PropValue::String("grüße".as_bytes())));
+
+    /* Empty strings never qualify */
+    let lenient = ClassifyOptions { min_string_len: 0, ..ClassifyOptions::default() };
+    assert_eq!(PropValue::classify_with(b"\0", &lenient), PropValue::Bytes(b"\0"));
+    assert_eq!(PropValue::classify_with(b"long enough\0", &strict), PropValue::String(b"long enough"));
+}
+
+#[test]
+fn test_classify_options_output() {
+    let fdt = common::build(256, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_str(b"tag", b"abcd").unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let strict = ClassifyOptions { min_string_len: 5, ..ClassifyOptions::default() };
+
+    let tag = dt.get(b"/tag").unwrap();
+    assert_eq!(tag.classify_with(&strict), Some(PropValue::Bytes(b"abcd\0")));
+    assert_eq!(tag.classify_with(&ClassifyOptions::default()), tag.prop_value());
+    assert_eq!(dt.root().classify_with(&strict), None);
+
+    /* The emitters classify the same way */
+    let mut dts = String::new();
+    dt.write_dts_with(&mut dts, &strict).unwrap();
+    assert!(dts.contains("tag = [61 62 63 64 00];"), "{}", dts);
+    assert_eq!(format!("{:?}", dt.root().debug_tree_with(&strict)), "{tag: Bytes([61 62 63 64 00])}");
+    assert_eq!(format!("{:?}", dt.root().debug_tree()), "{tag: String(\"abcd\")}");
+
+    let mut default = String::new();
+    dt.write_dts_with(&mut default, &ClassifyOptions::default()).unwrap();
+    let mut plain = String::new();
+    dt.write_dts(&mut plain).unwrap();
+    assert_eq!(default, plain);
+}
+
+/// A vendor property of `len` counting bytes, for hexdumps
+fn vendor_fdt(len: usize) -> Vec<u8> {
+    common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.property(b"vendor,blob", &(0..len).map(|i| (i * 7) as u8).collect::<Vec<_>>()).unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+#[test]
+fn test_snapshot_hexdump() {
+    let mut dump = String::new();
+    let fdt = common::classify_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    for prop in dt.get_node_by_path(b"/values").unwrap().props() {
+        prop.hexdump(&mut dump, 8).unwrap();
+    }
+
+    /* Lines not a multiple of 4 bytes have no cells, long values are cut */
+    let fdt = vendor_fdt(100);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let blob = dt.root().get_prop(b"vendor,blob").unwrap();
+    blob.hexdump(&mut dump, 6).unwrap();
+    blob.hexdump_with(&mut dump, &HexdumpOptions { bytes_per_line: 16, max_lines: 3 }).unwrap();
+    check_snapshot("hexdump.txt", &dump);
+}
+
+#[test]
+fn test_hexdump() {
+    let fdt = common::build(256, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.property(b"vendor,blob", &[0, 0, 0, 1, b'a', b'b', b'c', 0, 0xde, 0xad, 0xbe, 0xef]).unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let blob = dt.root().get_prop(b"vendor,blob").unwrap();
+
+    let mut dump = String::new();
+    blob.hexdump(&mut dump, 8).unwrap();
+    assert_eq!(dump, "vendor,blob (12 bytes)\n\
+                      \x20 0000: 00 00 00 01 61 62 63 00  |....abc.|  0x00000001 0x61626300\n\
+                      \x20 0008: de ad be ef              |....|      0xdeadbeef\n");
+
+    /* The cut counts the bytes left out */
+    let mut dump = String::new();
+    blob.hexdump_with(&mut dump, &HexdumpOptions { bytes_per_line: 4, max_lines: 1 }).unwrap();
+    assert_eq!(dump.lines().last(), Some("  ... 8 more bytes"));
+    assert_eq!(dump.lines().count(), 3);
+
+    /* Zero bytes per line is one, nodes write nothing */
+    let mut dump = String::new();
+    blob.hexdump(&mut dump, 0).unwrap();
+    assert_eq!(dump.lines().count(), 13);
+    let mut dump = String::new();
+    dt.root().hexdump(&mut dump, 8).unwrap();
+    assert_eq!(dump, "");
+}
@@ -0,0 +1,337 @@
+use static_dt_rs::{raw, DeviceTree, DeviceTreeMut, EditError, Error, NoWarnings, ParseOptions};
+
+mod common;
+
+/// A small tree with room for `spare` more bytes after `totalsize`
+fn editable(spare: usize) -> Vec<u8> {
+    let mut fdt = common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"a").unwrap();
+        builder.prop_u32(b"x", 1).unwrap();
+        builder.prop_u32(b"y", 2).unwrap();
+        builder.begin_node(b"child").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"b").unwrap();
+        builder.prop_str(b"label", b"after").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    fdt.resize(fdt.len() + spare, 0);
+    fdt
+}
+
+/// `editable()` with the strings block moved in front of the structure block, padded to 4 bytes
+fn strings_first(spare: usize) -> Vec<u8> {
+    let fdt = editable(0);
+    let header = |field: usize| u32::from_be_bytes([fdt[field], fdt[field + 1], fdt[field + 2], fdt[field + 3]]) as usize;
+    let (structs, strings) = (header(raw::OFF_DT_STRUCT), header(raw::OFF_DT_STRINGS));
+    let (struct_size, strings_size) = (header(raw::OFF_SIZE_DT_STRUCT), header(raw::OFF_SIZE_DT_STRINGS));
+
+    let mut out = fdt[..structs].to_vec();
+    out.extend_from_slice(&fdt[strings..strings + strings_size]);
+    out.resize(out.len().next_multiple_of(raw::FDT_STRUCT_ALIGN), 0);
+    let moved = out.len();
+    out.extend_from_slice(&fdt[structs..structs + struct_size]);
+    let totalsize = out.len();
+    for (field, value) in [(raw::OFF_DT_STRINGS, structs), (raw::OFF_DT_STRUCT, moved), (raw::OFF_TOTALSIZE, totalsize)] {
+        out[field..field + 4].copy_from_slice(&(value as u32).to_be_bytes());
+    }
+    out.resize(totalsize + spare, 0);
+    out
+}
+
+fn offset_of(dt: &DeviceTree, path: &[u8]) -> usize {
+    dt.get_node_by_path(path).unwrap().node_offset().unwrap()
+}
+
+#[test]
+fn test_nop_slack() {
+    let mut buf = editable(64);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    assert_eq!(dt.tree().total_nop_bytes(), 0);
+    assert_eq!(dt.tree().get_node_by_path(b"/a").unwrap().nop_slack(), 0);
+
+    dt.delete_prop(b"/a", b"y").unwrap();
+    let tree = dt.tree();
+    let a = tree.get_node_by_path(b"/a").unwrap();
+    assert_eq!(a.get_prop(b"y"), None);
+    assert_eq!(a.nop_slack(), 16);
+    assert_eq!(tree.total_nop_bytes(), 16);
+    assert_eq!(tree.root().nop_slack(), 0);
+    assert_eq!(a.get_prop(b"x").unwrap().nop_slack(), 0);
+    assert_eq!(tree.validate(), Ok(()));
+
+    /* NOPs before the properties are not slack */
+    dt.delete_prop(b"/a", b"x").unwrap();
+    dt.add_prop(b"/a", b"x", &[0, 0, 0, 3]).unwrap();
+    assert_eq!(dt.tree().get_node_by_path(b"/a").unwrap().nop_slack(), 16);
+}
+
+#[test]
+fn test_nop_slack_oversize_property() {
+    let mut buf = editable(64);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    dt.delete_prop(b"/a", b"y").unwrap();
+
+    /* x and label are oversized, only the NOPs of y count */
+    let options = ParseOptions { max_prop_size: 2 };
+    let tree = DeviceTree::parse_with_options(&buf, &options, &mut NoWarnings).unwrap();
+    let a = tree.get_node_by_path(b"/a").unwrap();
+    assert_eq!(a.nop_slack(), 0);
+    assert_eq!(tree.total_nop_bytes(), 16);
+}
+
+#[test]
+fn test_add_prop_uses_slack() {
+    let mut buf = editable(64);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    dt.delete_prop(b"/a", b"y").unwrap();
+
+    let (b, child, struct_size) = {
+        let tree = dt.tree();
+        (offset_of(&tree, b"/b"), offset_of(&tree, b"/a/child"), tree.struct_block().len())
+    };
+
+    /* "x" is in the strings block already, nothing moves */
+    let totalsize = dt.blob().len();
+    dt.delete_prop(b"/a", b"x").unwrap();
+    dt.add_prop(b"/a", b"x", &[0, 0, 0, 5, 0, 0, 0, 6]).unwrap();
+    assert_eq!(dt.blob().len(), totalsize);
+
+    /* A new name only grows the strings block after the structure block */
+    dt.add_prop(b"/a", b"z", &[]).unwrap();
+    let tree = dt.tree();
+    assert_eq!(tree.blob().len(), totalsize + 2);
+    assert_eq!(offset_of(&tree, b"/b"), b);
+    assert_eq!(offset_of(&tree, b"/a/child"), child);
+    assert_eq!(tree.struct_block().len(), struct_size);
+    assert_eq!(tree.total_nop_bytes(), 0);
+
+    let a = tree.get_node_by_path(b"/a").unwrap();
+    assert_eq!(a.get_prop(b"x").unwrap().prop_u32(1), Some(6));
+    assert!(a.get_prop(b"z").is_some());
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn test_add_prop_shifts() {
+    let mut buf = editable(64);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    let b = offset_of(&dt.tree(), b"/b");
+    let totalsize = dt.blob().len();
+
+    dt.add_prop(b"/a", b"label", b"grown\0").unwrap();
+    let tree = dt.tree();
+    assert_eq!(offset_of(&tree, b"/b"), b + 20);
+    assert_eq!(tree.blob().len(), totalsize + 20);
+    assert_eq!(tree.get_node_by_path(b"/a").unwrap().get_prop(b"label").unwrap().prop_str(), Some(&b"grown"[..]));
+    assert_eq!(tree.get_node_by_path(b"/b").unwrap().get_prop(b"label").unwrap().prop_str(), Some(&b"after"[..]));
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn test_add_prop_strings_first() {
+    let mut buf = strings_first(64);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    let structs = dt.tree().off_dt_struct();
+    let totalsize = dt.blob().len();
+
+    /* 3 bytes of name don't fit the 2 bytes of padding, the structure block moves by 4 */
+    dt.add_prop(b"/a", b"zz", &[0, 0, 0, 7]).unwrap();
+    let tree = dt.tree();
+    assert_eq!(tree.off_dt_struct(), structs + 4);
+    assert_eq!(tree.blob().len(), totalsize + 4 + 16);
+    assert_eq!(tree.get_node_by_path(b"/a").unwrap().get_prop(b"zz").unwrap().prop_u32(0), Some(7));
+    assert_eq!(tree.get_node_by_path(b"/b").unwrap().get_prop(b"label").unwrap().prop_str(), Some(&b"after"[..]));
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn test_edit_errors() {
+    let mut buf = editable(12);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    let blob = dt.blob().to_vec();
+
+    assert_eq!(dt.add_prop(b"/missing", b"x", &[]), Err(EditError::NodeNotFound));
+    assert_eq!(dt.add_prop(b"/a", b"x", &[]), Err(EditError::Exists));
+    assert_eq!(dt.add_prop(b"/a", b"", &[]), Err(EditError::InvalidName));
+    assert_eq!(dt.add_prop(b"/a", b"a\0b", &[]), Err(EditError::InvalidName));
+    assert_eq!(dt.add_prop(b"/a", b"big", &[0; 16]), Err(EditError::NoSpace));
+    assert_eq!(dt.delete_prop(b"/a", b"missing"), Err(EditError::PropNotFound));
+    assert_eq!(dt.delete_prop(b"/missing", b"x"), Err(EditError::NodeNotFound));
+    assert_eq!(dt.blob(), &blob[..]);
+    assert_eq!(dt.capacity(), blob.len() + 12);
+
+    /* An empty property with a known name fits in the spare bytes */
+    dt.add_prop(b"/b", b"x", &[]).unwrap();
+
+    let mut bad = vec![0u8; 64];
+    assert_eq!(DeviceTreeMut::new(&mut bad).unwrap_err(), Error::InvalidMagic);
+}
+
+#[test]
+fn test_set_prop() {
+    let mut buf = editable(16);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    let totalsize = dt.blob().len();
+
+    dt.set_prop_u32(b"/a", b"x", 0, 7).unwrap();
+    assert_eq!(dt.tree().get(b"/a/x").unwrap().prop_u32(0), Some(7));
+    assert_eq!(dt.set_prop_u32(b"/a", b"x", 1, 7), Err(EditError::OutOfRange));
+    assert_eq!(dt.set_prop_u32(b"/a", b"z", 0, 7), Err(EditError::PropNotFound));
+
+    /* Shrinking leaves NOPs behind, growing shifts the rest */
+    dt.set_prop_str(b"/b", b"label", b"ab").unwrap();
+    assert_eq!(dt.tree().get(b"/b/label").unwrap().prop_str(), Some(&b"ab"[..]));
+    assert_eq!(dt.tree().total_nop_bytes(), 4);
+    assert_eq!(dt.blob().len(), totalsize);
+
+    dt.set_prop(b"/a", b"x", &[1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    let tree = dt.tree();
+    assert_eq!(tree.get(b"/a/x").unwrap().prop_cells().collect::<Vec<_>>(), vec![0x01020304, 0x05060708]);
+    assert_eq!(tree.get(b"/a/y").unwrap().prop_u32(0), Some(2));
+    assert_eq!(tree.get(b"/b/label").unwrap().prop_str(), Some(&b"ab"[..]));
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(dt.blob().len(), totalsize + 4);
+
+    /* A missing property is added */
+    dt.set_prop(b"/b", b"x", &[]).unwrap();
+    assert_eq!(dt.tree().get(b"/b/x").unwrap().prop_len(), Some(0));
+
+    let before = dt.blob().to_vec();
+    assert_eq!(dt.set_prop(b"/a", b"y", &[0; 64]), Err(EditError::NoSpace));
+    assert_eq!(dt.blob(), &before[..]);
+}
+
+#[test]
+fn test_delete_node() {
+    let mut buf = editable(0);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+
+    dt.delete_node(b"/a").unwrap();
+    let tree = dt.tree();
+    assert_eq!(tree.get_node_by_path(b"/a"), None);
+    assert_eq!(tree.get_node_by_path(b"/a/child"), None);
+    assert_eq!(tree.get(b"/b/label").unwrap().prop_str(), Some(&b"after"[..]));
+    assert_eq!(tree.nodes().count(), 2);
+    assert_eq!(tree.validate(), Ok(()));
+
+    assert_eq!(dt.delete_node(b"/a"), Err(EditError::NodeNotFound));
+    assert_eq!(dt.delete_node(b"/"), Err(EditError::RootNode));
+}
+
+#[test]
+fn test_add_node() {
+    let mut buf = editable(64);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+
+    dt.add_node(b"/a", b"new@10").unwrap();
+    dt.add_prop(b"/a/new@10", b"z", &[1]).unwrap();
+    dt.add_node(b"/", b"last").unwrap();
+    let tree = dt.tree();
+    assert_eq!(tree.validate(), Ok(()));
+    assert_eq!(tree.get_node_by_path(b"/a").unwrap().children().map(|node| node.name()).collect::<Vec<_>>(),
+               vec![&b"child"[..], b"new@10"]);
+    assert_eq!(tree.get(b"/a/new@10/z").unwrap().prop_u32(0), None);
+    assert_eq!(tree.root().children().last().unwrap().name(), b"last");
+    assert_eq!(tree.get(b"/b/label").unwrap().prop_str(), Some(&b"after"[..]));
+
+    assert_eq!(dt.add_node(b"/a", b"child"), Err(EditError::Exists));
+    assert_eq!(dt.add_node(b"/missing", b"x"), Err(EditError::NodeNotFound));
+    assert_eq!(dt.add_node(b"/", b"a/b"), Err(EditError::InvalidName));
+    assert_eq!(dt.add_node(b"/", b""), Err(EditError::InvalidName));
+    assert_eq!(dt.add_node(b"/", &[b'x'; 64]), Err(EditError::NoSpace));
+}
+
+#[test]
+fn test_ensure_slack() {
+    let mut buf = editable(64);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    let totalsize = dt.blob().len();
+    assert_eq!(dt.ensure_slack(48, 17), Err(EditError::NoSpace));
+    assert_eq!(dt.blob().len(), totalsize);
+
+    /* The strings gap is rounded up to keep blocks aligned */
+    dt.ensure_slack(48, 9).unwrap();
+    assert_eq!(dt.blob().len(), totalsize + 64);
+    assert_eq!(dt.blob().len(), dt.capacity());
+    assert_eq!(dt.tree().validate(), Ok(()));
+
+    /* Reserved space is reused, not added again */
+    dt.ensure_slack(48, 16).unwrap();
+    assert_eq!(dt.blob().len(), totalsize + 64);
+
+    /* Edits within the reserved bytes fit although the buffer is full */
+    let (strings, b) = {
+        let tree = dt.tree();
+        (tree.strings_block().as_ptr() as usize - tree.blob().as_ptr() as usize, offset_of(&tree, b"/b"))
+    };
+    dt.add_prop(b"/a", b"p", &[0, 0, 0, 1]).unwrap();
+    dt.add_prop(b"/b", b"q", &[0, 0, 0, 2]).unwrap();
+    dt.set_prop_str(b"/b", b"longer-name", b"").unwrap();
+    let tree = dt.tree();
+    assert_eq!(tree.blob().len(), totalsize + 64);
+    assert_eq!(tree.strings_block().as_ptr() as usize - tree.blob().as_ptr() as usize, strings);
+    assert_eq!(offset_of(&tree, b"/b"), b + 16);
+    assert_eq!(tree.get(b"/a/p").unwrap().prop_u32(0), Some(1));
+    assert_eq!(tree.get(b"/b/q").unwrap().prop_u32(0), Some(2));
+    assert_eq!(tree.get(b"/b/label").unwrap().prop_str(), Some(&b"after"[..]));
+    assert_eq!(tree.validate(), Ok(()));
+
+    assert_eq!(dt.add_prop(b"/a", b"r", &[]), Err(EditError::NoSpace));
+}
+
+#[test]
+fn test_transaction() {
+    let edits = |dt: &mut DeviceTreeMut| {
+        dt.add_prop(b"/a", b"z", &[0, 0, 0, 3])?;
+        dt.set_prop_u32(b"/a", b"x", 0, 9)?;
+        dt.delete_prop(b"/b", b"label")?;
+        dt.set_prop(b"/a", b"y", &[0; 12])?;
+        dt.delete_node(b"/a/child")?;
+        dt.add_prop(b"/a", b"x", &[])
+    };
+
+    /* In place in reserved space and shifting the blob */
+    for reserve in [true, false] {
+        let mut buf = editable(128);
+        let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+        if reserve {
+            dt.ensure_slack(64, 16).unwrap();
+        }
+        let before = dt.blob().to_vec();
+
+        assert_eq!(dt.transaction(edits), Err(EditError::Exists));
+        assert_eq!(dt.blob(), &before[..]);
+        let tree = dt.tree();
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.get(b"/a/x").unwrap().prop_u32(0), Some(1));
+        assert_eq!(tree.get(b"/a/y").unwrap().prop_len(), Some(4));
+        assert_eq!(tree.get(b"/a/z"), None);
+        assert_eq!(tree.get(b"/b/label").unwrap().prop_str(), Some(&b"after"[..]));
+        assert!(tree.get_node_by_path(b"/a/child").is_some());
+    }
+
+    /* Edits are kept if all succeed, a failed nested transaction only undoes its own */
+    let mut buf = editable(128);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    dt.transaction(|dt| {
+        dt.add_prop(b"/a", b"z", &[0, 0, 0, 3])?;
+        assert_eq!(dt.transaction(edits), Err(EditError::Exists));
+        dt.set_prop_u32(b"/a", b"x", 0, 9)
+    }).unwrap();
+    let tree = dt.tree();
+    assert_eq!(tree.get(b"/a/z").unwrap().prop_u32(0), Some(3));
+    assert_eq!(tree.get(b"/a/x").unwrap().prop_u32(0), Some(9));
+    assert_eq!(tree.get(b"/a/y").unwrap().prop_len(), Some(4));
+    assert_eq!(tree.validate(), Ok(()));
+
+    /* Too many steps to undo */
+    let before = dt.blob().to_vec();
+    let result = dt.transaction(|dt| {
+        (0..=static_dt_rs::MAX_TRANSACTION_STEPS).try_for_each(|i| dt.set_prop_u32(b"/a", b"x", 0, i as u32))
+    });
+    assert_eq!(result, Err(EditError::NoSpace));
+    assert_eq!(dt.blob(), &before[..]);
+}
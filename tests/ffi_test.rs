@@ -0,0 +1,87 @@
+#![cfg(feature = "ffi")]
+
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::ptr;
+
+use static_dt_rs::ffi::*;
+use static_dt_rs::DeviceTree;
+
+static BOARD_FDT: &[u8] = include_bytes!("board.dtb");
+
+fn fdt() -> *const c_void {
+    BOARD_FDT.as_ptr() as *const c_void
+}
+
+fn c(s: &'static [u8]) -> *const c_char {
+    CStr::from_bytes_with_nul(s).unwrap().as_ptr()
+}
+
+#[test]
+fn test_ffi_check_header() {
+    unsafe {
+        assert_eq!(fdt_check_header(fdt()), 0);
+        assert_eq!(fdt_check_header(include_bytes!("bad_magic.dtb").as_ptr() as *const c_void), -FDT_ERR_BADMAGIC);
+
+        let mut buf = BOARD_FDT.to_vec();
+        buf[24..28].copy_from_slice(&17u32.to_be_bytes());
+        assert_eq!(fdt_check_header(buf.as_ptr() as *const c_void), -FDT_ERR_BADVERSION);
+    }
+}
+
+#[test]
+fn test_ffi_lookup() {
+    let dt = DeviceTree::parse(BOARD_FDT).unwrap();
+    let uart = dt.get_node_by_path(b"/soc/serial@10000000").unwrap();
+
+    unsafe {
+        let offset = fdt_path_offset(fdt(), c(b"/soc/serial@10000000\0"));
+        assert_eq!(Some(offset as usize), uart.node_offset());
+        assert_eq!(fdt_path_offset(fdt(), c(b"/\0")), 0);
+        assert_eq!(fdt_path_offset(fdt(), c(b"/soc/serial\0")), -FDT_ERR_NOTFOUND);
+        assert_eq!(fdt_path_offset(fdt(), c(b"soc\0")), -FDT_ERR_BADPATH);
+
+        let mut len: c_int = 0;
+        let name = fdt_get_name(fdt(), offset, &mut len);
+        assert_eq!(CStr::from_ptr(name).to_bytes(), b"serial@10000000");
+        assert_eq!(len, 15);
+        assert!(fdt_get_name(fdt(), offset + 4, &mut len).is_null());
+        assert_eq!(len, -FDT_ERR_BADOFFSET);
+
+        let val = fdt_getprop(fdt(), offset, c(b"compatible\0"), &mut len);
+        assert_eq!(std::slice::from_raw_parts(val as *const u8, len as usize), b"ns16550a\0");
+        assert!(fdt_getprop(fdt(), offset, c(b"status\0"), &mut len).is_null());
+        assert_eq!(len, -FDT_ERR_NOTFOUND);
+        assert!(fdt_getprop(fdt(), -1, c(b"status\0"), ptr::null_mut()).is_null());
+    }
+}
+
+#[test]
+fn test_ffi_next_node() {
+    let dt = DeviceTree::parse(BOARD_FDT).unwrap();
+    let expected: Vec<c_int> = dt.nodes().map(|node| node.node_offset().unwrap() as c_int).collect();
+
+    unsafe {
+        /* Walk the whole tree */
+        let mut offsets = Vec::new();
+        let mut depth = 0;
+        let mut offset = fdt_next_node(fdt(), -1, &mut depth);
+        while offset >= 0 {
+            offsets.push(offset);
+            offset = fdt_next_node(fdt(), offset, &mut depth);
+        }
+        assert_eq!(offsets, expected);
+        assert_eq!(offset, -FDT_ERR_NOTFOUND);
+
+        /* Children of soc, stops when leaving it */
+        let soc = fdt_path_offset(fdt(), c(b"/soc\0"));
+        let mut depth = 0;
+        let first = fdt_next_node(fdt(), soc, &mut depth);
+        assert_eq!((first, depth), (expected[2], 1));
+        let second = fdt_next_node(fdt(), first, &mut depth);
+        assert_eq!((second, depth), (expected[3], 1));
+        assert!(fdt_next_node(fdt(), second, &mut depth) >= 0);
+        assert_eq!(depth, -1);
+
+        assert_eq!(fdt_next_node(fdt(), 3, ptr::null_mut()), -FDT_ERR_BADOFFSET);
+    }
+}
@@ -0,0 +1,142 @@
+use static_dt_rs::{instrument, DeviceTree, NodePredicate};
+
+mod common;
+
+/// Peripherals outside of /soc to be skipped over, then a soc with a few devices
+fn soc_fdt() -> Vec<u8> {
+    common::build(8192, |builder| {
+        builder.begin_node(b"").unwrap();
+        for i in 0..32u32 {
+            builder.begin_node(format!("reserved@{:x}", i).as_bytes()).unwrap();
+            builder.prop_str(b"compatible", b"vendor,reserved").unwrap();
+            builder.prop_cells(b"reg", &[i, 0x1000]).unwrap();
+            builder.begin_node(b"config").unwrap();
+            builder.prop_u32(b"value", i).unwrap();
+            builder.end_node().unwrap();
+            builder.end_node().unwrap();
+        }
+
+        builder.begin_node(b"soc").unwrap();
+        builder.prop_str(b"compatible", b"simple-bus").unwrap();
+        builder.begin_node(b"timer@1000").unwrap();
+        builder.prop_str(b"compatible", b"vendor,timer").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"bus@2000").unwrap();
+        builder.begin_node(b"serial@2100").unwrap();
+        builder.property(b"compatible", b"vendor,uart\0ns16550a\0").unwrap();
+        builder.prop_u32(b"clock-frequency", 1_843_200).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"memory@80000000").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+    })
+}
+
+#[test]
+fn test_find_from() {
+    let _serial = common::serial();
+    let fdt = soc_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let name = |pred| dt.find_from(b"/soc", pred).map(|node| node.name());
+
+    assert_eq!(name(NodePredicate::HasProp(b"clock-frequency")), Some(&b"serial@2100"[..]));
+    assert_eq!(name(NodePredicate::Compatible(b"ns16550a")), Some(&b"serial@2100"[..]));
+    assert_eq!(name(NodePredicate::Compatible(b"vendor,timer")), Some(&b"timer@1000"[..]));
+    assert_eq!(name(NodePredicate::Named(b"serial")), Some(&b"serial@2100"[..]));
+    assert_eq!(name(NodePredicate::Named(b"bus@2000")), Some(&b"bus@2000"[..]));
+    assert_eq!(name(NodePredicate::DeviceType(b"memory")), Some(&b"memory@80000000"[..]));
+
+    /* The start node itself and nodes outside of the subtree are not matched */
+    assert_eq!(name(NodePredicate::Compatible(b"simple-bus")), None);
+    assert_eq!(name(NodePredicate::Compatible(b"vendor,reserved")), None);
+    assert_eq!(name(NodePredicate::Named(b"ser")), None);
+    assert_eq!(dt.find_from(b"/soc/bus@2000", NodePredicate::Named(b"timer")), None);
+    assert_eq!(dt.find_from(b"/missing", NodePredicate::Named(b"serial")), None);
+    assert_eq!(dt.find_from(b"soc", NodePredicate::Named(b"serial")), None);
+
+    /* Same results as the predicate evaluated on each node */
+    let found = dt.find_from(b"/", NodePredicate::HasProp(b"value")).unwrap();
+    assert_eq!(Some(found), dt.nodes().skip(1).find(|node| NodePredicate::HasProp(b"value").matches(node)));
+
+    /* Decoded tokens compared to a naive scan of the whole tree */
+    let pred = NodePredicate::Compatible(b"ns16550a");
+    instrument::reset();
+    let scoped = dt.find_from(b"/soc", pred).unwrap();
+    let scoped_reads = instrument::token_reads();
+
+    instrument::reset();
+    let naive = dt.nodes().find(|node| pred.matches(node)).unwrap();
+    let naive_reads = instrument::token_reads();
+
+    assert_eq!(scoped, naive);
+    assert!(scoped_reads * 4 < naive_reads, "scoped {} naive {}", scoped_reads, naive_reads);
+}
+
+#[test]
+fn test_str_names() {
+    let _serial = common::serial();
+    let fdt = common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"aliases").unwrap();
+        builder.prop_str(b"serial0", b"/soc/serial@2100").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"__symbols__").unwrap();
+        builder.prop_str(b"uart0", b"/soc/serial@2100").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"soc").unwrap();
+        builder.begin_node(b"serial@2100").unwrap();
+        builder.property(b"compatible", b"vendor,uart\0ns16550a\0").unwrap();
+        builder.prop_u32(b"clock-frequency", 1_843_200).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let uart = dt.get_node_by_path(b"/soc/serial@2100").unwrap();
+
+    /* Names from configuration arrive as &str and String */
+    let path = String::from("/soc/serial@2100");
+    let prop: &str = "clock-frequency";
+    assert_eq!(dt.get_node_by_path("/soc/serial@2100"), Some(uart));
+    assert_eq!(dt.get_node_by_path(&path), Some(uart));
+    assert_eq!(dt.get_node_by_path(path.as_bytes()), Some(uart));
+    assert_eq!(dt.root().get_node("soc").and_then(|soc| soc.get_node("serial@2100")), Some(uart));
+    assert_eq!(uart.get_prop(prop), uart.get_prop(b"clock-frequency"));
+    assert_eq!(uart.get_prop(prop).and_then(|tok| tok.prop_u32(0)), Some(1_843_200));
+    assert_eq!(uart.get_prop("missing"), None);
+    assert_eq!(uart.props_cached::<4>().unwrap().get_prop(prop), uart.get_prop(prop));
+    assert_eq!(dt.find_compatible("ns16550a"), Some(uart));
+    assert_eq!(dt.find_compatible(b"ns16550a"), Some(uart));
+    assert_eq!(dt.find_compatible("ns16550"), None);
+    assert_eq!(dt.alias("serial0"), dt.alias(b"serial0"));
+    assert_eq!(dt.alias(String::from("serial0")), Some(&b"/soc/serial@2100"[..]));
+    assert_eq!(dt.symbol("uart0"), Some(&b"/soc/serial@2100"[..]));
+    assert_eq!(dt.symbol("uart1"), None);
+}
+
+#[test]
+fn test_find_where() {
+    let _serial = common::serial();
+    let fdt = common::attribution_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* The closure sees every node in document order, the root first */
+    let mut seen = Vec::new();
+    assert_eq!(dt.find_node_where(|node| { seen.push(node.name()); false }), None);
+    assert_eq!(seen, vec![&b""[..], b"parent", b"child", b"grandchild", b"other"]);
+
+    let found = dt.find_node_where(|node| node.get_prop(b"deep").is_some()).unwrap();
+    assert_eq!(found.name(), b"grandchild");
+
+    /* Only direct children, not the grandchild */
+    let parent = dt.get_node_by_path(b"/parent").unwrap();
+    let mut seen = Vec::new();
+    assert_eq!(parent.find_child_where(|node| { seen.push(node.name()); false }), None);
+    assert_eq!(seen, vec![&b"child"[..]]);
+    assert_eq!(dt.root().find_child_where(|node| node.name() == b"other").unwrap().name(), b"other");
+    assert_eq!(parent.get_prop(b"first").unwrap().find_child_where(|_| true), None);
+}
@@ -0,0 +1,36 @@
+use static_dt_rs::{DeviceTree, FramebufferInfo, PixelFormat};
+
+static FRAMEBUFFER_FDT: &[u8] = include_bytes!("framebuffer.dtb");
+static BOARD_FDT: &[u8] = include_bytes!("board.dtb");
+
+#[test]
+fn test_simple_framebuffer() {
+    let dt = DeviceTree::parse(FRAMEBUFFER_FDT).unwrap();
+
+    /* The disabled framebuffer is skipped */
+    let fb = dt.simple_framebuffer().unwrap();
+    assert_eq!(fb.node.name(), b"framebuffer@100000");
+    assert_eq!(fb.address, 0xc0100000);
+    assert_eq!(fb.size, 0x7e9000);
+    assert_eq!((fb.width, fb.height, fb.stride), (1920, 1080, 7680));
+    assert_eq!(fb.format, PixelFormat::X8R8G8B8);
+    assert_eq!(fb.format.bytes_per_pixel(), Some(4));
+
+    let disabled = dt.get_node_by_path(b"/chosen/framebuffer@0").unwrap();
+    assert_eq!(FramebufferInfo::from_node(&disabled), None);
+}
+
+#[test]
+fn test_root_framebuffer() {
+    let dt = DeviceTree::parse(FRAMEBUFFER_FDT).unwrap();
+
+    let node = dt.get_node_by_path(b"/framebuffer@9c000000").unwrap();
+    let fb = FramebufferInfo::from_node(&node).unwrap();
+    assert_eq!((fb.address, fb.size), (0x9c000000, 0x96000));
+    assert_eq!((fb.width, fb.height, fb.stride), (640, 480, 1280));
+    assert_eq!(fb.format, PixelFormat::Unknown(b"y8u8v8"));
+    assert_eq!(fb.format.bytes_per_pixel(), None);
+
+    assert_eq!(FramebufferInfo::from_node(&dt.root()), None);
+    assert_eq!(DeviceTree::parse(BOARD_FDT).unwrap().simple_framebuffer(), None);
+}
@@ -0,0 +1,61 @@
+use static_dt_rs::{DeviceTree, GpioKey, GpioLed, GpioSpec, LedDefaultState, Token};
+
+static GPIO_FDT: &[u8] = include_bytes!("gpio.dtb");
+
+#[test]
+fn test_phandle_args() {
+    let dt = DeviceTree::parse(GPIO_FDT).unwrap();
+    let gpio0 = dt.get_node_by_path(b"/gpio@10060000").unwrap();
+    let gpio1 = dt.get_node_by_path(b"/gpio@10070000").unwrap();
+    let consumer = dt.get_node_by_path(b"/consumer").unwrap();
+
+    /* The empty entry is skipped */
+    let specs: Vec<(Token, Vec<u32>)> = consumer.get_prop(b"test-gpios").unwrap()
+        .phandle_args(b"#gpio-cells")
+        .map(|spec| (spec.provider, spec.args().to_vec()))
+        .collect();
+    assert_eq!(specs, vec![(gpio0, vec![1, 0]), (gpio1, vec![3, 1, 2])]);
+
+    /* Too many arguments, unknown phandle and truncated entry */
+    assert_eq!(consumer.get_prop(b"big-gpios").unwrap().phandle_args(b"#gpio-cells").count(), 0);
+    assert_eq!(consumer.get_prop(b"bad-gpios").unwrap().phandle_args(b"#gpio-cells").count(), 1);
+    assert_eq!(consumer.get_prop(b"short-gpios").unwrap().phandle_args(b"#gpio-cells").count(), 0);
+    /* Missing cells property */
+    assert_eq!(consumer.get_prop(b"test-gpios").unwrap().phandle_args(b"#pwm-cells").count(), 0);
+    assert_eq!(consumer.phandle_args(b"#gpio-cells").count(), 0);
+
+    assert_eq!(consumer.gpio(b"reset-gpios"), Some(GpioSpec { controller: gpio1, pin: 4, flags: 0 }));
+    assert_eq!(consumer.gpio(b"missing-gpios"), None);
+}
+
+#[test]
+fn test_gpio_keys() {
+    let dt = DeviceTree::parse(GPIO_FDT).unwrap();
+    let gpio0 = dt.get_node_by_path(b"/gpio@10060000").unwrap();
+    let gpio1 = dt.get_node_by_path(b"/gpio@10070000").unwrap();
+
+    let keys: Vec<GpioKey> = dt.find_compatible(b"gpio-keys").unwrap().gpio_keys().collect();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0].node.name(), b"power");
+    assert_eq!(keys[0].label, Some(&b"Power"[..]));
+    assert_eq!(keys[0].code, Some(116));
+    assert_eq!(keys[0].gpio, Some(GpioSpec { controller: gpio0, pin: 5, flags: 1 }));
+    assert_eq!((keys[1].label, keys[1].code), (None, None));
+    assert_eq!(keys[1].gpio, Some(GpioSpec { controller: gpio1, pin: 2, flags: 0 }));
+}
+
+#[test]
+fn test_gpio_leds() {
+    let dt = DeviceTree::parse(GPIO_FDT).unwrap();
+    let gpio0 = dt.get_node_by_path(b"/gpio@10060000").unwrap();
+
+    let leds: Vec<GpioLed> = dt.find_compatible(b"gpio-leds").unwrap().gpio_leds().collect();
+    assert_eq!(leds.len(), 3);
+    assert_eq!(leds[0].label, Some(&b"heartbeat"[..]));
+    assert_eq!(leds[0].gpio, Some(GpioSpec { controller: gpio0, pin: 7, flags: 0 }));
+    assert_eq!(leds[0].default_state, Some(LedDefaultState::On));
+    assert_eq!((leds[1].label, leds[1].default_state), (None, Some(LedDefaultState::Keep)));
+    assert_eq!((leds[2].gpio, leds[2].default_state), (None, None));
+
+    assert_eq!(dt.root().get_prop(b"#address-cells").unwrap().gpio_leds().count(), 0);
+}
@@ -0,0 +1,25 @@
+use static_dt_rs::{DeviceTree, I2cChild};
+
+mod common;
+
+#[test]
+fn test_i2c_children() {
+    let fdt = common::i2c_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let i2c = dt.get_node_by_path(b"/i2c@1000").unwrap();
+
+    /* mux has no reg and rtc@68 is disabled */
+    let children: Vec<I2cChild> = i2c.i2c_children().collect();
+    let summary: Vec<(&[u8], u32, bool, bool)> = children.iter()
+        .map(|child| (child.node.name(), child.address, child.ten_bit, child.own_slave))
+        .collect();
+    assert_eq!(summary, vec![
+        (&b"eeprom@50"[..], 0x50, false, false),
+        (b"sensor@1a", 0x1b, false, false),
+        (b"adc@a0", 0xa0, true, false),
+        (b"slave@64", 0x64, false, true),
+    ]);
+
+    /* Not a node */
+    assert_eq!(i2c.get_prop(b"#size-cells").unwrap().i2c_children().count(), 0);
+}
@@ -0,0 +1,81 @@
+use static_dt_rs::{DeviceTree, IndexError, NodeIndexEntry, PhandleEntry};
+
+mod common;
+
+#[test]
+fn test_phandle_index() {
+    let fdt = common::attribution_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    let mut storage = [PhandleEntry::default(); 4];
+    let index = dt.build_phandle_index(&mut storage).unwrap();
+    assert_eq!(index.entries().len(), 2);
+
+    /* Same result as the linear scan */
+    for phandle in 0..8 {
+        assert_eq!(dt.get_phandle_with(phandle, Some(&index)), dt.get_phandle(phandle));
+    }
+    assert_eq!(index.lookup(5), dt.get_phandle(5).unwrap().node_offset());
+    assert_eq!(index.lookup(7), None);
+
+    /* Too small storage reports the required size */
+    let mut small = [PhandleEntry::default(); 1];
+    assert_eq!(dt.build_phandle_index(&mut small).unwrap_err(), IndexError::InsufficientStorage(2));
+
+    /* Index of another tree is ignored */
+    let other = DeviceTree::parse(common::test_blob()).unwrap();
+    let prop = other.root().get_node(b"node2").unwrap().get_prop(b"a-phandle-property").unwrap();
+    assert_eq!(prop.prop_phandle_with(Some(&index)), prop.prop_phandle());
+    assert!(prop.prop_phandle().is_some());
+}
+
+#[test]
+fn test_phandle_index_invalid() {
+    let fdt = common::phandle_gaps_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* Reserved values and properties which are not one cell are left out */
+    assert_eq!(dt.build_phandle_index(&mut []).unwrap_err(), IndexError::InsufficientStorage(5));
+    let mut storage = [PhandleEntry::default(); 5];
+    let index = dt.build_phandle_index(&mut storage).unwrap();
+    for phandle in [1, 2, 4, 9] {
+        assert_eq!(index.lookup(phandle), dt.get_phandle(phandle).and_then(|node| node.node_offset()));
+    }
+    for phandle in [0, 50, 100, 0xffff_ffff] {
+        assert_eq!(index.lookup(phandle), None);
+    }
+}
+
+#[test]
+fn test_node_index() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    let mut storage = [NodeIndexEntry::default(); 8];
+    let index = dt.build_node_index(&mut storage).unwrap();
+    assert_eq!(index.entries().len(), 6);
+
+    let paths: [&[u8]; 9] = [b"/", b"/node1", b"/node1/child-node1", b"/node1/child-node2", b"/node2",
+        b"/node2/child-node1", b"/node2/child-node2", b"/node3", b"node1"];
+    for path in paths.iter() {
+        assert_eq!(index.lookup_path(path), dt.get_node_by_path(path));
+    }
+
+    let mut small = [NodeIndexEntry::default(); 5];
+    assert_eq!(dt.build_node_index(&mut small).unwrap_err(), IndexError::InsufficientStorage(6));
+}
+
+#[test]
+fn test_node_index_root() {
+    /* The root is found by its recorded offset, after leading NOPs */
+    let fdt = common::leading_nop_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let mut storage = [NodeIndexEntry::default(); 8];
+    let index = dt.build_node_index(&mut storage).unwrap();
+    assert_ne!(dt.root().node_offset(), Some(0));
+    assert_eq!(index.lookup_path(b"/"), Some(dt.root()));
+    assert_eq!(index.lookup_path(b"/node1"), dt.get_node_by_path(b"/node1"));
+    assert!(index.lookup_path(b"/node1").is_some());
+
+    /* Only the root has no parent */
+    assert_eq!(index.entries().iter().filter(|entry| entry.parent().is_none()).count(), 1);
+}
@@ -0,0 +1,132 @@
+use static_dt_rs::{DeviceTree, Token};
+
+mod common;
+
+static INTERRUPTS_FDT: &[u8] = include_bytes!("interrupts.dtb");
+
+fn specifiers<'a>(node: &Token<'a>) -> Vec<(Token<'a>, Vec<u32>)> {
+    node.interrupts().map(|spec| (spec.provider, spec.args().to_vec())).collect()
+}
+
+#[test]
+fn test_interrupt_parent() {
+    let dt = DeviceTree::parse(INTERRUPTS_FDT).unwrap();
+    let gic = dt.get_node_by_path(b"/interrupt-controller@8000000").unwrap();
+    let gpio = dt.get_node_by_path(b"/soc/gpio@9030000").unwrap();
+
+    /* Inherited from the root */
+    assert_eq!(dt.get_node_by_path(b"/soc/serial@9000000").unwrap().interrupt_parent(), Some(gic));
+    assert_eq!(dt.get_node_by_path(b"/soc/keys@9040000").unwrap().interrupt_parent(), Some(gpio));
+    assert_eq!(dt.root().get_prop(b"interrupt-parent").unwrap().interrupt_parent(), None);
+}
+
+#[test]
+fn test_interrupts() {
+    let dt = DeviceTree::parse(INTERRUPTS_FDT).unwrap();
+    let gic = dt.get_node_by_path(b"/interrupt-controller@8000000").unwrap();
+    let gpio = dt.get_node_by_path(b"/soc/gpio@9030000").unwrap();
+
+    assert_eq!(specifiers(&dt.get_node_by_path(b"/soc/serial@9000000").unwrap()), vec![(gic, vec![0, 1, 4])]);
+    assert_eq!(specifiers(&dt.get_node_by_path(b"/soc/keys@9040000").unwrap()), vec![
+        (gpio, vec![3, 1]),
+        (gpio, vec![4, 2]),
+    ]);
+    /* interrupts-extended takes precedence */
+    assert_eq!(specifiers(&dt.get_node_by_path(b"/soc/mixed@9050000").unwrap()), vec![
+        (gic, vec![0, 9, 4]),
+        (gpio, vec![5, 1]),
+    ]);
+
+    /* Interrupt parent without #interrupt-cells */
+    assert_eq!(dt.get_node_by_path(b"/soc/no-parent@9060000").unwrap().interrupts().count(), 0);
+    assert_eq!(dt.get_node_by_path(b"/soc").unwrap().interrupts().count(), 0);
+}
+
+#[test]
+fn test_prop_strings() {
+    let dt = DeviceTree::parse(INTERRUPTS_FDT).unwrap();
+    let compatible = dt.get_node_by_path(b"/soc/gpio@9030000").unwrap().get_prop(b"compatible").unwrap();
+
+    assert_eq!(compatible.prop_strings().collect::<Vec<_>>(), vec![&b"arm,pl061"[..]]);
+    let controller = dt.get_node_by_path(b"/soc/gpio@9030000").unwrap().get_prop(b"interrupt-controller").unwrap();
+    assert_eq!(controller.prop_strings().count(), 0);
+    assert_eq!(dt.root().prop_strings().count(), 0);
+}
+
+#[test]
+fn test_interrupt_controllers() {
+    let fdt = common::nexus_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let gic = dt.get_node_by_path(b"/interrupt-controller@8000000").unwrap();
+
+    let controllers: Vec<_> = dt.interrupt_controllers()
+        .map(|ctrl| (ctrl.node.name(), ctrl.cells, ctrl.phandle, ctrl.nexus))
+        .collect();
+    assert_eq!(controllers, vec![
+        (&b"interrupt-controller@8000000"[..], Some(3), Some(1), false),
+        (b"gpio@9030000", Some(2), Some(2), false),
+        (b"pcie@10000000", Some(1), None, true),
+    ]);
+
+    assert_eq!(dt.default_interrupt_parent(), Some(gic));
+    assert_eq!(DeviceTree::parse(INTERRUPTS_FDT).unwrap().default_interrupt_parent().unwrap().name(), b"interrupt-controller@8000000");
+    assert_eq!(DeviceTree::parse(&common::test_fdt()).unwrap().default_interrupt_parent(), None);
+}
+
+fn displayed(node: &Token) -> Vec<String> {
+    node.irqs().map(|irq| irq.display().to_string()).collect()
+}
+
+#[test]
+fn test_irq_display_gic() {
+    let dt = DeviceTree::parse(INTERRUPTS_FDT).unwrap();
+    let serial = dt.get_node_by_path(b"/soc/serial@9000000").unwrap();
+    assert_eq!(displayed(&serial), ["GIC SPI 1 LEVEL_HIGH"]);
+
+    /* Falls back to raw cells for controllers without a known encoding */
+    let keys = dt.get_node_by_path(b"/soc/keys@9040000").unwrap();
+    assert_eq!(displayed(&keys), ["gpio@9030000 <0x3 0x1>", "gpio@9030000 <0x4 0x2>"]);
+    let mixed = dt.get_node_by_path(b"/soc/mixed@9050000").unwrap();
+    assert_eq!(displayed(&mixed), ["GIC SPI 9 LEVEL_HIGH", "gpio@9030000 <0x5 0x1>"]);
+
+    let fdt = common::irq_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let timer = dt.get_node_by_path(b"/timer").unwrap();
+
+    /* The CPU mask of PPIs is ignored, unknown types and triggers are written raw */
+    assert_eq!(displayed(&timer), [
+        "GIC PPI 13 LEVEL_LOW",
+        "GIC PPI 14 LEVEL_HIGH",
+        "GIC ESPI 5 EDGE_RISING",
+        "gic@1000 <0x7 0x1 0x4>",
+        "gic@1000 <0x0 0x1 0x5>",
+    ]);
+}
+
+#[test]
+fn test_irq_display_plic() {
+    let fdt = common::irq_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let uart = dt.get_node_by_path(b"/uart").unwrap();
+
+    /* Only 3-cell GIC specifiers are decoded */
+    assert_eq!(displayed(&uart), ["PLIC 10", "gic@3000 <0x0 0x3 0x4 0x0>"]);
+}
+
+#[test]
+fn test_irq_raw() {
+    let fdt = common::irq_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let uart = dt.get_node_by_path(b"/uart").unwrap();
+
+    let irqs: Vec<_> = uart.irqs().collect();
+    let (controller, cells) = irqs[0].raw();
+    assert_eq!(*controller, dt.get_node_by_path(b"/plic@2000").unwrap());
+    assert_eq!(cells, [10]);
+    let (controller, cells) = irqs[1].raw();
+    assert_eq!(controller.name(), b"gic@3000");
+    assert_eq!(cells, [0, 3, 4, 0]);
+
+    assert_eq!(irqs[0], uart.interrupts().next().unwrap().into());
+    assert_eq!(dt.root().irqs().count(), 0);
+}
@@ -0,0 +1,23 @@
+use static_dt_rs::DeviceTree;
+
+mod common;
+
+#[test]
+fn test_include_dtb() {
+    static ALIGNED: &[u8] = static_dt_rs::include_dtb!("test.dtb");
+
+    /* Same content as include_bytes!(), but aligned to 8 bytes */
+    assert_eq!(ALIGNED, common::test_blob());
+    assert_eq!(ALIGNED.as_ptr() as usize % 8, 0);
+
+    let dt = DeviceTree::parse(ALIGNED).unwrap();
+    assert_eq!(dt.version(), 17)
+}
+
+/// `include_dtb!` on a corrupt blob must fail with the reason, see tests/ui/*.stderr.
+/// Regenerate the expected output with `TRYBUILD=overwrite`.
+#[test]
+fn test_include_dtb_compile_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
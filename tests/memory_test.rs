@@ -0,0 +1,49 @@
+use static_dt_rs::{DeviceTree, RegEntry};
+
+mod common;
+
+#[test]
+fn test_memory_regions() {
+    let fdt = common::kexec_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    let regions: Vec<RegEntry> = dt.memory_regions().collect();
+    assert_eq!(regions, vec![
+        RegEntry { address: 0x80000000, size: 0x40000000 },
+        RegEntry { address: 0x100000000, size: 0x40000000 },
+        RegEntry { address: 0x200000000, size: 0x1000 },
+    ]);
+}
+
+#[test]
+fn test_crash_kernel_chosen() {
+    let fdt = common::kexec_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    assert_eq!(dt.usable_memory_range(), Some((0xb0000000, 0x60000000)));
+    assert_eq!(dt.elfcorehdr(), Some((0x13ff00000, 0x10000)));
+}
+
+#[test]
+fn test_memory_regions_clamped() {
+    let fdt = common::kexec_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* The window cuts both of the first regions and excludes the last */
+    let regions: Vec<RegEntry> = dt.memory_regions_clamped().collect();
+    assert_eq!(regions, vec![
+        RegEntry { address: 0xb0000000, size: 0x10000000 },
+        RegEntry { address: 0x100000000, size: 0x10000000 },
+    ]);
+}
+
+#[test]
+fn test_memory_regions_unclamped() {
+    let fdt = common::boot_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* Without a usable range nothing is clamped */
+    assert_eq!(dt.usable_memory_range(), None);
+    assert_eq!(dt.elfcorehdr(), None);
+    assert!(dt.memory_regions_clamped().eq(dt.memory_regions()));
+}
@@ -0,0 +1,155 @@
+use static_dt_rs::{CapacityError, DeviceTree, MmioAttr, MmioRegion};
+
+mod common;
+
+/// Adjacent, overlapping and out of order windows on a `simple-bus`, and SRAM at the root
+fn mmio_fdt() -> Vec<u8> {
+    common::build(4096, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+
+        builder.begin_node(b"memory@80000000").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.prop_cells(b"reg", &[0x80000000, 0x1000000]).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"cpus").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 0).unwrap();
+        builder.begin_node(b"cpu@0").unwrap();
+        builder.prop_u32(b"reg", 0).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"sram@20000000").unwrap();
+        builder.prop_cells(b"reg", &[0x20000000, 0x1000]).unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"soc").unwrap();
+        builder.prop_str(b"compatible", b"simple-bus").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_cells(b"ranges", &[0x0, 0x40000000, 0x10000000]).unwrap();
+
+        let devices: &[(&[u8], &[u32])] = &[
+            (b"uart@1000", &[0x1000, 0x100]),
+            /* Adjacent to uart@1000 */
+            (b"uart@1100", &[0x1100, 0x100]),
+            /* Overlaps uart@1100, second window apart */
+            (b"dma@1180", &[0x1180, 0x10, 0x5000, 0x100]),
+            /* Out of order, overlapping */
+            (b"gpio@8000", &[0x8000, 0x100]),
+            (b"gpio@7000", &[0x7000, 0x1000]),
+            /* The last one joins the first two */
+            (b"spi@b000", &[0xb000, 0x10]),
+            (b"spi@b020", &[0xb020, 0x10]),
+            (b"spi@b010", &[0xb010, 0x10]),
+        ];
+        for (name, reg) in devices {
+            builder.begin_node(name).unwrap();
+            builder.prop_cells(b"reg", reg).unwrap();
+            builder.end_node().unwrap();
+        }
+
+        builder.begin_node(b"timer@3000").unwrap();
+        builder.prop_cells(b"reg", &[0x3000, 0x100]).unwrap();
+        builder.prop_str(b"status", b"disabled").unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"i2c@9000").unwrap();
+        builder.prop_cells(b"reg", &[0x9000, 0x100]).unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 0).unwrap();
+        builder.begin_node(b"eeprom@50").unwrap();
+        builder.prop_u32(b"reg", 0x50).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        /* A disabled bus hides its children */
+        builder.begin_node(b"bus@c000").unwrap();
+        builder.prop_str(b"compatible", b"simple-bus").unwrap();
+        builder.prop_str(b"status", b"disabled").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.prop_cells(b"ranges", &[0x0, 0xc000, 0x1000]).unwrap();
+        builder.begin_node(b"dev@0").unwrap();
+        builder.prop_cells(b"reg", &[0x0, 0x100]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+
+        /* Adjacent to sram@20000000, and overlapping the second window of dma@1180 */
+        builder.begin_node(b"sram@20001000").unwrap();
+        builder.prop_cells(b"reg", &[0x20001000, 0x1000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"rom@40005000").unwrap();
+        builder.prop_cells(b"reg", &[0x40005000, 0x80]).unwrap();
+        builder.end_node().unwrap();
+
+        builder.end_node().unwrap();
+    })
+}
+
+const EMPTY: MmioRegion = MmioRegion { base: 0, size: 0, attr: MmioAttr::Normal };
+
+#[test]
+fn test_mmio_regions() {
+    let fdt = mmio_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    let mut out = [EMPTY; 8];
+    let count = dt.mmio_regions(&mut out).unwrap();
+    assert_eq!(&out[..count], &[
+        MmioRegion { base: 0x20000000, size: 0x2000, attr: MmioAttr::Normal },
+        MmioRegion { base: 0x40001000, size: 0x200, attr: MmioAttr::Device },
+        MmioRegion { base: 0x40005000, size: 0x100, attr: MmioAttr::Device },
+        MmioRegion { base: 0x40005000, size: 0x80, attr: MmioAttr::Normal },
+        MmioRegion { base: 0x40007000, size: 0x1100, attr: MmioAttr::Device },
+        MmioRegion { base: 0x40009000, size: 0x100, attr: MmioAttr::Device },
+        MmioRegion { base: 0x4000b000, size: 0x30, attr: MmioAttr::Device },
+    ]);
+    assert_eq!(out[1].end(), 0x40001200);
+
+    /* Exactly enough */
+    let mut exact = [EMPTY; 7];
+    assert_eq!(dt.mmio_regions(&mut exact), Ok(7));
+    assert_eq!(exact, out[..7]);
+}
+
+#[test]
+fn test_mmio_regions_capacity() {
+    let fdt = mmio_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    for len in 0..7 {
+        let mut out = vec![EMPTY; len];
+        assert_eq!(dt.mmio_regions(&mut out), Err(CapacityError { required: 7 }));
+    }
+}
+
+#[test]
+fn test_mmio_regions_nested_bus() {
+    let fdt = common::soc_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* i2c@800 lies within bus@100000, timer@2000 is disabled */
+    let mut out = [EMPTY; 3];
+    assert_eq!(dt.mmio_regions(&mut out), Ok(3));
+    assert_eq!(out, [
+        MmioRegion { base: 0x8000000, size: 0x10000, attr: MmioAttr::Normal },
+        MmioRegion { base: 0x40001000, size: 0x100, attr: MmioAttr::Device },
+        MmioRegion { base: 0x40100000, size: 0x10000, attr: MmioAttr::Device },
+    ]);
+}
+
+#[test]
+fn test_mmio_regions_zero_cells() {
+    /* Used to loop forever on reg entries of zero cells */
+    let fdt = common::zero_cells_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let mut out = [EMPTY; 4];
+    assert_eq!(dt.mmio_regions(&mut out), Ok(0));
+    assert_eq!(dt.mmio_regions(&mut []), Ok(0));
+}
@@ -0,0 +1,40 @@
+use static_dt_rs::DeviceTree;
+
+mod common;
+
+#[test]
+fn test_nvmem_cell() {
+    let fdt = common::nvmem_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let eth = dt.get_node_by_path(b"/ethernet").unwrap();
+    let eeprom = dt.get_node_by_path(b"/eeprom@50").unwrap();
+
+    let mac = eth.nvmem_cell(b"mac-address").unwrap();
+    assert_eq!(mac.node.name(), b"mac@0");
+    assert_eq!(mac.provider, eeprom);
+    assert_eq!((mac.offset, mac.length, mac.bits), (0, 6, None));
+
+    let calib = eth.nvmem_cell(b"calibration").unwrap();
+    assert_eq!(calib.provider, eeprom);
+    assert_eq!((calib.offset, calib.length, calib.bits), (0x10, 2, Some((2, 10))));
+
+    assert!(eth.nvmem_cell(b"serial").is_none());
+}
+
+#[test]
+fn test_nvmem_cell_invalid() {
+    let fdt = common::nvmem_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* Two cells, one name */
+    let node = dt.get_node_by_path(b"/mismatched").unwrap();
+    assert!(node.nvmem_cell(b"mac-address").is_none());
+
+    /* A cell without reg and a phandle which does not exist */
+    let node = dt.get_node_by_path(b"/broken").unwrap();
+    assert!(node.nvmem_cell(b"no-reg").is_none());
+    assert!(node.nvmem_cell(b"missing").is_none());
+
+    /* Not a consumer */
+    assert!(dt.root().nvmem_cell(b"mac-address").is_none());
+}
@@ -0,0 +1,39 @@
+use static_dt_rs::{DeviceTree, Opp};
+
+mod common;
+
+#[test]
+fn test_operating_points_legacy() {
+    let fdt = common::opp_legacy_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let cpu = dt.get_node_by_path(b"/cpus/cpu@0").unwrap();
+
+    /* The incomplete pair at the end is dropped */
+    let opps: Vec<Opp> = cpu.operating_points().collect();
+    assert_eq!(opps, vec![
+        Opp { frequency_hz: 1_200_000_000, microvolt: Some(1_250_000) },
+        Opp { frequency_hz: 800_000_000, microvolt: Some(1_100_000) },
+        Opp { frequency_hz: 400_000_000, microvolt: Some(950_000) },
+    ]);
+    assert_eq!(dt.root().operating_points().count(), 0);
+}
+
+#[test]
+fn test_operating_points_v2() {
+    let fdt = common::opp_v2_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    let expected = vec![
+        Opp { frequency_hz: 400_000_000, microvolt: Some(950_000) },
+        Opp { frequency_hz: 2_000_000_000, microvolt: Some(1_300_000) },
+        Opp { frequency_hz: 800_000_000, microvolt: None },
+    ];
+    for path in [&b"/cpus/cpu@0"[..], b"/cpus/cpu@1"] {
+        let cpu = dt.get_node_by_path(path).unwrap();
+        assert_eq!(cpu.operating_points().collect::<Vec<_>>(), expected);
+    }
+
+    /* Table which does not exist */
+    let cpu = dt.get_node_by_path(b"/cpus/cpu@2").unwrap();
+    assert_eq!(cpu.operating_points().count(), 0);
+}
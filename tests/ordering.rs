@@ -0,0 +1,152 @@
+//! Pins the order in which iterators yield tokens and which of several equal matches lookups return.
+//! Hashing, diffing and id assignment depend on document order, so a change here is a breaking change.
+
+use static_dt_rs::{DeviceTree, NodeIndexEntry, PhandleEntry, Token};
+
+mod common;
+
+/// Properties and sub-nodes interleaved with NOPs, a property following a sub-node,
+/// two sibling nodes with the same name and two nodes with the same phandle
+fn ordering_fdt() -> Vec<u8> {
+    common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"a", 1).unwrap();
+        builder.nop().unwrap();
+        builder.prop_u32(b"b", 2).unwrap();
+        builder.begin_node(b"x").unwrap();
+        builder.prop_u32(b"x1", 3).unwrap();
+        builder.nop().unwrap();
+        builder.begin_node(b"xx").unwrap();
+        builder.prop_str(b"compatible", b"twin").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.nop().unwrap();
+        builder.begin_node(b"dup").unwrap();
+        builder.prop_u32(b"phandle", 5).unwrap();
+        builder.prop_u32(b"which", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"dup").unwrap();
+        builder.prop_u32(b"phandle", 5).unwrap();
+        builder.prop_u32(b"which", 2).unwrap();
+        builder.prop_str(b"compatible", b"twin").unwrap();
+        builder.end_node().unwrap();
+        builder.nop().unwrap();
+        builder.begin_node(b"y").unwrap();
+        builder.end_node().unwrap();
+        builder.prop_u32(b"c", 4).unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+fn describe(tok: &Token) -> String {
+    match tok {
+        Token::BeginNode(_, _, name) => format!("{{{}", String::from_utf8_lossy(name)),
+        Token::EndNode => "}".to_string(),
+        Token::Property(_, name, _) => String::from_utf8_lossy(name).into_owned(),
+        Token::NoOperation => "nop".to_string(),
+        Token::End => "end".to_string(),
+        Token::Invalid(id) => format!("invalid {}", id),
+    }
+}
+
+fn describe_all<'a>(tokens: impl Iterator<Item = Token<'a>>) -> Vec<String> {
+    tokens.map(|tok| describe(&tok)).collect()
+}
+
+#[test]
+fn test_tokens_order() {
+    let fdt = ordering_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(describe_all(dt.tokens()), [
+        "{", "a", "nop", "b",
+        "{x", "x1", "nop", "{xx", "compatible", "}", "}", "nop",
+        "{dup", "phandle", "which", "}",
+        "{dup", "phandle", "which", "compatible", "}", "nop",
+        "{y", "}", "c", "}", "end"
+    ]);
+}
+
+#[test]
+fn test_walk_order() {
+    let fdt = ordering_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* NOPs skipped, `c` attributed to the root after its sub-nodes */
+    let walk: Vec<(usize, String)> = dt.attributed_tokens().map(|attr| (attr.depth, describe(&attr.token))).collect();
+    let expected: Vec<(usize, String)> = [
+        (1, "{"), (1, "a"), (1, "b"),
+        (2, "{x"), (2, "x1"), (3, "{xx"), (3, "compatible"), (3, "}"), (2, "}"),
+        (2, "{dup"), (2, "phandle"), (2, "which"), (2, "}"),
+        (2, "{dup"), (2, "phandle"), (2, "which"), (2, "compatible"), (2, "}"),
+        (2, "{y"), (2, "}"), (1, "c"), (1, "}"),
+    ].iter().map(|(depth, s)| (*depth, s.to_string())).collect();
+    assert_eq!(walk, expected);
+
+    let props: Vec<(String, String)> = dt.all_props().map(|(node, prop)| (describe(&node), describe(&prop))).collect();
+    assert_eq!(props, [
+        ("{", "a"), ("{", "b"), ("{x", "x1"), ("{xx", "compatible"),
+        ("{dup", "phandle"), ("{dup", "which"), ("{dup", "phandle"), ("{dup", "which"), ("{dup", "compatible"),
+        ("{", "c"),
+    ].iter().map(|(n, p)| (n.to_string(), p.to_string())).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_nodes_order() {
+    let fdt = ordering_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(describe_all(dt.nodes()), ["{", "{x", "{xx", "{dup", "{dup", "{y"]);
+
+    let ids: Vec<u32> = dt.enumerate_nodes().map(|(id, _)| id).collect();
+    assert_eq!(ids, [0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_children_order() {
+    let fdt = ordering_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let root = dt.root();
+
+    assert_eq!(describe_all(root.children()), ["{x", "{dup", "{dup", "{y"]);
+    assert_eq!(describe_all(root.into_iter()), ["a", "nop", "b", "{x", "}", "nop", "{dup", "}", "{dup", "}", "nop", "{y", "}", "c"]);
+    assert_eq!(describe_all(dt.get_node_by_path(b"/x").unwrap().children()), ["{xx"]);
+    assert_eq!(dt.get(b"/a").unwrap().children().count(), 0);
+}
+
+#[test]
+fn test_props_order() {
+    let fdt = ordering_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let root = dt.root();
+
+    /* Stops at the first sub-node, `c` is not included */
+    assert_eq!(describe_all(root.props()), ["a", "b"]);
+    assert_eq!(root.get_prop(b"c"), None);
+    assert!(root.get_prop_relaxed(b"c").is_some());
+}
+
+#[test]
+fn test_equal_matches() {
+    let fdt = ordering_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let which = |node: Token| node.get_prop(b"which").unwrap().prop_u32(0);
+    let first = dt.root().children().nth(1).unwrap();
+    assert_eq!(which(first), Some(1));
+
+    /* Duplicate names resolve to the first sibling, with and without a index */
+    assert_eq!(dt.root().get_node(b"dup"), Some(first));
+    assert_eq!(dt.get_node_by_path(b"/dup"), Some(first));
+    assert_eq!(dt.root().find_child_where(|node| node.name() == b"dup"), Some(first));
+    let mut storage = [NodeIndexEntry::default(); 8];
+    let index = dt.build_node_index(&mut storage).unwrap();
+    assert_eq!(index.lookup_path(b"/dup"), Some(first));
+
+    /* Duplicate phandles resolve to the first node, with and without a index */
+    assert_eq!(dt.get_phandle(5), Some(first));
+    let mut storage = [PhandleEntry::default(); 4];
+    let index = dt.build_phandle_index(&mut storage).unwrap();
+    assert_eq!(dt.get_phandle_with(5, Some(&index)), Some(first));
+
+    /* The first matching property, /x/xx precedes the second dup */
+    assert_eq!(dt.find_compatible(b"twin"), dt.get_node_by_path(b"/x/xx"));
+    assert_eq!(dt.find_with_property(b"which"), Some(first));
+}
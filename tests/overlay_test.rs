@@ -0,0 +1,195 @@
+use std::collections::BTreeSet;
+
+use static_dt_rs::{overlay_dry_run, DeviceTree, DeviceTreeMut, EditError, OverlayChange, Token};
+
+mod common;
+
+/// A change as owned strings: kind, path, property name, old value and new value
+type Change = (&'static str, String, Vec<u8>, Vec<u8>, Vec<u8>);
+
+fn owned(change: OverlayChange) -> Change {
+    let s = |b: &[u8]| String::from_utf8(b.to_vec()).unwrap();
+    match change {
+        OverlayChange::PropAdded { path, name, value } => ("added", s(path), name.to_vec(), vec![], value.to_vec()),
+        OverlayChange::PropReplaced { path, name, old, new } => ("replaced", s(path), name.to_vec(), old.to_vec(), new.to_vec()),
+        OverlayChange::NodeCreated { path } => ("created", s(path), vec![], vec![], vec![]),
+        OverlayChange::TargetNotFound { fragment } => ("missing", s(fragment), vec![], vec![], vec![]),
+    }
+}
+
+fn dry_run(base: &[u8], overlay: &[u8]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    overlay_dry_run(&DeviceTree::parse(base).unwrap(), &DeviceTree::parse(overlay).unwrap(), &mut |change| changes.push(owned(change))).unwrap();
+    changes
+}
+
+/// Path, name and value of a property
+type Prop = (String, Vec<u8>, Vec<u8>);
+
+/// Every node path and every property with its value
+fn contents(fdt: &[u8]) -> (BTreeSet<String>, BTreeSet<Prop>) {
+    let dt = DeviceTree::parse(fdt).unwrap();
+    let nodes = dt.nodes().map(|node| node.full_path_string().unwrap()).collect();
+    let props = dt.all_props().map(|(node, prop)| match prop {
+        Token::Property(_, name, value) => (node.full_path_string().unwrap(), name.to_vec(), value.to_vec()),
+        _ => unreachable!()
+    }).collect();
+    (nodes, props)
+}
+
+/// The changes from `before` to `after`, as a dry run would report them
+fn observed(before: &[u8], after: &[u8]) -> BTreeSet<Change> {
+    let (old_nodes, old_props) = contents(before);
+    let (new_nodes, new_props) = contents(after);
+    let mut changes: BTreeSet<Change> = new_nodes.difference(&old_nodes)
+        .map(|path| ("created", path.clone(), vec![], vec![], vec![]))
+        .collect();
+    for (path, name, value) in new_props.difference(&old_props) {
+        match old_props.iter().find(|(p, n, _)| p == path && n == name) {
+            Some((_, _, old)) => changes.insert(("replaced", path.clone(), name.clone(), old.clone(), value.clone())),
+            None => changes.insert(("added", path.clone(), name.clone(), vec![], value.clone())),
+        };
+    }
+    changes
+}
+
+fn overlay_fdt() -> Vec<u8> {
+    common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"fragment@0").unwrap();
+        builder.prop_u32(b"target", 1).unwrap();
+        builder.begin_node(b"__overlay__").unwrap();
+        builder.prop_u32(b"second-child-property", 2).unwrap();
+        builder.prop_str(b"a-string-property", b"Hello, world").unwrap();
+        builder.prop_empty(b"new-prop").unwrap();
+        builder.begin_node(b"grandchild").unwrap();
+        builder.prop_u32(b"x", 5).unwrap();
+        builder.begin_node(b"deeper").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"fragment@1").unwrap();
+        builder.prop_str(b"target-path", b"/node2").unwrap();
+        builder.begin_node(b"__overlay__").unwrap();
+        builder.prop_empty(b"an-empty-property").unwrap();
+        builder.prop_u32(b"a-cell-property", 9).unwrap();
+        builder.begin_node(b"child-node1").unwrap();
+        builder.prop_str(b"added", b"yes").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"fragment@2").unwrap();
+        builder.prop_str(b"target-path", b"/").unwrap();
+        builder.begin_node(b"__overlay__").unwrap();
+        builder.prop_str(b"model", b"overlay").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+
+        builder.begin_node(b"__fixups__").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+#[test]
+fn test_overlay_dry_run() {
+    let overlay = overlay_fdt();
+    let changes = dry_run(common::test_blob(), &overlay);
+    let path = |p: &str| String::from(p);
+    assert_eq!(changes, vec![
+        ("replaced", path("/node1/child-node1"), b"second-child-property".to_vec(), vec![0, 0, 0, 1], vec![0, 0, 0, 2]),
+        ("added", path("/node1/child-node1"), b"new-prop".to_vec(), vec![], vec![]),
+        ("created", path("/node1/child-node1/grandchild"), vec![], vec![], vec![]),
+        ("added", path("/node1/child-node1/grandchild"), b"x".to_vec(), vec![], vec![0, 0, 0, 5]),
+        ("created", path("/node1/child-node1/grandchild/deeper"), vec![], vec![], vec![]),
+        ("replaced", path("/node2"), b"a-cell-property".to_vec(), vec![0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4], vec![0, 0, 0, 9]),
+        ("added", path("/node2/child-node1"), b"added".to_vec(), vec![], b"yes\0".to_vec()),
+        ("added", path("/"), b"model".to_vec(), vec![], b"overlay\0".to_vec()),
+    ]);
+
+    /* Applying makes exactly the reported changes */
+    let mut buf = common::test_blob().to_vec();
+    buf.resize(common::test_blob().len() + 512, 0);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    dt.apply_overlay(&DeviceTree::parse(&overlay).unwrap()).unwrap();
+    assert_eq!(dt.tree().validate(), Ok(()));
+    assert_eq!(observed(common::test_blob(), dt.blob()), changes.iter().cloned().collect());
+
+    /* Applying again changes nothing */
+    let applied = dt.blob().to_vec();
+    assert_eq!(dry_run(&applied, &overlay), vec![]);
+    dt.apply_overlay(&DeviceTree::parse(&overlay).unwrap()).unwrap();
+    assert_eq!(dt.blob(), &applied[..]);
+}
+
+#[test]
+fn test_overlay_missing_target() {
+    let overlay = common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"fragment@0").unwrap();
+        builder.prop_str(b"target-path", b"/node1").unwrap();
+        builder.begin_node(b"__overlay__").unwrap();
+        builder.prop_u32(b"x", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"fragment@1").unwrap();
+        builder.prop_u32(b"target", 99).unwrap();
+        builder.begin_node(b"__overlay__").unwrap();
+        builder.prop_u32(b"y", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    assert_eq!(dry_run(common::test_blob(), &overlay), vec![
+        ("added", String::from("/node1"), b"x".to_vec(), vec![], vec![0, 0, 0, 1]),
+        ("missing", String::from("fragment@1"), vec![], vec![], vec![]),
+    ]);
+
+    /* Nothing is applied */
+    let mut buf = common::test_blob().to_vec();
+    buf.resize(common::test_blob().len() + 256, 0);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    assert_eq!(dt.apply_overlay(&DeviceTree::parse(&overlay).unwrap()), Err(EditError::NodeNotFound));
+    assert_eq!(dt.blob(), common::test_blob());
+}
+
+#[test]
+fn test_overlay_target_path_normalized() {
+    let build = |targets: &[(&[u8], &[u8])]| common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        for (fragment, target) in targets {
+            builder.begin_node(fragment).unwrap();
+            builder.prop_str(b"target-path", target).unwrap();
+            builder.begin_node(b"__overlay__").unwrap();
+            builder.prop_u32(b"x", 1).unwrap();
+            builder.begin_node(b"sub").unwrap();
+            builder.end_node().unwrap();
+            builder.end_node().unwrap();
+            builder.end_node().unwrap();
+        }
+        builder.end_node().unwrap();
+    });
+    let overlay = build(&[(b"fragment@0", b" /node1//child-node1/ "), (b"fragment@1", b"/node1/ child-node1"), (b"fragment@2", b"node2")]);
+
+    /* Parsed like every other path, invalid paths are not found */
+    assert_eq!(dry_run(common::test_blob(), &overlay), vec![
+        ("added", String::from("/node1/child-node1"), b"x".to_vec(), vec![], vec![0, 0, 0, 1]),
+        ("created", String::from("/node1/child-node1/sub"), vec![], vec![], vec![]),
+        ("missing", String::from("fragment@1"), vec![], vec![], vec![]),
+        ("missing", String::from("fragment@2"), vec![], vec![], vec![]),
+    ]);
+
+    let mut buf = common::test_blob().to_vec();
+    buf.resize(common::test_blob().len() + 256, 0);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    assert_eq!(dt.apply_overlay(&DeviceTree::parse(&overlay).unwrap()), Err(EditError::NodeNotFound));
+    assert_eq!(dt.blob(), common::test_blob());
+
+    let overlay = build(&[(b"fragment@0", b" /node1//child-node1/ ")]);
+    dt.apply_overlay(&DeviceTree::parse(&overlay).unwrap()).unwrap();
+    assert_eq!(observed(common::test_blob(), dt.blob()), dry_run(common::test_blob(), &overlay).into_iter().collect());
+    assert!(dt.tree().get_node_by_path(b"/node1/child-node1/sub").is_some());
+}
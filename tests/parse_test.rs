@@ -1,12 +1,23 @@
-use static_dt_rs::{DeviceTree, Token, HierarchyTokenIterator};
-use static_dt_rs::utils::get_fdt_string;
+use std::convert::{TryFrom, TryInto};
 
+use static_dt_rs::{include_dtb, DeviceTree, Error, Token};
 
-static FDT: &[u8] = include_bytes!("test.dtb");
+mod common;
+
+/* Header checks evaluated at compile time, which needs the checked-in test.dtb */
+const CONST_FDT: &[u8] = include_bytes!("test.dtb");
+const _: () = assert!(static_dt_rs::validate_header(CONST_FDT));
+const CONST_DT: DeviceTree<'static> = match DeviceTree::parse(CONST_FDT) {
+    Ok(dt) => dt,
+    Err(_) => panic!("invalid test.dtb")
+};
+const _: () = assert!(CONST_DT.magic() == 0xd00dfeed);
+const _: () = assert!(CONST_DT.version() == 17 && CONST_DT.last_comp_version() == 16);
+const _: () = assert!(CONST_DT.totalsize() == CONST_FDT.len());
 
 fn print_token(token: &Token) {
     match token {
-        Token::BeginNode(buf, _, name) => {
+        Token::BeginNode(_, _, name) => {
             println!("node {}", String::from_utf8_lossy(name))
         },
         Token::EndNode => {
@@ -15,31 +26,95 @@ fn print_token(token: &Token) {
         Token::Invalid(id) => {
             println!("Invalid token {}", id)
         },
-        Token::Property(buf, name, dat) => {
+        Token::Property(_, name, _) => {
             println!("prop {}", String::from_utf8_lossy(name))
         },
         Token::NoOperation => {
             println!("nop")
         },
-        _ => ()
+        Token::End => {
+            println!("end")
+        }
     }
 }
 
 #[test]
 fn parse_header() {
 
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
 
     assert_eq!(dt.version(), 17)
 }
 
+#[test]
+#[allow(deprecated)]
+fn parse_deprecated_back() {
+    let dt = DeviceTree::back(common::test_blob()).unwrap();
+    assert_eq!(dt.version(), 17)
+}
+
+#[test]
+fn parse_from_blocks() {
+    let parsed = DeviceTree::parse(common::test_blob()).unwrap();
+    let dt = DeviceTree::from_blocks(common::test_blob(), parsed.struct_block(), parsed.strings_block()).unwrap();
+    let contents = |dt: &DeviceTree| dt.tokens()
+        .map(|tok| (format!("{:?}", core::mem::discriminant(&tok)), tok.name().to_vec(), tok.value().map(|v| v.to_vec())))
+        .collect::<Vec<_>>();
+    assert_eq!(contents(&dt), contents(&parsed));
+    assert_eq!(dt.root().node_offset(), parsed.root().node_offset());
+    assert_eq!((dt.totalsize(), dt.version(), dt.struct_block_range()), (common::test_blob().len(), 17, parsed.struct_block_range()));
+    assert_eq!(dt.get(b"/node1/child-node1/a-string-property").unwrap().prop_str(), Some(&b"Hello, world"[..]));
+    assert_eq!(dt.validate(), Ok(()));
+
+    /* Only the root node is checked */
+    assert_eq!(DeviceTree::from_blocks(common::test_blob(), &parsed.struct_block()[4..], parsed.strings_block()).unwrap_err(), Error::NoRootNode);
+
+    /* Header accessors and struct_block_range() need a header and blocks inside the blob */
+    let copy = common::test_blob().to_vec();
+    assert_eq!(DeviceTree::from_blocks(&common::test_blob()[..39], &common::test_blob()[..0], &common::test_blob()[..0]).unwrap_err(), Error::Truncated);
+    assert_eq!(DeviceTree::from_blocks(common::test_blob(), &copy[parsed.struct_block_range()], parsed.strings_block()).unwrap_err(), Error::BlockOutOfBounds);
+    assert_eq!(DeviceTree::from_blocks(common::test_blob(), parsed.struct_block(), &copy[..8]).unwrap_err(), Error::BlockOutOfBounds);
+    let short = &common::test_blob()[..parsed.struct_block_range().end - 4];
+    assert_eq!(DeviceTree::from_blocks(short, parsed.struct_block(), &common::test_blob()[..0]).unwrap_err(), Error::BlockOutOfBounds);
+}
+
+#[test]
+fn parse_try_from() {
+    let dt = DeviceTree::try_from(common::test_blob()).unwrap();
+    assert_eq!(dt.blob(), common::test_blob());
+    assert_eq!(dt.struct_block().len(), 0x13c);
+    assert_eq!(dt.strings_block().len(), 0xa6);
+
+    let res: Result<DeviceTree, Error> = common::test_blob()[..20].try_into();
+    assert!(matches!(res, Err(Error::Truncated)));
+}
+
+#[test]
+fn parse_header_errors() {
+    /* Corrupted magic */
+    let mut buf = [0u8; 538];
+    buf.copy_from_slice(common::test_blob());
+    buf[0] = 0;
+    assert!(matches!(DeviceTree::parse(&buf), Err(Error::InvalidMagic)));
+
+    /* Too short for a header */
+    assert!(matches!(DeviceTree::parse(&common::test_blob()[..20]), Err(Error::Truncated)));
+
+    /* Shorter than totalsize */
+    assert!(matches!(DeviceTree::parse(&common::test_blob()[..100]), Err(Error::Truncated)));
+
+    /* validate_header() never panics */
+    assert!(static_dt_rs::validate_header(common::test_blob()));
+    assert!(!static_dt_rs::validate_header(&buf));
+    assert!(!static_dt_rs::validate_header(&[]));
+}
+
 #[test]
 fn parse_nodes() {
 
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
 
     println!("---- token iterator ----");
-    let mut level = 0;
     for token in dt.tokens() {
         print_token(&token);
     }
@@ -47,15 +122,12 @@ fn parse_nodes() {
     println!("---- hierarchy token iterator ----");
     for token in dt.root() {
         print_token(&token);
-        match token {
-            Token::BeginNode(_,_,_) => {
-                println!(">>>>>>>>>>>>>");
-                for tok in token {
-                    print_token(&tok);
-                }
-                println!("<<<<<<<<<<<<<");
-            },
-            _ => ()
+        if let Token::BeginNode(_,_,_) = token {
+            println!(">>>>>>>>>>>>>");
+            for tok in token {
+                print_token(&tok);
+            }
+            println!("<<<<<<<<<<<<<");
         }
     }
 
@@ -63,27 +135,42 @@ fn parse_nodes() {
 
 #[test]
 fn test_len_prop() {
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
     let node1 = dt.root().get_node(b"node1").unwrap();
 
     /* Test propertis in node2*/
     let prop = node1.get_prop(b"a-byte-data-property").unwrap();
-    assert_eq!(prop.len(), 4);
+    assert_eq!(prop.prop_len(), Some(4));
+    assert_eq!(prop.child_count(), None);
 }
 
 #[test]
 fn test_len_node() {
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
     let node1 = dt.root().get_node(b"node1").unwrap();
 
     /* Test propertis in node2*/
     let prop = node1.get_node(b"child-node1").unwrap();
-    assert_eq!(prop.len(), 4);
+    assert_eq!(prop.prop_len(), None);
+    assert_eq!(prop.prop_count(), Some(4));
+    assert_eq!(prop.child_count(), Some(0));
+    assert_eq!(node1.counts(), Some((2, 3)));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_len_deprecated() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node1 = dt.root().get_node(b"node1").unwrap();
+
+    assert_eq!(node1.len(), 5);
+    assert_eq!(node1.get_prop(b"a-byte-data-property").unwrap().len(), 4);
+    assert!(node1.get_node(b"child-node2").unwrap().empty());
 }
 
 #[test]
 fn test_prop_a_cell_property() {
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
     let node2 = dt.root().get_node(b"node2").unwrap();
 
     /* Test propertis in node2*/
@@ -93,17 +180,17 @@ fn test_prop_a_cell_property() {
 
 #[test]
 fn test_prop_an_empty_property() {
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
     let node2 = dt.root().get_node(b"node2").unwrap();
 
     /* Test propertis in node2*/
     let prop = node2.get_prop(b"an-empty-property").unwrap();
-    assert!(prop.empty());
+    assert_eq!(prop.prop_len(), Some(0));
 }
 
 #[test]
 fn test_prop_a_string_property() {
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
     let node1 = dt.root().get_node(b"node1").unwrap();
 
     /* Test propertis in node2*/
@@ -113,7 +200,7 @@ fn test_prop_a_string_property() {
 
 #[test]
 fn test_phandle() {
-    let dt = DeviceTree::back(FDT).unwrap();
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
     let node2 = dt.root().get_node(b"node2").unwrap();
 
     /* a-phandle-property points to '/node1/child-node1' */
@@ -125,4 +212,620 @@ fn test_phandle() {
     /* Verify that phandle_node is '/node1/child-node1'*/
     let prop = phandle_node.get_prop(b"a-string-property").unwrap();
     assert_eq!(prop.prop_str().unwrap(), b"Hello, world");
-}
\ No newline at end of file
+}
+#[test]
+fn test_token_identity() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node1 = dt.root().get_node(b"node1").unwrap();
+    let node2 = dt.root().get_node(b"node2").unwrap();
+
+    /* Two lookups of the same node */
+    let a = node1.get_node(b"child-node1").unwrap();
+    let b = dt.root().get_node(b"node1").unwrap().get_node(b"child-node1").unwrap();
+    assert_eq!(a, b);
+    assert_eq!(a.handle(), b.handle());
+
+    /* Same name, different node */
+    let c = node2.get_node(b"child-node1").unwrap();
+    assert_ne!(a, c);
+    assert_ne!(a.handle(), c.handle());
+    assert!(a.handle().unwrap() < c.handle().unwrap());
+
+    /* phandle resolves to the same node */
+    let phandle_node = node2.get_prop(b"a-phandle-property").unwrap().prop_phandle().unwrap();
+    assert_eq!(phandle_node, a);
+
+    /* Properties with same name in different nodes */
+    assert_eq!(a.get_prop(b"a-string-property"), a.get_prop(b"a-string-property"));
+    assert_ne!(a.get_prop(b"a-string-property"), node1.get_prop(b"a-string-property"));
+    assert!(dt.root().handle().is_some());
+    assert!(a.get_prop(b"a-string-property").unwrap().handle().is_none());
+
+    /* Same content in another buffer is a different tree */
+    let copy = common::test_blob().to_vec();
+    let dt2 = DeviceTree::parse(&copy).unwrap();
+    assert_ne!(dt2.root(), dt.root());
+}
+
+#[test]
+fn test_nodes() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    let mut names = dt.nodes().map(|node| node.name());
+    assert_eq!(names.next(), Some(&b""[..]));
+    assert_eq!(names.next(), Some(&b"node1"[..]));
+    assert_eq!(names.next(), Some(&b"child-node1"[..]));
+    assert_eq!(names.next(), Some(&b"child-node2"[..]));
+    assert_eq!(names.next(), Some(&b"node2"[..]));
+    assert_eq!(names.next(), Some(&b"child-node1"[..]));
+    assert_eq!(names.next(), None);
+}
+
+#[test]
+fn test_node_ids() {
+    for fdt in [common::test_fdt(), common::attribution_fdt(), common::leading_nop_fdt()] {
+        let dt = DeviceTree::parse(&fdt).unwrap();
+        let mut count = 0;
+        for (id, node) in dt.enumerate_nodes() {
+            assert_eq!(id, count);
+            assert_eq!(dt.node_id(&node), Some(id));
+            count += 1;
+        }
+        assert_eq!(count as usize, dt.nodes().count());
+    }
+
+    /* Stable across passes and trees parsed from the same blob */
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node = dt.get_node_by_path(b"/node2/child-node1").unwrap();
+    assert_eq!(dt.node_id(&node), Some(5));
+    assert_eq!(DeviceTree::parse(common::test_blob()).unwrap().node_id(&node), Some(5));
+
+    /* Not a node, or a node of another blob */
+    assert_eq!(dt.node_id(&Token::EndNode), None);
+    let copy = common::test_blob().to_vec();
+    assert_eq!(DeviceTree::parse(&copy).unwrap().node_id(&node), None);
+}
+
+#[test]
+fn test_all_props() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let child = dt.root().get_node(b"node1").unwrap().get_node(b"child-node1").unwrap();
+
+    assert_eq!(dt.all_props().count(), 10);
+    for (node, prop) in dt.all_props() {
+        assert_eq!(node.get_prop(prop.name()), Some(prop));
+    }
+
+    let (node, _) = dt.all_props().find(|(_, prop)| prop.name() == b"first-child-property").unwrap();
+    assert_eq!(node, child);
+
+    assert_eq!(dt.find_with_property(b"second-child-property"), Some(child));
+    assert_eq!(dt.find_with_property(b"no-such-property"), None);
+}
+
+#[test]
+fn test_props_named() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    let found: Vec<(String, &[u8])> = dt.props_named(b"a-string-property")
+        .map(|(node, prop)| (node.full_path_string().unwrap(), prop.prop_str().unwrap()))
+        .collect();
+    assert_eq!(found, vec![
+        ("/node1".to_string(), &b"A string"[..]),
+        ("/node1/child-node1".to_string(), &b"Hello, world"[..]),
+    ]);
+    assert_eq!(dt.props_named(b"a-cell-property").count(), 1);
+    assert_eq!(dt.props_named(b"a-string").count(), 0);
+
+    /* Properties after a sub-node belong to their node */
+    let fdt = common::attribution_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let owners: Vec<&[u8]> = dt.props_named(b"phandle").map(|(node, _)| node.name()).collect();
+    assert_eq!(owners, vec![&b"parent"[..], b"other"]);
+}
+
+/* common::attribution_fdt() has properties after closed sub-nodes, which dtc never emits:
+ * / {
+ *     parent {
+ *         first = <1>;
+ *         child { grandchild { deep = <3>; }; inner = <2>; };
+ *         phandle = <5>;
+ *         last = "parent";
+ *     };
+ *     other { phandle = <6>; };
+ * };
+ */
+#[test]
+fn test_props_after_subnode() {
+    let fdt = common::attribution_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    let owners: Vec<(&[u8], &[u8])> = dt.all_props().map(|(node, prop)| (node.name(), prop.name())).collect();
+    assert_eq!(owners, vec![
+        (&b"parent"[..], &b"first"[..]),
+        (b"grandchild", b"deep"),
+        (b"child", b"inner"),
+        (b"parent", b"phandle"),
+        (b"parent", b"last"),
+        (b"other", b"phandle"),
+    ]);
+
+    assert_eq!(dt.get_phandle(5).unwrap().name(), b"parent");
+    assert_eq!(dt.get_phandle(6).unwrap().name(), b"other");
+    assert_eq!(dt.get_phandle(3), None);
+}
+
+#[test]
+fn test_attributed_tokens() {
+    let fdt = common::attribution_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let name = |offset| dt.node_at_offset(offset).unwrap().name();
+
+    let tokens: Vec<(&[u8], usize, &[u8])> = dt.attributed_tokens()
+        .map(|attributed| (name(attributed.node_offset), attributed.depth, attributed.token.name()))
+        .collect();
+    assert_eq!(tokens, vec![
+        (&b""[..], 1, &b""[..]),
+        (b"parent", 2, b"parent"),
+        (b"parent", 2, b"first"),
+        (b"child", 3, b"child"),
+        (b"grandchild", 4, b"grandchild"),
+        (b"grandchild", 4, b"deep"),
+        (b"grandchild", 4, b"end-node"),
+        (b"child", 3, b"inner"),
+        (b"child", 3, b"end-node"),
+        (b"parent", 2, b"phandle"),
+        (b"parent", 2, b"last"),
+        (b"parent", 2, b"end-node"),
+        (b"other", 2, b"other"),
+        (b"other", 2, b"phandle"),
+        (b"other", 2, b"end-node"),
+        (b"", 1, b"end-node"),
+    ]);
+
+    /* Subtree of parent, the phandle following the closed child belongs to parent */
+    let parent = dt.get_node_by_path(b"/parent").unwrap();
+    assert_eq!(parent.attributed_tokens().count(), 11);
+    assert_eq!(parent.attributed_tokens().last().unwrap().node_offset, parent.node_offset().unwrap());
+    assert_eq!(parent.get_phandle_in_subtree(5), Some(parent));
+    assert_eq!(parent.get_phandle_in_subtree(6), None);
+    assert_eq!(parent.get_prop(b"first").unwrap().attributed_tokens().count(), 0);
+
+    /* NOPs are skipped */
+    let fdt = common::leading_nop_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert!(dt.attributed_tokens().all(|attributed| !matches!(attributed.token, Token::NoOperation)));
+    assert_eq!(dt.attributed_tokens().next().unwrap().node_offset, dt.root().node_offset().unwrap());
+}
+
+#[test]
+fn test_attributed_tokens_unbalanced() {
+    /* A node left open stops cleanly at FDT_END */
+    let fdt = include_bytes!("corpus/unclosed_node.dtb");
+    let dt = DeviceTree::parse(fdt).unwrap();
+    let depths: Vec<usize> = dt.attributed_tokens().map(|attributed| attributed.depth).collect();
+    assert_eq!(depths, vec![1, 1, 2, 2]);
+
+    /* Tokens after the end of the root node are not attributed */
+    let fdt = include_bytes!("corpus/extra_end_node.dtb");
+    let dt = DeviceTree::parse(fdt).unwrap();
+    assert_eq!(dt.attributed_tokens().count(), 5);
+}
+
+#[test]
+fn test_get_prop_stops_at_subnode() {
+    let fdt = common::attribution_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let parent = dt.get_node_by_path(b"/parent").unwrap();
+
+    /* Properties following a sub-node are ignored... */
+    let props: Vec<&[u8]> = parent.props().map(|prop| prop.name()).collect();
+    assert_eq!(props, vec![&b"first"[..]]);
+    assert_eq!(parent.prop_count(), Some(1));
+    assert_eq!(parent.get_prop(b"last"), None);
+
+    /* ...unless asked for */
+    assert_eq!(parent.get_prop_relaxed(b"last").unwrap().prop_str(), Some(&b"parent"[..]));
+    assert_eq!(parent.get_prop_relaxed(b"first"), parent.get_prop(b"first"));
+    assert_eq!(parent.counts(), Some((1, 3)));
+
+    /* Conforming nodes are unaffected */
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    for node in dt.nodes() {
+        assert_eq!(node.prop_count(), node.counts().map(|(_, props)| props));
+    }
+}
+
+static BOARD_FDT: &[u8] = include_bytes!("board.dtb");
+
+#[test]
+fn test_find_compatible() {
+    let dt = DeviceTree::parse(BOARD_FDT).unwrap();
+
+    let uart = dt.find_compatible(b"ns16550a").unwrap();
+    assert_eq!(uart.name(), b"serial@10000000");
+    assert!(uart.is_compatible(b"ns16550a"));
+    assert!(!uart.is_compatible(b"ns16550"));
+
+    /* Second entry in the compatible list */
+    assert_eq!(dt.find_compatible(b"test,soc"), Some(dt.root()));
+    assert_eq!(dt.find_compatible(b"test,uart").unwrap().name(), b"serial@10001000");
+    assert_eq!(dt.find_compatible(b"test"), None);
+}
+
+#[test]
+fn test_raw_header() {
+    let header = static_dt_rs::raw::Header::parse(common::test_blob()).unwrap();
+
+    assert_eq!(header.magic, static_dt_rs::raw::FDT_MAGIC);
+    assert_eq!(header.totalsize, 0x21a);
+    assert_eq!(header.off_dt_struct, 0x38);
+    assert_eq!(header.off_dt_strings, 0x174);
+    assert_eq!(header.off_mem_rsvmap, 0x28);
+    assert_eq!(header.version, 17);
+    assert_eq!(header.last_comp_version, 16);
+    assert_eq!(header.boot_cpuid_phys, 0);
+    assert_eq!(header.size_dt_strings, 0xa6);
+    assert_eq!(header.size_dt_struct, 0x13c);
+
+    assert_eq!(static_dt_rs::raw::Header::parse(&common::test_blob()[..39]), None);
+}
+
+#[test]
+fn test_header_fields() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    assert_eq!(dt.totalsize(), common::test_blob().len());
+    assert_eq!(dt.off_mem_rsvmap(), 0x28);
+    assert_eq!(dt.off_dt_struct(), 0x38);
+    assert_eq!(dt.off_dt_strings(), 0x174);
+    assert_eq!(dt.size_dt_struct(), Some(0x13c));
+    assert_eq!(dt.size_dt_strings(), 0xa6);
+    assert_eq!(dt.boot_cpuid_phys(), 0);
+
+    /* Blocks matches the header */
+    assert_eq!(dt.struct_block(), &common::test_blob()[0x38..0x38 + 0x13c]);
+    assert_eq!(dt.strings_block(), &common::test_blob()[0x174..0x174 + 0xa6]);
+}
+
+#[test]
+fn test_strings() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    let mut strings = dt.strings();
+    assert_eq!(strings.next(), Some((0, &b"a-string-property"[..])));
+    assert_eq!(strings.next(), Some((0x12, &b"a-string-list-property"[..])));
+    assert_eq!(dt.strings().count(), 9);
+    assert_eq!(dt.strings().find(|(_, s)| s == b"phandle"), Some((0x69, &b"phandle"[..])));
+    assert_eq!(dt.strings().last(), Some((0x93, &b"a-phandle-property"[..])));
+
+    /* Every property name is found in the strings block */
+    for (_, prop) in dt.all_props() {
+        assert!(dt.strings().any(|(_, s)| s == prop.name()));
+    }
+}
+
+#[test]
+fn test_prop_nameoff() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    let nameoffs: Vec<(&[u8], u32)> = dt.all_props().map(|(_, prop)| (prop.name(), prop.prop_nameoff().unwrap())).collect();
+    /* The two a-string-property share their name */
+    assert_eq!(nameoffs, vec![
+        (&b"a-string-property"[..], 0x0),
+        (b"a-string-list-property", 0x12),
+        (b"a-byte-data-property", 0x29),
+        (b"first-child-property", 0x3e),
+        (b"second-child-property", 0x53),
+        (b"a-string-property", 0x0),
+        (b"phandle", 0x69),
+        (b"an-empty-property", 0x71),
+        (b"a-cell-property", 0x83),
+        (b"a-phandle-property", 0x93),
+    ]);
+    for (name, nameoff) in nameoffs {
+        assert_eq!(dt.name_at(nameoff), Some(name));
+    }
+
+    /* Offsets into a string resolve to its tail */
+    assert_eq!(dt.name_at(2), Some(&b"string-property"[..]));
+    assert_eq!(dt.name_at(dt.strings_block().len() as u32), None);
+    assert_eq!(dt.root().prop_nameoff(), None);
+    assert_eq!(Token::End.prop_nameoff(), None);
+}
+
+#[test]
+fn test_trailing_tokens() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    /* child-node1 of node2, node2 and root are closed and followed by End */
+    let tokens: Vec<Token> = dt.tokens().collect();
+    let n = tokens.len();
+    assert!(matches!(tokens[n-5], Token::BeginNode(_, _, b"child-node1")));
+    assert!(matches!(tokens[n-4..], [Token::EndNode, Token::EndNode, Token::EndNode, Token::End]));
+
+    /* Nothing after End */
+    let mut iter = dt.tokens();
+    assert!(matches!(iter.by_ref().last(), Some(Token::End)));
+    assert!(iter.next().is_none());
+
+    /* Iterating the root node stops at its EndNode, without End */
+    assert!(matches!(dt.root().into_iter().last(), Some(Token::EndNode)));
+}
+
+#[test]
+fn test_invalid_token() {
+    /* Replace FDT_END at the end of the structure block with an unknown token */
+    let mut buf = common::test_blob().to_vec();
+    buf[0x170..0x174].copy_from_slice(&[0, 0, 0, 0x0b]);
+    let dt = DeviceTree::parse(&buf).unwrap();
+
+    let mut iter = dt.tokens();
+    assert!(matches!(iter.by_ref().last(), Some(Token::Invalid(0x0b))));
+    assert!(iter.next().is_none());
+
+    /* Node lookups are unaffected */
+    assert!(dt.root().get_node(b"node2").is_some());
+}
+
+/// Walk `span` as a token stream, returns the name of its first node and the number of nodes in it
+/// if it holds exactly one balanced node
+fn reparse_span(span: &[u8], strings: &[u8]) -> Option<(Vec<u8>, usize)> {
+    let cell = |offs: usize| span.get(offs..offs + 4).map(|b| u32::from_be_bytes(b.try_into().unwrap()));
+    let mut offs = 0;
+    let mut depth = 0;
+    let mut nodes = 0;
+    let mut first = None;
+    loop {
+        let id = cell(offs)?;
+        offs += 4;
+        match id {
+            1 => {
+                let len = span[offs..].iter().position(|c| *c == 0)?;
+                first.get_or_insert_with(|| span[offs..offs + len].to_vec());
+                offs += (len / 4 + 1) * 4;
+                depth += 1;
+                nodes += 1;
+            },
+            2 => {
+                depth -= 1;
+                if depth == 0 {
+                    return (offs == span.len()).then(|| (first.unwrap(), nodes))
+                }
+            },
+            3 => {
+                let len = cell(offs)? as usize;
+                assert!((cell(offs + 4)? as usize) < strings.len());
+                offs += 8 + len.div_ceil(4) * 4;
+            },
+            4 => (),
+            _ => return None
+        }
+        if depth == 0 {
+            return None
+        }
+    }
+}
+
+#[test]
+fn test_byte_span() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let structs = dt.struct_block();
+    assert_eq!(&dt.blob()[dt.struct_block_range()], structs);
+    assert_eq!(dt.struct_block_range().start, dt.off_dt_struct());
+
+    /* Every span starts at the FDT_BEGIN_NODE token and holds exactly the node and its descendants */
+    for node in dt.nodes() {
+        let span = node.byte_span().unwrap();
+        assert_eq!(span.start, node.node_offset().unwrap());
+        let nodes = node.attributed_tokens().filter(|attr| matches!(attr.token, Token::BeginNode(..))).count();
+        assert_eq!(reparse_span(&structs[span], dt.strings_block()), Some((node.name().to_vec(), nodes)));
+    }
+
+    /* The root spans everything but FDT_END */
+    assert_eq!(dt.root().byte_span(), Some(0..structs.len() - 4));
+    assert_eq!(dt.get(b"/node1/a-string-property").unwrap().byte_span(), None);
+}
+
+#[test]
+fn test_node_offset() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    /* The root node is the first token */
+    assert_eq!(dt.root().node_offset(), Some(0));
+    for node in dt.nodes() {
+        assert_eq!(dt.node_at_offset(node.node_offset().unwrap()), Some(node));
+    }
+
+    /* Not a FDT_BEGIN_NODE token */
+    assert_eq!(dt.node_at_offset(4), None);
+    assert_eq!(dt.node_at_offset(1), None);
+    assert_eq!(dt.node_at_offset(0x13c), None);
+}
+
+#[test]
+fn test_property_overrun() {
+    /* Length of /node1/a-string-property runs past the structure block */
+    let mut buf = common::test_blob().to_vec();
+    buf[0x50..0x54].copy_from_slice(&[0xff, 0xff, 0xff, 0xf0]);
+    let dt = DeviceTree::parse(&buf).unwrap();
+
+    /* Iteration stops at the broken property without End */
+    let tokens: Vec<Token> = dt.tokens().collect();
+    assert_eq!(tokens.len(), 2);
+    assert!(dt.root().get_node(b"node1").unwrap().get_prop(b"a-string-property").is_none());
+}
+
+#[test]
+fn test_root_leading_nop() {
+    let fdt = common::leading_nop_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    assert!(matches!(dt.tokens().next(), Some(Token::NoOperation)));
+    assert_eq!(dt.root(), dt.nodes().next().unwrap());
+    assert_eq!(dt.root().node_offset(), Some(8));
+    assert_eq!(dt.root().child_count(), Some(2));
+    assert!(dt.get_node_by_path(b"/node1/child-node1").is_some());
+
+    /* Header values are unchanged */
+    assert_eq!(dt.totalsize(), fdt.len());
+    assert_eq!(dt.version(), 17);
+}
+
+#[test]
+fn test_no_root() {
+    /* Replace the root FDT_BEGIN_NODE with FDT_END */
+    let mut buf = common::test_blob().to_vec();
+    buf[0x38..0x3c].copy_from_slice(&[0, 0, 0, 9]);
+    assert!(matches!(DeviceTree::parse(&buf), Err(Error::NoRootNode)));
+}
+
+#[test]
+fn test_parent() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node1 = dt.get_node_by_path(b"/node1").unwrap();
+
+    assert_eq!(dt.root().parent(), None);
+    assert_eq!(node1.parent(), Some(dt.root()));
+    assert_eq!(node1.get_node(b"child-node2").unwrap().parent(), Some(node1));
+    assert_eq!(node1.get_prop(b"a-string-property").unwrap().parent(), None);
+}
+
+#[test]
+fn test_cells_as_native() {
+    let dt = DeviceTree::parse(include_dtb!("test.dtb")).unwrap();
+    let node2 = dt.get_node_by_path(b"/node2").unwrap();
+    let cells = node2.get_prop(b"a-cell-property").unwrap();
+
+    if cfg!(target_endian = "big") {
+        assert_eq!(cells.cells_as_native(), Some(&[1u32, 2, 3, 4][..]));
+    } else {
+        assert_eq!(cells.cells_as_native(), None);
+    }
+
+    /* Not a property */
+    assert_eq!(node2.cells_as_native(), None);
+}
+
+#[test]
+fn test_phandle_in_subtree() {
+    let fdt = common::attribution_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let parent = dt.get_node_by_path(b"/parent").unwrap();
+    let other = dt.get_node_by_path(b"/other").unwrap();
+
+    /* Hit, same result as the global search */
+    assert_eq!(parent.get_phandle_in_subtree(5), dt.get_phandle(5));
+    assert_eq!(dt.root().get_phandle_in_subtree(6), dt.get_phandle(6));
+    assert_eq!(other.get_phandle_in_subtree(6), Some(other));
+
+    /* Miss because the node is outside the subtree */
+    assert_eq!(parent.get_phandle_in_subtree(6), None);
+    assert_eq!(parent.get_node(b"child").unwrap().get_phandle_in_subtree(5), None);
+
+    /* Hint falls back to the whole tree */
+    assert_eq!(dt.get_phandle_near(6, &parent), Some(other));
+    assert_eq!(dt.get_phandle_near(5, &parent), Some(parent));
+    assert_eq!(dt.get_phandle_near(7, &parent), None);
+
+    /* Subtree properties stop at the end of the node */
+    let props: Vec<&[u8]> = parent.get_node(b"child").unwrap().subtree_props().map(|(_, p)| p.name()).collect();
+    assert_eq!(props, vec![&b"deep"[..], b"inner"]);
+}
+
+#[test]
+fn test_is_enabled() {
+    let dt = DeviceTree::parse(BOARD_FDT).unwrap();
+
+    assert!(dt.get_node_by_path(b"/soc/serial@10000000").unwrap().is_enabled());
+    assert!(!dt.get_node_by_path(b"/soc/serial@10001000").unwrap().is_enabled());
+    assert!(!dt.root().get_prop(b"model").unwrap().is_enabled());
+}
+
+#[test]
+fn test_from_ptr() {
+    let dt = unsafe { DeviceTree::from_ptr(common::test_blob().as_ptr(), 0x10000) }.unwrap();
+    assert_eq!(dt.blob(), common::test_blob());
+    assert_eq!(dt.root().get_node(b"node1").map(|node| node.name()), Some(&b"node1"[..]));
+
+    /* Exactly totalsize is allowed */
+    assert!(unsafe { DeviceTree::from_ptr(common::test_blob().as_ptr(), common::test_blob().len()) }.is_ok());
+    assert_eq!(unsafe { DeviceTree::from_ptr(common::test_blob().as_ptr(), common::test_blob().len() - 1) }.unwrap_err(), Error::TooLarge(common::test_blob().len()));
+
+    let bad_magic = include_bytes!("bad_magic.dtb");
+    assert_eq!(unsafe { DeviceTree::from_ptr(bad_magic.as_ptr(), 0x10000) }.unwrap_err(), Error::InvalidMagic);
+
+    /* totalsize smaller than the header */
+    let mut buf = common::test_blob()[..8].to_vec();
+    buf[4..8].copy_from_slice(&8u32.to_be_bytes());
+    assert_eq!(unsafe { DeviceTree::from_ptr(buf.as_ptr(), 0x10000) }.unwrap_err(), Error::Truncated);
+}
+
+#[test]
+fn test_copy_to() {
+    #[repr(C, align(8))]
+    struct Aligned([u8; 0x400]);
+
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let mut buf = Aligned([0xff; 0x400]);
+
+    let copy = dt.copy_to(&mut buf.0).unwrap();
+    assert_eq!(copy.blob(), common::test_blob());
+    assert_eq!(copy.root().counts(), dt.root().counts());
+    assert!(buf.0[common::test_blob().len()..].iter().all(|b| *b == 0));
+
+    /* Exactly totalsize */
+    assert!(dt.copy_to(&mut buf.0[..common::test_blob().len()]).is_ok());
+    assert_eq!(dt.copy_to(&mut buf.0[..common::test_blob().len() - 1]).unwrap_err(), Error::BufferTooSmall(common::test_blob().len()));
+    assert_eq!(dt.copy_to(&mut buf.0[4..]).unwrap_err(), Error::Unaligned);
+}
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+#[test]
+fn test_send_sync() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    assert_send_sync(&dt);
+    assert_send_sync(&dt.root());
+    assert_send_sync(&dt.root().get_prop(b"compatible"));
+}
+
+#[test]
+fn test_token_offsets() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let structs = dt.struct_block();
+
+    let mut tokens = dt.tokens();
+    let mut last = tokens.offset();
+    assert_eq!(last, 0);
+    while let Some(tok) = tokens.next() {
+        let offset = tokens.offset();
+        assert_eq!(offset % 4, 0);
+        assert!(offset > last, "{} after {}", offset, last);
+        last = offset;
+
+        match tok {
+            Token::Property(_, _, val) => {
+                let value = tok.value_offset().unwrap();
+                assert_eq!(&structs[value..value + val.len()], val);
+                assert!(value + val.len() <= offset);
+            },
+            _ => assert_eq!(tok.value_offset(), None)
+        }
+    }
+    assert_eq!(last, structs.len());
+
+    /* Sub-nodes are skipped in one step and the offset is kept after the end */
+    let node = dt.get(b"/node1").unwrap();
+    let mut children = node.into_iter();
+    let mut last = children.offset();
+    while children.next().is_some() {
+        assert_eq!(children.offset() % 4, 0);
+        assert!(children.offset() > last);
+        last = children.offset();
+    }
+    let end = children.offset();
+    assert!(end > last);
+    assert_eq!(children.next(), None);
+    assert_eq!(children.offset(), end);
+}
@@ -0,0 +1,36 @@
+use static_dt_rs::{DeviceTree, Partition};
+
+static FLASH_FDT: &[u8] = include_bytes!("flash.dtb");
+
+#[test]
+fn test_fixed_partitions() {
+    let dt = DeviceTree::parse(FLASH_FDT).unwrap();
+    let flash = dt.get_node_by_path(b"/spi@10040000/flash@0").unwrap();
+
+    let parts: Vec<(u64, u64, Option<&[u8]>, bool)> = flash.partitions()
+        .map(|part| (part.offset, part.size, part.label, part.read_only))
+        .collect();
+    assert_eq!(parts, vec![
+        (0x0, 0x100000, Some(&b"u-boot"[..]), true),
+        (0x100000, 0x10000, Some(&b"env"[..]), false),
+        (0x110000, 0xef0000, None, false),
+    ]);
+
+    let env = flash.partitions().find(|part| part.label == Some(b"env")).unwrap();
+    assert_eq!(env.node.name(), b"partition@100000");
+}
+
+#[test]
+fn test_legacy_partitions() {
+    let dt = DeviceTree::parse(FLASH_FDT).unwrap();
+    let flash = dt.get_node_by_path(b"/spi@10040000/flash@1").unwrap();
+
+    let parts: Vec<Partition> = flash.partitions().collect();
+    assert_eq!(parts.len(), 2);
+    assert_eq!((parts[0].label, parts[0].offset, parts[0].size), (Some(&b"kernel"[..]), 0x0, 0x800000));
+    assert_eq!((parts[1].label, parts[1].offset, parts[1].size), (Some(&b"rootfs"[..]), 0x800000, 0x1_0000_0000));
+
+    /* No partitions */
+    assert_eq!(dt.root().get_node(b"spi@10040000").unwrap().partitions().count(), 0);
+    assert_eq!(flash.get_prop(b"reg").unwrap().partitions().count(), 0);
+}
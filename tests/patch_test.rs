@@ -0,0 +1,108 @@
+use static_dt_rs::{DeviceTreeMut, DtPath, EditError, Patch, PatchSet};
+
+mod common;
+
+/// A tree with `/chosen` and `/ethernet`, with room for `spare` more bytes
+fn board(spare: usize) -> Vec<u8> {
+    let mut fdt = common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"chosen").unwrap();
+        builder.prop_str(b"bootargs", b"console=ttyS0").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"memory@0").unwrap();
+        builder.prop_cells(b"reg", &[0, 0x1000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"ethernet").unwrap();
+        builder.property(b"local-mac-address", &[0; 6]).unwrap();
+        builder.prop_str(b"status", b"disabled").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"debug").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    fdt.resize(fdt.len() + spare, 0);
+    fdt
+}
+
+fn path(p: &[u8]) -> DtPath<'_> {
+    DtPath::parse(p).unwrap()
+}
+
+#[test]
+fn test_patch_set() {
+    let mut patches = PatchSet::<4>::new();
+    assert!(patches.is_empty());
+    patches.push(Patch::SetU32 { path: path(b"/memory@0"), prop: b"reg", index: 1, value: 0x8000 }).unwrap();
+    patches.push(Patch::SetBytes { path: path(b"/ethernet"), prop: b"local-mac-address", value: &[2, 0, 0, 0, 0, 1] }).unwrap();
+    patches.push(Patch::DeleteProp { path: path(b"/ethernet"), prop: b"status" }).unwrap();
+    patches.push(Patch::SetBootargs { value: b"console=ttyS0 root=/dev/mmcblk0p2 rw" }).unwrap();
+
+    let full = Patch::DeleteNode { path: path(b"/debug") };
+    assert_eq!(patches.push(full), Err(full));
+    assert_eq!(patches.len(), 4);
+
+    let mut buf = board(32);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    let report = patches.apply(&mut dt).unwrap();
+    assert!(report.is_ok());
+    assert_eq!(report.results(), &[Ok(()); 4]);
+
+    let tree = dt.tree();
+    assert_eq!(tree.get(b"/memory@0/reg").unwrap().prop_u32(1), Some(0x8000));
+    let mac = tree.get(b"/ethernet/local-mac-address").unwrap();
+    assert_eq!((0..6).map(|n| mac.prop_u8(n).unwrap()).collect::<Vec<_>>(), vec![2, 0, 0, 0, 0, 1]);
+    assert_eq!(mac.prop_len(), Some(6));
+    assert!(tree.get_node_by_path(b"/ethernet").unwrap().is_enabled());
+    assert_eq!(tree.get(b"/chosen/bootargs").unwrap().prop_str(), Some(&b"console=ttyS0 root=/dev/mmcblk0p2 rw"[..]));
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn test_patch_set_partial_failure() {
+    let mut patches = PatchSet::<5>::new();
+    patches.push(Patch::DeleteNode { path: path(b"/debug") }).unwrap();
+    patches.push(Patch::SetU32 { path: path(b"/memory@0"), prop: b"reg", index: 2, value: 0 }).unwrap();
+    patches.push(Patch::DeleteProp { path: path(b"/missing"), prop: b"status" }).unwrap();
+    patches.push(Patch::SetBootargs { value: &[b'x'; 64] }).unwrap();
+    patches.push(Patch::DeleteProp { path: path(b"/ethernet"), prop: b"status" }).unwrap();
+
+    /* Failed edits do not stop the rest and leave no trace */
+    let mut buf = board(16);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    let report = patches.apply(&mut dt).unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(report.applied(), 2);
+    assert_eq!(report.failures().collect::<Vec<_>>(), vec![
+        (1, EditError::OutOfRange),
+        (2, EditError::NodeNotFound),
+        (3, EditError::NoSpace),
+    ]);
+
+    let tree = dt.tree();
+    assert_eq!(tree.get_node_by_path(b"/debug"), None);
+    assert_eq!(tree.get(b"/chosen/bootargs").unwrap().prop_str(), Some(&b"console=ttyS0"[..]));
+    assert_eq!(tree.get(b"/ethernet/status"), None);
+    assert_eq!(tree.validate(), Ok(()));
+}
+
+#[test]
+fn test_patch_set_stop_on_error() {
+    let mut patches = PatchSet::<3>::new().stop_on_error(true);
+    patches.push(Patch::DeleteProp { path: path(b"/ethernet"), prop: b"status" }).unwrap();
+    patches.push(Patch::DeleteNode { path: path(b"/") }).unwrap();
+    patches.push(Patch::DeleteNode { path: path(b"/debug") }).unwrap();
+
+    let mut buf = board(0);
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+    assert_eq!(patches.apply(&mut dt), Err(EditError::RootNode));
+
+    /* Edits before the failing one remain, the ones after are not attempted */
+    let tree = dt.tree();
+    assert_eq!(tree.get(b"/ethernet/status"), None);
+    assert!(tree.get_node_by_path(b"/debug").is_some());
+
+    let empty = PatchSet::<0>::default();
+    let report = empty.apply(&mut dt).unwrap();
+    assert!(report.is_ok());
+    assert_eq!(report.results(), &[]);
+}
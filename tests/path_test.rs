@@ -0,0 +1,163 @@
+use static_dt_rs::{DeviceTree, DtPath, DtValue, PathError, PathLookupError};
+
+mod common;
+
+#[test]
+fn test_path_normalization() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let child = dt.get_node_by_path(b"/node1/child-node1").unwrap();
+
+    /* Duplicate and trailing separators */
+    assert_eq!(dt.get_node_by_path(b"//node1//child-node1"), Some(child));
+    assert_eq!(dt.get_node_by_path(b"/node1/child-node1/"), Some(child));
+    assert_eq!(dt.get_node_by_path(b"//"), Some(dt.root()));
+
+    /* Whitespace at the ends, e.g. a newline from a shell pipeline */
+    assert_eq!(dt.get_node_by_path(b"/node1/child-node1\n"), Some(child));
+    assert_eq!(dt.get_node_by_path(b" \t/node1/child-node1 \r\n"), Some(child));
+    assert_eq!(dt.get(b"  /node1/a-string-property\n").unwrap().prop_str(), Some(&b"A string"[..]));
+
+    /* Names are compared exactly */
+    assert_eq!(dt.get_node_by_path(b"/Node1"), None);
+}
+
+#[test]
+fn test_path_parse() {
+    let path = DtPath::parse(b" //soc//uart@0/\n").unwrap();
+    assert_eq!(path.as_bytes(), b"//soc//uart@0/");
+    assert_eq!(path.components().collect::<Vec<_>>(), vec![&b"soc"[..], b"uart@0"]);
+    assert!(!path.is_root());
+
+    let (parent, name) = path.split_last().unwrap();
+    assert_eq!(name, b"uart@0");
+    assert_eq!(parent.components().collect::<Vec<_>>(), vec![&b"soc"[..]]);
+    let (root, name) = parent.split_last().unwrap();
+    assert_eq!(name, b"soc");
+    assert!(root.is_root());
+    assert_eq!(root.split_last(), None);
+}
+
+#[test]
+fn test_path_errors() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    assert_eq!(DtPath::parse(b""), Err(PathError::Empty));
+    assert_eq!(DtPath::parse(b" \n"), Err(PathError::Empty));
+    assert_eq!(DtPath::parse(b"node1"), Err(PathError::NotAbsolute));
+    assert_eq!(DtPath::parse(b" node1/"), Err(PathError::NotAbsolute));
+    assert_eq!(DtPath::parse(b"/node1/ child-node1"), Err(PathError::Whitespace { offset: 7 }));
+    assert_eq!(DtPath::parse(b"  /node 1"), Err(PathError::Whitespace { offset: 7 }));
+
+    assert_eq!(dt.get_node_by_path(b"/node1/ child-node1"), None);
+    assert_eq!(dt.get(b"/node1 /a-string-property"), None);
+}
+
+#[test]
+fn test_path_lookup_verbose() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node1 = dt.get_node_by_path(b"/node1").unwrap();
+    let child = dt.get_node_by_path(b"/node1/child-node1").unwrap();
+
+    assert_eq!(dt.get_node_by_path_verbose(b"/node1/child-node1"), Ok(child));
+    assert_eq!(dt.get_node_by_path_verbose(b" /"), Ok(dt.root()));
+
+    /* Fails three components deep, the indices are into the given bytes including whitespace */
+    let path = b" /node1/child-node1//missing/deeper";
+    assert_eq!(dt.get_node_by_path_verbose(path), Err(PathLookupError::NotFound {
+        resolved: 2,
+        node_offset: child.node_offset().unwrap(),
+        start: 21,
+        end: 28
+    }));
+    assert_eq!(&path[21..28], b"missing");
+
+    assert_eq!(dt.get_node_by_path_verbose(b"/node1/child-node3"), Err(PathLookupError::NotFound {
+        resolved: 1,
+        node_offset: node1.node_offset().unwrap(),
+        start: 7,
+        end: 18
+    }));
+    assert_eq!(dt.get_node_by_path_verbose(b"/node3"), Err(PathLookupError::NotFound {
+        resolved: 0,
+        node_offset: dt.root().node_offset().unwrap(),
+        start: 1,
+        end: 6
+    }));
+
+    /* Properties are not nodes */
+    assert!(matches!(dt.get_node_by_path_verbose(b"/node1/a-string-property"),
+        Err(PathLookupError::NotFound { resolved: 1, start: 7, .. })));
+
+    assert_eq!(dt.get_node_by_path_verbose(b"node1"), Err(PathLookupError::Invalid(PathError::NotAbsolute)));
+    assert_eq!(dt.get_node_by_path_verbose(b""), Err(PathLookupError::Invalid(PathError::Empty)));
+}
+
+#[test]
+fn test_relative_get() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node1 = dt.get_node_by_path(b"/node1").unwrap();
+    let node2 = dt.get_node_by_path(b"/node2").unwrap();
+    let child = dt.get_node_by_path(b"/node1/child-node1").unwrap();
+
+    assert_eq!(node1.get(b""), Some(DtValue::Node(node1)));
+    assert_eq!(node1.get(b"child-node1"), Some(DtValue::Node(child)));
+    assert_eq!(node1.get(b"child-node1/a-string-property"), Some(DtValue::Prop(child.get_prop(b"a-string-property").unwrap())));
+    assert_eq!(node1.get(b"//child-node1//a-string-property/"), node1.get(b"child-node1/a-string-property"));
+    assert_eq!(dt.root().get(b"node1/child-node1"), Some(DtValue::Node(child)));
+
+    /* Cells of a property by index */
+    assert_eq!(node2.get(b"a-cell-property/0"), Some(DtValue::Cell(1)));
+    assert_eq!(node2.get(b"a-cell-property/3"), Some(DtValue::Cell(4)));
+    assert_eq!(node2.get(b"a-cell-property/4"), None);
+    assert_eq!(node2.get(b"a-cell-property/99999999999999999999"), None);
+    assert_eq!(node2.get(b"a-cell-property/+1"), None);
+    assert_eq!(node2.get(b"a-cell-property/0/0"), None);
+    assert_eq!(node1.get(b"a-byte-data-property/0"), Some(DtValue::Cell(0x01233456)));
+
+    assert_eq!(node1.get(b"missing"), None);
+    assert_eq!(node1.get(b"missing/0"), None);
+    assert_eq!(node1.get(b"child-node1/missing"), None);
+    let prop = node1.get_prop(b"a-string-property").unwrap();
+    assert_eq!(prop.get(b""), None);
+}
+
+#[test]
+fn test_relative_get_collision() {
+    /* "clocks" is both a property and a child node */
+    let fdt = common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_cells(b"clocks", &[7, 8]).unwrap();
+        builder.begin_node(b"clocks").unwrap();
+        builder.prop_u32(b"rate", 100).unwrap();
+        builder.begin_node(b"1").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let root = dt.root();
+    let node = root.get_node(b"clocks").unwrap();
+
+    /* Last component: the property wins, like DeviceTree::get() */
+    assert_eq!(root.get(b"clocks"), Some(DtValue::Prop(root.get_prop(b"clocks").unwrap())));
+    assert_eq!(dt.get(b"/clocks"), root.get_prop(b"clocks"));
+
+    /* Other components: the node wins */
+    assert_eq!(root.get(b"clocks/rate"), Some(DtValue::Prop(node.get_prop(b"rate").unwrap())));
+    assert_eq!(root.get(b"clocks/1"), Some(DtValue::Node(node.get_node(b"1").unwrap())));
+    assert_eq!(root.get(b"clocks/0"), None);
+}
+
+#[test]
+fn test_get_node_by_path() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    assert_eq!(dt.get_node_by_path(b"/"), Some(dt.root()));
+    let child = dt.get_node_by_path(b"/node1/child-node1").unwrap();
+    assert_eq!(child.get_prop(b"a-string-property").unwrap().prop_str(), Some(&b"Hello, world"[..]));
+    assert_eq!(dt.get_node_by_path(b"/node2/child-node1").unwrap().prop_count(), Some(0));
+
+    assert_eq!(dt.get_node_by_path(b"node1"), None);
+    assert_eq!(dt.get_node_by_path(b"/node1/child-node3"), None);
+    assert_eq!(dt.get_node_by_path(b"/node1/a-string-property"), None);
+}
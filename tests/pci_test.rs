@@ -0,0 +1,32 @@
+use static_dt_rs::{DeviceTree, PciRange, PciSpace};
+
+static PCI_FDT: &[u8] = include_bytes!("pci.dtb");
+
+#[test]
+fn test_pci_ranges() {
+    let dt = DeviceTree::parse(PCI_FDT).unwrap();
+    let host = dt.get_node_by_path(b"/soc/pcie@30000000").unwrap();
+
+    let ranges: Vec<PciRange> = host.pci_ranges().unwrap().collect();
+    assert_eq!(ranges, vec![
+        PciRange { space: PciSpace::Io, prefetchable: false, pci_address: 0x0, cpu_address: Some(0x4_3eff_0000), size: 0x10000 },
+        PciRange { space: PciSpace::Memory64, prefetchable: true, pci_address: 0x1_0000_0000, cpu_address: Some(0x4_1000_0000), size: 0x2000_0000 },
+    ]);
+
+    /* Outside of the soc ranges */
+    let host = dt.get_node_by_path(b"/soc/pcie@38000000").unwrap();
+    let range = host.pci_ranges().unwrap().next().unwrap();
+    assert_eq!((range.space, range.cpu_address), (PciSpace::Memory32, None));
+
+    /* Not a PCI node */
+    assert!(dt.get_node_by_path(b"/soc").unwrap().pci_ranges().is_none());
+}
+
+#[test]
+fn test_pci_bus_range() {
+    let dt = DeviceTree::parse(PCI_FDT).unwrap();
+
+    assert_eq!(dt.get_node_by_path(b"/soc/pcie@30000000").unwrap().pci_bus_range(), Some((0, 0x7f)));
+    assert_eq!(dt.get_node_by_path(b"/soc/pcie@38000000").unwrap().pci_bus_range(), None);
+    assert_eq!(dt.root().pci_bus_range(), None);
+}
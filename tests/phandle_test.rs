@@ -0,0 +1,108 @@
+use static_dt_rs::{DeviceTree, DeviceTreeMut, EditError, Token};
+
+mod common;
+
+#[test]
+fn test_max_phandle() {
+    let fdt = common::phandle_gaps_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    /* 100 is too long, 0xffffffff reserved */
+    assert_eq!(dt.max_phandle(), 9);
+
+    let fdt = common::test_fdt();
+    assert_eq!(DeviceTree::parse(&fdt).unwrap().max_phandle(), 1);
+    let fdt = common::build(128, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.end_node().unwrap();
+    });
+    assert_eq!(DeviceTree::parse(&fdt).unwrap().max_phandle(), 0);
+}
+
+#[test]
+fn test_phandle_of() {
+    let fdt = common::phandle_gaps_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let phandle = |path: &[u8]| dt.phandle_of(&dt.get_node_by_path(path).unwrap());
+
+    assert_eq!(phandle(b"/a"), Some(4));
+    assert_eq!(phandle(b"/b"), Some(9));
+    assert_eq!(phandle(b"/c"), Some(1));
+    assert_eq!(phandle(b"/too-long"), None);
+    assert_eq!(phandle(b"/too-short"), None);
+    assert_eq!(phandle(b"/reserved"), None);
+    /* A invalid phandle falls back to linux,phandle */
+    assert_eq!(phandle(b"/zero"), Some(2));
+    assert_eq!(phandle(b"/"), None);
+
+    /* Not a node, or a node of another tree */
+    let a = dt.get_node_by_path(b"/a").unwrap();
+    assert_eq!(dt.phandle_of(&a.get_prop(b"phandle").unwrap()), None);
+    let copy = fdt.clone();
+    let other = DeviceTree::parse(&copy).unwrap();
+    assert_eq!(other.phandle_of(&a), None);
+}
+
+/// Providers with phandles 1 to 4 and a device referring to 1 and 2
+fn referenced_fdt() -> Vec<u8> {
+    common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"clock").unwrap();
+        builder.prop_u32(b"phandle", 1).unwrap();
+        builder.prop_u32(b"#clock-cells", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"regulator").unwrap();
+        builder.prop_u32(b"phandle", 2).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"unused").unwrap();
+        builder.prop_u32(b"phandle", 3).unwrap();
+        builder.begin_node(b"child").unwrap();
+        builder.prop_u32(b"phandle", 4).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"helper").unwrap();
+        builder.prop_cells(b"link-gpios", &[4, 0]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"dev").unwrap();
+        builder.prop_cells(b"clocks", &[1, 7]).unwrap();
+        builder.prop_u32(b"vdd-supply", 2).unwrap();
+        builder.prop_u32(b"other", 3).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+#[test]
+fn test_phandle_references() {
+    let fdt = referenced_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let refs = |iter: &mut dyn Iterator<Item = (Token, Token, usize)>| {
+        iter.map(|(node, prop, n)| (node.name().to_vec(), prop.name().to_vec(), n)).collect::<Vec<_>>()
+    };
+
+    assert_eq!(refs(&mut dt.phandle_references(1)), vec![(b"dev".to_vec(), b"clocks".to_vec(), 0)]);
+    assert_eq!(refs(&mut dt.phandle_references(2)), vec![(b"dev".to_vec(), b"vdd-supply".to_vec(), 0)]);
+    assert_eq!(refs(&mut dt.phandle_references(4)), vec![(b"helper".to_vec(), b"link-gpios".to_vec(), 0)]);
+    assert_eq!(refs(&mut dt.phandle_references(7)), vec![(b"dev".to_vec(), b"clocks".to_vec(), 1)]);
+    assert_eq!(dt.phandle_references(0).count(), 0);
+
+    /* Unreferenced unless every property is searched */
+    assert_eq!(dt.phandle_references(3).count(), 0);
+    assert_eq!(refs(&mut dt.phandle_references_raw(3)), vec![(b"dev".to_vec(), b"other".to_vec(), 0)]);
+}
+
+#[test]
+fn test_delete_referenced() {
+    let mut buf = referenced_fdt();
+    let mut dt = DeviceTreeMut::new(&mut buf).unwrap();
+
+    assert_eq!(dt.delete_node_checked(b"/clock", true), Err(EditError::Referenced));
+    assert_eq!(dt.delete_node_checked(b"/unused/child", true), Err(EditError::Referenced));
+    assert!(dt.tree().get_node_by_path(b"/clock").is_some());
+
+    /* References from inside the deleted subtree do not count */
+    dt.delete_node_checked(b"/unused", true).unwrap();
+    assert_eq!(dt.tree().get_node_by_path(b"/unused"), None);
+    dt.delete_node_checked(b"/clock", false).unwrap();
+    assert_eq!(dt.tree().phandle_references(1).count(), 1);
+    assert_eq!(dt.tree().validate(), Ok(()));
+}
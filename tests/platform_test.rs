@@ -0,0 +1,45 @@
+use static_dt_rs::DeviceTree;
+
+mod common;
+
+static INTERRUPTS_FDT: &[u8] = include_bytes!("interrupts.dtb");
+
+#[test]
+fn test_platform_devices() {
+    let fdt = common::soc_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let gic = dt.get_node_by_path(b"/interrupt-controller@8000000").unwrap();
+
+    let devices: Vec<_> = dt.platform_devices().collect();
+    let names: Vec<&[u8]> = devices.iter().map(|dev| dev.node.name()).collect();
+    /* timer@2000 is disabled, the GIC is not on a bus */
+    assert_eq!(names, vec![&b"serial@1000"[..], b"bare", b"bus@100000", b"i2c@800"]);
+
+    let serial = &devices[0];
+    assert_eq!(serial.compatible, Some(&b"vendor,uart"[..]));
+    assert_eq!(serial.base, Some(0x40001000));
+    assert_eq!(serial.size, Some(0x100));
+    let irq = serial.interrupt.unwrap();
+    assert_eq!((irq.provider, irq.args()), (gic, &[0, 33, 4][..]));
+
+    let bare = &devices[1];
+    assert_eq!((bare.compatible, bare.base, bare.size, bare.interrupt), (None, None, None, None));
+
+    /* Translated through both buses */
+    let bus = &devices[2];
+    assert_eq!((bus.base, bus.size, bus.interrupt), (Some(0x40100000), Some(0x10000), None));
+    let i2c = &devices[3];
+    assert_eq!(i2c.compatible, Some(&b"vendor,i2c"[..]));
+    assert_eq!((i2c.base, i2c.size), (Some(0x40100800), Some(0x80)));
+    assert_eq!(i2c.interrupt.unwrap().args(), &[0, 40, 4]);
+}
+
+#[test]
+fn test_platform_devices_identity() {
+    /* Empty ranges, an identity mapping */
+    let dt = DeviceTree::parse(INTERRUPTS_FDT).unwrap();
+    let serial = dt.platform_devices().next().unwrap();
+    assert_eq!(serial.node.name(), b"serial@9000000");
+    assert_eq!((serial.compatible, serial.base, serial.size), (Some(&b"arm,pl011"[..]), Some(0x9000000), Some(0x1000)));
+    assert_eq!(dt.platform_devices().count(), 6);
+}
@@ -0,0 +1,95 @@
+use static_dt_rs::{Check, DeviceTree, DtWarnSink, ValidationError, ValidationProfile, Warning};
+
+mod common;
+
+#[derive(Default)]
+struct Collect(Vec<Warning>);
+
+impl DtWarnSink for Collect {
+    fn warn(&mut self, w: Warning) {
+        self.0.push(w)
+    }
+}
+
+/// A structurally valid tree with a finding for most lints
+fn dirty_fdt() -> Vec<u8> {
+    common::build(512, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.begin_node(b"dev@1000").unwrap();
+        builder.property(b"reg", &[0, 0, 0x10, 0, 0, 0x10]).unwrap();
+        builder.prop_u32(b"linux,phandle", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"Bad Name").unwrap();
+        builder.end_node().unwrap();
+        builder.nop().unwrap();
+        builder.prop_str(b"late", b"after a node").unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+fn lint(fdt: &[u8], profile: ValidationProfile) -> Vec<Option<Check>> {
+    let mut sink = Collect::default();
+    DeviceTree::parse(fdt).unwrap().lint(&profile, &mut sink).unwrap();
+    sink.0.iter().map(|w| w.check()).collect()
+}
+
+#[test]
+fn test_profiles() {
+    let fdt = dirty_fdt();
+
+    assert_eq!(lint(&fdt, ValidationProfile::minimal()), vec![]);
+    assert_eq!(lint(&fdt, ValidationProfile::standard()), vec![Some(Check::PropertyOrder)]);
+    assert_eq!(lint(&fdt, ValidationProfile::pedantic()), vec![
+        Some(Check::CellLength),
+        Some(Check::NameCharset),
+        Some(Check::NopRuns),
+        Some(Check::PropertyOrder),
+        Some(Check::Deprecated),
+    ]);
+
+    /* Single checks can be toggled */
+    assert_eq!(lint(&fdt, ValidationProfile::standard().without(Check::PropertyOrder)), vec![]);
+    assert_eq!(lint(&fdt, ValidationProfile::minimal().with(Check::NameCharset)), vec![Some(Check::NameCharset)]);
+    assert_eq!(lint(&fdt, ValidationProfile::empty()), vec![]);
+
+    /* warnings() is Pedantic without name characters */
+    let mut sink = Collect::default();
+    DeviceTree::parse(&fdt).unwrap().warnings(&mut sink);
+    assert_eq!(sink.0.iter().map(|w| w.check()).collect::<Vec<_>>(),
+               lint(&fdt, ValidationProfile::pedantic().without(Check::NameCharset)));
+    assert!(!sink.0.iter().any(|w| matches!(w, Warning::NameCharset { .. })));
+}
+
+#[test]
+fn test_profile_checks() {
+    assert_eq!(ValidationProfile::minimal().checks().collect::<Vec<_>>(), vec![Check::Structure]);
+    assert_eq!(ValidationProfile::pedantic().checks().collect::<Vec<_>>(), Check::ALL.to_vec());
+    assert_eq!(ValidationProfile::default(), ValidationProfile::standard());
+    assert!(ValidationProfile::standard().contains(Check::Reservations));
+    assert!(!ValidationProfile::standard().contains(Check::NameCharset));
+    assert_eq!(ValidationProfile::pedantic().without(Check::NopRuns).with(Check::NopRuns), ValidationProfile::pedantic());
+}
+
+#[test]
+fn test_profile_structure() {
+    /* Overwrite the terminating entry of the reservation block */
+    let mut fdt = dirty_fdt();
+    let rsvmap = DeviceTree::parse(&fdt).unwrap().off_mem_rsvmap();
+    fdt[rsvmap + 7] = 1;
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    assert_eq!(dt.validate_with(&ValidationProfile::minimal()), Ok(()));
+    let err = dt.validate_with(&ValidationProfile::standard()).unwrap_err();
+    assert!(matches!(err, ValidationError::UnterminatedReservations { .. }));
+    assert_eq!(err.check(), Check::Reservations);
+    assert_eq!(dt.validate(), Err(err));
+
+    /* No lints are reported for a tree failing a structural check */
+    let mut sink = Collect::default();
+    assert_eq!(dt.lint(&ValidationProfile::pedantic(), &mut sink), Err(err));
+    assert_eq!(sink.0, vec![]);
+    assert_eq!(dt.lint(&ValidationProfile::pedantic().without(Check::Reservations), &mut sink), Ok(()));
+    assert_eq!(sink.0.len(), 5);
+}
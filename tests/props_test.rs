@@ -0,0 +1,129 @@
+use static_dt_rs::{props, DeviceTree, NodeProps, StdProp, Token};
+
+mod common;
+
+const NAMES: [(StdProp, &[u8], &[u8]); 21] = [
+    (StdProp::Compatible, props::COMPATIBLE, b"compatible"),
+    (StdProp::Model, props::MODEL, b"model"),
+    (StdProp::Reg, props::REG, b"reg"),
+    (StdProp::Ranges, props::RANGES, b"ranges"),
+    (StdProp::Status, props::STATUS, b"status"),
+    (StdProp::Phandle, props::PHANDLE, b"phandle"),
+    (StdProp::LinuxPhandle, props::LINUX_PHANDLE, b"linux,phandle"),
+    (StdProp::AddressCells, props::ADDRESS_CELLS, b"#address-cells"),
+    (StdProp::SizeCells, props::SIZE_CELLS, b"#size-cells"),
+    (StdProp::Interrupts, props::INTERRUPTS, b"interrupts"),
+    (StdProp::InterruptsExtended, props::INTERRUPTS_EXTENDED, b"interrupts-extended"),
+    (StdProp::InterruptParent, props::INTERRUPT_PARENT, b"interrupt-parent"),
+    (StdProp::InterruptCells, props::INTERRUPT_CELLS, b"#interrupt-cells"),
+    (StdProp::DeviceType, props::DEVICE_TYPE, b"device_type"),
+    (StdProp::Bootargs, props::BOOTARGS, b"bootargs"),
+    (StdProp::StdoutPath, props::STDOUT_PATH, b"stdout-path"),
+    (StdProp::LinuxStdoutPath, props::LINUX_STDOUT_PATH, b"linux,stdout-path"),
+    (StdProp::InitrdStart, props::INITRD_START, b"linux,initrd-start"),
+    (StdProp::InitrdEnd, props::INITRD_END, b"linux,initrd-end"),
+    (StdProp::KaslrSeed, props::KASLR_SEED, b"kaslr-seed"),
+    (StdProp::RngSeed, props::RNG_SEED, b"rng-seed"),
+];
+
+#[test]
+fn test_std_props() {
+    /* One node with every standard property, each holding its index */
+    let fdt = common::build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+        for (i, (_, _, literal)) in NAMES.iter().enumerate() {
+            builder.prop_u32(literal, i as u32).unwrap();
+        }
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let root = dt.root();
+
+    assert_eq!(StdProp::ALL.len(), NAMES.len());
+    for (i, (std, constant, literal)) in NAMES.iter().enumerate() {
+        assert_eq!(StdProp::ALL[i], *std);
+        assert_eq!(std.name(), *literal);
+        let prop = root.get_prop(literal).unwrap();
+        assert_eq!(prop.prop_u32(0), Some(i as u32));
+        assert_eq!(root.get_prop(constant), Some(prop));
+        assert_eq!(root.get_std_prop(*std), Some(prop));
+    }
+
+    let fdt = common::test_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(dt.root().get_std_prop(StdProp::Compatible), None);
+}
+
+#[test]
+fn test_props_by_prefix() {
+    let fdt = common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"uart@1000").unwrap();
+        builder.prop_u32(b"pinctrl-2", 2).unwrap();
+        builder.prop_str(b"pinctrl-names", b"default").unwrap();
+        builder.prop_u32(b"qcom,rx-fifo", 16).unwrap();
+        builder.prop_u32(b"pinctrl-0", 0).unwrap();
+        builder.prop_u32(b"pinctrl-02", 20).unwrap();
+        builder.prop_u32(b"qcom,tx-fifo", 32).unwrap();
+        builder.prop_u32(b"pinctrl-0", 10).unwrap();
+        builder.begin_node(b"child").unwrap();
+        builder.prop_u32(b"pinctrl-1", 1).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let uart = dt.get(b"/uart@1000").unwrap();
+
+    /* Document order */
+    let vendor: Vec<u32> = uart.props_with_prefix(b"qcom,").map(|tok| tok.prop_u32(0).unwrap()).collect();
+    assert_eq!(vendor, vec![16, 32]);
+    assert_eq!(uart.props_with_prefix(b"pinctrl-").count(), 5);
+    assert_eq!(uart.props_with_prefix(b"").count(), 7);
+
+    /* Ordered by index with the gap skipped, only the first pinctrl-0, no pinctrl-names or pinctrl-02 */
+    let numbered: Vec<(u32, u32)> = uart.numbered_props(b"pinctrl-")
+        .map(|(index, tok)| (index, tok.prop_u32(0).unwrap()))
+        .collect();
+    assert_eq!(numbered, vec![(0, 0), (2, 2)]);
+
+    assert_eq!(uart.numbered_props(b"clock-").count(), 0);
+    assert_eq!(uart.get_prop(b"pinctrl-2").unwrap().numbered_props(b"pinctrl-").count(), 0);
+}
+
+#[test]
+fn test_props_cached() {
+    for fdt in [common::test_fdt(), common::attribution_fdt()] {
+        let dt = DeviceTree::parse(&fdt).unwrap();
+        for node in dt.nodes() {
+            let all = node.props_cached::<16>().unwrap();
+            let few: NodeProps<1> = NodeProps::new(&node).unwrap();
+            assert!(all.is_complete());
+            assert_eq!(all.len(), node.prop_count().unwrap());
+            assert_eq!(all.node(), node);
+
+            for prop in node.props() {
+                let expected = node.get_prop(prop.name());
+                assert_eq!(all.get_prop(prop.name()), expected);
+                /* Falls back on the token stream for properties that didn't fit */
+                assert_eq!(few.get_prop(prop.name()), expected);
+                assert_eq!(few.get(prop.name()), Some(prop_value(&prop)));
+            }
+            assert_eq!(all.get(b"no-such-prop"), None);
+            assert_eq!(few.get(b"no-such-prop"), None);
+        }
+    }
+
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node = dt.get_node_by_path(b"/node1").unwrap();
+    assert!(!node.props_cached::<1>().unwrap().is_complete());
+    assert!(node.props_cached::<0>().unwrap().is_empty());
+    assert!(node.get_prop(b"a-string-property").unwrap().props_cached::<4>().is_none());
+}
+
+fn prop_value<'a>(prop: &Token<'a>) -> &'a [u8] {
+    match prop {
+        Token::Property(_, _, val) => val,
+        _ => panic!("not a property")
+    }
+}
@@ -0,0 +1,32 @@
+use static_dt_rs::{DeviceTree, PsciInfo, PsciMethod, PsciVersion};
+
+static PSCI_FDT: &[u8] = include_bytes!("psci.dtb");
+static BOARD_FDT: &[u8] = include_bytes!("board.dtb");
+
+#[test]
+fn test_psci() {
+    let dt = DeviceTree::parse(PSCI_FDT).unwrap();
+
+    let psci = dt.psci().unwrap();
+    assert_eq!(psci.node.name(), b"psci");
+    assert_eq!(psci.version, PsciVersion::V1_0);
+    assert_eq!(psci.method, PsciMethod::Smc);
+    assert_eq!(psci.cpu_suspend, Some(0xc4000001));
+    assert_eq!(psci.cpu_off, Some(0x84000002));
+    assert_eq!(psci.cpu_on, Some(0xc4000003));
+    assert_eq!(psci.migrate, None);
+    assert!(PsciVersion::V1_0 > PsciVersion::V0_1);
+
+    /* Missing method */
+    let legacy = dt.get_node_by_path(b"/legacy-psci").unwrap();
+    assert_eq!(PsciInfo::from_node(&legacy), None);
+
+    /* Not a PSCI node */
+    assert_eq!(PsciInfo::from_node(&dt.root()), None);
+}
+
+#[test]
+fn test_psci_missing() {
+    let dt = DeviceTree::parse(BOARD_FDT).unwrap();
+    assert_eq!(dt.psci(), None);
+}
@@ -0,0 +1,236 @@
+//! Randomized round trip of trees through `DtbBuilder` and the parser.
+//! The seed can be fixed with `ROUNDTRIP_SEED=<n>` to reproduce a failure,
+//! a failing tree is shrunk before it is reported.
+
+use static_dt_rs::{DeviceTree, DtbBuilder, Token};
+
+const CASES: u64 = 500;
+const MAX_DEPTH: usize = 6;
+const MAX_CHILDREN: usize = 8;
+const MAX_PROPS: usize = 6;
+const MAX_VALUE: usize = 64;
+
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789,._+-#";
+
+/// xorshift64*
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn name(&mut self) -> Vec<u8> {
+        let len = 1 + self.below(12);
+        (0..len).map(|_| ALPHABET[self.below(ALPHABET.len())]).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Node {
+    name: Vec<u8>,
+    props: Vec<(Vec<u8>, Vec<u8>)>,
+    children: Vec<Node>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Begin(Vec<u8>),
+    Prop(Vec<u8>, Vec<u8>),
+    End,
+}
+
+fn gen_node(rng: &mut Rng, name: Vec<u8>, depth: usize) -> Node {
+    let props = (0..rng.below(MAX_PROPS + 1)).map(|_| {
+        let name = rng.name();
+        let value = (0..rng.below(MAX_VALUE + 1)).map(|_| rng.next() as u8).collect();
+        (name, value)
+    }).collect();
+
+    /* Fewer children deeper down to keep the trees reasonably sized */
+    let children = if depth < MAX_DEPTH {
+        (0..rng.below(MAX_CHILDREN + 1) >> depth.min(2)).map(|_| {
+            let mut name = rng.name();
+            if rng.below(2) == 0 {
+                name.extend_from_slice(format!("@{:x}", rng.next() as u32).as_bytes());
+            }
+            gen_node(rng, name, depth + 1)
+        }).collect()
+    } else {
+        Vec::new()
+    };
+
+    Node { name, props, children }
+}
+
+fn count_nodes(node: &Node) -> usize {
+    1 + node.children.iter().map(count_nodes).sum::<usize>()
+}
+
+fn size(node: &Node) -> usize {
+    12 + node.name.len() + 4
+        + node.props.iter().map(|(n, v)| 12 + v.len() + 4 + n.len() + 1).sum::<usize>()
+        + node.children.iter().map(size).sum::<usize>()
+}
+
+fn write(builder: &mut DtbBuilder, node: &Node) {
+    builder.begin_node(&node.name).unwrap();
+    for (name, value) in &node.props {
+        builder.property(name, value).unwrap();
+    }
+    for child in &node.children {
+        write(builder, child);
+    }
+    builder.end_node().unwrap();
+}
+
+fn expected(node: &Node, events: &mut Vec<Event>) {
+    events.push(Event::Begin(node.name.clone()));
+    for (name, value) in &node.props {
+        events.push(Event::Prop(name.clone(), value.clone()));
+    }
+    for child in &node.children {
+        expected(child, events);
+    }
+    events.push(Event::End);
+}
+
+/// Rebuild the tree of `token` through the hierarchical iterator
+fn read(token: Token) -> Node {
+    let name = token.name().to_vec();
+    let mut node = Node { name, props: Vec::new(), children: Vec::new() };
+    for tok in token {
+        match tok {
+            Token::Property(_, name, value) => node.props.push((name.to_vec(), value.to_vec())),
+            Token::BeginNode(..) => node.children.push(read(tok)),
+            _ => {}
+        }
+    }
+    node
+}
+
+/// Returns a description of the first disagreement between `root` and its parsed blob
+fn check(root: &Node) -> Result<(), String> {
+    let mut buf = vec![0u8; 64 + size(root)];
+    let mut builder = DtbBuilder::new(&mut buf);
+    write(&mut builder, root);
+    let fdt = builder.finish().map_err(|e| format!("finish: {:?}", e))?;
+    let dt = DeviceTree::parse(fdt).map_err(|e| format!("parse: {:?}", e))?;
+
+    /* Flat walk */
+    let mut events = Vec::new();
+    expected(root, &mut events);
+    let walked: Vec<Event> = dt.tokens().filter_map(|tok| match tok {
+        Token::BeginNode(_, _, name) => Some(Event::Begin(name.to_vec())),
+        Token::Property(_, name, value) => Some(Event::Prop(name.to_vec(), value.to_vec())),
+        Token::EndNode => Some(Event::End),
+        _ => None,
+    }).collect();
+    if walked != events {
+        return Err(format!("walk: {:?}", walked))
+    }
+
+    /* Hierarchy */
+    let read = read(dt.root());
+    if &read != root {
+        return Err(format!("hierarchy: {:?}", read))
+    }
+    if dt.nodes().count() != count_nodes(root) {
+        return Err("node count".into())
+    }
+    Ok(())
+}
+
+/// Smaller variants of `node`, largest reductions first
+fn shrink(node: &Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    for i in 0..node.children.len() {
+        let mut n = node.clone();
+        n.children.remove(i);
+        out.push(n);
+    }
+    for i in 0..node.children.len() {
+        /* Replace a child by its children */
+        let mut n = node.clone();
+        let child = n.children.remove(i);
+        for (j, c) in child.children.into_iter().enumerate() {
+            n.children.insert(i + j, c);
+        }
+        out.push(n);
+    }
+    for i in 0..node.props.len() {
+        let mut n = node.clone();
+        n.props.remove(i);
+        out.push(n);
+    }
+    for i in 0..node.props.len() {
+        let (name, value) = &node.props[i];
+        if !value.is_empty() {
+            let mut n = node.clone();
+            n.props[i].1.truncate(value.len() / 2);
+            out.push(n);
+        }
+        if name.len() > 1 {
+            let mut n = node.clone();
+            n.props[i].0.truncate(1);
+            out.push(n);
+        }
+    }
+    for i in 0..node.children.len() {
+        for c in shrink(&node.children[i]) {
+            let mut n = node.clone();
+            n.children[i] = c;
+            out.push(n);
+        }
+    }
+    out
+}
+
+/// Greedily shrink a failing tree until no smaller variant fails
+fn minimize(mut root: Node) -> (Node, String) {
+    let mut err = check(&root).unwrap_err();
+    while let Some((smaller, e)) = shrink(&root).into_iter()
+        .find_map(|n| check(&n).err().map(|e| (n, e))) {
+        root = smaller;
+        err = e;
+    }
+    (root, err)
+}
+
+#[test]
+fn test_roundtrip_random_trees() {
+    let seed = std::env::var("ROUNDTRIP_SEED").ok()
+        .map(|s| s.parse().expect("ROUNDTRIP_SEED must be a number"));
+    let seeds = match seed {
+        Some(seed) => seed..seed + 1,
+        None => 1..CASES + 1,
+    };
+
+    for seed in seeds {
+        let mut rng = Rng(seed.wrapping_mul(0x9E3779B97F4A7C15) | 1);
+        let root = gen_node(&mut rng, Vec::new(), 0);
+        if check(&root).is_err() {
+            let (root, err) = minimize(root);
+            panic!("round trip failed for ROUNDTRIP_SEED={}: {}\nminimal tree: {:#?}", seed, err, root);
+        }
+    }
+}
+
+#[test]
+fn test_roundtrip_shrink() {
+    /* The shrinker reduces a tree to nothing but a bare root */
+    let mut rng = Rng(42);
+    let mut root = gen_node(&mut rng, Vec::new(), 0);
+    while let Some(smaller) = shrink(&root).into_iter().next() {
+        root = smaller;
+    }
+    assert_eq!(root, Node { name: Vec::new(), props: Vec::new(), children: Vec::new() });
+    assert!(check(&root).is_ok());
+}
@@ -0,0 +1,23 @@
+use static_dt_rs::{Error, StaticDt};
+
+mod common;
+
+#[test]
+fn test_static_dt() {
+    static DT: StaticDt = StaticDt::new();
+    assert!(DT.get().is_none());
+
+    /* A failed init can be retried */
+    assert_eq!(DT.init(&common::test_blob()[..20]), Err(Error::Truncated));
+    assert!(DT.get().is_none());
+
+    DT.init(common::test_blob()).unwrap();
+    assert_eq!(DT.init(common::test_blob()), Err(Error::AlreadyInitialized));
+
+    /* Shared between threads */
+    let names: Vec<&'static [u8]> = std::thread::scope(|s| {
+        let workers: Vec<_> = (0..4).map(|_| s.spawn(|| DT.get().unwrap().root().get_node(b"node2").unwrap().name())).collect();
+        workers.into_iter().map(|w| w.join().unwrap()).collect()
+    });
+    assert_eq!(names, vec![&b"node2"[..]; 4]);
+}
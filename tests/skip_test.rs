@@ -0,0 +1,42 @@
+use static_dt_rs::{instrument, DeviceTree};
+
+mod common;
+
+/* The counter is global, every test takes `common::serial()` */
+#[test]
+fn test_counts_skip_subtrees() {
+    let _serial = common::serial();
+    let fdt = common::deep_bus_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let bus = dt.get_node_by_path(b"/bus").unwrap();
+
+    instrument::reset();
+    assert_eq!(bus.counts(), Some((3, 2)));
+
+    /* Only the 2 properties, 3 children and the end of the bus are decoded */
+    assert_eq!(instrument::token_reads(), 2 + 3 + 1);
+
+    /* Streaming all tokens in the subtree decodes every one of them */
+    instrument::reset();
+    let total = dt.tokens().count();
+    assert_eq!(instrument::token_reads(), total);
+    assert!(total > 100);
+}
+
+#[test]
+fn test_skip_subtree_corrupt_length() {
+    let _serial = common::serial();
+
+    /* Length of /node1/a-string-property is 0xfffffffc, which overflows a 32-bit offset */
+    let mut buf = common::test_blob().to_vec();
+    buf[0x50..0x54].copy_from_slice(&0xffff_fffcu32.to_be_bytes());
+    let dt = DeviceTree::parse(&buf).unwrap();
+
+    let mut tokens = dt.tokens();
+    assert_eq!(tokens.next().map(|tok| tok.name()), Some(&b""[..]));
+    assert_eq!(tokens.next().map(|tok| tok.name()), Some(&b"node1"[..]));
+    assert!(!tokens.skip_subtree());
+    assert!(tokens.offset() <= dt.struct_block().len());
+    assert_eq!(tokens.next(), None);
+    assert!(!tokens.skip_subtree());
+}
@@ -0,0 +1,56 @@
+use static_dt_rs::{DeviceTree, NodeSnapshot};
+
+mod common;
+
+#[test]
+fn test_snapshot() {
+    let long_name = b"a-node-with-a-name-longer-than-thirty-two-bytes@1,400000";
+    let fdt = common::build(1024, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"soc").unwrap();
+        builder.begin_node(b"serial@10000000").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(long_name).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+
+    /* Snapshots outlive the tree */
+    let (root, uart, long, short, prop) = {
+        let dt = DeviceTree::parse(&fdt).unwrap();
+        let uart = dt.get_node_by_path("/soc/serial@10000000").unwrap();
+        let soc = dt.root().get_node("soc").unwrap();
+        let long = soc.get_node(long_name).unwrap();
+        let prop = soc.get_prop("missing");
+        assert_eq!(uart.snapshot().unwrap().offset(), uart.node_offset().unwrap());
+        (dt.root().snapshot().unwrap(), uart.snapshot().unwrap(), long.snapshot().unwrap(),
+            long.snapshot_with::<8>().unwrap(), prop.and_then(|prop| prop.snapshot()))
+    };
+    drop(fdt);
+    assert_eq!(prop, None);
+
+    assert_eq!(root.name(), b"");
+    assert_eq!(root.depth(), 1);
+    assert_eq!(root.offset(), 0);
+    assert_eq!(format!("{}", root), "/ (offset 0x0, depth 1)");
+
+    assert_eq!(uart.name(), b"serial@10000000");
+    assert!(!uart.is_truncated());
+    assert_eq!(uart.depth(), 3);
+    assert_eq!(uart.unit_address().as_slice(), &[0x10000000]);
+    assert_eq!(format!("{}", uart), format!("serial@10000000 (offset {:#x}, depth 3)", uart.offset()));
+
+    /* Long names are cut off and marked, the unit address is parsed from the full name */
+    assert!(long.is_truncated());
+    assert_eq!(long.name(), &long_name[..32]);
+    assert_eq!(long.depth(), 3);
+    assert_eq!(long.unit_address().as_slice(), &[1, 0x400000]);
+    assert_eq!(format!("{}", long),
+        format!("a-node-with-a-name-longer-than-t...@1,400000 (offset {:#x}, depth 3)", long.offset()));
+
+    let short: NodeSnapshot<8> = short;
+    assert!(short.is_truncated());
+    assert_eq!(short.name(), b"a-node-w");
+    assert_eq!(short.offset(), long.offset());
+}
@@ -0,0 +1,42 @@
+use static_dt_rs::{DeviceTree, SpiChild, SpiMode};
+
+mod common;
+
+#[test]
+fn test_spi_children() {
+    let fdt = common::spi_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let spi = dt.get_node_by_path(b"/spi@2000").unwrap();
+
+    /* off@6 is disabled and no-reg has no chip select */
+    let children: Vec<SpiChild> = spi.spi_children().collect();
+    let summary: Vec<(u32, Option<u32>, SpiMode)> = children.iter()
+        .map(|child| (child.chip_select, child.max_frequency, child.mode))
+        .collect();
+    assert_eq!(summary, vec![
+        (0, Some(1_000_000), SpiMode::CPHA),
+        (1, Some(2_000_000), SpiMode::CPOL),
+        (2, Some(3_000_000), SpiMode::CS_HIGH),
+        (3, Some(4_000_000), SpiMode::THREE_WIRE),
+        (4, None, SpiMode::empty()),
+        (5, None, SpiMode::CPHA | SpiMode::CPOL | SpiMode::CS_HIGH | SpiMode::THREE_WIRE),
+    ]);
+    assert_eq!(children[0].node.name(), b"dev@0");
+}
+
+#[test]
+fn test_spi_mode() {
+    assert_eq!(SpiMode::CPHA.bits(), 0x01);
+    assert_eq!(SpiMode::CPOL.bits(), 0x02);
+    assert_eq!(SpiMode::CS_HIGH.bits(), 0x04);
+    assert_eq!(SpiMode::THREE_WIRE.bits(), 0x10);
+
+    /* Mode 3 */
+    let mode = SpiMode::CPOL | SpiMode::CPHA;
+    assert_eq!(mode.bits(), 3);
+    assert!(mode.contains(SpiMode::CPOL));
+    assert!(mode.contains(SpiMode::CPOL | SpiMode::CPHA));
+    assert!(!mode.contains(SpiMode::CS_HIGH));
+    assert!(mode.contains(SpiMode::empty()));
+    assert_eq!(SpiMode::default(), SpiMode::empty());
+}
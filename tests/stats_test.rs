@@ -0,0 +1,134 @@
+use static_dt_rs::{BootDigest, DeviceTree, QuickFacts, TreeStats};
+
+mod common;
+
+#[test]
+fn test_stats() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    assert_eq!(dt.stats(), TreeStats {
+        nodes: 6,
+        props: 10,
+        max_depth: 3,
+        max_name_len: 11,
+        prop_bytes: 81,
+        max_phandle: 1
+    });
+
+    /* Consistent with the other iterators */
+    let stats = dt.stats();
+    assert_eq!(stats.nodes, dt.nodes().count());
+    assert_eq!(stats.props, dt.all_props().count());
+
+    let stats = DeviceTree::parse(&common::attribution_fdt()).unwrap().stats();
+    assert_eq!((stats.nodes, stats.props, stats.max_depth, stats.max_phandle), (5, 6, 4, 6));
+}
+
+#[test]
+fn test_quick_facts() {
+    let fdt = common::boot_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(dt.quick_facts(), QuickFacts {
+        chosen: true,
+        aliases: true,
+        psci: true,
+        reserved_memory: true,
+        stdout_path: true,
+        cpus: 2,
+        memory_nodes: 2,
+        max_phandle: 7
+    });
+    assert_eq!(dt.quick_facts().max_phandle, dt.max_phandle());
+
+    /* Nothing optional, the CPUs and memory of test.dtb are not in the expected places */
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    assert_eq!(dt.quick_facts(), QuickFacts { max_phandle: 1, ..QuickFacts::default() });
+
+    let fdt = common::build(256, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"chosen").unwrap();
+        builder.prop_str(b"bootargs", b"quiet").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpu@0").unwrap();
+        builder.prop_str(b"device_type", b"cpu").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(dt.quick_facts(), QuickFacts { chosen: true, ..QuickFacts::default() });
+}
+
+#[test]
+fn test_boot_digest() {
+    let fdt = common::build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.begin_node(b"chosen").unwrap();
+        builder.prop_str(b"stdout-path", b"/serial@1000:115200").unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"cpus").unwrap();
+        builder.begin_node(b"cpu@0").unwrap();
+        builder.prop_str(b"device_type", b"cpu").unwrap();
+        builder.prop_u32(b"phandle", 3).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"memory@80000000").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        builder.prop_cells(b"reg", &[0x80000000, 0x40000000, 0xd0000000, 0x1000]).unwrap();
+        builder.end_node().unwrap();
+        builder.begin_node(b"serial@1000").unwrap();
+        builder.prop_cells(b"reg", &[0x1000, 0x100]).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let digest = dt.boot_digest();
+    assert!(digest.validate());
+    assert_eq!(digest.cpus, 1);
+    assert_eq!(digest.max_phandle, 3);
+    assert_eq!(digest.memory(), &[[0x80000000, 0x40000000], [0xd0000000, 0x1000]]);
+    assert_eq!(digest.earlycon_address(), Some(0x1000));
+    assert_eq!(digest.flags & BootDigest::FLAG_MEMORY_TRUNCATED, 0);
+
+    /* Round trip through raw bytes, both ways must agree with the in-memory layout */
+    let bytes = digest.to_bytes();
+    assert_eq!(BootDigest::from_bytes(&bytes), Some(digest));
+    let raw = unsafe {
+        core::slice::from_raw_parts(&digest as *const BootDigest as *const u8, core::mem::size_of::<BootDigest>())
+    };
+    assert_eq!(raw, &bytes[..]);
+    let read = unsafe { core::ptr::read_unaligned(bytes.as_ptr() as *const BootDigest) };
+    assert_eq!(read, digest);
+    assert!(BootDigest::from_bytes(&bytes[..BootDigest::SIZE - 1]).is_none());
+
+    /* Any corruption is caught */
+    for offs in (0..BootDigest::SIZE).step_by(3) {
+        let mut corrupt = bytes;
+        corrupt[offs] ^= 0x10;
+        assert!(!BootDigest::from_bytes(&corrupt).unwrap().validate(), "flip at {}", offs);
+    }
+    let mut newer = digest;
+    newer.version += 1;
+    newer.checksum = newer.checksum();
+    assert!(!newer.validate());
+
+    /* More regions than fit are flagged, no console */
+    let fdt = common::build(2048, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"#address-cells", 1).unwrap();
+        builder.prop_u32(b"#size-cells", 1).unwrap();
+        builder.begin_node(b"memory").unwrap();
+        builder.prop_str(b"device_type", b"memory").unwrap();
+        let cells: Vec<u32> = (0..10).flat_map(|i| [i * 0x1000_0000, 0x1000]).collect();
+        builder.prop_cells(b"reg", &cells).unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let digest = DeviceTree::parse(&fdt).unwrap().boot_digest();
+    assert!(digest.validate());
+    assert_eq!(digest.memory().len(), BootDigest::MAX_MEMORY);
+    assert_ne!(digest.flags & BootDigest::FLAG_MEMORY_TRUNCATED, 0);
+    assert_eq!(digest.earlycon_address(), None);
+    assert_eq!(digest.cpus, 0);
+}
@@ -0,0 +1,61 @@
+use static_dt_rs::{DeviceTree, DtWarnSink, TripType, Warning};
+
+mod common;
+
+#[derive(Default)]
+struct Collect(Vec<Warning>);
+
+impl DtWarnSink for Collect {
+    fn warn(&mut self, w: Warning) {
+        self.0.push(w);
+    }
+}
+
+#[test]
+fn test_thermal_zones() {
+    let fdt = common::thermal_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let tsens = dt.get_node_by_path(b"/tsens@4a0000").unwrap();
+
+    let zones: Vec<_> = dt.thermal_zones().collect();
+    assert_eq!(zones.len(), 2);
+
+    let cpu = zones[0];
+    assert_eq!(cpu.node.name(), b"cpu-thermal");
+    assert_eq!((cpu.polling_delay, cpu.polling_delay_passive), (1000, Some(250)));
+    assert_eq!(cpu.sensor.provider, tsens);
+    assert_eq!(cpu.sensor.args(), &[0]);
+
+    let trips: Vec<_> = cpu.trips().map(|trip| (trip.node.name(), trip.temperature, trip.hysteresis, trip.kind)).collect();
+    assert_eq!(trips, vec![
+        (&b"cpu-alert"[..], 85_000, 2_000, TripType::Passive),
+        (b"cpu-crit", 105_000, 0, TripType::Critical),
+    ]);
+
+    let battery = zones[1];
+    assert_eq!(battery.polling_delay_passive, None);
+    assert_eq!(battery.sensor.args(), &[1]);
+    let cold = battery.trips().next().unwrap();
+    assert_eq!((cold.temperature, cold.kind), (-10_000, TripType::Hot));
+
+    /* No thermal-zones node */
+    let fdt = common::test_fdt();
+    assert_eq!(DeviceTree::parse(&fdt).unwrap().thermal_zones().count(), 0);
+}
+
+#[test]
+fn test_thermal_warnings() {
+    let fdt = common::thermal_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    let mut sink = Collect::default();
+    assert_eq!(dt.thermal_zones_with_warnings(&mut sink).count(), 2);
+    let no_sensor = dt.get_node_by_path(b"/thermal-zones/no-sensor-thermal").unwrap();
+    assert_eq!(sink.0, vec![Warning::InvalidNode { offset: no_sensor.node_offset().unwrap(), property: b"thermal-sensors" }]);
+
+    let mut sink = Collect::default();
+    let cpu = dt.thermal_zones().next().unwrap();
+    assert_eq!(cpu.trips_with_warnings(&mut sink).count(), 2);
+    let no_type = dt.get_node_by_path(b"/thermal-zones/cpu-thermal/trips/cpu-no-type").unwrap();
+    assert_eq!(sink.0, vec![Warning::InvalidNode { offset: no_type.node_offset().unwrap(), property: b"type" }]);
+}
@@ -0,0 +1,57 @@
+use static_dt_rs::{DeviceTree, Token};
+
+mod common;
+
+#[test]
+fn test_get() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    let prop = dt.get(b"/node1/a-string-property").unwrap();
+    assert!(matches!(prop, Token::Property(_, b"a-string-property", b"A string\0")));
+    let node = dt.get(b"/node1/child-node1").unwrap();
+    assert_eq!(Some(node), dt.get_node_by_path(b"/node1/child-node1"));
+    assert_eq!(dt.get(b"/node1/child-node1/").unwrap().name(), b"child-node1");
+    assert_eq!(dt.get(b"/"), Some(dt.root()));
+    assert_eq!(dt.get(b"/node1").unwrap().name(), b"node1");
+
+    assert!(dt.get(b"/node1/missing").is_none());
+    assert!(dt.get(b"/missing/a-string-property").is_none());
+    assert!(dt.get(b"node1/a-string-property").is_none());
+    assert!(dt.get(b"").is_none());
+}
+
+#[test]
+fn test_get_prefers_property() {
+    let fdt = common::build(256, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.begin_node(b"soc").unwrap();
+        builder.prop_u32(b"clock", 24_000_000).unwrap();
+        builder.begin_node(b"clock").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert!(matches!(dt.get(b"/soc/clock"), Some(Token::Property(..))));
+    assert_eq!(dt.get_as::<u32>(b"/soc/clock"), Some(24_000_000));
+    assert!(matches!(dt.get_node_by_path(b"/soc/clock"), Some(Token::BeginNode(..))));
+}
+
+#[test]
+fn test_get_as() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    assert_eq!(dt.get_as::<&str>(b"/node1/a-string-property"), Some("A string"));
+    assert_eq!(dt.get_as::<&str>(b"/node1/a-string-list-property"), Some("first string"));
+    assert_eq!(dt.get_as::<u32>(b"/node1/child-node1/second-child-property"), Some(1));
+    assert_eq!(dt.get_as::<u64>(b"/node1/child-node1/second-child-property"), None);
+    assert_eq!(dt.get_as::<u32>(b"/node2/a-cell-property"), None);
+    assert_eq!(dt.get_as::<&[u8]>(b"/node2/an-empty-property"), Some(&[][..]));
+    assert_eq!(dt.get_as::<u32>(b"/node2/an-empty-property"), None);
+
+    /* Nodes have no value */
+    assert_eq!(dt.get_as::<&[u8]>(b"/node2/child-node1"), None);
+
+    let cells = dt.get(b"/node2/a-cell-property").unwrap();
+    assert_eq!(cells.prop_as::<&[u8]>().map(|val| val.len()), Some(16));
+}
@@ -0,0 +1,7 @@
+use static_dt_rs::include_dtb;
+
+static FDT: &[u8] = include_dtb!("../bad_magic.dtb");
+
+fn main() {
+    let _ = FDT;
+}
@@ -0,0 +1,61 @@
+use static_dt_rs::{DeviceTree, NodeIndexEntry, Token};
+
+mod common;
+
+#[test]
+fn test_checked_reads() {
+    use static_dt_rs::utils::{try_read_fdt_u32, try_read_fdt_u64};
+
+    assert_eq!(try_read_fdt_u32(common::test_blob(), 0), Some(0xd00dfeed));
+    assert_eq!(try_read_fdt_u64(common::test_blob(), 0), Some(0xd00dfeed_0000021a));
+    assert_eq!(try_read_fdt_u32(common::test_blob(), common::test_blob().len() - 3), None);
+    assert_eq!(try_read_fdt_u64(common::test_blob(), common::test_blob().len() - 4), None);
+    assert_eq!(try_read_fdt_u32(common::test_blob(), usize::MAX), None);
+}
+
+#[test]
+fn test_can_view_as_cells() {
+    use static_dt_rs::utils::can_view_as_cells;
+
+    let words = [0u32; 4];
+    let bytes = unsafe { std::slice::from_raw_parts(words.as_ptr() as *const u8, 16) };
+    assert!(can_view_as_cells(bytes));
+    assert!(can_view_as_cells(&bytes[4..12]));
+    assert!(can_view_as_cells(&bytes[..0]));
+
+    /* Misaligned or trailing bytes */
+    assert!(!can_view_as_cells(&bytes[1..5]));
+    assert!(!can_view_as_cells(&bytes[..6]));
+}
+
+#[test]
+fn test_dt_hash() {
+    use static_dt_rs::utils::dt_hash;
+    use static_dt_rs::DtPath;
+
+    /* Pinned values, the hash may be persisted and must never change */
+    assert_eq!(dt_hash(b""), 0x811c9dc5);
+    assert_eq!(dt_hash(b"a"), 0xe40c292c);
+    assert_eq!(dt_hash(b"foobar"), 0xbf9cf968);
+    assert_eq!(dt_hash(b"compatible"), 0xd697fc33);
+    assert_eq!(dt_hash(b"serial@10000000"), 0x702b41fa);
+    assert_eq!(dt_hash(b"/soc/serial@10000000"), 0xcf8d1c6b);
+    const ROOT: u32 = dt_hash(b"/");
+    assert_eq!(ROOT, 0x2a0c975e);
+
+    let path = |p: &'static [u8]| DtPath::parse(p).unwrap().hash();
+    assert_eq!(path(b"/soc/serial@10000000"), 0xcf8d1c6b);
+    assert_eq!(path(b" /soc//serial@10000000/ "), 0xcf8d1c6b);
+    assert_eq!(path(b"/"), ROOT);
+    assert_eq!(path(b"//"), ROOT);
+
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    let node = dt.get_node_by_path(b"/node1/child-node1").unwrap();
+    assert_eq!(node.name_hash(), Some(dt_hash(b"child-node1")));
+    assert_eq!(node.get_prop(b"phandle").unwrap().name_hash(), Some(dt_hash(b"phandle")));
+    assert_eq!(Token::EndNode.name_hash(), None);
+
+    let mut storage = [NodeIndexEntry::default(); 8];
+    let index = dt.build_node_index(&mut storage).unwrap();
+    assert!(index.entries().iter().all(|entry| Some(entry.hash()) == dt.node_at_offset(entry.offset()).unwrap().name_hash()));
+}
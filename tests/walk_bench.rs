@@ -0,0 +1,32 @@
+//! Throughput check of the token walk, run with
+//! `cargo test --release --test walk_bench -- --ignored --nocapture`
+
+use std::time::Instant;
+
+use static_dt_rs::DeviceTree;
+
+mod common;
+
+const ITERATIONS: usize = 200_000;
+
+#[test]
+#[ignore]
+fn walk_tokens() {
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+
+    let start = Instant::now();
+    let mut tokens = 0;
+    for _ in 0..ITERATIONS {
+        tokens += std::hint::black_box(&dt).tokens().count();
+    }
+    let elapsed = start.elapsed();
+
+    println!("{} tokens in {:?}, {:.1} ns/token", tokens, elapsed, elapsed.as_nanos() as f64 / tokens as f64);
+}
+
+#[test]
+fn walk_tokens_count() {
+    /* Every token is visited exactly once per walk */
+    let dt = DeviceTree::parse(common::test_blob()).unwrap();
+    assert_eq!(dt.tokens().count(), 23);
+}
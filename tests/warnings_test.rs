@@ -0,0 +1,151 @@
+use static_dt_rs::{DeviceTree, DtWarnSink, NoWarnings, ParseOptions, Token, TokenError, Warning, DEFAULT_MAX_PROP_SIZE};
+
+mod common;
+
+#[derive(Default)]
+struct Collect(Vec<Warning>);
+
+impl DtWarnSink for Collect {
+    fn warn(&mut self, w: Warning) {
+        self.0.push(w)
+    }
+}
+
+fn warnings(fdt: &[u8]) -> Vec<Warning> {
+    let mut sink = Collect::default();
+    DeviceTree::parse_with_warnings(fdt, &mut sink).unwrap();
+    sink.0
+}
+
+#[test]
+fn test_no_warnings() {
+    assert_eq!(warnings(common::test_blob()), vec![]);
+    assert!(DeviceTree::parse_with_warnings(common::test_blob(), &mut NoWarnings).is_ok());
+    assert!(DeviceTree::parse_with_warnings(&common::test_blob()[..20], &mut NoWarnings).is_err());
+}
+
+#[test]
+fn test_warnings() {
+    assert_eq!(warnings(&common::leading_nop_fdt()), vec![Warning::NopRun { offset: 0, count: 2 }]);
+
+    let props_after: Vec<Warning> = warnings(&common::attribution_fdt());
+    /* inner, phandle and last */
+    assert_eq!(props_after.len(), 3);
+    assert!(props_after.iter().all(|w| matches!(w, Warning::PropertyAfterNode { .. })));
+
+    /* Replace FDT_END with an unknown token and rename a-string-list-property to the last string */
+    let mut buf = common::test_blob().to_vec();
+    buf[0x170..0x174].copy_from_slice(&[0, 0, 0, 0x0b]);
+    let dt = DeviceTree::parse(&buf).unwrap();
+    let (nameoff, _) = dt.strings().find(|(_, s)| *s == b"a-string-list-property").unwrap();
+    let (last, _) = dt.strings().last().unwrap();
+    buf[0x6c..0x70].copy_from_slice(&(last as u32).to_be_bytes());
+
+    assert_eq!(warnings(&buf), vec![
+        Warning::UnknownToken { offset: 0x170 - 0x38, id: 0x0b },
+        Warning::UnreferencedString { offset: nameoff },
+    ]);
+}
+
+#[test]
+fn test_tokens_lossy() {
+    /* 12 bytes of garbage in place of the empty property between first and second */
+    let mut fdt = common::build(256, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"first", 1).unwrap();
+        builder.prop_empty(b"x").unwrap();
+        builder.prop_u32(b"second", 2).unwrap();
+        builder.end_node().unwrap();
+    });
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let garbage = dt.off_dt_struct() + 8 + 16;
+    fdt[garbage..garbage + 12].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef, 0xca, 0xfe, 0xba, 0xbe, 0x12, 0x34, 0x56, 0x78]);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+
+    /* Strict iteration stops at the garbage */
+    let strict: Vec<&[u8]> = dt.tokens().map(|tok| tok.name()).collect();
+    assert_eq!(strict, vec![&b""[..], b"first", b"-"]);
+
+    let mut sink = Collect::default();
+    let lossy: Vec<Token> = dt.tokens_lossy(&mut sink).collect();
+    let names: Vec<&[u8]> = lossy.iter().map(|tok| tok.name()).collect();
+    assert_eq!(names, vec![&b""[..], b"first", b"second", b"end-node", b"end"]);
+    assert_eq!(lossy[2].prop_u32(0), Some(2));
+    assert_eq!(sink.0, vec![Warning::SkippedBytes { offset: 8 + 16, len: 12 }]);
+
+    /* Garbage up to the end of the block */
+    let end = dt.off_dt_struct() + dt.struct_block().len();
+    fdt[garbage..end].fill(0xff);
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let mut sink = Collect::default();
+    assert!(matches!(dt.tokens_lossy(&mut sink).last(), Some(Token::Invalid(0xffffffff))));
+    assert_eq!(sink.0, vec![Warning::SkippedBytes { offset: 8 + 16, len: end - garbage }]);
+}
+
+/// A 8 KiB property between two small ones
+fn big_prop_fdt() -> Vec<u8> {
+    common::build(16384, |builder| {
+        builder.begin_node(b"").unwrap();
+        builder.prop_u32(b"before", 1).unwrap();
+        builder.property(b"blob", &[0xa5; 8192]).unwrap();
+        builder.prop_u32(b"after", 2).unwrap();
+        builder.begin_node(b"child").unwrap();
+        builder.end_node().unwrap();
+        builder.end_node().unwrap();
+    })
+}
+
+#[test]
+fn test_max_prop_size() {
+    let fdt = big_prop_fdt();
+    let plain = DeviceTree::parse(&fdt).unwrap();
+    let blob = plain.get(b"/blob").unwrap();
+    let offset = blob.value_offset().unwrap() - 12;
+    assert_eq!(ParseOptions::default().max_prop_size, DEFAULT_MAX_PROP_SIZE);
+    assert!(plain.try_tokens().all(|tok| tok.is_ok()));
+
+    let mut sink = Collect::default();
+    let options = ParseOptions { max_prop_size: 4096 };
+    let dt = DeviceTree::parse_with_options(&fdt, &options, &mut sink).unwrap();
+    assert_eq!(sink.0, vec![Warning::OversizeProperty { offset, len: 8192 }]);
+
+    /* Iterators and lookups stop at it, the lossy iterator skips it */
+    assert_eq!(dt.get(b"/before").unwrap().prop_u32(0), Some(1));
+    assert_eq!(dt.get(b"/blob"), None);
+    assert_eq!(dt.get(b"/after"), None);
+    assert!(matches!(dt.tokens().last(), Some(Token::Invalid(0x3))));
+    let mut lossy = Collect::default();
+    assert_eq!(dt.tokens_lossy(&mut lossy).count(), plain.tokens().count() - 1);
+    assert_eq!(lossy.0, vec![Warning::OversizeProperty { offset, len: 8192 }]);
+
+    let errors: Vec<TokenError> = dt.try_tokens().filter_map(Result::err).collect();
+    assert_eq!(errors, vec![TokenError::OversizeProperty { offset, len: 8192 }]);
+    assert_eq!(dt.try_tokens().count(), plain.tokens().count());
+}
+
+#[test]
+fn test_max_prop_size_corrupted_len() {
+    let mut fdt = big_prop_fdt();
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    let offset = dt.get(b"/after").unwrap().value_offset().unwrap() - 12;
+    let len_field = dt.off_dt_struct() + offset + 4;
+    fdt[len_field..len_field + 4].copy_from_slice(&0xffff_fff0u32.to_be_bytes());
+
+    /* The guard triggers before the length is checked against the buffer, the name is not readable */
+    let mut sink = Collect::default();
+    let dt = DeviceTree::parse_with_options(&fdt, &ParseOptions::default(), &mut sink).unwrap();
+    assert_eq!(sink.0[0], Warning::OversizeProperty { offset, len: 0xffff_fff0 });
+    assert!(matches!(sink.0[1..], [Warning::UnreferencedString { .. }]));
+
+    let results: Vec<Result<Token, TokenError>> = dt.try_tokens().collect();
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[3], Err(TokenError::OversizeProperty { offset, len: 0xffff_fff0 }));
+    assert!(matches!(results[4], Err(TokenError::Truncated { .. })));
+    assert_eq!(dt.get(b"/before").unwrap().prop_u32(0), Some(1));
+    assert_eq!(dt.get(b"/after"), None);
+
+    /* Without the guard the bounds check stops iteration at the property */
+    let dt = DeviceTree::parse(&fdt).unwrap();
+    assert_eq!(dt.tokens().count(), 3);
+    assert!(matches!(dt.try_tokens().last(), Some(Err(TokenError::Truncated { offset: o })) if o == offset));
+}